@@ -0,0 +1,78 @@
+//! Authorization
+//!
+//! `Authorized<P>` extracts the logged in user and rejects the request with `403`
+//! unless the role of the user grants the permission `P`:
+//! ```ignore
+//! pub async fn delete_meme(Authorized(admin_user, _): Authorized<perm::DeleteMemes>) { .. }
+//! ```
+//! the role is read from the access token, the permissions of it from `RoleRepository`
+
+use std::marker::PhantomData;
+
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+
+use crate::{
+    app::shared_data::RoleRepoSSType, authentication::AuthInformation, business::roles::Permission,
+    controllers::ApiError,
+};
+
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+/// markers of the permissions, named after `Permission`
+pub mod perm {
+    use super::{Permission, RequiredPermission};
+
+    macro_rules! permission_markers {
+        ($($name: ident),* $(,)?) => {
+            $(
+                pub struct $name;
+
+                impl RequiredPermission for $name {
+                    const PERMISSION: Permission = Permission::$name;
+                }
+            )*
+        };
+    }
+
+    permission_markers!(
+        ViewMemes,
+        PostMemes,
+        DeleteMemes,
+        EditCategories,
+        ReviewSuggests,
+        ManageAccounts,
+    );
+}
+
+pub struct Authorized<P: RequiredPermission>(pub AuthInformation, pub PhantomData<P>);
+
+impl<S, P> FromRequestParts<S> for Authorized<P>
+where
+    S: Send + Sync,
+    RoleRepoSSType: FromRef<S>,
+    P: RequiredPermission,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<AuthInformation>()
+            .cloned()
+            .ok_or(ApiError::Unauthorized)?;
+
+        let role_repo = RoleRepoSSType::from_ref(state);
+        if !role_repo
+            .repo
+            .has_permission(&user.role, P::PERMISSION)
+            .await?
+        {
+            return Err(ApiError::Forbidden);
+        }
+
+        Ok(Authorized(user, PhantomData))
+    }
+}
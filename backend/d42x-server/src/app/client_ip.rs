@@ -0,0 +1,67 @@
+//! Client ip
+//!
+//! the peer address of the connection, or the address forwarded by a trusted proxy.
+//! `X-Forwarded-For` is read from the right, the first address that is not a trusted proxy
+//! is the client, the ones on its left are set by the client and cannot be trusted
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, HeaderName, request::Parts},
+};
+
+use crate::{config, controllers::ApiError};
+
+static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .ok_or_else(|| {
+                ApiError::Internal(String::from(
+                    "no ConnectInfo, serve with into_make_service_with_connect_info",
+                ))
+            })?;
+
+        Ok(ClientIp(resolve_client_ip(
+            &parts.headers,
+            peer,
+            &config::TRUSTED_PROXIES,
+        )))
+    }
+}
+
+/// the peer, unless it is a trusted proxy: then the rightmost address of `X-Forwarded-For`
+/// that is not a trusted proxy. the hops are read from the right and the reading stops at the
+/// first one that is not an address, as everything on its left is unreliable
+pub fn resolve_client_ip(headers: &HeaderMap, peer: IpAddr, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all(&X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    hops.into_iter()
+        .rev()
+        .map_while(|hop| hop.parse::<IpAddr>().ok())
+        .find(|ip| !trusted_proxies.contains(ip))
+        .unwrap_or(peer)
+}
@@ -1,22 +1,33 @@
 use crate::{
-    authentication::{AuthInformation, CLAIM_UID, CLAIM_USERNAME, validate_claims},
+    app::shared_data::SessionRepoSSType,
+    authentication::{
+        AuthInformation, CLAIM_ROLE, CLAIM_SESSION_ID, CLAIM_UID, CLAIM_USERNAME, validate_claims,
+    },
     config,
+    controllers::ApiError,
 };
 use axum::{
-    extract::Request,
-    http::{Method, StatusCode, header::AUTHORIZATION},
+    extract::{Request, State},
+    http::{HeaderMap, Method, header::AUTHORIZATION},
     middleware::Next,
     response::{IntoResponse, Response},
 };
-use jwt::VerifyWithKey;
 use sea_orm::prelude::Uuid;
 
-static WHITE_LIST: &[&str] = &["/api/admin/login"];
+static WHITE_LIST: &[&str] = &[
+    "/api/admin/login",
+    "/api/admin/login/two-factor",
+    "/api/admin/token/refresh",
+];
 static WHITE_METHODS: &[Method] = &[Method::OPTIONS, Method::HEAD];
 
 const BEARER_PREFIX: &str = "Bearer ";
 
-pub async fn jwt_auth_middleware(mut request: Request, next: Next) -> Response {
+pub async fn jwt_auth_middleware(
+    State(session_repo): State<SessionRepoSSType>,
+    mut request: Request,
+    next: Next,
+) -> Response {
     let path = request.uri().path();
     if !path.starts_with("/api/admin")
         || WHITE_LIST.contains(&path)
@@ -25,32 +36,49 @@ pub async fn jwt_auth_middleware(mut request: Request, next: Next) -> Response {
         return next.run(request).await;
     }
 
-    let mut response = (StatusCode::UNAUTHORIZED).into_response();
-
-    // athentication, bearer
-    if let Some(bearer) = request.headers().get(AUTHORIZATION) {
-        if let Some(token) = bearer.to_str().unwrap().strip_prefix(BEARER_PREFIX) {
-            if let Ok(claims) = token.verify_with_key(&*config::JWT_KEY) {
-                if validate_claims(&claims) {
-                    match (
-                        claims.private.get(CLAIM_UID),
-                        claims.private.get(CLAIM_USERNAME),
-                    ) {
-                        (Some(id), Some(username)) => {
-                            let id: Uuid = serde_json::from_value(id.clone()).unwrap();
-                            let username: String =
-                                serde_json::from_value(username.clone()).unwrap();
-                            let au = AuthInformation { id, username };
-
-                            request.extensions_mut().insert(au);
-
-                            response = next.run(request).await;
-                        }
-                        _ => {}
-                    }
-                }
-            }
+    let Some(au) = authenticate(request.headers()) else {
+        return ApiError::Unauthorized.into_response();
+    };
+
+    // logged out, refreshed or the password has been changed
+    match session_repo.repo.is_revoked(&au.token_id).await {
+        Ok(false) => {
+            request.extensions_mut().insert(au);
+            next.run(request).await
         }
+        Ok(true) => ApiError::Unauthorized.into_response(),
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// athentication, bearer
+pub fn authenticate(headers: &HeaderMap) -> Option<AuthInformation> {
+    let token = headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix(BEARER_PREFIX)?;
+
+    let claims = config::JWT_KEYRING.verify(token)?;
+    if !validate_claims(&claims) {
+        return None;
     }
-    response
+
+    let token_id = claims.registered.json_web_token_id.clone()?;
+
+    let id: Uuid = serde_json::from_value(claims.private.get(CLAIM_UID)?.clone()).ok()?;
+    let username: String =
+        serde_json::from_value(claims.private.get(CLAIM_USERNAME)?.clone()).ok()?;
+    let role: String = serde_json::from_value(claims.private.get(CLAIM_ROLE)?.clone()).ok()?;
+
+    let session_id: Uuid =
+        serde_json::from_value(claims.private.get(CLAIM_SESSION_ID)?.clone()).ok()?;
+
+    Some(AuthInformation {
+        id,
+        username,
+        role,
+        session_id,
+        token_id,
+    })
 }
@@ -1,18 +1,38 @@
+pub mod authorization;
+pub mod client_ip;
 pub mod middlewares;
 pub mod shared_data;
+pub mod visitor;
+
+#[cfg(test)]
+mod test;
 
 use crate::controllers::{
     admin::{
-        change_password, check_logged_in, delete_meme, list_memes, log_in, post_memes,
-        update_categories,
+        approve_suggest, change_password, check_logged_in, clear_login_throttle, confirm_totp,
+        create_account, create_category, delete_account, delete_category, delete_meme,
+        disable_account, disable_totp, enable_account, enroll_totp, get_account, get_category_tree,
+        list_accounts, list_audit_logs, list_duplicates, list_login_throttles, list_memes,
+        list_roles, list_suggests, list_trash, log_in, log_in_two_factor, log_out,
+        merge_categories, merge_memes, move_category, post_memes, purge_trash, refresh_token,
+        refuse_suggest, remove_upload, rename_category, reset_password, restore_meme,
+        set_category_aliases, set_role_permissions, update_account, update_meme_categories,
+        upload_media,
     },
     client::{
-        interaction::{get_interactions, like_increase, unlike_increase},
-        ui::{create_suggest, get_categories, get_paginated_memes, meme_detail},
+        interaction::{
+            get_interactions, get_reactions, issue_visitor_token, like_increase, like_withdraw,
+            reaction_add, reaction_remove, unlike_increase, unlike_withdraw,
+        },
+        ui::{
+            create_suggest, get_categories, get_paginated_memes, get_trending_memes, meme_detail,
+            search_memes,
+        },
     },
 };
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     http::{
         HeaderName, HeaderValue, Method,
         header::{AUTHORIZATION, CONTENT_TYPE},
@@ -22,8 +42,11 @@ use axum::{
 };
 use middlewares::{CipherLayer, jwt_auth_middleware};
 use shared_data::{
-    AccountRepoSS, AccountRepoSSType, AppStates, CategoryRepoSS, CategoryRepoSSType,
-    IntoRepoSSType, MemeRepoSS, MemeRepoSSType, SuggestRepoSS, SuggestRepoSSType,
+    AccountRepoSS, AccountRepoSSType, AppStates, AuditRepoSS, AuditRepoSSType, CategoryRepoSS,
+    CategoryRepoSSType, DuplicateRepoSS, DuplicateRepoSSType, IntoRepoSSType, MemeRepoSS,
+    MemeRepoSSType, RoleRepoSS, RoleRepoSSType, SearchRepoSS, SearchRepoSSType, SessionRepoSS,
+    SessionRepoSSType, SuggestRepoSS, SuggestRepoSSType, ThrottleRepoSS, ThrottleRepoSSType,
+    TrendingRepoSS, TrendingRepoSSType, UploadRepoSS, UploadRepoSSType,
 };
 use soft_aes::aes::AES_BLOCK_SIZE;
 use tokio::net::TcpListener;
@@ -37,10 +60,46 @@ pub struct App {
 }
 
 impl App {
+    /// serve until ctrl-c or SIGTERM
     pub async fn run(self) {
-        axum::serve(self.listener, self.router).await.unwrap();
+        axum::serve(
+            self.listener,
+            self.router
+                .into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
     }
 }
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.unwrap();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .unwrap()
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("shutting down");
+}
+/// where the files of the media directory are served
+pub const MEDIA_PATH: &str = "/media";
+const DEFAULT_UPLOAD_LIMIT: usize = 20 * 1024 * 1024;
+
 pub struct AppBuilder {
     address: String,
     cors: String,
@@ -48,6 +107,18 @@ pub struct AppBuilder {
     category_repo: Option<CategoryRepoSSType>,
     meme_repo: Option<MemeRepoSSType>,
     suggest_repo: Option<SuggestRepoSSType>,
+    session_repo: Option<SessionRepoSSType>,
+    throttle_repo: Option<ThrottleRepoSSType>,
+    role_repo: Option<RoleRepoSSType>,
+    audit_repo: Option<AuditRepoSSType>,
+    search_repo: Option<SearchRepoSSType>,
+    trending_repo: Option<TrendingRepoSSType>,
+    upload_repo: Option<UploadRepoSSType>,
+    duplicate_repo: Option<DuplicateRepoSSType>,
+    /// served under `MEDIA_PATH` when set
+    media_dir: Option<String>,
+    /// the largest request body of an upload, in bytes
+    upload_limit: usize,
     aes_key: String,
     aes_iv: [u8; AES_BLOCK_SIZE],
 }
@@ -61,6 +132,16 @@ impl AppBuilder {
             category_repo: None,
             meme_repo: None,
             suggest_repo: None,
+            session_repo: None,
+            throttle_repo: None,
+            role_repo: None,
+            audit_repo: None,
+            search_repo: None,
+            trending_repo: None,
+            upload_repo: None,
+            duplicate_repo: None,
+            media_dir: None,
+            upload_limit: DEFAULT_UPLOAD_LIMIT,
             aes_key: String::new(),
             aes_iv: [0; 16],
         }
@@ -96,6 +177,56 @@ impl AppBuilder {
         self
     }
 
+    pub fn session_repo(mut self, repo: impl IntoRepoSSType<SessionRepoSSType>) -> Self {
+        self.session_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn throttle_repo(mut self, repo: impl IntoRepoSSType<ThrottleRepoSSType>) -> Self {
+        self.throttle_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn role_repo(mut self, repo: impl IntoRepoSSType<RoleRepoSSType>) -> Self {
+        self.role_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn audit_repo(mut self, repo: impl IntoRepoSSType<AuditRepoSSType>) -> Self {
+        self.audit_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn search_repo(mut self, repo: impl IntoRepoSSType<SearchRepoSSType>) -> Self {
+        self.search_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn trending_repo(mut self, repo: impl IntoRepoSSType<TrendingRepoSSType>) -> Self {
+        self.trending_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn upload_repo(mut self, repo: impl IntoRepoSSType<UploadRepoSSType>) -> Self {
+        self.upload_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn duplicate_repo(mut self, repo: impl IntoRepoSSType<DuplicateRepoSSType>) -> Self {
+        self.duplicate_repo = Some(repo.into_shared());
+        self
+    }
+
+    pub fn media_dir(mut self, media_dir: String) -> Self {
+        self.media_dir = Some(media_dir);
+        self
+    }
+
+    pub fn upload_limit(mut self, upload_limit: usize) -> Self {
+        self.upload_limit = upload_limit;
+        self
+    }
+
     pub fn aes_key(mut self, aes_key: String) -> Self {
         self.aes_key = aes_key;
         self
@@ -117,28 +248,83 @@ impl AppBuilder {
                 Router::new()
                     .route("/check-logged-in", get(check_logged_in))
                     .route("/login", post(log_in))
+                    .route("/login/two-factor", post(log_in_two_factor))
+                    .route("/logout", post(log_out))
+                    .route("/two-factor/enroll", post(enroll_totp))
+                    .route("/two-factor/confirm", post(confirm_totp))
+                    .route("/two-factor/disable", post(disable_totp))
+                    .route("/token/refresh", post(refresh_token))
+                    .route("/login/throttles", get(list_login_throttles))
+                    .route(
+                        "/login/throttles/{kind}/{key}",
+                        delete(clear_login_throttle),
+                    )
+                    .route("/accounts", get(list_accounts).post(create_account))
+                    .route(
+                        "/accounts/{id}",
+                        get(get_account).put(update_account).delete(delete_account),
+                    )
+                    .route("/accounts/{id}/password", put(reset_password))
+                    .route("/accounts/{id}/disable", put(disable_account))
+                    .route("/accounts/{id}/enable", put(enable_account))
+                    .route("/audit-logs", get(list_audit_logs))
+                    .route("/roles", get(list_roles))
+                    .route("/roles/{name}/permissions", put(set_role_permissions))
                     .route("/change-password", put(change_password))
-                    .route("/categories", get(get_categories))
-                    .route("/categories/{meme_id}", put(update_categories))
+                    .route("/categories", get(get_categories).post(create_category))
+                    .route("/categories/tree", get(get_category_tree))
+                    .route("/categories/merge", post(merge_categories))
+                    // `PUT` is the released path of `/memes/{id}/categories`, kept for old clients
+                    .route(
+                        "/categories/{id}",
+                        put(update_meme_categories).delete(delete_category),
+                    )
+                    .route("/categories/{id}/parent", put(move_category))
+                    .route("/categories/{id}/name", put(rename_category))
+                    .route("/categories/{id}/aliases", put(set_category_aliases))
                     .route("/post-memes", post(post_memes))
                     .route("/memes", get(list_memes))
-                    .route("/memes/{id}", delete(delete_meme)),
+                    .route("/memes/trash", get(list_trash).delete(purge_trash))
+                    .route("/memes/duplicates", get(list_duplicates))
+                    .route("/memes/{id}", delete(delete_meme))
+                    .route("/memes/{id}/categories", put(update_meme_categories))
+                    .route("/memes/{id}/restore", put(restore_meme))
+                    .route("/memes/{id}/merge", post(merge_memes))
+                    .route("/suggests", get(list_suggests))
+                    .route("/suggests/{id}/approve", put(approve_suggest))
+                    .route("/suggests/{id}/refuse", put(refuse_suggest))
+                    .route(
+                        "/uploads",
+                        post(upload_media).layer(DefaultBodyLimit::max(self.upload_limit)),
+                    )
+                    .route("/uploads/{bed_id}", delete(remove_upload)),
             )
             .nest(
                 "/client",
                 Router::new()
                     .route("/categories", get(get_categories))
                     .route("/memes", get(get_paginated_memes))
+                    .route("/memes/search", get(search_memes))
+                    .route("/memes/trending", get(get_trending_memes))
                     .route("/memes/interactions", post(get_interactions))
                     .route("/memes/{id}", get(meme_detail))
-                    .route("/memes/{id}/like", put(like_increase))
-                    .route("/memes/{id}/unlike", put(unlike_increase))
-                    .route("/suggests", post(create_suggest)),
+                    .route("/memes/{id}/like", put(like_increase).delete(like_withdraw))
+                    .route(
+                        "/memes/{id}/unlike",
+                        put(unlike_increase).delete(unlike_withdraw),
+                    )
+                    .route(
+                        "/memes/{id}/reactions/{reaction}",
+                        put(reaction_add).delete(reaction_remove),
+                    )
+                    .route("/reactions", get(get_reactions))
+                    .route("/suggests", post(create_suggest))
+                    .route("/visitor", post(issue_visitor_token)),
             )
             .with_state(app_state.clone());
         // .with_state(meme_repo);
 
-        let router = Router::new()
+        let mut router = Router::new()
             // .route("/", get(home))
             .nest("/api", api_routes)
             .nest_service(
@@ -149,6 +335,10 @@ impl AppBuilder {
                 "/favicon.ico",
                 tower_http::services::ServeDir::new("wwwroot/favicon.ico"),
             );
+        if let Some(media_dir) = &self.media_dir {
+            router =
+                router.nest_service(MEDIA_PATH, tower_http::services::ServeDir::new(media_dir));
+        }
 
         let cors_layer = self.build_cors();
 
@@ -158,13 +348,13 @@ impl AppBuilder {
                 .layer(cors_layer)
                 // .layer(middleware::from_fn(crate::middleware::cipher_middleware))
                 .layer(CipherLayer::new(self.aes_key.clone(), self.aes_iv.clone()))
-                .layer(middleware::from_fn(jwt_auth_middleware)),
+                .layer(middleware::from_fn_with_state(
+                    app_state,
+                    jwt_auth_middleware,
+                )),
         );
 
-        axum::serve(listener, router).await.unwrap();
-
-        todo!()
-        // App { listener, router }
+        App { listener, router }
     }
 
     fn build_state(&mut self) -> AppStates {
@@ -192,11 +382,67 @@ impl AppBuilder {
             SuggestRepoSS::non().into_shared()
         };
 
+        let session_repo = if let Some(session_repo) = self.session_repo.take() {
+            session_repo
+        } else {
+            SessionRepoSS::non().into_shared()
+        };
+
+        let throttle_repo = if let Some(throttle_repo) = self.throttle_repo.take() {
+            throttle_repo
+        } else {
+            ThrottleRepoSS::non().into_shared()
+        };
+
+        let role_repo = if let Some(role_repo) = self.role_repo.take() {
+            role_repo
+        } else {
+            RoleRepoSS::non().into_shared()
+        };
+
+        let audit_repo = if let Some(audit_repo) = self.audit_repo.take() {
+            audit_repo
+        } else {
+            AuditRepoSS::non().into_shared()
+        };
+
+        let search_repo = if let Some(search_repo) = self.search_repo.take() {
+            search_repo
+        } else {
+            SearchRepoSS::non().into_shared()
+        };
+
+        let trending_repo = if let Some(trending_repo) = self.trending_repo.take() {
+            trending_repo
+        } else {
+            TrendingRepoSS::non().into_shared()
+        };
+
+        let upload_repo = if let Some(upload_repo) = self.upload_repo.take() {
+            upload_repo
+        } else {
+            UploadRepoSS::non().into_shared()
+        };
+
+        let duplicate_repo = if let Some(duplicate_repo) = self.duplicate_repo.take() {
+            duplicate_repo
+        } else {
+            DuplicateRepoSS::non().into_shared()
+        };
+
         AppStates {
             account_repo: acc_repo,
             cate_repo,
             meme_repo,
             suggest_repo,
+            session_repo,
+            throttle_repo,
+            role_repo,
+            audit_repo,
+            search_repo,
+            trending_repo,
+            upload_repo,
+            duplicate_repo,
         }
     }
 
@@ -224,6 +470,7 @@ impl AppBuilder {
                 AUTHORIZATION,
                 CONTENT_TYPE,
                 HeaderName::from_lowercase(b"x-date").unwrap(),
+                visitor::X_VISITOR_TOKEN.clone(),
             ])
     }
 
@@ -5,9 +5,17 @@ use tokio::sync::RwLock;
 
 use crate::business::{
     accounts::{AccountRepository, PanicAccountRepo},
+    audit::{AuditRepository, PanicAuditRepository},
     category::{CategoryRepository, PanicCategoryRepo},
+    duplicates::{DuplicateRepository, PanicDuplicateRepository},
     meme::{MemeRepository, PanicMemeRepository},
+    roles::{PanicRoleRepository, RoleRepository},
+    search::{PanicSearchRepository, SearchRepository},
+    sessions::{PanicSessionRepository, SessionRepository},
     suggests::{PanicSuggestRepository, SuggestRepository},
+    throttle::{PanicThrottleRepository, ThrottleRepository},
+    trending::{PanicTrendingRepository, TrendingRepository},
+    uploads::{PanicUploadRepository, UploadRepository},
 };
 
 #[derive(Clone)]
@@ -16,6 +24,14 @@ pub struct AppStates {
     pub meme_repo: MemeRepoSSType,
     pub cate_repo: CategoryRepoSSType,
     pub suggest_repo: SuggestRepoSSType,
+    pub session_repo: SessionRepoSSType,
+    pub throttle_repo: ThrottleRepoSSType,
+    pub role_repo: RoleRepoSSType,
+    pub audit_repo: AuditRepoSSType,
+    pub search_repo: SearchRepoSSType,
+    pub trending_repo: TrendingRepoSSType,
+    pub upload_repo: UploadRepoSSType,
+    pub duplicate_repo: DuplicateRepoSSType,
 }
 
 impl FromRef<AppStates> for AccountRepoSSType {
@@ -42,6 +58,54 @@ impl FromRef<AppStates> for SuggestRepoSSType {
     }
 }
 
+impl FromRef<AppStates> for SessionRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.session_repo)
+    }
+}
+
+impl FromRef<AppStates> for ThrottleRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.throttle_repo)
+    }
+}
+
+impl FromRef<AppStates> for RoleRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.role_repo)
+    }
+}
+
+impl FromRef<AppStates> for AuditRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.audit_repo)
+    }
+}
+
+impl FromRef<AppStates> for SearchRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.search_repo)
+    }
+}
+
+impl FromRef<AppStates> for TrendingRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.trending_repo)
+    }
+}
+
+impl FromRef<AppStates> for UploadRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.upload_repo)
+    }
+}
+
+impl FromRef<AppStates> for DuplicateRepoSSType {
+    fn from_ref(input: &AppStates) -> Self {
+        Arc::clone(&input.duplicate_repo)
+    }
+}
+
 pub type AccountRepoSSType = Arc<AccountRepoSS>;
 
 pub struct AccountRepoSS {
@@ -141,3 +205,195 @@ impl IntoRepoSSType<SuggestRepoSSType> for SuggestRepoSS {
         Arc::new(self)
     }
 }
+
+pub type SessionRepoSSType = Arc<SessionRepoSS>;
+
+pub struct SessionRepoSS {
+    pub repo: Box<dyn SessionRepository + 'static + Sync + Send>,
+}
+
+impl SessionRepoSS {
+    pub fn new(repo: impl SessionRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicSessionRepository)
+    }
+}
+
+impl IntoRepoSSType<SessionRepoSSType> for SessionRepoSS {
+    fn into_shared(self) -> SessionRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type ThrottleRepoSSType = Arc<ThrottleRepoSS>;
+
+pub struct ThrottleRepoSS {
+    pub repo: Box<dyn ThrottleRepository + 'static + Sync + Send>,
+}
+
+impl ThrottleRepoSS {
+    pub fn new(repo: impl ThrottleRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicThrottleRepository)
+    }
+}
+
+impl IntoRepoSSType<ThrottleRepoSSType> for ThrottleRepoSS {
+    fn into_shared(self) -> ThrottleRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type RoleRepoSSType = Arc<RoleRepoSS>;
+
+pub struct RoleRepoSS {
+    pub repo: Box<dyn RoleRepository + 'static + Sync + Send>,
+}
+
+impl RoleRepoSS {
+    pub fn new(repo: impl RoleRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicRoleRepository)
+    }
+}
+
+impl IntoRepoSSType<RoleRepoSSType> for RoleRepoSS {
+    fn into_shared(self) -> RoleRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type AuditRepoSSType = Arc<AuditRepoSS>;
+
+pub struct AuditRepoSS {
+    pub repo: Box<dyn AuditRepository + 'static + Sync + Send>,
+}
+
+impl AuditRepoSS {
+    pub fn new(repo: impl AuditRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicAuditRepository)
+    }
+}
+
+impl IntoRepoSSType<AuditRepoSSType> for AuditRepoSS {
+    fn into_shared(self) -> AuditRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type SearchRepoSSType = Arc<SearchRepoSS>;
+
+pub struct SearchRepoSS {
+    pub repo: Box<dyn SearchRepository + 'static + Sync + Send>,
+}
+
+impl SearchRepoSS {
+    pub fn new(repo: impl SearchRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicSearchRepository)
+    }
+}
+
+impl IntoRepoSSType<SearchRepoSSType> for SearchRepoSS {
+    fn into_shared(self) -> SearchRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type TrendingRepoSSType = Arc<TrendingRepoSS>;
+
+pub struct TrendingRepoSS {
+    pub repo: Box<dyn TrendingRepository + 'static + Sync + Send>,
+}
+
+impl TrendingRepoSS {
+    pub fn new(repo: impl TrendingRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicTrendingRepository)
+    }
+}
+
+impl IntoRepoSSType<TrendingRepoSSType> for TrendingRepoSS {
+    fn into_shared(self) -> TrendingRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type UploadRepoSSType = Arc<UploadRepoSS>;
+
+pub struct UploadRepoSS {
+    pub repo: Box<dyn UploadRepository + 'static + Sync + Send>,
+}
+
+impl UploadRepoSS {
+    pub fn new(repo: impl UploadRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicUploadRepository)
+    }
+}
+
+impl IntoRepoSSType<UploadRepoSSType> for UploadRepoSS {
+    fn into_shared(self) -> UploadRepoSSType {
+        Arc::new(self)
+    }
+}
+
+pub type DuplicateRepoSSType = Arc<DuplicateRepoSS>;
+
+pub struct DuplicateRepoSS {
+    pub repo: Box<dyn DuplicateRepository + 'static + Sync + Send>,
+}
+
+impl DuplicateRepoSS {
+    pub fn new(repo: impl DuplicateRepository + 'static + Sync + Send) -> Self {
+        Self {
+            repo: Box::new(repo),
+        }
+    }
+
+    pub fn non() -> Self {
+        Self::new(PanicDuplicateRepository)
+    }
+}
+
+impl IntoRepoSSType<DuplicateRepoSSType> for DuplicateRepoSS {
+    fn into_shared(self) -> DuplicateRepoSSType {
+        Arc::new(self)
+    }
+}
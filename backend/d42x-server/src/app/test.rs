@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use axum::http::{HeaderMap, HeaderValue, Request, Uri, header::AUTHORIZATION};
+    use axum_extra::extract::Query;
+    use pretty_assertions::assert_eq;
+    use sea_orm::prelude::Uuid;
+
+    use crate::{
+        app::{
+            client_ip::resolve_client_ip,
+            shared_data::{IntoRepoSSType, SessionRepoSS},
+            visitor::{Visitor, X_VISITOR_TOKEN, resolve_visitor_with},
+        },
+        business::meme::ListFilter,
+        controllers::{ApiError, list_filter::ListFilterQuery},
+    };
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("1.1.1.1"));
+
+        let client = resolve_client_ip(&headers, ip("2.2.2.2"), &[ip("10.0.0.1")]);
+
+        assert_eq!(client, ip("2.2.2.2"));
+    }
+
+    #[test]
+    fn trusted_peer_uses_rightmost_untrusted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("6.6.6.6, 1.1.1.1, 10.0.0.2"),
+        );
+
+        let client = resolve_client_ip(&headers, ip("10.0.0.1"), &[ip("10.0.0.1"), ip("10.0.0.2")]);
+
+        assert_eq!(client, ip("1.1.1.1"));
+    }
+
+    #[test]
+    fn trusted_peer_ignores_forged_hops() {
+        // a garbage hop on the left set by the client does not hide the real one
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("not-an-ip, 1.1.1.1"),
+        );
+        let client = resolve_client_ip(&headers, ip("10.0.0.1"), &[ip("10.0.0.1")]);
+        assert_eq!(client, ip("1.1.1.1"));
+
+        // nothing left of a garbage hop is trusted
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("6.6.6.6, not-an-ip, 10.0.0.2"),
+        );
+        let client = resolve_client_ip(&headers, ip("10.0.0.1"), &[ip("10.0.0.1"), ip("10.0.0.2")]);
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn trusted_peer_ignores_real_ip() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("1.1.1.1"));
+
+        let client = resolve_client_ip(&headers, ip("10.0.0.1"), &[ip("10.0.0.1")]);
+
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn list_filter_repeated_keys() {
+        let uri: Uri =
+            "/memes?category=cat&categories=a,b&categories=%20dog%20&categories=&exclude=x"
+                .parse()
+                .unwrap();
+        let Query(query) = Query::<ListFilterQuery>::try_from_uri(&uri).unwrap();
+        let filter = ListFilter::from(query);
+
+        // a comma is part of a name
+        assert_eq!(filter.categories, vec!["cat", "a,b", "dog"]);
+        assert_eq!(filter.exclude_categories, vec!["x"]);
+    }
+
+    #[tokio::test]
+    async fn expired_bearer_does_not_fall_back_to_visitor() {
+        let device = Uuid::now_v7();
+        let session_repo = SessionRepoSS::non().into_shared();
+
+        let (parts, _) = Request::builder()
+            .header(AUTHORIZATION, "Bearer expired")
+            .header(&X_VISITOR_TOKEN, "visitor")
+            .body(())
+            .unwrap()
+            .into_parts();
+        // an expired access token does not verify
+        let res = resolve_visitor_with(&parts, &session_repo, |_| None, |_| Some(device)).await;
+        assert!(matches!(res, Err(ApiError::Unauthorized)));
+
+        let (parts, _) = Request::builder()
+            .header(&X_VISITOR_TOKEN, "visitor")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let res = resolve_visitor_with(&parts, &session_repo, |_| None, |_| Some(device)).await;
+        assert_eq!(res.unwrap(), Some(Visitor::Device(device)));
+
+        let (parts, _) = Request::builder().body(()).unwrap().into_parts();
+        let res = resolve_visitor_with(&parts, &session_repo, |_| None, |_| Some(device)).await;
+        assert_eq!(res.unwrap(), None);
+    }
+}
@@ -0,0 +1,117 @@
+//! Visitor
+//!
+//! who votes on memes: the logged in account of a bearer access token, otherwise the
+//! anonymous device of a visitor token from `POST /api/client/visitor`, sent back in
+//! `X-Visitor-Token`. `Option<Visitor>` accepts requests without either of them
+
+use axum::{
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
+    http::{HeaderMap, HeaderName, header::AUTHORIZATION, request::Parts},
+};
+use sea_orm::prelude::Uuid;
+
+use crate::{
+    app::{middlewares::authenticate, shared_data::SessionRepoSSType},
+    authentication::{AuthInformation, verify_visitor_token},
+    business::meme::Voter,
+    config,
+    controllers::ApiError,
+};
+
+pub static X_VISITOR_TOKEN: HeaderName = HeaderName::from_static("x-visitor-token");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visitor {
+    Account(Uuid),
+    Device(Uuid),
+}
+
+impl Visitor {
+    /// one vote per meme is kept for each key
+    pub fn key(&self) -> String {
+        match self {
+            Visitor::Account(id) => format!("account:{}", id.simple()),
+            Visitor::Device(id) => format!("device:{}", id.simple()),
+        }
+    }
+
+    /// the key and its hash keyed by `AES_KEY`, which is all the interaction log gets
+    pub fn voter(&self) -> Voter {
+        let key = self.key();
+        let secret = blake3::derive_key("d42x visitor id", config::KEY.as_bytes());
+        let client_id = blake3::keyed_hash(&secret, key.as_bytes()).to_hex()[..32].to_owned();
+
+        Voter { key, client_id }
+    }
+}
+
+impl<S> FromRequestParts<S> for Visitor
+where
+    S: Send + Sync,
+    SessionRepoSSType: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        resolve_visitor(parts, state)
+            .await?
+            .ok_or(ApiError::Unauthorized)
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for Visitor
+where
+    S: Send + Sync,
+    SessionRepoSSType: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        resolve_visitor(parts, state).await
+    }
+}
+
+/// credentials that are sent but not valid are rejected rather than ignored,
+/// an expired access token does not fall back to the visitor token
+async fn resolve_visitor<S>(parts: &Parts, state: &S) -> Result<Option<Visitor>, ApiError>
+where
+    SessionRepoSSType: FromRef<S>,
+{
+    resolve_visitor_with(parts, state, authenticate, verify_visitor_token).await
+}
+
+/// `resolve_visitor` with the checks of the access token and of the visitor token
+pub async fn resolve_visitor_with<S>(
+    parts: &Parts,
+    state: &S,
+    authenticate: impl FnOnce(&HeaderMap) -> Option<AuthInformation>,
+    verify_visitor_token: impl FnOnce(&str) -> Option<Uuid>,
+) -> Result<Option<Visitor>, ApiError>
+where
+    SessionRepoSSType: FromRef<S>,
+{
+    if parts.headers.contains_key(AUTHORIZATION) {
+        let au = authenticate(&parts.headers).ok_or(ApiError::Unauthorized)?;
+
+        let session_repo = SessionRepoSSType::from_ref(state);
+        if session_repo.repo.is_revoked(&au.token_id).await? {
+            return Err(ApiError::Unauthorized);
+        }
+
+        return Ok(Some(Visitor::Account(au.id)));
+    }
+
+    let Some(token) = parts.headers.get(&X_VISITOR_TOKEN) else {
+        return Ok(None);
+    };
+
+    token
+        .to_str()
+        .ok()
+        .and_then(verify_visitor_token)
+        .map(|id| Some(Visitor::Device(id)))
+        .ok_or(ApiError::Unauthorized)
+}
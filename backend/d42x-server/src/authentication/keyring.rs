@@ -0,0 +1,220 @@
+//! JWT signing keys
+//!
+//! a token is signed by the active key and carries the key id in its `kid` header,
+//! the other keys of the keyring are kept for verification during a rotation.
+//!
+//! the keyring comes either from a key file (`JWT_KEY_FILE`):
+//! ```json
+//! {
+//!     "active": "k1a2b3c4d5",
+//!     "keys": [{ "kid": "k1a2b3c4d5", "secret": "...", "created_date_time": "2025-04-20T00:00:00Z" }]
+//! }
+//! ```
+//! or from a single secret (`JWT_SECRET`, `JWT_KID`)
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use jwt::{AlgorithmType, Claims, Header, SignWithKey, Token, VerifyWithStore};
+use rand::{RngCore, rngs::OsRng};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+/// the shortest secret accepted for HS256
+pub const MIN_SECRET_LENGTH: usize = 32;
+const SECRET_BYTES: usize = 32;
+const KID_LENGTH: usize = 10;
+
+pub type KeyringResult<T> = Result<T, KeyringError>;
+
+#[derive(Error, Debug)]
+pub enum KeyringError {
+    #[error("cannot access key file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid key file: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("secret of key `{0}` is shorter than {MIN_SECRET_LENGTH}")]
+    WeakSecret(String),
+    #[error("key `{0}` not found")]
+    KeyNotFound(String),
+    #[error("key file already exists: {0}")]
+    AlreadyExists(String),
+    #[error("no key file, set JWT_KEY_FILE or --file")]
+    NoKeyFile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyEntry {
+    pub kid: String,
+    pub secret: String,
+    pub created_date_time: DateTime<Utc>,
+}
+
+impl KeyEntry {
+    /// a new key with a random secret
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+
+        Self {
+            kid: format!("k{}", nanoid::nanoid!(KID_LENGTH)),
+            secret: hex::encode(bytes),
+            created_date_time: Utc::now(),
+        }
+    }
+}
+
+/// the persisted form of a keyring
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyFile {
+    pub active: String,
+    pub keys: Vec<KeyEntry>,
+}
+
+impl KeyFile {
+    /// a key file with a single, active key
+    pub fn generate() -> Self {
+        let key = KeyEntry::generate();
+        Self {
+            active: key.kid.clone(),
+            keys: vec![key],
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> KeyringResult<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// write to a temporary file first, a crashed write never leaves a half key file behind
+    pub fn save(&self, path: impl AsRef<Path>) -> KeyringResult<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))?;
+        }
+        fs::rename(tmp, path)?;
+
+        Ok(())
+    }
+
+    /// add a new key, with `activate` it signs the new tokens from now on,
+    /// otherwise it is only staged for verification until `activate` is called.
+    ///
+    /// only the `keep` newest keys are kept, the active key is never removed
+    pub fn rotate(&mut self, keep: usize, activate: bool) -> KeyEntry {
+        let key = KeyEntry::generate();
+        self.keys.push(key.clone());
+        if activate {
+            self.active = key.kid.clone();
+        }
+        self.prune(keep);
+
+        key
+    }
+
+    pub fn activate(&mut self, kid: &str) -> KeyringResult<()> {
+        if !self.keys.iter().any(|k| k.kid == kid) {
+            return Err(KeyringError::KeyNotFound(kid.to_owned()));
+        }
+        self.active = kid.to_owned();
+
+        Ok(())
+    }
+
+    fn prune(&mut self, keep: usize) {
+        self.keys
+            .sort_by_key(|k| std::cmp::Reverse(k.created_date_time));
+
+        let mut kept = 0;
+        let active = self.active.clone();
+        self.keys.retain(|k| {
+            kept += 1;
+            k.kid == active || kept <= keep
+        });
+    }
+}
+
+/// signing and verification keys, indexed by key id
+#[derive(Clone, Debug)]
+pub struct JwtKeyring {
+    active: String,
+    keys: BTreeMap<String, Hmac<Sha256>>,
+}
+
+impl JwtKeyring {
+    /// a keyring that only has one key
+    pub fn from_secret(kid: &str, secret: &str) -> KeyringResult<Self> {
+        Self::try_from(KeyFile {
+            active: kid.to_owned(),
+            keys: vec![KeyEntry {
+                kid: kid.to_owned(),
+                secret: secret.to_owned(),
+                created_date_time: Utc::now(),
+            }],
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> KeyringResult<Self> {
+        Self::try_from(KeyFile::load(path)?)
+    }
+
+    pub fn active_kid(&self) -> &str {
+        &self.active
+    }
+
+    pub fn contains(&self, kid: &str) -> bool {
+        self.keys.contains_key(kid)
+    }
+
+    /// sign by the active key, the key id is written to the `kid` header
+    pub fn sign(&self, claims: Claims) -> Result<String, jwt::Error> {
+        let header = Header {
+            algorithm: AlgorithmType::Hs256,
+            key_id: Some(self.active.clone()),
+            ..Default::default()
+        };
+
+        let token = Token::new(header, claims).sign_with_key(&self.keys[&self.active])?;
+        Ok(token.into())
+    }
+
+    /// verify by the key named by the `kid` header, only the signature is checked here
+    pub fn verify(&self, token: &str) -> Option<Claims> {
+        let token: Token<Header, Claims, _> = token.verify_with_store(&self.keys).ok()?;
+        let (_, claims) = token.into();
+
+        Some(claims)
+    }
+}
+
+impl TryFrom<KeyFile> for JwtKeyring {
+    type Error = KeyringError;
+
+    fn try_from(value: KeyFile) -> Result<Self, Self::Error> {
+        let mut keys = BTreeMap::new();
+        for key in value.keys {
+            if key.secret.len() < MIN_SECRET_LENGTH {
+                return Err(KeyringError::WeakSecret(key.kid));
+            }
+            let hmac = Hmac::new_from_slice(key.secret.as_bytes())
+                .map_err(|_| KeyringError::WeakSecret(key.kid.clone()))?;
+            keys.insert(key.kid, hmac);
+        }
+
+        if !keys.contains_key(&value.active) {
+            return Err(KeyringError::KeyNotFound(value.active));
+        }
+
+        Ok(Self {
+            active: value.active,
+            keys,
+        })
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use crate::config;
+use chrono::{DateTime, Utc};
+use jwt::{Claims, RegisteredClaims};
+use sea_orm::prelude::Uuid;
+
+pub mod keyring;
+#[cfg(test)]
+mod test;
+
+pub const CLAIM_UID: &str = "UID";
+pub const CLAIM_USERNAME: &str = "USERNAME";
+pub const CLAIM_SESSION_ID: &str = "SID";
+pub const CLAIM_ROLE: &str = "ROLE";
+
+const SUBJECT: &str = "user.log_in";
+/// the password is verified, the second factor is not
+const CHALLENGE_SUBJECT: &str = "user.two_factor";
+const CHALLENGE_LIFETIME: Duration = Duration::from_secs(5 * 60);
+/// an anonymous device, it can only vote on memes
+const VISITOR_SUBJECT: &str = "visitor.device";
+const VISITOR_LIFETIME: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+#[derive(Clone, Debug)]
+pub struct AuthInformation {
+    pub id: Uuid,
+    pub username: String,
+    /// role when the token was issued, see `business::roles`
+    pub role: String,
+    pub session_id: Uuid,
+    /// `jti` of the access token
+    pub token_id: String,
+}
+
+/// identity of an access token, it is stored with the session before the token is signed
+#[derive(Clone, Debug)]
+pub struct AccessTokenMeta {
+    pub token_id: String,
+    pub expiration: DateTime<Utc>,
+}
+
+impl AccessTokenMeta {
+    /// a new token id, expires after `EXP` seconds
+    pub fn new() -> Self {
+        Self::with_lifetime(Duration::from_secs(*config::EXP as u64))
+    }
+
+    pub fn with_lifetime(lifetime: Duration) -> Self {
+        Self {
+            token_id: Uuid::new_v4().to_string(),
+            expiration: Utc::now() + lifetime,
+        }
+    }
+}
+
+impl Default for AccessTokenMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn gen_jwt_token(
+    uid: &Uuid,
+    username: &str,
+    role: &str,
+    session_id: &Uuid,
+    meta: &AccessTokenMeta,
+) -> String {
+    let now = chrono::Utc::now();
+
+    let mut claims = Claims::new(RegisteredClaims {
+        issuer: Some(config::ISS.to_string()),
+        subject: Some(SUBJECT.to_owned()),
+        audience: Some(config::AUD.to_string()),
+        expiration: Some(meta.expiration.timestamp() as u64),
+        not_before: Some(now.timestamp() as u64),
+        issued_at: Some(now.timestamp() as u64),
+        json_web_token_id: Some(meta.token_id.clone()),
+    });
+
+    claims
+        .private
+        .insert(CLAIM_UID.to_string(), serde_json::json!(uid));
+    claims
+        .private
+        .insert(CLAIM_USERNAME.to_string(), serde_json::json!(username));
+    claims
+        .private
+        .insert(CLAIM_ROLE.to_string(), serde_json::json!(role));
+    claims
+        .private
+        .insert(CLAIM_SESSION_ID.to_string(), serde_json::json!(session_id));
+
+    config::JWT_KEYRING.sign(claims).unwrap()
+}
+
+/// a short-lived token proving the password, exchanged for a session by a second factor code
+pub fn gen_challenge_token(uid: &Uuid, username: &str) -> String {
+    let now = chrono::Utc::now();
+
+    let mut claims = Claims::new(RegisteredClaims {
+        issuer: Some(config::ISS.to_string()),
+        subject: Some(CHALLENGE_SUBJECT.to_owned()),
+        audience: Some(config::AUD.to_string()),
+        expiration: Some((now + CHALLENGE_LIFETIME).timestamp() as u64),
+        not_before: Some(now.timestamp() as u64),
+        issued_at: Some(now.timestamp() as u64),
+        json_web_token_id: Some(Uuid::new_v4().to_string()),
+    });
+
+    claims
+        .private
+        .insert(CLAIM_UID.to_string(), serde_json::json!(uid));
+    claims
+        .private
+        .insert(CLAIM_USERNAME.to_string(), serde_json::json!(username));
+
+    config::JWT_KEYRING.sign(claims).unwrap()
+}
+
+/// the account id and username of a valid challenge token
+pub fn verify_challenge_token(token: &str) -> Option<(Uuid, String)> {
+    let claims = config::JWT_KEYRING.verify(token)?;
+    if !validate_claims_of(&claims, CHALLENGE_SUBJECT) {
+        return None;
+    }
+
+    let id: Uuid = serde_json::from_value(claims.private.get(CLAIM_UID)?.clone()).ok()?;
+    let username: String =
+        serde_json::from_value(claims.private.get(CLAIM_USERNAME)?.clone()).ok()?;
+
+    Some((id, username))
+}
+
+/// a token identifying an anonymous device, for one year
+pub fn gen_visitor_token(visitor_id: &Uuid) -> String {
+    let now = chrono::Utc::now();
+
+    let mut claims = Claims::new(RegisteredClaims {
+        issuer: Some(config::ISS.to_string()),
+        subject: Some(VISITOR_SUBJECT.to_owned()),
+        audience: Some(config::AUD.to_string()),
+        expiration: Some((now + VISITOR_LIFETIME).timestamp() as u64),
+        not_before: Some(now.timestamp() as u64),
+        issued_at: Some(now.timestamp() as u64),
+        json_web_token_id: Some(Uuid::new_v4().to_string()),
+    });
+
+    claims
+        .private
+        .insert(CLAIM_UID.to_string(), serde_json::json!(visitor_id));
+
+    config::JWT_KEYRING.sign(claims).unwrap()
+}
+
+/// the device id of a valid visitor token
+pub fn verify_visitor_token(token: &str) -> Option<Uuid> {
+    let claims = config::JWT_KEYRING.verify(token)?;
+    if !validate_claims_of(&claims, VISITOR_SUBJECT) {
+        return None;
+    }
+
+    serde_json::from_value(claims.private.get(CLAIM_UID)?.clone()).ok()
+}
+
+/// claims of an access token
+pub fn validate_claims(claims: &Claims) -> bool {
+    validate_claims_of(claims, SUBJECT)
+}
+
+fn validate_claims_of(claims: &Claims, subject: &str) -> bool {
+    let now = Utc::now();
+    let now_timestamp = now.timestamp() as u64;
+
+    match &claims.registered {
+        RegisteredClaims {
+            issuer: Some(iss),
+            subject: Some(sub),
+            audience: Some(aud),
+            expiration: Some(exp),
+            not_before: Some(nbe),
+            issued_at: Some(issat),
+            json_web_token_id: Some(_),
+        } if *iss == *config::ISS
+            && *sub == subject
+            && *aud == *config::AUD
+            && *exp > now_timestamp
+            && *nbe <= now_timestamp
+            && *issat <= now_timestamp =>
+        {
+            true
+        }
+        _ => false,
+    }
+}
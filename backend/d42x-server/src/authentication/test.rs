@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use jwt::{Claims, RegisteredClaims};
+    use pretty_assertions::assert_eq;
+
+    use crate::authentication::keyring::{JwtKeyring, KeyFile, KeyringError};
+
+    fn claims(jti: &str) -> Claims {
+        Claims::new(RegisteredClaims {
+            json_web_token_id: Some(jti.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn sign_verify_success() {
+        let keyring = JwtKeyring::try_from(KeyFile::generate()).unwrap();
+
+        let token = keyring.sign(claims("1")).unwrap();
+        let verified = keyring.verify(&token).unwrap();
+
+        assert_eq!(verified.registered.json_web_token_id, Some("1".to_owned()));
+    }
+
+    #[test]
+    fn verify_after_rotate_success() {
+        let mut key_file = KeyFile::generate();
+        let old_kid = key_file.active.clone();
+        let old_token = JwtKeyring::try_from(key_file.clone())
+            .unwrap()
+            .sign(claims("old"))
+            .unwrap();
+
+        let new_key = key_file.rotate(2, true);
+        let keyring = JwtKeyring::try_from(key_file).unwrap();
+
+        assert_eq!(keyring.active_kid(), new_key.kid);
+        assert!(keyring.contains(&old_kid));
+        assert!(keyring.verify(&old_token).is_some());
+    }
+
+    #[test]
+    fn verify_pruned_key_fail() {
+        let mut key_file = KeyFile::generate();
+        let old_token = JwtKeyring::try_from(key_file.clone())
+            .unwrap()
+            .sign(claims("old"))
+            .unwrap();
+
+        key_file.rotate(1, true);
+        let keyring = JwtKeyring::try_from(key_file).unwrap();
+
+        assert!(keyring.verify(&old_token).is_none());
+    }
+
+    #[test]
+    fn stage_keeps_active_key() {
+        let mut key_file = KeyFile::generate();
+        let active = key_file.active.clone();
+
+        let staged = key_file.rotate(1, false);
+
+        assert_eq!(key_file.active, active);
+        assert_eq!(key_file.keys.len(), 2);
+
+        key_file.activate(&staged.kid).unwrap();
+        assert_eq!(key_file.active, staged.kid);
+    }
+
+    #[test]
+    fn weak_secret_fail() {
+        let res = JwtKeyring::from_secret("default", "short");
+
+        assert!(matches!(res, Err(KeyringError::WeakSecret(_))));
+    }
+}
@@ -5,17 +5,26 @@
 //! the client passing the password that is the hashed_password getting blake3 and bcrypt
 //! so verify the password need bcrypt the password that getting blake3 from client and compares with hashed_password from database
 //! use function verify_password()
+//!
+//! ## About two-factor
+//! an account can enable a TOTP second factor, see `totp`.
+//! `begin_totp_enrollment` keeps a pending secret until `confirm_totp` receives a code of it,
+//! the confirmation returns the recovery codes, they are only shown once
 
 use bcrypt::verify;
-use db_entity::accounts;
-use sea_orm::ActiveModelTrait;
+use db_entity::{accounts, recovery_codes};
 use sea_orm::prelude::Uuid;
-use sea_orm::{ColumnTrait, DbErr, Set};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelBehavior, ActiveModelTrait, TransactionTrait};
+use sea_orm::{ColumnTrait, Condition, DbErr, Set};
 use sea_orm::{EntityTrait, QueryFilter};
+use serde::Serialize;
 use thiserror::Error;
 
 use crate::db::DbConnHelper;
 
+use super::totp;
+
 pub struct Administrator {
     db: Box<dyn DbConnHelper + 'static + Sync + Send>,
     pub model: accounts::Model,
@@ -32,7 +41,9 @@ impl Administrator {
             accounts::Entity::find()
                 .filter(accounts::Column::Username.eq(username.clone()))
                 // .filter(accounts::Column::HashedPassword.eq(hashed_password))
-                .filter(accounts::Column::IsAdmin.eq(true))
+                // an account without a role has no access
+                .filter(accounts::Column::Role.is_not_null())
+                .filter(accounts::Column::DisabledDateTime.is_null())
                 .one(&db_conn)
                 .await
                 .map_err(AdministratorError::from)?
@@ -56,7 +67,8 @@ impl Administrator {
 
         let admin = {
             accounts::Entity::find_by_id(id)
-                .filter(accounts::Column::IsAdmin.eq(true))
+                .filter(accounts::Column::Role.is_not_null())
+                .filter(accounts::Column::DisabledDateTime.is_null())
                 .one(&db_conn)
                 .await
                 .map_err(AdministratorError::from)?
@@ -72,6 +84,11 @@ impl Administrator {
         }
     }
 
+    /// name of the role, see `business::roles`
+    pub fn role(&self) -> &str {
+        self.model.role.as_deref().unwrap_or_default()
+    }
+
     pub async fn change_password(&mut self, cur_pwd: &str, new_pwd: &str) -> AdminResult<()> {
         if !self.verify_password(cur_pwd) {
             return Err(AdministratorError::IncorrectPassword);
@@ -95,7 +112,7 @@ impl Administrator {
     /// # Arguments
     /// hashed_password: the password getting blake3 and bcrypt from client
     pub fn verify_password(&self, hashed_password: &str) -> bool {
-        verify(&self.model.hashed_password, hashed_password).unwrap()
+        verify(&self.model.hashed_password, hashed_password).unwrap_or(false)
     }
 
     pub async fn log_in_activity(&mut self, ip_addr: &str) -> AdminResult<()> {
@@ -112,6 +129,169 @@ impl Administrator {
     }
 }
 
+/// a pending TOTP secret, it is enabled by `Administrator::confirm_totp`
+#[derive(Serialize, Debug)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+impl Administrator {
+    pub fn totp_enabled(&self) -> bool {
+        self.model.totp_enabled
+    }
+
+    /// create a pending secret, enrolling again replaces the pending one
+    pub async fn begin_totp_enrollment(&mut self, issuer: &str) -> AdminResult<TotpEnrollment> {
+        if self.model.totp_enabled {
+            return Err(AdministratorError::TotpAlreadyEnabled);
+        }
+
+        let secret = totp::generate_secret();
+
+        let mut model: accounts::ActiveModel = self.model.clone().into();
+        model.totp_secret = Set(Some(secret.clone()));
+        model.totp_last_step = Set(None);
+        model.update(&self.db.get_connection().await?).await?;
+
+        self.model.totp_secret = Some(secret.clone());
+        self.model.totp_last_step = None;
+
+        Ok(TotpEnrollment {
+            otpauth_uri: totp::otpauth_uri(issuer, &self.model.username, &secret),
+            secret,
+        })
+    }
+
+    /// enable the pending secret by a code of it, returns new recovery codes
+    pub async fn confirm_totp(&mut self, code: &str) -> AdminResult<Vec<String>> {
+        if self.model.totp_enabled {
+            return Err(AdministratorError::TotpAlreadyEnabled);
+        }
+        let secret = self
+            .model
+            .totp_secret
+            .clone()
+            .ok_or(AdministratorError::TotpNotEnrolled)?;
+
+        let step = totp::verify(&secret, code, chrono::Utc::now().timestamp(), None)
+            .ok_or(AdministratorError::InvalidTotpCode)?;
+
+        let codes = totp::generate_recovery_codes();
+
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let mut model: accounts::ActiveModel = self.model.clone().into();
+        model.totp_enabled = Set(true);
+        model.totp_last_step = Set(Some(step));
+        model.update(&txn).await?;
+
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::AccountId.eq(self.model.id))
+            .exec(&txn)
+            .await?;
+        recovery_codes::Entity::insert_many(codes.iter().map(|code| recovery_codes::ActiveModel {
+            account_id: Set(self.model.id),
+            code_hash: Set(totp::hash_recovery_code(code)),
+            ..recovery_codes::ActiveModel::new()
+        }))
+        .exec(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        self.model.totp_enabled = true;
+        self.model.totp_last_step = Some(step);
+
+        Ok(codes)
+    }
+
+    /// the password and a code, or a recovery code, are both required
+    pub async fn disable_totp(&mut self, hashed_password: &str, code: &str) -> AdminResult<()> {
+        if !self.model.totp_enabled {
+            return Err(AdministratorError::TotpNotEnabled);
+        }
+        if !self.verify_password(hashed_password) {
+            return Err(AdministratorError::IncorrectPassword);
+        }
+        if !self.verify_second_factor(code).await? {
+            return Err(AdministratorError::InvalidTotpCode);
+        }
+
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let mut model: accounts::ActiveModel = self.model.clone().into();
+        model.totp_enabled = Set(false);
+        model.totp_secret = Set(None);
+        model.totp_last_step = Set(None);
+        model.update(&txn).await?;
+
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::AccountId.eq(self.model.id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        self.model.totp_enabled = false;
+        self.model.totp_secret = None;
+        self.model.totp_last_step = None;
+
+        Ok(())
+    }
+
+    /// verify a TOTP code or a recovery code, both of them are only accepted once
+    pub async fn verify_second_factor(&mut self, code: &str) -> AdminResult<bool> {
+        let Some(secret) = self
+            .model
+            .totp_secret
+            .clone()
+            .filter(|_| self.model.totp_enabled)
+        else {
+            return Err(AdministratorError::TotpNotEnabled);
+        };
+
+        let db = self.db.get_connection().await?;
+
+        let now = chrono::Utc::now();
+        if let Some(step) = totp::verify(&secret, code, now.timestamp(), self.model.totp_last_step)
+        {
+            // another log in may have used the step since the account was loaded
+            let used = accounts::Entity::update_many()
+                .col_expr(accounts::Column::TotpLastStep, Expr::value(Some(step)))
+                .filter(accounts::Column::Id.eq(self.model.id))
+                .filter(
+                    Condition::any()
+                        .add(accounts::Column::TotpLastStep.is_null())
+                        .add(accounts::Column::TotpLastStep.lt(step)),
+                )
+                .exec(&db)
+                .await?;
+            if used.rows_affected == 0 {
+                return Ok(false);
+            }
+
+            self.model.totp_last_step = Some(step);
+            return Ok(true);
+        }
+
+        let used = recovery_codes::Entity::update_many()
+            .col_expr(
+                recovery_codes::Column::UsedDateTime,
+                Expr::value(Some(now.fixed_offset())),
+            )
+            .filter(recovery_codes::Column::AccountId.eq(self.model.id))
+            .filter(recovery_codes::Column::CodeHash.eq(totp::hash_recovery_code(code)))
+            .filter(recovery_codes::Column::UsedDateTime.is_null())
+            .exec(&db)
+            .await?;
+
+        Ok(used.rows_affected > 0)
+    }
+}
+
 pub type AdminResult<T> = Result<T, AdministratorError>;
 
 #[derive(Error, Debug)]
@@ -122,4 +302,20 @@ pub enum AdministratorError {
     NotFound(String),
     #[error("Incorrect password")]
     IncorrectPassword,
+    #[error("two-factor authentication is already enabled")]
+    TotpAlreadyEnabled,
+    #[error("two-factor authentication is not enabled")]
+    TotpNotEnabled,
+    #[error("two-factor enrollment has not been started")]
+    TotpNotEnrolled,
+    #[error("invalid two-factor code")]
+    InvalidTotpCode,
+    #[error("username already exists: {0}")]
+    UsernameTaken(String),
+    #[error("role not found: {0}")]
+    RoleNotFound(String),
+    #[error("cannot disable, delete or change the role of your own account")]
+    OwnAccount,
+    #[error("the last administrator cannot be disabled, deleted or changed")]
+    LastAdministrator,
 }
@@ -1,9 +1,24 @@
+use db_entity::{accounts, recovery_codes, roles};
 use migration::async_trait;
 use sea_orm::prelude::Uuid;
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde_json::json;
 
-use crate::db::DbConnHelper;
+use crate::{
+    business::{
+        audit::{self, Actor},
+        roles::ADMINISTRATOR,
+    },
+    db::DbConnHelper,
+};
 
-use super::{AccountRepository, admin::Administrator};
+use super::{
+    Account, AccountRepository, NewAccount, UpdateAccount,
+    admin::{AdminResult, Administrator, AdministratorError},
+};
 
 pub struct GenAccountRepo<T: DbConnHelper + Clone> {
     db: T,
@@ -20,11 +35,278 @@ impl<T> AccountRepository for GenAccountRepo<T>
 where
     T: DbConnHelper + 'static + Sync + Send + Clone,
 {
-    async fn get_administractor_by_id(&self, id: Uuid) -> Option<Administrator> {
-        Administrator::new_from_id(id, self.db.clone()).await.ok()
+    async fn get_administractor_by_id(&self, id: Uuid) -> AdminResult<Option<Administrator>> {
+        not_found_as_none(Administrator::new_from_id(id, self.db.clone()).await)
     }
 
-    async fn get_administractor_by_username(&self, username: String) -> Option<Administrator> {
-        Administrator::new(username, self.db.clone()).await.ok()
+    async fn get_administractor_by_username(
+        &self,
+        username: String,
+    ) -> AdminResult<Option<Administrator>> {
+        not_found_as_none(Administrator::new(username, self.db.clone()).await)
     }
+
+    async fn get_accounts(&self) -> AdminResult<Vec<Account>> {
+        let db = self.db.get_connection().await?;
+
+        let list = accounts::Entity::find()
+            .order_by_asc(accounts::Column::CreatedDateTime)
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(Account::from)
+            .collect();
+
+        Ok(list)
+    }
+
+    async fn get_account(&self, id: Uuid) -> AdminResult<Option<Account>> {
+        let db = self.db.get_connection().await?;
+
+        Ok(accounts::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .map(Account::from))
+    }
+
+    async fn get_account_by_username(&self, username: &str) -> AdminResult<Option<Account>> {
+        let db = self.db.get_connection().await?;
+
+        Ok(accounts::Entity::find()
+            .filter(accounts::Column::Username.eq(username))
+            .one(&db)
+            .await?
+            .map(Account::from))
+    }
+
+    async fn create_account(&self, actor: &Actor, account: NewAccount) -> AdminResult<Account> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        if accounts::Entity::find()
+            .filter(accounts::Column::Username.eq(&account.username))
+            .one(&txn)
+            .await?
+            .is_some()
+        {
+            return Err(AdministratorError::UsernameTaken(account.username));
+        }
+        ensure_role(&txn, &account.role).await?;
+
+        let model = accounts::ActiveModel {
+            username: Set(account.username),
+            email: Set(account.email),
+            hashed_password: Set(account.hashed_password),
+            is_admin: Set(account.role == ADMINISTRATOR),
+            role: Set(Some(account.role)),
+            ..accounts::ActiveModel::new()
+        }
+        .insert(&txn)
+        .await?;
+
+        audit::record(
+            &txn,
+            actor,
+            "account.create",
+            Some(model.id.to_string()),
+            json!({ "username": model.username, "email": model.email, "role": model.role }),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(model.into())
+    }
+
+    async fn update_account(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        update: UpdateAccount,
+    ) -> AdminResult<Account> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = find_account(&txn, id).await?;
+        let mut details = serde_json::Map::new();
+        let mut active: accounts::ActiveModel = model.clone().into();
+
+        if let Some(email) = update.email.filter(|email| *email != model.email) {
+            details.insert(String::from("email"), json!(email));
+            active.email = Set(email);
+        }
+
+        if let Some(role) = update.role.filter(|role| model.role.as_ref() != Some(role)) {
+            if actor.id == Some(id) {
+                return Err(AdministratorError::OwnAccount);
+            }
+            ensure_role(&txn, &role).await?;
+            ensure_not_last_administrator(&txn, &model).await?;
+
+            details.insert(
+                String::from("role"),
+                json!({ "from": model.role, "to": role }),
+            );
+            active.is_admin = Set(role == ADMINISTRATOR);
+            active.role = Set(Some(role));
+        }
+
+        if details.is_empty() {
+            return Ok(model.into());
+        }
+
+        let model = active.update(&txn).await?;
+
+        audit::record(
+            &txn,
+            actor,
+            "account.update",
+            Some(id.to_string()),
+            details.into(),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(model.into())
+    }
+
+    async fn reset_password(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        hashed_password: String,
+    ) -> AdminResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = find_account(&txn, id).await?;
+        let mut active: accounts::ActiveModel = model.into();
+        active.hashed_password = Set(hashed_password);
+        active.update(&txn).await?;
+
+        audit::record(
+            &txn,
+            actor,
+            "account.reset_password",
+            Some(id.to_string()),
+            json!({}),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    async fn set_disabled(&self, actor: &Actor, id: Uuid, disabled: bool) -> AdminResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = find_account(&txn, id).await?;
+        if model.disabled_date_time.is_some() == disabled {
+            return Ok(());
+        }
+        if disabled {
+            if actor.id == Some(id) {
+                return Err(AdministratorError::OwnAccount);
+            }
+            ensure_not_last_administrator(&txn, &model).await?;
+        }
+
+        let mut active: accounts::ActiveModel = model.into();
+        active.disabled_date_time = Set(disabled.then(|| chrono::Utc::now().into()));
+        active.update(&txn).await?;
+
+        audit::record(
+            &txn,
+            actor,
+            if disabled {
+                "account.disable"
+            } else {
+                "account.enable"
+            },
+            Some(id.to_string()),
+            json!({}),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_account(&self, actor: &Actor, id: Uuid) -> AdminResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = find_account(&txn, id).await?;
+        if actor.id == Some(id) {
+            return Err(AdministratorError::OwnAccount);
+        }
+        ensure_not_last_administrator(&txn, &model).await?;
+
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::AccountId.eq(id))
+            .exec(&txn)
+            .await?;
+        accounts::Entity::delete_by_id(id).exec(&txn).await?;
+
+        audit::record(
+            &txn,
+            actor,
+            "account.delete",
+            Some(id.to_string()),
+            json!({ "username": model.username }),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
+
+fn not_found_as_none(res: AdminResult<Administrator>) -> AdminResult<Option<Administrator>> {
+    match res {
+        Ok(admin) => Ok(Some(admin)),
+        Err(AdministratorError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn find_account(txn: &DatabaseTransaction, id: Uuid) -> AdminResult<accounts::Model> {
+    accounts::Entity::find_by_id(id)
+        .one(txn)
+        .await?
+        .ok_or_else(|| AdministratorError::NotFound(id.to_string()))
+}
+
+async fn ensure_role(txn: &DatabaseTransaction, role: &str) -> AdminResult<()> {
+    if roles::Entity::find_by_id(role).one(txn).await?.is_none() {
+        return Err(AdministratorError::RoleNotFound(role.to_owned()));
+    }
+
+    Ok(())
+}
+
+/// somebody has to be able to manage the accounts
+async fn ensure_not_last_administrator(
+    txn: &DatabaseTransaction,
+    model: &accounts::Model,
+) -> AdminResult<()> {
+    if model.role.as_deref() != Some(ADMINISTRATOR) || model.disabled_date_time.is_some() {
+        return Ok(());
+    }
+
+    let administrators = accounts::Entity::find()
+        .filter(accounts::Column::Role.eq(ADMINISTRATOR))
+        .filter(accounts::Column::DisabledDateTime.is_null())
+        .count(txn)
+        .await?;
+    if administrators <= 1 {
+        return Err(AdministratorError::LastAdministrator);
+    }
+
+    Ok(())
 }
@@ -1,20 +1,77 @@
+//! Accounts
+//!
+//! `Administrator` is an account that may log in, the other methods of `AccountRepository`
+//! manage the accounts themselves, every change is written to the audit log
+
+use chrono::{DateTime, FixedOffset};
+use db_entity::accounts;
 use migration::async_trait;
 use sea_orm::prelude::Uuid;
+use serde::Serialize;
+
+use super::audit::Actor;
 
 #[cfg(test)]
 mod test;
 
 pub mod admin;
 pub mod gen_account_repo;
+pub mod totp;
 
-use admin::Administrator;
+use admin::{AdminResult, Administrator};
 #[async_trait::async_trait]
 pub trait AccountRepository {
-    async fn get_administractor_by_id(&self, _id: Uuid) -> Option<Administrator> {
+    async fn get_administractor_by_id(&self, _id: Uuid) -> AdminResult<Option<Administrator>> {
+        unimplemented!()
+    }
+
+    async fn get_administractor_by_username(
+        &self,
+        _username: String,
+    ) -> AdminResult<Option<Administrator>> {
         unimplemented!()
     }
 
-    async fn get_administractor_by_username(&self, _username: String) -> Option<Administrator> {
+    async fn get_accounts(&self) -> AdminResult<Vec<Account>> {
+        unimplemented!()
+    }
+
+    async fn get_account(&self, _id: Uuid) -> AdminResult<Option<Account>> {
+        unimplemented!()
+    }
+
+    async fn get_account_by_username(&self, _username: &str) -> AdminResult<Option<Account>> {
+        unimplemented!()
+    }
+
+    async fn create_account(&self, _actor: &Actor, _account: NewAccount) -> AdminResult<Account> {
+        unimplemented!()
+    }
+
+    async fn update_account(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _update: UpdateAccount,
+    ) -> AdminResult<Account> {
+        unimplemented!()
+    }
+
+    /// `hashed_password` is hashed by `hash_password`, the same as the client does
+    async fn reset_password(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _hashed_password: String,
+    ) -> AdminResult<()> {
+        unimplemented!()
+    }
+
+    async fn set_disabled(&self, _actor: &Actor, _id: Uuid, _disabled: bool) -> AdminResult<()> {
+        unimplemented!()
+    }
+
+    async fn delete_account(&self, _actor: &Actor, _id: Uuid) -> AdminResult<()> {
         unimplemented!()
     }
 }
@@ -23,3 +80,52 @@ pub struct PanicAccountRepo;
 
 #[async_trait::async_trait]
 impl AccountRepository for PanicAccountRepo {}
+
+/// the stored form of a password, clients send the bcrypt of it to log in, see `admin`
+pub fn hash_password(password: &str) -> String {
+    blake3::hash(password.as_bytes()).to_hex().to_string()
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Account {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: Option<String>,
+    pub disabled: bool,
+    pub disabled_date_time: Option<DateTime<FixedOffset>>,
+    pub totp_enabled: bool,
+    pub created_date_time: DateTime<FixedOffset>,
+    pub last_activity_date_time: DateTime<FixedOffset>,
+}
+
+impl From<accounts::Model> for Account {
+    fn from(value: accounts::Model) -> Self {
+        Self {
+            id: value.id,
+            username: value.username,
+            email: value.email,
+            role: value.role,
+            disabled: value.disabled_date_time.is_some(),
+            disabled_date_time: value.disabled_date_time,
+            totp_enabled: value.totp_enabled,
+            created_date_time: value.created_date_time,
+            last_activity_date_time: value.last_actiity_date_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAccount {
+    pub username: String,
+    pub email: String,
+    pub hashed_password: String,
+    pub role: String,
+}
+
+/// the fields left `None` are unchanged
+#[derive(Debug, Clone, Default)]
+pub struct UpdateAccount {
+    pub email: Option<String>,
+    pub role: Option<String>,
+}
@@ -3,7 +3,16 @@ mod tests {
     use sea_orm::EntityTrait;
 
     use crate::{
-        business::accounts::{AccountRepository, gen_account_repo::GenAccountRepo},
+        business::accounts::{
+            AccountRepository, admin::AdministratorError, gen_account_repo::GenAccountRepo, totp,
+        },
+        business::{
+            accounts::{NewAccount, UpdateAccount, hash_password},
+            audit::{
+                Actor, AuditRepository, GetFilter, MAX_PAGE_SIZE, gen_audit_repo::GenAuditRepo,
+            },
+            roles::ADMINISTRATOR,
+        },
         db::{DbConnHelper, test::TestDB},
     };
     use pretty_assertions::assert_eq;
@@ -15,13 +24,15 @@ mod tests {
         let acc_repo = GenAccountRepo::new(db);
         let admin = acc_repo
             .get_administractor_by_username("dvorak".to_owned())
-            .await;
+            .await
+            .unwrap();
 
         assert!(admin.is_some());
         let admin = admin.unwrap();
 
         assert_eq!(admin.model.username, "dvorak".to_owned());
         assert!(admin.model.is_admin);
+        assert_eq!(admin.role(), ADMINISTRATOR);
     }
 
     #[tokio::test]
@@ -36,7 +47,7 @@ mod tests {
         let id = model.unwrap().id;
 
         let acc_repo = GenAccountRepo::new(db);
-        let admin = acc_repo.get_administractor_by_id(id).await;
+        let admin = acc_repo.get_administractor_by_id(id).await.unwrap();
 
         let admin = admin.unwrap();
 
@@ -44,4 +55,258 @@ mod tests {
         assert_eq!(admin.model.username, "dvorak".to_owned());
         assert!(admin.model.is_admin);
     }
+
+    /// RFC 6238 appendix B, SHA1
+    #[test]
+    fn totp_rfc_vectors() {
+        let secret = totp::base32_encode(b"12345678901234567890");
+
+        assert_eq!(secret, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(totp::code_at(&secret, totp::step_at(59)).unwrap(), "287082");
+        assert_eq!(
+            totp::code_at(&secret, totp::step_at(1111111109)).unwrap(),
+            "081804"
+        );
+        assert_eq!(
+            totp::base32_decode("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap(),
+            b"12345678901234567890"
+        );
+    }
+
+    #[tokio::test]
+    async fn totp_enroll_and_verify_success() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db);
+        let mut admin = acc_repo
+            .get_administractor_by_username("dvorak".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let enrollment = admin.begin_totp_enrollment("d42x").await.unwrap();
+        assert!(
+            enrollment
+                .otpauth_uri
+                .starts_with("otpauth://totp/d42x:dvorak?")
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let code = totp::code_at(&enrollment.secret, totp::step_at(now)).unwrap();
+        let recovery_codes = admin.confirm_totp(&code).await.unwrap();
+        assert!(admin.totp_enabled());
+
+        // a code is accepted only once
+        assert!(!admin.verify_second_factor(&code).await.unwrap());
+
+        assert!(
+            admin
+                .verify_second_factor(&recovery_codes[0].to_uppercase())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !admin
+                .verify_second_factor(&recovery_codes[0])
+                .await
+                .unwrap()
+        );
+
+        let res = admin.begin_totp_enrollment("d42x").await;
+        assert!(matches!(res, Err(AdministratorError::TotpAlreadyEnabled)));
+    }
+
+    #[tokio::test]
+    async fn totp_code_replayed_by_stale_login_fail() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db);
+        let mut admin = acc_repo
+            .get_administractor_by_username("dvorak".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let enrollment = admin.begin_totp_enrollment("d42x").await.unwrap();
+        let step = totp::step_at(chrono::Utc::now().timestamp());
+        let code = totp::code_at(&enrollment.secret, step).unwrap();
+        admin.confirm_totp(&code).await.unwrap();
+
+        // two log ins loaded the account before either of them verified
+        let mut first = acc_repo
+            .get_administractor_by_username("dvorak".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut second = acc_repo
+            .get_administractor_by_username("dvorak".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let code = totp::code_at(&enrollment.secret, step + 1).unwrap();
+        assert!(first.verify_second_factor(&code).await.unwrap());
+        assert!(!second.verify_second_factor(&code).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn totp_confirm_wrong_code_fail() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db);
+        let mut admin = acc_repo
+            .get_administractor_by_username("dvorak".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let res = admin.confirm_totp("123456").await;
+        assert!(matches!(res, Err(AdministratorError::TotpNotEnrolled)));
+
+        admin.begin_totp_enrollment("d42x").await.unwrap();
+        let res = admin.confirm_totp("not a code").await;
+        assert!(matches!(res, Err(AdministratorError::InvalidTotpCode)));
+        assert!(!admin.totp_enabled());
+    }
+
+    fn new_account(username: &str, role: &str) -> NewAccount {
+        NewAccount {
+            username: username.to_owned(),
+            email: format!("{}@d42x.test", username),
+            hashed_password: hash_password("correct horse"),
+            role: role.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_account_is_audited() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db.clone());
+
+        let account = acc_repo
+            .create_account(&Actor::cli(), new_account("mod", "moderator"))
+            .await
+            .unwrap();
+        assert_eq!(account.role.as_deref(), Some("moderator"));
+        assert!(!account.disabled);
+
+        let admin = acc_repo
+            .get_administractor_by_username("mod".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(admin.role(), "moderator");
+        assert!(!admin.model.is_admin);
+
+        let res = acc_repo
+            .create_account(&Actor::cli(), new_account("mod", "editor"))
+            .await;
+        assert!(matches!(res, Err(AdministratorError::UsernameTaken(_))));
+
+        let res = acc_repo
+            .create_account(&Actor::cli(), new_account("someone", "nobody"))
+            .await;
+        assert!(matches!(res, Err(AdministratorError::RoleNotFound(_))));
+
+        let logs = GenAuditRepo::new(db.clone())
+            .get_paginated_logs(GetFilter {
+                page: 1,
+                size: 10,
+                action: None,
+                actor_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(logs.list.len(), 1);
+        assert_eq!(logs.list[0].action, "account.create");
+        assert_eq!(logs.list[0].actor_name, "cli");
+        assert_eq!(logs.list[0].target_id, Some(account.id.to_string()));
+
+        let logs = GenAuditRepo::new(db)
+            .get_paginated_logs(GetFilter {
+                page: 1,
+                size: u64::MAX,
+                action: None,
+                actor_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(logs.size, MAX_PAGE_SIZE);
+    }
+
+    #[tokio::test]
+    async fn disabled_account_cannot_log_in() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db);
+
+        let dvorak = acc_repo
+            .get_account_by_username("dvorak")
+            .await
+            .unwrap()
+            .unwrap();
+        let actor = Actor::account(dvorak.id, &dvorak.username);
+        let account = acc_repo
+            .create_account(&actor, new_account("editor", "editor"))
+            .await
+            .unwrap();
+
+        acc_repo
+            .set_disabled(&actor, account.id, true)
+            .await
+            .unwrap();
+        assert!(
+            acc_repo
+                .get_administractor_by_username("editor".to_owned())
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        acc_repo
+            .set_disabled(&actor, account.id, false)
+            .await
+            .unwrap();
+        assert!(
+            acc_repo
+                .get_administractor_by_id(account.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn last_administrator_is_kept() {
+        let db = TestDB::new().await;
+        let acc_repo = GenAccountRepo::new(db);
+
+        let dvorak = acc_repo
+            .get_account_by_username("dvorak")
+            .await
+            .unwrap()
+            .unwrap();
+        let actor = Actor::account(dvorak.id, &dvorak.username);
+
+        let res = acc_repo.delete_account(&actor, dvorak.id).await;
+        assert!(matches!(res, Err(AdministratorError::OwnAccount)));
+
+        let res = acc_repo.set_disabled(&Actor::cli(), dvorak.id, true).await;
+        assert!(matches!(res, Err(AdministratorError::LastAdministrator)));
+
+        let res = acc_repo
+            .update_account(
+                &Actor::cli(),
+                dvorak.id,
+                UpdateAccount {
+                    role: Some(String::from("editor")),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(res, Err(AdministratorError::LastAdministrator)));
+
+        let other = acc_repo
+            .create_account(&actor, new_account("other", ADMINISTRATOR))
+            .await
+            .unwrap();
+        acc_repo.delete_account(&actor, other.id).await.unwrap();
+        assert!(acc_repo.get_account(other.id).await.unwrap().is_none());
+    }
 }
@@ -0,0 +1,59 @@
+use db_entity::audit_logs;
+use migration::async_trait;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
+
+use crate::{business::Pagination, db::DbConnHelper};
+
+use super::{AuditLog, AuditRepository, AuditResult, GetFilter, MAX_PAGE_SIZE};
+
+pub struct GenAuditRepo<TDb: DbConnHelper> {
+    db: TDb,
+}
+
+impl<TDb: DbConnHelper> GenAuditRepo<TDb> {
+    pub fn new(db: TDb) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb> AuditRepository for GenAuditRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn get_paginated_logs(&self, filter: GetFilter) -> AuditResult<Pagination<AuditLog>> {
+        let db = self.db.get_connection().await?;
+
+        let mut condition = Condition::all();
+        if let Some(action) = filter.action {
+            condition = condition.add(audit_logs::Column::Action.eq(action));
+        }
+        if let Some(actor_id) = filter.actor_id {
+            condition = condition.add(audit_logs::Column::ActorId.eq(actor_id));
+        }
+
+        let fetch_page = if filter.page > 0 { filter.page - 1 } else { 0 };
+        let size = filter.size.clamp(1, MAX_PAGE_SIZE);
+
+        let page_query = audit_logs::Entity::find()
+            .filter(condition)
+            .order_by_desc(audit_logs::Column::CreatedDateTime)
+            .order_by_desc(audit_logs::Column::Id)
+            .paginate(&db, size);
+
+        let list = page_query
+            .fetch_page(fetch_page)
+            .await?
+            .into_iter()
+            .map(AuditLog::from)
+            .collect();
+        let total = page_query.num_pages().await?;
+
+        Ok(Pagination {
+            page: filter.page,
+            total,
+            size,
+            list,
+        })
+    }
+}
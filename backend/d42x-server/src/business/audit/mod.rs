@@ -0,0 +1,117 @@
+//! Audit log
+//!
+//! administrative changes are recorded with the actor who did them,
+//! `record` is called with the transaction of the change, so a change is never left unaudited
+
+use chrono::{DateTime, FixedOffset};
+use db_entity::audit_logs;
+use migration::async_trait;
+use sea_orm::{ActiveModelBehavior, ActiveModelTrait, ConnectionTrait, DbErr, Set, prelude::Uuid};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::Pagination;
+
+pub mod gen_audit_repo;
+
+pub type AuditResult<T> = Result<T, AuditError>;
+
+#[async_trait::async_trait]
+pub trait AuditRepository {
+    /// the newest first
+    async fn get_paginated_logs(&self, _filter: GetFilter) -> AuditResult<Pagination<AuditLog>> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicAuditRepository;
+
+impl AuditRepository for PanicAuditRepository {}
+
+/// who made a change
+#[derive(Clone, Debug)]
+pub struct Actor {
+    pub id: Option<Uuid>,
+    pub name: String,
+}
+
+impl Actor {
+    pub fn account(id: Uuid, username: &str) -> Self {
+        Self {
+            id: Some(id),
+            name: username.to_owned(),
+        }
+    }
+
+    /// the `d42x-server` command line
+    pub fn cli() -> Self {
+        Self {
+            id: None,
+            name: String::from("cli"),
+        }
+    }
+}
+
+pub async fn record(
+    conn: &impl ConnectionTrait,
+    actor: &Actor,
+    action: &str,
+    target_id: Option<String>,
+    details: serde_json::Value,
+) -> Result<(), DbErr> {
+    audit_logs::ActiveModel {
+        actor_id: Set(actor.id),
+        actor_name: Set(actor.name.clone()),
+        action: Set(action.to_owned()),
+        target_id: Set(target_id),
+        details: Set(details.to_string()),
+        ..audit_logs::ActiveModel::new()
+    }
+    .insert(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// the largest page of logs
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+pub struct GetFilter {
+    /// page number, base 1
+    pub page: u64,
+    /// capped by `MAX_PAGE_SIZE`
+    pub size: u64,
+    pub action: Option<String>,
+    pub actor_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuditLog {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: String,
+    pub action: String,
+    pub target_id: Option<String>,
+    pub details: serde_json::Value,
+    pub created_date_time: DateTime<FixedOffset>,
+}
+
+impl From<audit_logs::Model> for AuditLog {
+    fn from(value: audit_logs::Model) -> Self {
+        Self {
+            id: value.id,
+            actor_id: value.actor_id,
+            actor_name: value.actor_name,
+            action: value.action,
+            target_id: value.target_id,
+            details: serde_json::from_str(&value.details).unwrap_or_default(),
+            created_date_time: value.created_date_time,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AuditError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+}
@@ -0,0 +1,85 @@
+//! a category may be known by several names, e.g. "猫", "cat" and "kitty".
+//! names are compared after `normalize`, an alias belongs to one category only
+
+use std::collections::HashMap;
+
+use db_entity::{categories, category_aliases};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use unicode_normalization::UnicodeNormalization;
+
+/// in chars, of a name or an alias, normalized or not
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// NFKC normalized, trimmed and lower cased, so "ＣＡＴ " and "cat" are the same name
+pub fn normalize(name: &str) -> String {
+    name.nfkc().collect::<String>().trim().to_lowercase()
+}
+
+/// map each name to its canonical category, tried in order:
+/// the exact category name, an alias, a category name equal after normalization.
+/// names not known at all are left out
+pub async fn resolve_categories(
+    conn: &impl ConnectionTrait,
+    names: &[String],
+) -> Result<HashMap<String, categories::Model>, DbErr> {
+    let mut result = HashMap::new();
+    if names.is_empty() {
+        return Ok(result);
+    }
+
+    let all = categories::Entity::find().all(conn).await?;
+
+    let aliases: HashMap<String, sea_orm::prelude::Uuid> = category_aliases::Entity::find()
+        .filter(category_aliases::Column::Normalized.is_in(names.iter().map(|t| normalize(t))))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|t| (t.normalized, t.category_id))
+        .collect();
+
+    for name in names {
+        if result.contains_key(name) {
+            continue;
+        }
+
+        let normalized = normalize(name);
+        let found = all
+            .iter()
+            .find(|item| item.name == *name)
+            .or_else(|| {
+                aliases
+                    .get(&normalized)
+                    .and_then(|id| all.iter().find(|item| item.id == *id))
+            })
+            .or_else(|| all.iter().find(|item| normalize(&item.name) == normalized));
+
+        if let Some(model) = found {
+            result.insert(name.clone(), model.clone());
+        }
+    }
+
+    Ok(result)
+}
+
+/// replace names with their canonical category names, unknown names are kept.
+/// names equal after normalization are kept once
+pub async fn canonical_names(
+    conn: &impl ConnectionTrait,
+    names: Vec<String>,
+) -> Result<Vec<String>, DbErr> {
+    let resolved = resolve_categories(conn, &names).await?;
+
+    let mut list: Vec<String> = vec![];
+    for name in names {
+        let name = match resolved.get(&name) {
+            Some(model) => model.name.clone(),
+            None => name,
+        };
+        let normalized = normalize(&name);
+        if !list.iter().any(|item| normalize(item) == normalized) {
+            list.push(name);
+        }
+    }
+
+    Ok(list)
+}
@@ -1,19 +1,31 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::{business::cache::Cache, db::DbConnHelper};
-use db_entity::categories;
+use crate::{
+    business::{cache::Cache, search::index::index_memes},
+    db::DbConnHelper,
+};
+use db_entity::{categories, category_aliases, meme_categories, suggests};
 use migration::async_trait;
 use sea_orm::prelude::Uuid;
 use sea_orm::{
-    ActiveModelBehavior, ActiveModelTrait, DbBackend, EntityTrait, FromQueryResult, Set, Statement,
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set, Statement,
+    TransactionTrait,
+    sea_query::{Expr, OnConflict},
 };
 use serde_json::json;
 use tracing::debug;
 
-use super::{CategoryItem, CategoryRepository};
+use super::{
+    CategoryError, CategoryItem, CategoryNode, CategoryRepository, CategoryResult,
+    aliases::{MAX_NAME_LENGTH, normalize, resolve_categories},
+    meme_categories::{get_meme_categories, set_meme_categories},
+    tree::{descendants_of, load_tree},
+};
 
 lazy_static::lazy_static! {
     static ref TOP_CATEGORIES_CACHE_KEY: String = String::from("TOP_CATEGORIES_CACHE_KEY");
+    static ref CATEGORY_TREE_CACHE_KEY: String = String::from("CATEGORY_TREE_CACHE_KEY");
 }
 
 pub struct GenCategoryRepo<TCache, TDb>
@@ -43,6 +55,7 @@ where
 struct CategoriesWithMemeCount {
     id: Uuid,
     name: String,
+    parent: Uuid,
     meme_count: i64,
 }
 
@@ -52,12 +65,12 @@ where
     TCache: Cache<String, String> + Sync + Send,
     TDb: DbConnHelper + Sync + Send,
 {
-    async fn get_categories(&self) -> Vec<super::CategoryItem> {
+    async fn get_categories(&self) -> CategoryResult<Vec<CategoryItem>> {
         if let Some(cache) = &self.cache {
             if let Some(value) = cache.get(&TOP_CATEGORIES_CACHE_KEY) {
                 if let Ok(value) = serde_json::from_str::<Vec<CategoryItem>>(value.as_str()) {
                     debug!("get in cache: {:?}", value);
-                    return value;
+                    return Ok(value);
                 } else {
                     debug!("incorrect data in cache, remove");
                     cache.remove(&TOP_CATEGORIES_CACHE_KEY);
@@ -67,22 +80,23 @@ where
 
         debug!("has not cache data");
 
-        let db = self.db.get_connection().await.unwrap();
+        let db = self.db.get_connection().await?;
 
         let category_list: Vec<_> =
             CategoriesWithMemeCount::find_by_statement(Statement::from_string(
                 DbBackend::Postgres,
-                r#"select a.id, a.name, count(b.id) as meme_count from categories as a 
-            left join memes as b on b.categories like concat('%;',a.name, ';%') 
-            group by a.id, a.name order by meme_count desc;"#,
+                r#"select a.id, a.name, a.parent, count(b.id) as meme_count from categories as a
+            left join meme_categories as c on c.category_id = a.id
+            left join memes as b on b.id = c.meme_id and b.status <> 'deleted'
+            group by a.id, a.name, a.parent order by meme_count desc;"#,
             ))
             .all(&db)
-            .await
-            .unwrap()
+            .await?
             .into_iter()
             .map(|cwm| CategoryItem {
                 id: cwm.id,
                 name: cwm.name,
+                parent: cwm.parent,
                 meme_count: cwm.meme_count,
             })
             .collect();
@@ -94,76 +108,560 @@ where
             cache.insert(TOP_CATEGORIES_CACHE_KEY.clone(), cache_value);
         }
 
-        category_list
+        Ok(category_list)
     }
 
-    async fn append_categories(&self, mut list: Vec<String>) {
-        let list: HashSet<_> = list.drain(..).collect();
+    async fn append_categories(&self, list: Vec<String>) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+
+        let list: Vec<String> = list.into_iter().filter(|item| !item.is_empty()).collect();
+        let existed_categories = resolve_categories(&db, &list).await?;
+
+        let mut new_names: Vec<String> = vec![];
+        for item in list {
+            let normalized = normalize(&item);
+            if existed_categories.contains_key(&item)
+                || new_names.iter().any(|name| normalize(name) == normalized)
+            {
+                continue;
+            }
+            new_names.push(item);
+        }
 
-        let existed_categories = self.get_categories().await;
-        let existed_categories: HashSet<_> =
-            existed_categories.into_iter().map(|t| t.name).collect();
+        if new_names.is_empty() {
+            return Ok(());
+        }
 
-        let list: Vec<_> = list
+        let list: Vec<_> = new_names
             .into_iter()
-            .filter_map(|item| {
-                if existed_categories.contains(&item) {
-                    None
-                } else {
-                    Some(categories::ActiveModel {
-                        name: Set(item),
-                        ..categories::ActiveModel::new()
-                    })
-                }
+            .map(|item| categories::ActiveModel {
+                name: Set(item),
+                ..categories::ActiveModel::new()
             })
             .collect();
+        categories::Entity::insert_many(list).exec(&db).await?;
+
+        self.clear_cache().await;
+
+        Ok(())
+    }
+
+    async fn update_catgories(&self, meme_id: Uuid, new_list: Vec<String>) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+        self.update_catgories_in_txn(&txn, meme_id, new_list)
+            .await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    async fn update_catgories_in_txn(
+        &self,
+        txn: &DatabaseTransaction,
+        meme_id: Uuid,
+        new_list: Vec<String>,
+    ) -> CategoryResult<()> {
+        db_entity::memes::Entity::find_by_id(meme_id)
+            .one(txn)
+            .await?
+            .ok_or(CategoryError::MemeNotFound(meme_id))?;
+
+        set_meme_categories(txn, meme_id, new_list).await?;
+        index_memes(txn, vec![meme_id]).await?;
 
-        if list.len() == 0 {
-            return;
+        Ok(())
+    }
+
+    async fn get_category_tree(&self) -> CategoryResult<Vec<CategoryNode>> {
+        if let Some(cache) = &self.cache
+            && let Some(value) = cache.get(&CATEGORY_TREE_CACHE_KEY)
+        {
+            if let Ok(value) = serde_json::from_str::<Vec<CategoryNode>>(value.as_str()) {
+                return Ok(value);
+            }
+            cache.remove(&CATEGORY_TREE_CACHE_KEY);
         }
 
-        let db = self
-            .db
-            .get_connection()
-            .await
-            .expect("categories get db failed");
-        categories::Entity::insert_many(list)
-            .exec(&db)
-            .await
-            .expect("categories insert many failed");
+        let db = self.db.get_connection().await?;
+        let tree = load_tree(&db).await?;
 
         if let Some(cache) = &self.cache {
-            cache.remove(&TOP_CATEGORIES_CACHE_KEY);
+            cache.insert(CATEGORY_TREE_CACHE_KEY.clone(), json!(tree).to_string());
         }
+
+        Ok(tree)
     }
 
-    async fn update_catgories(&self, meme_id: Uuid, new_list: Vec<String>) {
-        let db = self
-            .db
-            .get_connection()
-            .await
-            .expect("update categories get db failed");
+    async fn create_category(
+        &self,
+        name: String,
+        parent: Option<Uuid>,
+    ) -> CategoryResult<CategoryItem> {
+        let db = self.db.get_connection().await?;
 
-        let mut model: db_entity::memes::ActiveModel =
-            db_entity::memes::Entity::find_by_id(meme_id)
+        if !resolve_categories(&db, std::slice::from_ref(&name))
+            .await?
+            .is_empty()
+        {
+            return Err(CategoryError::NameTaken(name));
+        }
+
+        let parent = parent.unwrap_or_default();
+        if !parent.is_nil() {
+            categories::Entity::find_by_id(parent)
                 .one(&db)
-                .await
-                .expect("update_categories get meme failed")
-                .expect("cannot find meme")
-                .into();
+                .await?
+                .ok_or(CategoryError::ParentNotFound(parent))?;
+        }
 
-        let new_category = new_list
-            .into_iter()
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>()
-            .join(";");
+        let model = categories::ActiveModel {
+            name: Set(name),
+            parent: Set(parent),
+            ..categories::ActiveModel::new()
+        }
+        .insert(&db)
+        .await?;
+
+        self.clear_cache().await;
+
+        Ok(CategoryItem {
+            id: model.id,
+            name: model.name,
+            parent: model.parent,
+            meme_count: 0,
+        })
+    }
+
+    async fn move_category(&self, id: Uuid, parent: Option<Uuid>) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let all = categories::Entity::find().all(&txn).await?;
+        let model = all
+            .iter()
+            .find(|item| item.id == id)
+            .cloned()
+            .ok_or(CategoryError::NotFound(id))?;
+
+        let parent = parent.unwrap_or_default();
+        if !parent.is_nil() {
+            if !all.iter().any(|item| item.id == parent) {
+                return Err(CategoryError::ParentNotFound(parent));
+            }
+            if descendants_of(&all, id).contains(&parent) {
+                return Err(CategoryError::InvalidParent);
+            }
+        }
+
+        let mut model: categories::ActiveModel = model.into();
+        model.parent = Set(parent);
+        model.update(&txn).await?;
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
+
+        Ok(())
+    }
+
+    async fn set_aliases(&self, id: Uuid, aliases: Vec<String>) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let all = categories::Entity::find().all(&txn).await?;
+        let model = all
+            .iter()
+            .find(|item| item.id == id)
+            .ok_or(CategoryError::NotFound(id))?;
+        let own_normalized = normalize(&model.name);
+
+        let mut list: Vec<category_aliases::ActiveModel> = vec![];
+        for alias in aliases {
+            let normalized = normalize(&alias);
+            // NFKC can make a name longer, "㌀" is "アパート"
+            if normalized.chars().count() > MAX_NAME_LENGTH
+                || alias.trim().chars().count() > MAX_NAME_LENGTH
+            {
+                return Err(CategoryError::AliasTooLong(alias));
+            }
+            if normalized.is_empty()
+                || normalized == own_normalized
+                || list
+                    .iter()
+                    .any(|item| item.normalized.as_ref() == &normalized)
+            {
+                continue;
+            }
+
+            if all
+                .iter()
+                .any(|item| item.id != id && normalize(&item.name) == normalized)
+            {
+                return Err(CategoryError::AliasTaken(alias));
+            }
+
+            list.push(category_aliases::ActiveModel {
+                normalized: Set(normalized),
+                alias: Set(alias.trim().to_owned()),
+                category_id: Set(id),
+                ..category_aliases::ActiveModel::new()
+            });
+        }
+
+        let taken = category_aliases::Entity::find()
+            .filter(
+                category_aliases::Column::Normalized
+                    .is_in(list.iter().map(|item| item.normalized.as_ref().clone())),
+            )
+            .filter(category_aliases::Column::CategoryId.ne(id))
+            .one(&txn)
+            .await?;
+        if let Some(taken) = taken {
+            return Err(CategoryError::AliasTaken(taken.alias));
+        }
+
+        category_aliases::Entity::delete_many()
+            .filter(category_aliases::Column::CategoryId.eq(id))
+            .exec(&txn)
+            .await?;
+
+        if !list.is_empty() {
+            category_aliases::Entity::insert_many(list)
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
+
+        Ok(())
+    }
+
+    async fn rename_category(&self, id: Uuid, name: String) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = categories::Entity::find_by_id(id)
+            .one(&txn)
+            .await?
+            .ok_or(CategoryError::NotFound(id))?;
+        if model.name == name {
+            return Ok(());
+        }
+        if model.name == db_entity::DEFAULT_CATEGORY || name == db_entity::DEFAULT_CATEGORY {
+            return Err(CategoryError::DefaultCategory);
+        }
+
+        let existed = resolve_categories(&txn, std::slice::from_ref(&name))
+            .await?
+            .remove(&name);
+        if existed.is_some_and(|item| item.id != id) {
+            return Err(CategoryError::NameTaken(name));
+        }
+
+        let old_name = model.name.clone();
+        let mut model: categories::ActiveModel = model.into();
+        model.name = Set(name.clone());
+        model.update(&txn).await?;
+
+        rewrite_waiting_suggests(&txn, &HashMap::from([(old_name, Some(name))])).await?;
+
+        let meme_ids = memes_of_categories(&txn, vec![id]).await?;
+        index_memes(&txn, meme_ids).await?;
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
 
-        model.categories = Set(format!(";{};", new_category));
+        Ok(())
+    }
+
+    async fn merge_categories(&self, sources: Vec<Uuid>, target: Uuid) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let all = categories::Entity::find().all(&txn).await?;
+        let find = |id: Uuid| all.iter().find(|item| item.id == id);
+
+        let target = find(target).ok_or(CategoryError::NotFound(target))?;
+
+        let mut source_list: Vec<&categories::Model> = vec![];
+        for id in sources {
+            let source = find(id).ok_or(CategoryError::NotFound(id))?;
+            if source.id == target.id || source_list.iter().any(|item| item.id == id) {
+                continue;
+            }
+            if source.name == db_entity::DEFAULT_CATEGORY {
+                return Err(CategoryError::DefaultCategory);
+            }
+            source_list.push(source);
+        }
+        if source_list.is_empty() {
+            return Ok(());
+        }
+
+        let source_ids: Vec<Uuid> = source_list.iter().map(|item| item.id).collect();
 
-        model
-            .update(&db)
-            .await
-            .expect("update_categories update fail");
+        let meme_ids = memes_of_categories(&txn, source_ids.clone()).await?;
+        merge_meme_rows(&txn, &source_ids, target.id).await?;
+        index_memes(&txn, meme_ids).await?;
+
+        // the target may sit below a source, it takes the first ancestor not merged
+        let mut parent = target.parent;
+        let mut visited = HashSet::new();
+        while source_ids.contains(&parent) && visited.insert(parent) {
+            parent = find(parent).map(|item| item.parent).unwrap_or_default();
+        }
+        if parent != target.parent {
+            categories::Entity::update_many()
+                .col_expr(categories::Column::Parent, Expr::value(parent))
+                .filter(categories::Column::Id.eq(target.id))
+                .exec(&txn)
+                .await?;
+        }
+
+        categories::Entity::update_many()
+            .col_expr(categories::Column::Parent, Expr::value(target.id))
+            .filter(categories::Column::Parent.is_in(source_ids.clone()))
+            .filter(categories::Column::Id.ne(target.id))
+            .exec(&txn)
+            .await?;
+
+        category_aliases::Entity::update_many()
+            .col_expr(category_aliases::Column::CategoryId, Expr::value(target.id))
+            .filter(category_aliases::Column::CategoryId.is_in(source_ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        let target_normalized = normalize(&target.name);
+        for source in source_list.iter() {
+            let normalized = normalize(&source.name);
+            if normalized == target_normalized {
+                continue;
+            }
+            category_aliases::Entity::insert(category_aliases::ActiveModel {
+                normalized: Set(normalized),
+                alias: Set(source.name.clone()),
+                category_id: Set(target.id),
+                ..category_aliases::ActiveModel::new()
+            })
+            .on_conflict(
+                OnConflict::column(category_aliases::Column::Normalized)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(&txn)
+            .await?;
+        }
+
+        categories::Entity::delete_many()
+            .filter(categories::Column::Id.is_in(source_ids))
+            .exec(&txn)
+            .await?;
+
+        let renamed: HashMap<_, _> = source_list
+            .iter()
+            .map(|item| (item.name.clone(), Some(target.name.clone())))
+            .collect();
+        rewrite_waiting_suggests(&txn, &renamed).await?;
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
+
+        Ok(())
     }
+
+    async fn delete_category(&self, id: Uuid, reassign: bool) -> CategoryResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let model = categories::Entity::find_by_id(id)
+            .one(&txn)
+            .await?
+            .ok_or(CategoryError::NotFound(id))?;
+        if model.name == db_entity::DEFAULT_CATEGORY {
+            return Err(CategoryError::DefaultCategory);
+        }
+
+        let meme_ids = memes_of_categories(&txn, vec![id]).await?;
+
+        if reassign {
+            let default_id = match categories::Entity::find()
+                .filter(categories::Column::Name.eq(db_entity::DEFAULT_CATEGORY))
+                .one(&txn)
+                .await?
+            {
+                Some(default) => default.id,
+                None => {
+                    categories::ActiveModel {
+                        name: Set(db_entity::DEFAULT_CATEGORY.to_owned()),
+                        ..categories::ActiveModel::new()
+                    }
+                    .insert(&txn)
+                    .await?
+                    .id
+                }
+            };
+
+            merge_meme_rows(&txn, &[id], default_id).await?;
+
+            rewrite_waiting_suggests(
+                &txn,
+                &HashMap::from([(
+                    model.name.clone(),
+                    Some(db_entity::DEFAULT_CATEGORY.to_owned()),
+                )]),
+            )
+            .await?;
+        } else {
+            meme_categories::Entity::delete_many()
+                .filter(meme_categories::Column::CategoryId.eq(id))
+                .exec(&txn)
+                .await?;
+
+            let left = get_meme_categories(&txn, meme_ids.clone()).await?;
+            for meme_id in meme_ids.iter().copied() {
+                if !left.contains_key(&meme_id) {
+                    set_meme_categories(&txn, meme_id, vec![]).await?;
+                }
+            }
+
+            rewrite_waiting_suggests(&txn, &HashMap::from([(model.name.clone(), None)])).await?;
+        }
+
+        categories::Entity::update_many()
+            .col_expr(categories::Column::Parent, Expr::value(model.parent))
+            .filter(categories::Column::Parent.eq(id))
+            .exec(&txn)
+            .await?;
+
+        category_aliases::Entity::delete_many()
+            .filter(category_aliases::Column::CategoryId.eq(id))
+            .exec(&txn)
+            .await?;
+
+        categories::Entity::delete_by_id(id).exec(&txn).await?;
+
+        index_memes(&txn, meme_ids).await?;
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
+
+        Ok(())
+    }
+
+    async fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.remove(&TOP_CATEGORIES_CACHE_KEY);
+            cache.remove(&CATEGORY_TREE_CACHE_KEY);
+        }
+    }
+}
+
+async fn memes_of_categories(
+    txn: &DatabaseTransaction,
+    category_ids: Vec<Uuid>,
+) -> Result<Vec<Uuid>, DbErr> {
+    meme_categories::Entity::find()
+        .select_only()
+        .column(meme_categories::Column::MemeId)
+        .distinct()
+        .filter(meme_categories::Column::CategoryId.is_in(category_ids))
+        .into_tuple()
+        .all(txn)
+        .await
+}
+
+/// point the rows of `sources` to `target`, a meme tagged with both keeps a single row
+async fn merge_meme_rows(
+    txn: &DatabaseTransaction,
+    sources: &[Uuid],
+    target: Uuid,
+) -> Result<(), DbErr> {
+    let mut tagged: HashSet<Uuid> = meme_categories::Entity::find()
+        .select_only()
+        .column(meme_categories::Column::MemeId)
+        .filter(meme_categories::Column::CategoryId.eq(target))
+        .into_tuple::<Uuid>()
+        .all(txn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let rows = meme_categories::Entity::find()
+        .filter(meme_categories::Column::CategoryId.is_in(sources.iter().copied()))
+        .order_by_asc(meme_categories::Column::Sort)
+        .all(txn)
+        .await?;
+
+    meme_categories::Entity::delete_many()
+        .filter(meme_categories::Column::CategoryId.is_in(sources.iter().copied()))
+        .exec(txn)
+        .await?;
+
+    let new_rows: Vec<_> = rows
+        .into_iter()
+        .filter(|row| tagged.insert(row.meme_id))
+        .map(|row| meme_categories::ActiveModel {
+            meme_id: Set(row.meme_id),
+            category_id: Set(target),
+            sort: Set(row.sort),
+        })
+        .collect();
+    if !new_rows.is_empty() {
+        meme_categories::Entity::insert_many(new_rows)
+            .exec(txn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// waiting suggestions keep category names, rewrite them so approving one
+/// does not bring an old name back. a `None` drops the name
+async fn rewrite_waiting_suggests(
+    txn: &DatabaseTransaction,
+    renamed: &HashMap<String, Option<String>>,
+) -> Result<(), DbErr> {
+    let list = suggests::Entity::find()
+        .filter(suggests::Column::Status.eq(suggests::Status::Wait))
+        .all(txn)
+        .await?;
+
+    for item in list {
+        let before = rewrite_names(&item.before, renamed);
+        let after = rewrite_names(&item.after, renamed);
+        if before == item.before && after == item.after {
+            continue;
+        }
+
+        let mut model: suggests::ActiveModel = item.into();
+        model.before = Set(before);
+        model.after = Set(after);
+        model.update(txn).await?;
+    }
+
+    Ok(())
+}
+
+fn rewrite_names(value: &str, renamed: &HashMap<String, Option<String>>) -> String {
+    let mut list: Vec<&str> = vec![];
+    for name in value.split(';').filter(|name| !name.is_empty()) {
+        let name = match renamed.get(name) {
+            Some(Some(new_name)) => new_name.as_str(),
+            Some(None) => continue,
+            None => name,
+        };
+        if !list.contains(&name) {
+            list.push(name);
+        }
+    }
+
+    format!(";{};", list.join(";"))
 }
@@ -0,0 +1,105 @@
+//! reads and writes of the `meme_categories` join table, shared by the meme,
+//! category and suggest repositories
+
+use std::collections::HashMap;
+
+use db_entity::{categories, meme_categories};
+
+use super::aliases::canonical_names;
+use sea_orm::{
+    ActiveModelBehavior, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait, Set, prelude::Uuid, sea_query::SelectStatement,
+};
+
+/// category names of each meme, in the order they were assigned
+pub async fn get_meme_categories(
+    conn: &impl ConnectionTrait,
+    meme_ids: Vec<Uuid>,
+) -> Result<HashMap<Uuid, Vec<String>>, DbErr> {
+    let mut result: HashMap<Uuid, Vec<String>> = HashMap::new();
+    if meme_ids.is_empty() {
+        return Ok(result);
+    }
+
+    let rows = meme_categories::Entity::find()
+        .find_also_related(categories::Entity)
+        .filter(meme_categories::Column::MemeId.is_in(meme_ids))
+        .order_by_asc(meme_categories::Column::Sort)
+        .all(conn)
+        .await?;
+
+    for (row, category) in rows {
+        if let Some(category) = category {
+            result.entry(row.meme_id).or_default().push(category.name);
+        }
+    }
+
+    Ok(result)
+}
+
+/// replace the categories of a meme, aliases are mapped to their categories
+/// and categories not existed yet are created. returns the names actually stored
+pub async fn set_meme_categories(
+    conn: &impl ConnectionTrait,
+    meme_id: Uuid,
+    names: Vec<String>,
+) -> Result<Vec<String>, DbErr> {
+    let names: Vec<String> = names.into_iter().filter(|name| !name.is_empty()).collect();
+    let mut list = canonical_names(conn, names).await?;
+    if list.is_empty() {
+        list.push(db_entity::DEFAULT_CATEGORY.to_string());
+    }
+
+    let mut category_ids: HashMap<String, Uuid> = categories::Entity::find()
+        .filter(categories::Column::Name.is_in(list.iter().cloned()))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|t| (t.name, t.id))
+        .collect();
+
+    let new_categories: Vec<_> = list
+        .iter()
+        .filter(|name| !category_ids.contains_key(*name))
+        .map(|name| categories::ActiveModel {
+            name: Set(name.clone()),
+            ..categories::ActiveModel::new()
+        })
+        .collect();
+
+    for model in new_categories {
+        let id = model.id.clone().unwrap();
+        let name = model.name.clone().unwrap();
+        categories::Entity::insert(model).exec(conn).await?;
+        category_ids.insert(name, id);
+    }
+
+    meme_categories::Entity::delete_many()
+        .filter(meme_categories::Column::MemeId.eq(meme_id))
+        .exec(conn)
+        .await?;
+
+    let rows: Vec<_> = list
+        .iter()
+        .enumerate()
+        .map(|(sort, name)| meme_categories::ActiveModel {
+            meme_id: Set(meme_id),
+            category_id: Set(category_ids[name]),
+            sort: Set(sort as i32),
+        })
+        .collect();
+    meme_categories::Entity::insert_many(rows)
+        .exec(conn)
+        .await?;
+
+    Ok(list)
+}
+
+/// sub query selecting the ids of the memes in any of the categories
+pub fn memes_in_categories(category_ids: Vec<Uuid>) -> SelectStatement {
+    meme_categories::Entity::find()
+        .select_only()
+        .column(meme_categories::Column::MemeId)
+        .filter(meme_categories::Column::CategoryId.is_in(category_ids))
+        .into_query()
+}
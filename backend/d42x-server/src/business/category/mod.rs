@@ -1,20 +1,90 @@
+pub mod aliases;
 pub mod gen_cate_repo;
+pub mod meme_categories;
+pub mod tree;
+
+#[cfg(test)]
+mod test;
 
 use migration::async_trait;
-use sea_orm::prelude::Uuid;
+use sea_orm::{DatabaseTransaction, DbErr, prelude::Uuid};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub type CategoryResult<T> = Result<T, CategoryError>;
 
 #[async_trait::async_trait]
 pub trait CategoryRepository {
-    async fn get_categories(&self) -> Vec<CategoryItem> {
+    async fn get_categories(&self) -> CategoryResult<Vec<CategoryItem>> {
+        unimplemented!()
+    }
+
+    async fn append_categories(&self, _list: Vec<String>) -> CategoryResult<()> {
         unimplemented!()
     }
 
-    async fn append_categories(&self, _list: Vec<String>) {
+    async fn update_catgories(&self, _meme_id: Uuid, _new_list: Vec<String>) -> CategoryResult<()> {
         unimplemented!()
     }
 
-    async fn update_catgories(&self, _meme_id: Uuid, _new_list: Vec<String>) {
+    /// same as `update_catgories`, but runs inside the caller's transaction,
+    /// categories not existed yet are created as well
+    async fn update_catgories_in_txn(
+        &self,
+        _txn: &DatabaseTransaction,
+        _meme_id: Uuid,
+        _new_list: Vec<String>,
+    ) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// all categories as a tree, see `tree::load_tree` for the meme counts
+    async fn get_category_tree(&self) -> CategoryResult<Vec<CategoryNode>> {
+        unimplemented!()
+    }
+
+    /// create a category, `parent` as `None` creates a top category
+    async fn create_category(
+        &self,
+        _name: String,
+        _parent: Option<Uuid>,
+    ) -> CategoryResult<CategoryItem> {
+        unimplemented!()
+    }
+
+    /// move a category and its descendants under `parent`,
+    /// `None` makes it a top category
+    async fn move_category(&self, _id: Uuid, _parent: Option<Uuid>) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// replace the aliases of a category, an alias already used by another
+    /// category is rejected
+    async fn set_aliases(&self, _id: Uuid, _aliases: Vec<String>) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// rename a category, the names in waiting suggestions are rewritten as well
+    async fn rename_category(&self, _id: Uuid, _name: String) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// move the memes, children and aliases of `sources` to `target`, then delete `sources`.
+    /// the names of `sources` are kept as aliases of `target`
+    async fn merge_categories(&self, _sources: Vec<Uuid>, _target: Uuid) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// delete a category, its children move up to its parent.
+    /// with `reassign` its memes are tagged with `db_entity::DEFAULT_CATEGORY` instead,
+    /// otherwise the category is only removed from them,
+    /// memes left without any category fall back to the default one anyway
+    async fn delete_category(&self, _id: Uuid, _reassign: bool) -> CategoryResult<()> {
+        unimplemented!()
+    }
+
+    /// remove all cached categories
+    async fn clear_cache(&self) {
         unimplemented!()
     }
 }
@@ -23,10 +93,45 @@ pub trait CategoryRepository {
 pub struct CategoryItem {
     pub id: Uuid,
     pub name: String,
+    /// `Uuid::nil()` for a top category
+    pub parent: Uuid,
     pub meme_count: i64,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CategoryNode {
+    pub id: Uuid,
+    pub name: String,
+    pub parent: Uuid,
+    pub aliases: Vec<String>,
+    pub meme_count: i64,
+    pub total_meme_count: i64,
+    pub children: Vec<CategoryNode>,
+}
+
 pub struct PanicCategoryRepo;
 
 #[async_trait::async_trait]
 impl CategoryRepository for PanicCategoryRepo {}
+
+#[derive(Error, Debug)]
+pub enum CategoryError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error("meme not found: {0}")]
+    MemeNotFound(Uuid),
+    #[error("category not found: {0}")]
+    NotFound(Uuid),
+    #[error("parent category not found: {0}")]
+    ParentNotFound(Uuid),
+    #[error("category existed already: {0}")]
+    NameTaken(String),
+    #[error("category cannot be moved under itself or its descendant")]
+    InvalidParent,
+    #[error("alias is used by another category: {0}")]
+    AliasTaken(String),
+    #[error("alias is too long: {0}")]
+    AliasTooLong(String),
+    #[error("the default category cannot be renamed, merged or deleted")]
+    DefaultCategory,
+}
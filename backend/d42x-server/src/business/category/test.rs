@@ -0,0 +1,367 @@
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::Uuid};
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            category::{
+                CategoryError, CategoryRepository, aliases::normalize,
+                gen_cate_repo::GenCategoryRepo,
+            },
+            meme::{
+                ListFilter, MemeRepository, gen_meme_repo::GenMemeRepo, test_support::post_meme,
+            },
+            suggests::{SuggestRepository, gen_suggest_repo::GenSuggestRepo},
+        },
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    async fn find_category(db: &TestDB, name: &str) -> db_entity::categories::Model {
+        let db_conn = db.get_connection().await.unwrap();
+        db_entity::categories::Entity::find()
+            .filter(db_entity::categories::Column::Name.eq(name))
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn create_category_name_taken_fail() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db);
+
+        let res = repo
+            .create_category(db_entity::DEFAULT_CATEGORY.to_owned(), None)
+            .await;
+        assert!(matches!(res, Err(CategoryError::NameTaken(_))));
+
+        let res = repo
+            .create_category("animal".to_owned(), Some(Uuid::now_v7()))
+            .await;
+        assert!(matches!(res, Err(CategoryError::ParentNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn get_category_tree_rolled_up_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+
+        let animal = repo
+            .create_category("animal".to_owned(), None)
+            .await
+            .unwrap();
+        let cat = repo
+            .create_category("cat".to_owned(), Some(animal.id))
+            .await
+            .unwrap();
+        repo.create_category("dog".to_owned(), Some(animal.id))
+            .await
+            .unwrap();
+
+        meme_repo
+            .post_memes(vec![
+                post_meme("", &["cat"]),
+                post_meme("", &["dog"]),
+                post_meme("", &["cat", "dog"]),
+                post_meme("", &["animal"]),
+            ])
+            .await
+            .unwrap();
+
+        let tree = repo.get_category_tree().await.unwrap();
+        let animal_node = tree.iter().find(|item| item.id == animal.id).unwrap();
+        assert_eq!(animal_node.meme_count, 1);
+        assert_eq!(animal_node.total_meme_count, 4);
+        assert_eq!(animal_node.children.len(), 2);
+
+        let cat_node = animal_node
+            .children
+            .iter()
+            .find(|item| item.id == cat.id)
+            .unwrap();
+        assert_eq!(cat_node.meme_count, 2);
+        assert_eq!(cat_node.total_meme_count, 2);
+
+        let list = meme_repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec!["animal".to_owned()],
+                    include_descendants: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.list.len(), 1);
+
+        let list = meme_repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec!["animal".to_owned()],
+                    include_descendants: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.list.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn move_category_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db);
+
+        let animal = repo
+            .create_category("animal".to_owned(), None)
+            .await
+            .unwrap();
+        let cat = repo
+            .create_category("cat".to_owned(), Some(animal.id))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            repo.move_category(animal.id, Some(cat.id)).await,
+            Err(CategoryError::InvalidParent)
+        ));
+        assert!(matches!(
+            repo.move_category(animal.id, Some(animal.id)).await,
+            Err(CategoryError::InvalidParent)
+        ));
+
+        repo.move_category(cat.id, None).await.unwrap();
+
+        let tree = repo.get_category_tree().await.unwrap();
+        assert!(tree.iter().any(|item| item.id == cat.id));
+        assert!(
+            tree.iter()
+                .find(|item| item.id == animal.id)
+                .unwrap()
+                .children
+                .is_empty()
+        );
+    }
+
+    async fn categories_of(db: &TestDB, category: &str) -> Vec<Vec<String>> {
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        meme_repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec![category.to_owned()],
+                    include_descendants: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .list
+            .into_iter()
+            .map(|item| item.categories)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rename_category_rewrites_suggests_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let suggest_repo = GenSuggestRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![post_meme("", &["cats"])])
+            .await
+            .unwrap();
+        let cats = find_category(&db, "cats").await;
+
+        let (meme_id, account_id) = {
+            let db_conn = db.get_connection().await.unwrap();
+            let meme = db_entity::meme_categories::Entity::find()
+                .filter(db_entity::meme_categories::Column::CategoryId.eq(cats.id))
+                .one(&db_conn)
+                .await
+                .unwrap()
+                .unwrap();
+            let account = db_entity::accounts::Entity::find()
+                .one(&db_conn)
+                .await
+                .unwrap()
+                .unwrap();
+            (meme.meme_id, account.id)
+        };
+        suggest_repo
+            .create(meme_id, vec!["cats".to_owned()], account_id)
+            .await
+            .unwrap();
+
+        repo.rename_category(cats.id, "cat".to_owned())
+            .await
+            .unwrap();
+
+        assert_eq!(categories_of(&db, "cat").await, vec![vec!["cat"]]);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let suggest = db_entity::suggests::Entity::find()
+            .filter(db_entity::suggests::Column::MemeId.eq(meme_id))
+            .filter(db_entity::suggests::Column::Status.eq(db_entity::suggests::Status::Wait))
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(suggest.after, ";cat;");
+
+        assert!(matches!(
+            repo.rename_category(cats.id, db_entity::DEFAULT_CATEGORY.to_owned())
+                .await,
+            Err(CategoryError::DefaultCategory)
+        ));
+    }
+
+    #[tokio::test]
+    async fn merge_categories_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![
+                post_meme("", &["cat"]),
+                post_meme("", &["cats", "kitty"]),
+                post_meme("", &["kitty", "cat"]),
+            ])
+            .await
+            .unwrap();
+        let cat = find_category(&db, "cat").await;
+        let cats = find_category(&db, "cats").await;
+        let kitty = find_category(&db, "kitty").await;
+        let kitten = repo
+            .create_category("kitten".to_owned(), Some(kitty.id))
+            .await
+            .unwrap();
+
+        repo.merge_categories(vec![cats.id, kitty.id], cat.id)
+            .await
+            .unwrap();
+
+        let mut list = categories_of(&db, "cat").await;
+        list.sort();
+        assert_eq!(list, vec![vec!["cat"], vec!["cat"], vec!["cat"]]);
+
+        let tree = repo.get_category_tree().await.unwrap();
+        let cat_node = tree.iter().find(|item| item.id == cat.id).unwrap();
+        assert_eq!(cat_node.meme_count, 3);
+        assert!(cat_node.children.iter().any(|item| item.id == kitten.id));
+        assert!(
+            tree.iter()
+                .all(|item| item.id != cats.id && item.id != kitty.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_category_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![
+                post_meme("", &["cat"]),
+                post_meme("", &["dog", "fox"]),
+            ])
+            .await
+            .unwrap();
+        let cat = find_category(&db, "cat").await;
+        let dog = find_category(&db, "dog").await;
+
+        // dropped from the meme, a meme left without any falls back to the default one
+        repo.delete_category(cat.id, false).await.unwrap();
+        let default_count = categories_of(&db, db_entity::DEFAULT_CATEGORY).await.len();
+
+        repo.delete_category(dog.id, true).await.unwrap();
+        assert_eq!(
+            categories_of(&db, "fox").await,
+            vec![vec![db_entity::DEFAULT_CATEGORY, "fox"]]
+        );
+        assert_eq!(
+            categories_of(&db, db_entity::DEFAULT_CATEGORY).await.len(),
+            default_count + 1
+        );
+
+        let default = find_category(&db, db_entity::DEFAULT_CATEGORY).await;
+        assert!(matches!(
+            repo.delete_category(default.id, true).await,
+            Err(CategoryError::DefaultCategory)
+        ));
+    }
+
+    #[test]
+    fn normalize_success() {
+        assert_eq!(normalize(" ＣＡＴ "), "cat");
+        assert_eq!(normalize("猫"), "猫");
+    }
+
+    #[tokio::test]
+    async fn aliases_resolve_success() {
+        let db = TestDB::new().await;
+        let repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+
+        let cat = repo.create_category("cat".to_owned(), None).await.unwrap();
+        repo.set_aliases(cat.id, vec!["猫".to_owned(), "Kitty".to_owned()])
+            .await
+            .unwrap();
+
+        repo.append_categories(vec![
+            "kitty".to_owned(),
+            "猫".to_owned(),
+            "Cat".to_owned(),
+            "dog".to_owned(),
+            "Dog".to_owned(),
+        ])
+        .await
+        .unwrap();
+        let names: Vec<_> = repo
+            .get_categories()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&"dog".to_owned()));
+
+        meme_repo
+            .post_memes(vec![post_meme("", &["KITTY", "猫"])])
+            .await
+            .unwrap();
+        assert_eq!(categories_of(&db, "ｋｉｔｔｙ").await, vec![vec!["cat"]]);
+        assert_eq!(categories_of(&db, "CAT").await, vec![vec!["cat"]]);
+
+        let dog = find_category(&db, "dog").await;
+        assert!(matches!(
+            repo.set_aliases(dog.id, vec!["kitty".to_owned()]).await,
+            Err(CategoryError::AliasTaken(_))
+        ));
+        assert!(matches!(
+            repo.create_category("猫".to_owned(), None).await,
+            Err(CategoryError::NameTaken(_))
+        ));
+
+        // 20 chars, 80 once normalized
+        assert!(matches!(
+            repo.set_aliases(dog.id, vec!["㌀".repeat(20)]).await,
+            Err(CategoryError::AliasTooLong(_))
+        ));
+        assert_eq!(categories_of(&db, "kitty").await, vec![vec!["cat"]]);
+    }
+}
@@ -0,0 +1,167 @@
+//! categories form a tree through `categories.parent`, `Uuid::nil()` is the root
+
+use std::collections::{HashMap, HashSet};
+
+use db_entity::{categories, category_aliases};
+use sea_orm::{
+    ConnectionTrait, DbBackend, DbErr, EntityTrait, FromQueryResult, QueryOrder, Statement,
+    prelude::Uuid,
+};
+
+use super::{CategoryNode, aliases::resolve_categories};
+
+/// ids of `root` and all categories below it
+pub fn descendants_of(categories: &[categories::Model], root: Uuid) -> Vec<Uuid> {
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for item in categories {
+        children.entry(item.parent).or_default().push(item.id);
+    }
+
+    let mut result = vec![];
+    let mut visited = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        result.push(id);
+        if let Some(list) = children.get(&id) {
+            stack.extend(list.iter().copied());
+        }
+    }
+
+    result
+}
+
+/// ids of the category `name` resolves to and, when `include_descendants` is set,
+/// all categories below it. `name` may be an alias, see `aliases::resolve_categories`
+pub async fn category_ids_by_name(
+    conn: &impl ConnectionTrait,
+    name: &str,
+    include_descendants: bool,
+) -> Result<Vec<Uuid>, DbErr> {
+    let name = name.to_owned();
+    let Some(category) = resolve_categories(conn, std::slice::from_ref(&name))
+        .await?
+        .remove(&name)
+    else {
+        return Ok(vec![]);
+    };
+
+    if !include_descendants {
+        return Ok(vec![category.id]);
+    }
+
+    let all = categories::Entity::find().all(conn).await?;
+
+    Ok(descendants_of(&all, category.id))
+}
+
+/// build the category tree, `meme_count` counts the memes tagged with the node itself,
+/// `total_meme_count` the distinct memes tagged with the node or any descendant
+pub async fn load_tree(conn: &impl ConnectionTrait) -> Result<Vec<CategoryNode>, DbErr> {
+    let all = categories::Entity::find().all(conn).await?;
+
+    // `tree` pairs every category with itself and each category below it, `union` stops at
+    // a cycle of parents
+    let counts: HashMap<Uuid, MemeCounts> = MemeCounts::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"with recursive tree(ancestor, id) as (
+                select id, id from categories
+                union
+                select tree.ancestor, a.id from categories as a
+                join tree on a.parent = tree.id
+            )
+            select tree.ancestor as category_id,
+                count(distinct case when tree.id = tree.ancestor then c.meme_id end) as meme_count,
+                count(distinct c.meme_id) as total_meme_count
+            from tree
+            join meme_categories as c on c.category_id = tree.id
+            join memes as b on b.id = c.meme_id and b.status <> 'deleted'
+            group by tree.ancestor;"#,
+    ))
+    .all(conn)
+    .await?
+    .into_iter()
+    .map(|item| (item.category_id, item))
+    .collect();
+
+    let mut aliases_of: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for item in category_aliases::Entity::find()
+        .order_by_asc(category_aliases::Column::Alias)
+        .all(conn)
+        .await?
+    {
+        aliases_of
+            .entry(item.category_id)
+            .or_default()
+            .push(item.alias);
+    }
+
+    let ids: HashSet<Uuid> = all.iter().map(|item| item.id).collect();
+    let mut children: HashMap<Uuid, Vec<&categories::Model>> = HashMap::new();
+    for item in all.iter() {
+        // a category whose parent is gone is shown as a top category
+        let parent = if ids.contains(&item.parent) {
+            item.parent
+        } else {
+            Uuid::nil()
+        };
+        children.entry(parent).or_default().push(item);
+    }
+
+    let mut visited = HashSet::new();
+    Ok(build_nodes(
+        Uuid::nil(),
+        &children,
+        &counts,
+        &mut aliases_of,
+        &mut visited,
+    ))
+}
+
+#[derive(FromQueryResult)]
+struct MemeCounts {
+    category_id: Uuid,
+    meme_count: i64,
+    total_meme_count: i64,
+}
+
+fn build_nodes(
+    parent: Uuid,
+    children: &HashMap<Uuid, Vec<&categories::Model>>,
+    counts: &HashMap<Uuid, MemeCounts>,
+    aliases_of: &mut HashMap<Uuid, Vec<String>>,
+    visited: &mut HashSet<Uuid>,
+) -> Vec<CategoryNode> {
+    let mut nodes = vec![];
+
+    for item in children.get(&parent).into_iter().flatten() {
+        if !visited.insert(item.id) {
+            continue;
+        }
+
+        let (meme_count, total_meme_count) = counts
+            .get(&item.id)
+            .map(|item| (item.meme_count, item.total_meme_count))
+            .unwrap_or_default();
+
+        nodes.push(CategoryNode {
+            id: item.id,
+            name: item.name.clone(),
+            parent: item.parent,
+            aliases: aliases_of.remove(&item.id).unwrap_or_default(),
+            meme_count,
+            total_meme_count,
+            children: build_nodes(item.id, children, counts, aliases_of, visited),
+        });
+    }
+
+    nodes.sort_by(|a, b| {
+        b.total_meme_count
+            .cmp(&a.total_meme_count)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    nodes
+}
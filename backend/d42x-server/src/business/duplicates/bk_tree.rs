@@ -0,0 +1,72 @@
+//! BK-tree of perceptual hashes
+//!
+//! the children of a node are keyed by their distance to it, so by the triangle inequality a
+//! search within `max` bits of a hash `d` bits from the node only walks the children keyed
+//! `d - max ..= d + max` instead of comparing every hash
+
+use crate::business::media::perceptual::distance;
+
+#[derive(Default)]
+pub struct BkTree {
+    nodes: Vec<Node>,
+}
+
+struct Node {
+    hash: u64,
+    children: Vec<(u32, usize)>,
+}
+
+impl BkTree {
+    /// a hash already in the tree is not added again
+    pub fn insert(&mut self, hash: u64) {
+        let leaf = Node {
+            hash,
+            children: vec![],
+        };
+        if self.nodes.is_empty() {
+            self.nodes.push(leaf);
+            return;
+        }
+
+        let mut at = 0;
+        loop {
+            let bits = distance(self.nodes[at].hash, hash);
+            if bits == 0 {
+                return;
+            }
+            match self.nodes[at].children.iter().find(|(key, _)| *key == bits) {
+                Some((_, child)) => at = *child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes[at].children.push((bits, child));
+                    self.nodes.push(leaf);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// the hashes at most `max` bits from `hash` and their distances
+    pub fn within(&self, hash: u64, max: u32) -> Vec<(u64, u32)> {
+        let mut found = vec![];
+        let mut pending = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(at) = pending.pop() {
+            let node = &self.nodes[at];
+            let bits = distance(node.hash, hash);
+            if bits <= max {
+                found.push((node.hash, bits));
+            }
+            pending.extend(
+                node.children
+                    .iter()
+                    .filter(|(key, _)| bits.saturating_sub(max) <= *key && *key <= bits + max)
+                    .map(|(_, child)| *child),
+            );
+        }
+        found
+    }
+}
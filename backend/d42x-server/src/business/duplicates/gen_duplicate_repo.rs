@@ -0,0 +1,472 @@
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::{
+    meme_hot_scores,
+    meme_interactions::{self, Kind},
+    meme_reaction_counts, meme_reactions, meme_urls, meme_votes,
+    memes::{self, Status},
+};
+use migration::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait, Set, TransactionTrait,
+    prelude::{Expr, Uuid},
+    sea_query::OnConflict,
+};
+use serde_json::json;
+
+use crate::{
+    business::{
+        audit::{self, Actor},
+        category::meme_categories::{get_meme_categories, set_meme_categories},
+        meme::{MemeError, gen_meme_repo::models_2_meme_list},
+        search::index::index_memes,
+    },
+    db::DbConnHelper,
+};
+
+use super::{
+    DuplicateCluster, DuplicateError, DuplicateMeme, DuplicateRepository, DuplicateResult,
+    MAX_DISTANCE, PostedFile, bk_tree::BkTree,
+};
+
+/// the rows of one insert, kept small for the bind parameter limit of SQLite
+const INSERT_CHUNK: usize = 200;
+
+pub struct GenDuplicateRepo<TDb: DbConnHelper> {
+    db: TDb,
+}
+
+impl<TDb: DbConnHelper> GenDuplicateRepo<TDb> {
+    pub fn new(db: TDb) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb> DuplicateRepository for GenDuplicateRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn find_by_hashes(&self, hashes: Vec<String>) -> DuplicateResult<Vec<PostedFile>> {
+        let hashes: Vec<_> = hashes.into_iter().filter(|hash| !hash.is_empty()).collect();
+        if hashes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let db = self.db.get_connection().await?;
+
+        let files = meme_urls::Entity::find()
+            .find_also_related(memes::Entity)
+            .filter(meme_urls::Column::Hash.is_in(hashes))
+            .filter(memes::Column::Status.ne(Status::Deleted))
+            .order_by_asc(meme_urls::Column::CreatedDateTime)
+            .all(&db)
+            .await?
+            .into_iter()
+            .filter_map(|(file, meme)| {
+                let meme = meme?;
+                Some(PostedFile {
+                    meme_id: meme.id,
+                    short_id: meme.short_id,
+                    url_id: file.id,
+                    url: file.url,
+                    hash: file.hash,
+                })
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    async fn clusters(
+        &self,
+        max_distance: u32,
+        limit: usize,
+    ) -> DuplicateResult<Vec<DuplicateCluster>> {
+        let max_distance = max_distance.min(MAX_DISTANCE);
+
+        let db = self.db.get_connection().await?;
+
+        let files: Vec<(Uuid, String, Option<i64>)> = meme_urls::Entity::find()
+            .select_only()
+            .columns([
+                meme_urls::Column::MemeId,
+                meme_urls::Column::Hash,
+                meme_urls::Column::Phash,
+            ])
+            .filter(
+                meme_urls::Column::MemeId.in_subquery(
+                    memes::Entity::find()
+                        .select_only()
+                        .column(memes::Column::Id)
+                        .filter(memes::Column::Status.ne(Status::Deleted))
+                        .into_query(),
+                ),
+            )
+            .into_tuple()
+            .all(&db)
+            .await?;
+
+        // near pictures are searched in a BK-tree, still too slow for the async threads
+        let mut groups = tokio::task::spawn_blocking(move || group(files, max_distance))
+            .await
+            .map_err(|e| DuplicateError::Cluster(e.to_string()))?;
+        groups.truncate(limit);
+
+        let mut models: HashMap<Uuid, memes::Model> = memes::Entity::find()
+            .filter(memes::Column::Id.is_in(groups.iter().flat_map(|(_, ids)| ids.clone())))
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(|model| (model.id, model))
+            .collect();
+
+        let mut clusters = vec![];
+        for (distance, ids) in groups {
+            let mut group: Vec<_> = ids.iter().filter_map(|id| models.remove(id)).collect();
+            group.sort_by_key(|model| (model.created_date_time, model.id));
+
+            clusters.push(DuplicateCluster {
+                distance,
+                memes: duplicate_memes(&db, group).await?,
+            });
+        }
+
+        Ok(clusters)
+    }
+
+    async fn merge(
+        &self,
+        actor: &Actor,
+        keep: Uuid,
+        duplicate: Uuid,
+    ) -> DuplicateResult<DuplicateMeme> {
+        if keep == duplicate {
+            return Err(DuplicateError::SameMeme);
+        }
+
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+
+        let kept = memes::Entity::find_by_id(keep)
+            .one(&txn)
+            .await?
+            .ok_or(MemeError::HasNotAnyMeme)?;
+        let merged = memes::Entity::find_by_id(duplicate)
+            .one(&txn)
+            .await?
+            .ok_or(MemeError::HasNotAnyMeme)?;
+        if kept.status == Status::Deleted || merged.status == Status::Deleted {
+            return Err(MemeError::AlreadyDeleted.into());
+        }
+
+        let (left_likes, left_unlikes) = move_votes(&txn, keep, duplicate).await?;
+        // the counters may hold likes from before the votes were kept, those are added up too
+        memes::Entity::update_many()
+            .col_expr(
+                memes::Column::Likes,
+                Expr::col(memes::Column::Likes).add((merged.likes - left_likes).max(0)),
+            )
+            .col_expr(
+                memes::Column::Unlikes,
+                Expr::col(memes::Column::Unlikes).add((merged.unlikes - left_unlikes).max(0)),
+            )
+            .filter(memes::Column::Id.eq(keep))
+            .exec(&txn)
+            .await?;
+
+        move_reactions(&txn, keep, duplicate).await?;
+
+        meme_interactions::Entity::update_many()
+            .col_expr(meme_interactions::Column::MemeId, Expr::value(keep))
+            .filter(meme_interactions::Column::MemeId.eq(duplicate))
+            .exec(&txn)
+            .await?;
+
+        meme_hot_scores::Entity::delete_many()
+            .filter(meme_hot_scores::Column::MemeId.eq(duplicate))
+            .exec(&txn)
+            .await?;
+
+        let mut categories = get_meme_categories(&txn, vec![keep, duplicate]).await?;
+        let mut names = categories.remove(&keep).unwrap_or_default();
+        for name in categories.remove(&duplicate).unwrap_or_default() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        if names.len() > 1 {
+            names.retain(|name| name != db_entity::DEFAULT_CATEGORY);
+        }
+        set_meme_categories(&txn, keep, names.clone()).await?;
+
+        // its votes are gone, restoring it later does not count them twice
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let status_before_delete = merged.status;
+        let details = json!({
+            "duplicate": duplicate,
+            "likes": merged.likes,
+            "unlikes": merged.unlikes,
+            "categories": names,
+        });
+        let mut merged: memes::ActiveModel = merged.into();
+        merged.likes = Set(0);
+        merged.unlikes = Set(0);
+        merged.status = Set(Status::Deleted);
+        merged.status_before_delete = Set(Some(status_before_delete));
+        merged.deleted_by = Set(actor.id);
+        merged.deleted_date_time = Set(Some(now));
+        merged.last_actiity_date_time = Set(now);
+        merged.update(&txn).await?;
+
+        index_memes(&txn, vec![keep]).await?;
+
+        audit::record(&txn, actor, "meme.merge", Some(keep.to_string()), details).await?;
+
+        let kept = memes::Entity::find_by_id(keep)
+            .one(&txn)
+            .await?
+            .ok_or(MemeError::HasNotAnyMeme)?;
+
+        txn.commit().await?;
+
+        let mut list = duplicate_memes(&db, vec![kept]).await?;
+        list.pop().ok_or_else(|| MemeError::HasNotAnyMeme.into())
+    }
+}
+
+/// the memes tied by their files, with the largest distance of the files tying them,
+/// the largest groups first
+fn group(files: Vec<(Uuid, String, Option<i64>)>, max_distance: u32) -> Vec<(u32, Vec<Uuid>)> {
+    let mut ids: Vec<Uuid> = files.iter().map(|(id, _, _)| *id).collect();
+    ids.sort();
+    ids.dedup();
+    let index = |id: &Uuid| ids.binary_search(id).unwrap_or_default();
+
+    // two memes and the distance of the files tying them
+    let mut links: Vec<(usize, usize, u32)> = vec![];
+
+    let mut by_hash: HashMap<&str, usize> = HashMap::new();
+    for (id, hash, _) in files.iter().filter(|(_, hash, _)| !hash.is_empty()) {
+        match by_hash.entry(hash.as_str()) {
+            Entry::Occupied(first) => links.push((*first.get(), index(id), 0)),
+            Entry::Vacant(entry) => {
+                entry.insert(index(id));
+            }
+        }
+    }
+
+    // reposts often share a phash, each one is searched once
+    let mut by_phash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (id, _, phash) in files.iter() {
+        if let Some(phash) = phash {
+            by_phash.entry(*phash as u64).or_default().push(index(id));
+        }
+    }
+    let mut phashes: Vec<u64> = by_phash.keys().copied().collect();
+    phashes.sort();
+
+    let mut tree = BkTree::default();
+    for phash in phashes {
+        let owners = &by_phash[&phash];
+        for b in &owners[1..] {
+            links.push((owners[0], *b, 0));
+        }
+        // only the hashes inserted before, so each two are linked once
+        for (near, bits) in tree.within(phash, max_distance) {
+            for a in owners {
+                for b in &by_phash[&near] {
+                    if a != b {
+                        links.push((*a, *b, bits));
+                    }
+                }
+            }
+        }
+        tree.insert(phash);
+    }
+
+    let mut parents: Vec<usize> = (0..ids.len()).collect();
+    for (a, b, _) in links.iter() {
+        let (a, b) = (root(&mut parents, *a), root(&mut parents, *b));
+        if a != b {
+            parents[a] = b;
+        }
+    }
+
+    let mut distances: HashMap<usize, u32> = HashMap::new();
+    for (a, _, bits) in links {
+        let farthest = distances.entry(root(&mut parents, a)).or_default();
+        *farthest = (*farthest).max(bits);
+    }
+
+    let mut members: HashMap<usize, Vec<Uuid>> = HashMap::new();
+    for (at, id) in ids.iter().enumerate() {
+        members.entry(root(&mut parents, at)).or_default().push(*id);
+    }
+
+    let mut groups: Vec<(u32, Vec<Uuid>)> = members
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(group, ids)| (distances.get(&group).copied().unwrap_or_default(), ids))
+        .collect();
+    groups.sort_by(|(a_bits, a), (b_bits, b)| {
+        b.len()
+            .cmp(&a.len())
+            .then(a_bits.cmp(b_bits))
+            .then(a[0].cmp(&b[0]))
+    });
+    groups
+}
+
+fn root(parents: &mut [usize], mut at: usize) -> usize {
+    while parents[at] != at {
+        parents[at] = parents[parents[at]];
+        at = parents[at];
+    }
+    at
+}
+
+async fn duplicate_memes(
+    db: &impl ConnectionTrait,
+    models: Vec<memes::Model>,
+) -> DuplicateResult<Vec<DuplicateMeme>> {
+    let counters: Vec<_> = models
+        .iter()
+        .map(|model| (model.likes, model.unlikes))
+        .collect();
+
+    Ok(models_2_meme_list(models, db)
+        .await?
+        .into_iter()
+        .zip(counters)
+        .map(|(meme, (likes, unlikes))| DuplicateMeme {
+            meme,
+            likes,
+            unlikes,
+        })
+        .collect())
+}
+
+/// move the votes on `from` to `to` unless the visitor voted on `to` as well,
+/// return the likes and the unlikes left out
+async fn move_votes(
+    conn: &impl ConnectionTrait,
+    to: Uuid,
+    from: Uuid,
+) -> Result<(i32, i32), DbErr> {
+    let voted: HashSet<String> = meme_votes::Entity::find()
+        .select_only()
+        .column(meme_votes::Column::Visitor)
+        .filter(meme_votes::Column::MemeId.eq(to))
+        .into_tuple::<String>()
+        .all(conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let rows = meme_votes::Entity::find()
+        .filter(meme_votes::Column::MemeId.eq(from))
+        .all(conn)
+        .await?;
+
+    meme_votes::Entity::delete_many()
+        .filter(meme_votes::Column::MemeId.eq(from))
+        .exec(conn)
+        .await?;
+
+    let (moved, left): (Vec<_>, Vec<_>) = rows
+        .into_iter()
+        .partition(|row| !voted.contains(&row.visitor));
+
+    for chunk in moved.chunks(INSERT_CHUNK) {
+        meme_votes::Entity::insert_many(chunk.iter().map(|row| meme_votes::ActiveModel {
+            meme_id: Set(to),
+            visitor: Set(row.visitor.clone()),
+            kind: Set(row.kind),
+            created_date_time: Set(row.created_date_time),
+            updated_date_time: Set(row.updated_date_time),
+        }))
+        .exec_without_returning(conn)
+        .await?;
+    }
+
+    let count = |kind| left.iter().filter(|row| row.kind == kind).count() as i32;
+    Ok((count(Kind::Like), count(Kind::Unlike)))
+}
+
+/// move the reactions on `from` to `to` unless the visitor gave the same reaction to `to`
+async fn move_reactions(conn: &impl ConnectionTrait, to: Uuid, from: Uuid) -> Result<(), DbErr> {
+    let given: HashSet<(String, String)> = meme_reactions::Entity::find()
+        .filter(meme_reactions::Column::MemeId.eq(to))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|row| (row.visitor, row.reaction))
+        .collect();
+
+    let rows = meme_reactions::Entity::find()
+        .filter(meme_reactions::Column::MemeId.eq(from))
+        .all(conn)
+        .await?;
+
+    meme_reactions::Entity::delete_many()
+        .filter(meme_reactions::Column::MemeId.eq(from))
+        .exec(conn)
+        .await?;
+
+    meme_reaction_counts::Entity::delete_many()
+        .filter(meme_reaction_counts::Column::MemeId.eq(from))
+        .exec(conn)
+        .await?;
+
+    let moved: Vec<_> = rows
+        .into_iter()
+        .filter(|row| !given.contains(&(row.visitor.clone(), row.reaction.clone())))
+        .collect();
+
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for row in moved.iter() {
+        *counts.entry(row.reaction.as_str()).or_default() += 1;
+    }
+
+    for chunk in moved.chunks(INSERT_CHUNK) {
+        meme_reactions::Entity::insert_many(chunk.iter().map(|row| meme_reactions::ActiveModel {
+            meme_id: Set(to),
+            visitor: Set(row.visitor.clone()),
+            reaction: Set(row.reaction.clone()),
+            created_date_time: Set(row.created_date_time),
+        }))
+        .exec_without_returning(conn)
+        .await?;
+    }
+
+    for (reaction, count) in counts {
+        meme_reaction_counts::Entity::insert(meme_reaction_counts::ActiveModel {
+            meme_id: Set(to),
+            reaction: Set(reaction.to_owned()),
+            count: Set(count),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                meme_reaction_counts::Column::MemeId,
+                meme_reaction_counts::Column::Reaction,
+            ])
+            .value(
+                meme_reaction_counts::Column::Count,
+                Expr::col((
+                    meme_reaction_counts::Entity,
+                    meme_reaction_counts::Column::Count,
+                ))
+                .add(count),
+            )
+            .to_owned(),
+        )
+        .exec_without_returning(conn)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Duplicates
+//!
+//! a file posted again has the same `hash` in `meme_urls`, a resized or re-encoded copy of it
+//! has a `phash` a few bits away, see `media::perceptual`. memes sharing such files are
+//! grouped for the admins, who merge them into one
+
+mod bk_tree;
+pub mod gen_duplicate_repo;
+
+#[cfg(test)]
+mod test;
+
+use migration::async_trait;
+use sea_orm::{DbErr, prelude::Uuid};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    audit::Actor,
+    meme::{Meme, MemeError},
+};
+
+pub type DuplicateResult<T> = Result<T, DuplicateError>;
+
+/// the distance of `clusters` unless asked otherwise
+pub const DEFAULT_MAX_DISTANCE: u32 = 6;
+/// farther pictures have little in common
+pub const MAX_DISTANCE: u32 = 16;
+
+#[async_trait::async_trait]
+pub trait DuplicateRepository {
+    /// the files with one of `hashes` of the memes not in trash
+    async fn find_by_hashes(&self, _hashes: Vec<String>) -> DuplicateResult<Vec<PostedFile>> {
+        unimplemented!()
+    }
+
+    /// memes not in trash sharing a file or with files at most `max_distance` bits apart,
+    /// the largest groups first and at most `limit` of them. `max_distance` is capped by
+    /// `MAX_DISTANCE`
+    async fn clusters(
+        &self,
+        _max_distance: u32,
+        _limit: usize,
+    ) -> DuplicateResult<Vec<DuplicateCluster>> {
+        unimplemented!()
+    }
+
+    /// fold `duplicate` into `keep` and move it to trash. the votes and reactions of visitors
+    /// who did not vote on `keep` are moved to it, the likes counted before votes were kept
+    /// are added up, and `keep` takes the categories of both. the merge is audited
+    async fn merge(
+        &self,
+        _actor: &Actor,
+        _keep: Uuid,
+        _duplicate: Uuid,
+    ) -> DuplicateResult<DuplicateMeme> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicDuplicateRepository;
+
+impl DuplicateRepository for PanicDuplicateRepository {}
+
+/// a file of a meme already posted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PostedFile {
+    pub meme_id: Uuid,
+    pub short_id: String,
+    pub url_id: Uuid,
+    pub url: String,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicateCluster {
+    /// the largest distance of two files that tied memes together
+    pub distance: u32,
+    /// the earliest posted first
+    pub memes: Vec<DuplicateMeme>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicateMeme {
+    #[serde(flatten)]
+    pub meme: Meme,
+    pub likes: i32,
+    pub unlikes: i32,
+}
+
+#[derive(Error, Debug)]
+pub enum DuplicateError {
+    #[error("a meme cannot be merged into itself")]
+    SameMeme,
+    #[error("grouping the files failed: {0}")]
+    Cluster(String),
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error(transparent)]
+    Meme(#[from] MemeError),
+}
@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests {
+    use db_entity::{meme_votes, memes};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::Expr, prelude::Uuid};
+
+    use crate::{
+        business::{
+            audit::{Actor, AuditRepository, GetFilter, gen_audit_repo::GenAuditRepo},
+            cache::MockCache,
+            duplicates::{
+                DuplicateError, DuplicateRepository, bk_tree::BkTree,
+                gen_duplicate_repo::GenDuplicateRepo,
+            },
+            media::perceptual::distance,
+            meme::{
+                MemeError, MemeRepository, PostMeme, PostMemeUrl, Voter,
+                gen_meme_repo::GenMemeRepo,
+                test_support::{self, meme_url, post_meme},
+            },
+        },
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    fn meme_repo(db: &TestDB) -> GenMemeRepo<MockCache<String, String>, TestDB> {
+        GenMemeRepo::new(db.clone())
+    }
+
+    /// post a meme of one file, return its id
+    async fn post(
+        db: &TestDB,
+        message: &str,
+        category: &str,
+        hash: &str,
+        phash: Option<u64>,
+    ) -> Uuid {
+        let media = PostMemeUrl {
+            hash: hash.to_owned(),
+            phash,
+            ..meme_url(&format!("https://example.com/{}.png", message))
+        };
+        test_support::post(
+            db,
+            PostMeme {
+                memes: vec![media],
+                ..post_meme(message, &[category])
+            },
+        )
+        .await
+    }
+
+    fn voter(name: &str) -> Voter {
+        Voter {
+            key: format!("device:{}", name),
+            client_id: format!("client-{}", name),
+        }
+    }
+
+    async fn trash(db: &TestDB, id: Uuid) {
+        let meme = meme_repo(db).get_meme(id).await.unwrap().unwrap();
+        meme.delete(Uuid::nil()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn find_by_hashes_success() {
+        let db = TestDB::new().await;
+        let repo = GenDuplicateRepo::new(db.clone());
+
+        let cat = post(&db, "cat", "animals", "hash-cat", None).await;
+        let dog = post(&db, "dog", "animals", "hash-dog", None).await;
+        trash(&db, dog).await;
+
+        let found = repo
+            .find_by_hashes(vec![
+                "hash-cat".to_owned(),
+                "hash-dog".to_owned(),
+                "hash-new".to_owned(),
+                String::new(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].meme_id, cat);
+        assert_eq!(found[0].hash, "hash-cat");
+        assert_eq!(found[0].url, "https://example.com/cat.png");
+
+        assert!(repo.find_by_hashes(vec![]).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn bk_tree_within_success() {
+        let hashes: Vec<u64> = (0..500u64)
+            .map(|at| at.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (at % 48))
+            .collect();
+
+        let mut tree = BkTree::default();
+        for hash in hashes.iter() {
+            tree.insert(*hash);
+        }
+
+        for hash in hashes.iter().step_by(7) {
+            let mut found = tree.within(*hash, 12);
+            found.sort();
+            let mut expected: Vec<_> = hashes
+                .iter()
+                .map(|other| (*other, distance(*hash, *other)))
+                .filter(|(_, bits)| *bits <= 12)
+                .collect();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn clusters_success() {
+        let db = TestDB::new().await;
+        let repo = GenDuplicateRepo::new(db.clone());
+
+        let a = post(&db, "a", "animals", "hash-a", Some(0)).await;
+        let b = post(&db, "b", "animals", "hash-b", Some(0b111)).await;
+        let c = post(&db, "c", "animals", "hash-c", Some(u64::MAX)).await;
+        // the same file without a perceptual hash, e.g. a VP9 video
+        let d = post(&db, "d", "animals", "hash-c", None).await;
+        let e = post(&db, "e", "animals", "hash-e", Some(0xFFFF_0000_FFFF_0000)).await;
+        let trashed = post(&db, "f", "animals", "hash-e", Some(0)).await;
+        trash(&db, trashed).await;
+
+        let ids = |cluster: &crate::business::duplicates::DuplicateCluster| -> Vec<Uuid> {
+            cluster.memes.iter().map(|item| item.meme.id).collect()
+        };
+
+        let clusters = repo.clusters(4, 10).await.unwrap();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].distance, 0);
+        assert_eq!(ids(&clusters[0]), vec![c, d]);
+        assert_eq!(clusters[1].distance, 3);
+        assert_eq!(ids(&clusters[1]), vec![a, b]);
+
+        let clusters = repo.clusters(2, 10).await.unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(ids(&clusters[0]), vec![c, d]);
+
+        let clusters = repo.clusters(4, 1).await.unwrap();
+        assert_eq!(clusters.len(), 1);
+
+        // the distance is capped, a and e are 32 bits apart
+        let clusters = repo.clusters(64, 10).await.unwrap();
+        assert!(clusters.iter().all(|cluster| !ids(cluster).contains(&e)));
+    }
+
+    #[tokio::test]
+    async fn merge_success() {
+        let db = TestDB::new().await;
+        let repo = GenDuplicateRepo::new(db.clone());
+        let memes = meme_repo(&db);
+
+        let keep = post(&db, "keep", "cats", "hash-keep", Some(0)).await;
+        let duplicate = post(&db, "duplicate", "funny", "hash-duplicate", Some(1)).await;
+
+        memes
+            .add_reaction(keep, &voter("both"), "like")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(keep, &voter("both"), "😂")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("both"), "like")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("both"), "😂")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("fan"), "like")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("fan"), "😂")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("hater"), "unlike")
+            .await
+            .unwrap();
+        memes
+            .add_reaction(duplicate, &voter("hater"), "💀")
+            .await
+            .unwrap();
+
+        // likes counted before the votes were kept
+        let db_conn = db.get_connection().await.unwrap();
+        memes::Entity::update_many()
+            .col_expr(memes::Column::Likes, Expr::col(memes::Column::Likes).add(2))
+            .filter(memes::Column::Id.eq(duplicate))
+            .exec(&db_conn)
+            .await
+            .unwrap();
+
+        let merged = repo.merge(&Actor::cli(), keep, duplicate).await.unwrap();
+        assert_eq!(merged.meme.id, keep);
+        // 1 of its own, 2 voted on the duplicate only and the 2 counted before votes
+        assert_eq!(merged.likes, 4);
+        assert_eq!(merged.unlikes, 1);
+        assert_eq!(merged.meme.categories, vec!["cats", "funny"]);
+
+        let voters: Vec<String> = meme_votes::Entity::find()
+            .filter(meme_votes::Column::MemeId.eq(keep))
+            .all(&db_conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|vote| vote.visitor)
+            .collect();
+        assert_eq!(voters.len(), 3);
+
+        let interactions = memes
+            .get_interactions(vec![keep], Some("device:fan".to_owned()))
+            .await
+            .unwrap();
+        let interaction = serde_json::to_value(&interactions[0]).unwrap();
+        assert_eq!(
+            interaction["my_reactions"],
+            serde_json::json!(["like", "😂"])
+        );
+        let count = |reaction: &str| {
+            interaction["reactions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|item| item["reaction"] == reaction)
+                .unwrap()["count"]
+                .clone()
+        };
+        assert_eq!(count("😂"), 2);
+        assert_eq!(count("💀"), 1);
+
+        let trashed = memes::Entity::find_by_id(duplicate)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(trashed.status, memes::Status::Deleted);
+        assert_eq!(trashed.likes, 0);
+
+        let logs = GenAuditRepo::new(db)
+            .get_paginated_logs(GetFilter {
+                page: 1,
+                size: 10,
+                action: Some("meme.merge".to_owned()),
+                actor_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(logs.list.len(), 1);
+        assert_eq!(logs.list[0].target_id, Some(keep.to_string()));
+    }
+
+    #[tokio::test]
+    async fn merge_rejected() {
+        let db = TestDB::new().await;
+        let repo = GenDuplicateRepo::new(db.clone());
+
+        let keep = post(&db, "keep", "cats", "hash-keep", None).await;
+        let trashed = post(&db, "trashed", "cats", "hash-trashed", None).await;
+        trash(&db, trashed).await;
+
+        let res = repo.merge(&Actor::cli(), keep, keep).await;
+        assert!(matches!(res, Err(DuplicateError::SameMeme)));
+
+        let res = repo.merge(&Actor::cli(), keep, trashed).await;
+        assert!(matches!(
+            res,
+            Err(DuplicateError::Meme(MemeError::AlreadyDeleted))
+        ));
+
+        let res = repo.merge(&Actor::cli(), keep, Uuid::now_v7()).await;
+        assert!(matches!(
+            res,
+            Err(DuplicateError::Meme(MemeError::HasNotAnyMeme))
+        ));
+    }
+}
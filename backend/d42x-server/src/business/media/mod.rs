@@ -0,0 +1,210 @@
+//! Media validation
+//!
+//! the files of a meme are judged by their content rather than by what the client declares:
+//! the format comes from the magic bytes, the size and the dimensions are limited per format
+//! and the hash is computed by the server, see `perceptual` for the hash of what a file looks like
+//! and `placeholder` for what is shown while it loads
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, ImageReader};
+use thiserror::Error;
+
+use crate::config::AllowMemeFormats;
+
+pub mod perceptual;
+pub mod placeholder;
+pub mod webm;
+
+#[cfg(test)]
+mod test;
+
+pub type MediaResult<T> = Result<T, MediaError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatLimit {
+    /// in bytes
+    pub max_size: usize,
+    /// the longest side, in pixels
+    pub max_dimension: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaPolicy {
+    pub jpeg: FormatLimit,
+    pub png: FormatLimit,
+    pub gif: FormatLimit,
+    pub webp: FormatLimit,
+    pub webm: FormatLimit,
+}
+
+impl MediaPolicy {
+    pub fn limit(&self, format: AllowMemeFormats) -> FormatLimit {
+        match format {
+            AllowMemeFormats::JPG | AllowMemeFormats::JPEG => self.jpeg,
+            AllowMemeFormats::PNG => self.png,
+            AllowMemeFormats::GIF => self.gif,
+            AllowMemeFormats::WEBP => self.webp,
+            AllowMemeFormats::WEBM => self.webm,
+        }
+    }
+
+    /// the largest file of any format, a download is cut off after it
+    pub fn max_size(&self) -> usize {
+        [self.jpeg, self.png, self.gif, self.webp, self.webm]
+            .iter()
+            .map(|limit| limit.max_size)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for MediaPolicy {
+    fn default() -> Self {
+        const MB: usize = 1024 * 1024;
+
+        let image = FormatLimit {
+            max_size: 10 * MB,
+            max_dimension: 8192,
+        };
+        Self {
+            jpeg: image,
+            png: image,
+            gif: FormatLimit {
+                max_size: 20 * MB,
+                max_dimension: 4096,
+            },
+            webp: image,
+            webm: FormatLimit {
+                max_size: 50 * MB,
+                max_dimension: 4096,
+            },
+        }
+    }
+}
+
+/// what the server found out about a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaInfo {
+    pub format: AllowMemeFormats,
+    pub size: usize,
+    pub width: u32,
+    pub height: u32,
+    /// blake3 of the bytes, in hex
+    pub hash: String,
+}
+
+/// the format by the magic bytes, `JPEG` for any JPEG file
+pub fn sniff(bytes: &[u8]) -> Option<AllowMemeFormats> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(AllowMemeFormats::JPEG)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(AllowMemeFormats::PNG)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(AllowMemeFormats::GIF)
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(AllowMemeFormats::WEBP)
+    } else if webm::is_webm(bytes) {
+        Some(AllowMemeFormats::WEBM)
+    } else {
+        None
+    }
+}
+
+/// `JPG` and `JPEG` are the same format
+pub fn same_format(a: AllowMemeFormats, b: AllowMemeFormats) -> bool {
+    let normalize = |format| match format {
+        AllowMemeFormats::JPG => AllowMemeFormats::JPEG,
+        other => other,
+    };
+    normalize(a) == normalize(b)
+}
+
+pub fn hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// check `bytes` against `policy`
+pub fn inspect(bytes: &[u8], policy: &MediaPolicy) -> MediaResult<MediaInfo> {
+    let format = sniff(bytes).ok_or(MediaError::UnknownFormat)?;
+
+    let limit = policy.limit(format);
+    if bytes.len() > limit.max_size {
+        return Err(MediaError::TooLarge(bytes.len(), limit.max_size));
+    }
+
+    let (width, height) = dimensions(bytes, format)?;
+    if width.max(height) > limit.max_dimension {
+        return Err(MediaError::TooBig(width, height, limit.max_dimension));
+    }
+
+    Ok(MediaInfo {
+        format,
+        size: bytes.len(),
+        width,
+        height,
+        hash: hash(bytes),
+    })
+}
+
+/// read from the headers, the frames are not decoded
+fn dimensions(bytes: &[u8], format: AllowMemeFormats) -> MediaResult<(u32, u32)> {
+    let Some(image_format) = image_format(format) else {
+        return webm::dimensions(bytes)
+            .ok_or_else(|| MediaError::Unreadable(String::from("no video track")));
+    };
+
+    ImageReader::with_format(Cursor::new(bytes), image_format)
+        .into_dimensions()
+        .map_err(|e| MediaError::Unreadable(e.to_string()))
+}
+
+/// the picture, the first frame of an animation or the first frame of a VP8 video
+pub fn first_frame(bytes: &[u8], format: AllowMemeFormats) -> MediaResult<DynamicImage> {
+    let res = match image_format(format) {
+        Some(image_format) => image::load_from_memory_with_format(bytes, image_format),
+        None => {
+            let frame = webm::first_vp8_frame(bytes)
+                .ok_or_else(|| MediaError::Unreadable(String::from("not a VP8 video")))?;
+            image::load_from_memory_with_format(&webm::vp8_as_webp(frame), ImageFormat::WebP)
+        }
+    };
+
+    res.map_err(|e| MediaError::Unreadable(e.to_string()))
+}
+
+/// GIF and WebM are taken as animated, a WebP is animated by the flag of its VP8X header
+pub fn is_animated(bytes: &[u8], format: AllowMemeFormats) -> bool {
+    match format {
+        AllowMemeFormats::GIF | AllowMemeFormats::WEBM => true,
+        AllowMemeFormats::WEBP => {
+            bytes.len() > 20 && &bytes[12..16] == b"VP8X" && bytes[20] & 0x02 != 0
+        }
+        _ => false,
+    }
+}
+
+/// `None` for WebM, which `image` does not read
+fn image_format(format: AllowMemeFormats) -> Option<ImageFormat> {
+    match format {
+        AllowMemeFormats::JPG | AllowMemeFormats::JPEG => Some(ImageFormat::Jpeg),
+        AllowMemeFormats::PNG => Some(ImageFormat::Png),
+        AllowMemeFormats::GIF => Some(ImageFormat::Gif),
+        AllowMemeFormats::WEBP => Some(ImageFormat::WebP),
+        AllowMemeFormats::WEBM => None,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MediaError {
+    #[error("not a supported image or video")]
+    UnknownFormat,
+    #[error("declared as {0} but the file is {1}")]
+    FormatMismatch(AllowMemeFormats, AllowMemeFormats),
+    #[error("{0} bytes is larger than the limit of {1} bytes")]
+    TooLarge(usize, usize),
+    #[error("{0}x{1} is larger than the limit of {2} pixels")]
+    TooBig(u32, u32, u32),
+    #[error("unreadable file: {0}")]
+    Unreadable(String),
+}
@@ -0,0 +1,33 @@
+//! Perceptual hashes
+//!
+//! a dHash tells whether each pixel of a 9x8 grayscale thumbnail is darker than the one on
+//! its right, so a resized or re-encoded copy of a picture is only a few bits away from it
+
+use image::{DynamicImage, imageops::FilterType};
+
+use crate::config::AllowMemeFormats;
+
+use super::first_frame;
+
+/// the dHash of the picture or its first frame, `None` when it cannot be decoded
+pub fn perceptual_hash(bytes: &[u8], format: AllowMemeFormats) -> Option<u64> {
+    first_frame(bytes, format).ok().map(|image| dhash(&image))
+}
+
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let thumbnail = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let darker = thumbnail.get_pixel(x, y)[0] < thumbnail.get_pixel(x + 1, y)[0];
+            hash = hash << 1 | u64::from(darker);
+        }
+    }
+    hash
+}
+
+/// the count of differing bits
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
@@ -0,0 +1,86 @@
+//! Placeholders
+//!
+//! a BlurHash and the dominant colour of a picture, painted by the client in the place of the
+//! file until it is loaded. neither has any detail, so they are computed from a small copy
+
+use std::collections::BTreeMap;
+
+use image::{DynamicImage, RgbaImage, imageops::FilterType};
+use serde::Serialize;
+
+use crate::config::AllowMemeFormats;
+
+use super::first_frame;
+
+/// the BlurHash components, across and down
+const COMPONENTS: (u32, u32) = (4, 3);
+/// the longest side of the copy
+const SAMPLE_SIZE: u32 = 32;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub blurhash: String,
+    /// `#rrggbb`
+    pub dominant_color: String,
+}
+
+/// the placeholder of the picture or its first frame, `None` when it cannot be decoded
+pub fn placeholder_of(bytes: &[u8], format: AllowMemeFormats) -> Option<Placeholder> {
+    first_frame(bytes, format)
+        .ok()
+        .and_then(|image| placeholder(&image))
+}
+
+pub fn placeholder(image: &DynamicImage) -> Option<Placeholder> {
+    let sample = image
+        .resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+        .to_rgba8();
+
+    let blurhash = blurhash::encode(
+        COMPONENTS.0,
+        COMPONENTS.1,
+        sample.width(),
+        sample.height(),
+        sample.as_raw(),
+    )
+    .ok()?;
+
+    Some(Placeholder {
+        blurhash,
+        dominant_color: dominant_color(&sample),
+    })
+}
+
+/// the average of the pixels of the most common colour, the colours cut down to 4 bits a
+/// channel. the transparent pixels are left out unless there are only transparent ones
+pub fn dominant_color(image: &RgbaImage) -> String {
+    let buckets = |min_alpha: u8| {
+        let mut buckets: BTreeMap<[u8; 3], (u32, [u32; 3])> = BTreeMap::new();
+        for pixel in image.pixels().filter(|pixel| pixel[3] >= min_alpha) {
+            let [r, g, b, _] = pixel.0;
+            let (count, sum) = buckets.entry([r >> 4, g >> 4, b >> 4]).or_default();
+            *count += 1;
+            sum[0] += u32::from(r);
+            sum[1] += u32::from(g);
+            sum[2] += u32::from(b);
+        }
+        buckets
+    };
+
+    let mut opaque = buckets(128);
+    if opaque.is_empty() {
+        opaque = buckets(0);
+    }
+
+    let (count, sum) = opaque
+        .into_values()
+        .max_by_key(|(count, _)| *count)
+        .unwrap_or((1, [0; 3]));
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        sum[0] / count,
+        sum[1] / count,
+        sum[2] / count
+    )
+}
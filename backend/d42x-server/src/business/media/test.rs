@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage, imageops::FilterType};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        business::media::{
+            FormatLimit, MediaError, MediaPolicy, first_frame, hash, inspect,
+            perceptual::{dhash, distance, perceptual_hash},
+            placeholder::{dominant_color, placeholder, placeholder_of},
+            same_format, sniff, webm,
+        },
+        config::AllowMemeFormats,
+    };
+
+    fn encode(format: ImageFormat, width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        RgbImage::new(width, height)
+            .write_to(&mut bytes, format)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    fn element(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.push(0x80 | data.len() as u8);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// an EBML header of `doc_type` and a segment of unknown size with one video track
+    fn webm_file(doc_type: &[u8], video: bool) -> Vec<u8> {
+        let mut bytes = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], doc_type));
+
+        let mut entry = element(&[0xD7], &[0x01]);
+        if video {
+            let mut size = element(&[0xB0], &[0x02, 0x80]);
+            size.extend(element(&[0xBA], &[0x01, 0x68]));
+            entry.extend(element(&[0xE0], &size));
+        }
+        let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &entry));
+
+        bytes.extend([
+            0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ]);
+        bytes.extend(tracks);
+        bytes
+    }
+
+    /// a 16x16 VP8 keyframe, dark on the left and bright on the right
+    const VP8_FRAME: [u8; 28] = [
+        80, 1, 0, 157, 1, 42, 16, 0, 16, 0, 2, 192, 76, 37, 164, 0, 4, 116, 0, 0, 254, 75, 255,
+        253, 95, 112, 0, 0,
+    ];
+
+    /// a video track of `codec` and a cluster with a block of another track before the frame
+    fn video_webm(codec: &[u8]) -> Vec<u8> {
+        let mut bytes = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"webm"));
+
+        let mut size = element(&[0xB0], &[16]);
+        size.extend(element(&[0xBA], &[16]));
+        let mut entry = element(&[0xD7], &[0x01]);
+        entry.extend(element(&[0x86], codec));
+        entry.extend(element(&[0xE0], &size));
+        let mut segment = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &entry));
+
+        let mut cluster = element(&[0xE7], &[0x00]);
+        cluster.extend(element(&[0xA3], &[0x82, 0x00, 0x00, 0x80, 0xFF]));
+        let mut block = vec![0x81, 0x00, 0x00, 0x80];
+        block.extend(VP8_FRAME);
+        cluster.extend(element(&[0xA3], &block));
+        segment.extend(element(&[0x1F, 0x43, 0xB6, 0x75], &cluster));
+
+        bytes.extend(element(&[0x18, 0x53, 0x80, 0x67], &segment));
+        bytes
+    }
+
+    /// dark on the left and bright on the right, like `VP8_FRAME`
+    fn halves(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgb([20, 20, 20])
+            } else {
+                Rgb([230, 230, 230])
+            }
+        })
+    }
+
+    fn gradient(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, y| {
+            let value = ((x * 7 + y * 3) % 256) as u8;
+            Rgb([value, 255 - value, value / 2])
+        })
+    }
+
+    #[test]
+    fn sniff_success() {
+        let cases = [
+            (ImageFormat::Jpeg, AllowMemeFormats::JPEG),
+            (ImageFormat::Png, AllowMemeFormats::PNG),
+            (ImageFormat::Gif, AllowMemeFormats::GIF),
+            (ImageFormat::WebP, AllowMemeFormats::WEBP),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(sniff(&encode(format, 4, 4)), Some(expected));
+        }
+
+        assert_eq!(
+            sniff(&webm_file(b"webm", true)),
+            Some(AllowMemeFormats::WEBM)
+        );
+        assert_eq!(sniff(&webm_file(b"matroska", true)), None);
+        assert_eq!(sniff(b"<svg></svg>"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn same_format_success() {
+        assert!(same_format(AllowMemeFormats::JPG, AllowMemeFormats::JPEG));
+        assert!(same_format(AllowMemeFormats::GIF, AllowMemeFormats::GIF));
+        assert!(!same_format(AllowMemeFormats::GIF, AllowMemeFormats::WEBP));
+    }
+
+    #[test]
+    fn inspect_success() {
+        let bytes = encode(ImageFormat::Png, 30, 20);
+
+        let info = inspect(&bytes, &MediaPolicy::default()).unwrap();
+        assert_eq!(info.format, AllowMemeFormats::PNG);
+        assert_eq!((info.width, info.height), (30, 20));
+        assert_eq!(info.size, bytes.len());
+        assert_eq!(info.hash, hash(&bytes));
+
+        let info = inspect(&webm_file(b"webm", true), &MediaPolicy::default()).unwrap();
+        assert_eq!(info.format, AllowMemeFormats::WEBM);
+        assert_eq!((info.width, info.height), (640, 360));
+    }
+
+    #[test]
+    fn inspect_limits() {
+        let bytes = encode(ImageFormat::Gif, 30, 20);
+        let policy = |limit| MediaPolicy {
+            gif: limit,
+            ..MediaPolicy::default()
+        };
+
+        let res = inspect(
+            &bytes,
+            &policy(FormatLimit {
+                max_size: 10,
+                max_dimension: 100,
+            }),
+        );
+        assert!(matches!(res, Err(MediaError::TooLarge(_, 10))));
+
+        let res = inspect(
+            &bytes,
+            &policy(FormatLimit {
+                max_size: 1024,
+                max_dimension: 25,
+            }),
+        );
+        assert!(matches!(res, Err(MediaError::TooBig(30, 20, 25))));
+
+        // the limits of another format do not apply
+        assert!(
+            inspect(
+                &encode(ImageFormat::Png, 30, 20),
+                &policy(FormatLimit {
+                    max_size: 10,
+                    max_dimension: 10,
+                })
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn inspect_unreadable() {
+        let res = inspect(&webm_file(b"webm", false), &MediaPolicy::default());
+        assert!(matches!(res, Err(MediaError::Unreadable(_))));
+
+        let mut bytes = encode(ImageFormat::Png, 30, 20);
+        bytes.truncate(12);
+        let res = inspect(&bytes, &MediaPolicy::default());
+        assert!(matches!(res, Err(MediaError::Unreadable(_))));
+
+        let res = inspect(b"hello", &MediaPolicy::default());
+        assert!(matches!(res, Err(MediaError::UnknownFormat)));
+    }
+
+    #[test]
+    fn webm_truncated() {
+        let bytes = webm_file(b"webm", true);
+        assert!(webm::is_webm(&bytes[..16]));
+        assert_eq!(webm::dimensions(&bytes[..bytes.len() - 2]), None);
+    }
+
+    #[test]
+    fn first_frame_success() {
+        let frame = first_frame(&video_webm(b"V_VP8"), AllowMemeFormats::WEBM).unwrap();
+        assert_eq!((frame.width(), frame.height()), (16, 16));
+
+        let res = first_frame(&video_webm(b"V_VP9"), AllowMemeFormats::WEBM);
+        assert!(matches!(res, Err(MediaError::Unreadable(_))));
+
+        let frame = first_frame(&encode(ImageFormat::Gif, 12, 8), AllowMemeFormats::GIF).unwrap();
+        assert_eq!((frame.width(), frame.height()), (12, 8));
+    }
+
+    #[test]
+    fn perceptual_hash_success() {
+        let picture = DynamicImage::ImageRgb8(gradient(120, 90));
+        let resized = picture.resize_exact(60, 45, FilterType::Lanczos3);
+        assert!(distance(dhash(&picture), dhash(&resized)) <= 4);
+
+        let flipped = picture.fliph();
+        assert!(distance(dhash(&picture), dhash(&flipped)) > 16);
+
+        // the frame of the video looks like the same picture in another format
+        let mut png = Cursor::new(vec![]);
+        halves(16, 16).write_to(&mut png, ImageFormat::Png).unwrap();
+        let video = perceptual_hash(&video_webm(b"V_VP8"), AllowMemeFormats::WEBM).unwrap();
+        let still = perceptual_hash(&png.into_inner(), AllowMemeFormats::PNG).unwrap();
+        assert!(distance(video, still) <= 4);
+
+        assert_eq!(
+            perceptual_hash(&video_webm(b"V_VP9"), AllowMemeFormats::WEBM),
+            None
+        );
+        assert_eq!(perceptual_hash(b"hello", AllowMemeFormats::PNG), None);
+    }
+
+    #[test]
+    fn vp8_as_webp_success() {
+        let bytes = webm::vp8_as_webp(&VP8_FRAME);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+            bytes.len() - 8
+        );
+        assert_eq!(sniff(&bytes), Some(AllowMemeFormats::WEBP));
+
+        // a chunk of odd size is padded
+        assert_eq!(webm::vp8_as_webp(&VP8_FRAME[..27]).len() % 2, 0);
+    }
+
+    #[test]
+    fn placeholder_success() {
+        // mostly red with a blue stripe
+        let picture = RgbaImage::from_fn(64, 48, |x, _| {
+            if x < 48 {
+                Rgba([200, 16, 16, 255])
+            } else {
+                Rgba([16, 16, 200, 255])
+            }
+        });
+        assert_eq!(dominant_color(&picture), "#c81010");
+
+        let found = placeholder(&DynamicImage::ImageRgba8(picture)).unwrap();
+        // 4x3 components
+        assert_eq!(found.blurhash.len(), 4 + 2 * 4 * 3);
+        assert_eq!(found.dominant_color, "#c81010");
+
+        // the transparent pixels are left out
+        let sticker = RgbaImage::from_fn(10, 10, |x, _| {
+            if x < 3 {
+                Rgba([0, 160, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 0])
+            }
+        });
+        assert_eq!(dominant_color(&sticker), "#00a000");
+        assert_eq!(
+            dominant_color(&RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 0]))),
+            "#ffffff"
+        );
+
+        let video = placeholder_of(&video_webm(b"V_VP8"), AllowMemeFormats::WEBM).unwrap();
+        assert_eq!(video.dominant_color.len(), 7);
+        assert_eq!(placeholder_of(b"hello", AllowMemeFormats::PNG), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! just enough EBML to tell a WebM file, the size of its video and the first VP8 frame
+
+const EBML: u64 = 0x1A45DFA3;
+const DOC_TYPE: u64 = 0x4282;
+const SEGMENT: u64 = 0x18538067;
+const TRACKS: u64 = 0x1654AE6B;
+const TRACK_ENTRY: u64 = 0xAE;
+const VIDEO: u64 = 0xE0;
+const PIXEL_WIDTH: u64 = 0xB0;
+const PIXEL_HEIGHT: u64 = 0xBA;
+const TRACK_NUMBER: u64 = 0xD7;
+const CODEC_ID: u64 = 0x86;
+const CLUSTER: u64 = 0x1F43B675;
+const BLOCK_GROUP: u64 = 0xA0;
+const BLOCK: u64 = 0xA1;
+const SIMPLE_BLOCK: u64 = 0xA3;
+const VP8: &[u8] = b"V_VP8";
+
+/// an EBML header with the `webm` doc type
+pub fn is_webm(bytes: &[u8]) -> bool {
+    children(bytes)
+        .next()
+        .filter(|(id, _)| *id == EBML)
+        .and_then(|(_, header)| child(header, DOC_TYPE))
+        .is_some_and(|doc_type| doc_type == b"webm")
+}
+
+/// the pixel size of the first video track
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let segment = child(bytes, SEGMENT)?;
+    let tracks = child(segment, TRACKS)?;
+
+    children(tracks)
+        .filter(|(id, _)| *id == TRACK_ENTRY)
+        .find_map(|(_, entry)| {
+            let video = child(entry, VIDEO)?;
+            let width = unsigned(child(video, PIXEL_WIDTH)?)?;
+            let height = unsigned(child(video, PIXEL_HEIGHT)?)?;
+            Some((u32::try_from(width).ok()?, u32::try_from(height).ok()?))
+        })
+}
+
+/// the first frame of the first video track when the codec is VP8, such a keyframe is the
+/// same bitstream as a lossy WebP. VP9 and AV1 are not read
+pub fn first_vp8_frame(bytes: &[u8]) -> Option<&[u8]> {
+    let segment = child(bytes, SEGMENT)?;
+    let track = children(child(segment, TRACKS)?)
+        .filter(|(id, _)| *id == TRACK_ENTRY)
+        .map(|(_, entry)| entry)
+        .find(|entry| child(entry, VIDEO).is_some())?;
+    if child(track, CODEC_ID)? != VP8 {
+        return None;
+    }
+    let number = unsigned(child(track, TRACK_NUMBER)?)?;
+
+    children(segment)
+        .filter(|(id, _)| *id == CLUSTER)
+        .flat_map(|(_, cluster)| children(cluster))
+        .filter_map(|(id, data)| match id {
+            SIMPLE_BLOCK => Some(data),
+            BLOCK_GROUP => child(data, BLOCK),
+            _ => None,
+        })
+        .find_map(|block| frame_of(block, number))
+}
+
+/// wrap a VP8 keyframe in a WebP file
+pub fn vp8_as_webp(frame: &[u8]) -> Vec<u8> {
+    let padding = frame.len() % 2;
+    let mut bytes = Vec::with_capacity(20 + frame.len() + padding);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&((12 + frame.len() + padding) as u32).to_le_bytes());
+    bytes.extend_from_slice(b"WEBPVP8 ");
+    bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(frame);
+    bytes.resize(bytes.len() + padding, 0);
+    bytes
+}
+
+/// the frame of a block of `track`, a laced block holds several frames and is skipped
+fn frame_of(block: &[u8], track: u64) -> Option<&[u8]> {
+    let (number, len) = vint(block, false)?;
+    // the timecode takes two bytes and the flags one
+    let flags = *block.get(len + 2)?;
+    if number != track || flags & 0x06 != 0 {
+        return None;
+    }
+    block.get(len + 3..)
+}
+
+fn child(bytes: &[u8], id: u64) -> Option<&[u8]> {
+    children(bytes)
+        .find(|(child, _)| *child == id)
+        .map(|(_, data)| data)
+}
+
+/// the elements directly in `bytes`, an element of unknown size or cut off by the end of the
+/// buffer takes the rest of it
+fn children(mut bytes: &[u8]) -> impl Iterator<Item = (u64, &[u8])> {
+    std::iter::from_fn(move || {
+        let (id, id_len) = vint(bytes, true)?;
+        let (size, size_len) = vint(&bytes[id_len..], false)?;
+        let start = id_len + size_len;
+        let unknown = size == (1u64 << (7 * size_len)) - 1;
+        let end = if unknown {
+            bytes.len()
+        } else {
+            usize::try_from(size)
+                .ok()
+                .and_then(|size| start.checked_add(size))
+                .map_or(bytes.len(), |end| end.min(bytes.len()))
+        };
+
+        let data = &bytes[start..end];
+        bytes = &bytes[end..];
+        Some((id, data))
+    })
+}
+
+/// a variable length integer, the length marker is kept for element ids
+fn vint(bytes: &[u8], keep_marker: bool) -> Option<(u64, usize)> {
+    let first = *bytes.first()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 || bytes.len() < len {
+        return None;
+    }
+
+    let first = if keep_marker {
+        first
+    } else {
+        first & 0xFFu8.checked_shr(len as u32).unwrap_or(0)
+    };
+    let value = bytes[1..len].iter().fold(u64::from(first), |value, byte| {
+        value << 8 | u64::from(*byte)
+    });
+
+    Some((value, len))
+}
+
+fn unsigned(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .fold(0, |value, byte| value << 8 | u64::from(*byte)),
+    )
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use sea_orm::prelude::Uuid;
+
+use super::MemeError;
+
+/// position in the feed ordered by `show_date_time` and `id`, both descending
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemeCursor {
+    pub show_date_time: DateTime<FixedOffset>,
+    pub id: Uuid,
+    /// list the newer memes before this position rather than the older ones after it
+    pub backward: bool,
+}
+
+impl MemeCursor {
+    pub fn next(show_date_time: DateTime<FixedOffset>, id: Uuid) -> Self {
+        Self {
+            show_date_time,
+            id,
+            backward: false,
+        }
+    }
+
+    pub fn prev(show_date_time: DateTime<FixedOffset>, id: Uuid) -> Self {
+        Self {
+            show_date_time,
+            id,
+            backward: true,
+        }
+    }
+
+    /// opaque to clients, only meant to be handed back
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}|{}|{}",
+            if self.backward { "p" } else { "n" },
+            self.show_date_time
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            self.id.simple()
+        ))
+    }
+
+    pub fn decode(value: &str) -> Result<Self, MemeError> {
+        let value = hex::decode(value)
+            .ok()
+            .and_then(|value| String::from_utf8(value).ok())
+            .ok_or(MemeError::InvalidCursor)?;
+
+        let mut parts = value.splitn(3, '|');
+        let (Some(direction), Some(show_date_time), Some(id)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(MemeError::InvalidCursor);
+        };
+
+        let backward = match direction {
+            "n" => false,
+            "p" => true,
+            _ => return Err(MemeError::InvalidCursor),
+        };
+
+        Ok(Self {
+            show_date_time: DateTime::parse_from_rfc3339(show_date_time)
+                .map_err(|_| MemeError::InvalidCursor)?,
+            id: Uuid::parse_str(id).map_err(|_| MemeError::InvalidCursor)?,
+            backward,
+        })
+    }
+}
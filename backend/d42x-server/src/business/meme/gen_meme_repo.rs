@@ -1,27 +1,50 @@
+use std::collections::HashMap;
+
 use crate::{
     business::{
-        Pagination,
+        CursorPage, Pagination,
         cache::Cache,
-        meme::{Interaction, MemeUrl},
+        category::{
+            meme_categories::{get_meme_categories, memes_in_categories, set_meme_categories},
+            tree::category_ids_by_name,
+        },
+        meme::{
+            DEFAULT_REACTIONS, Interaction, LIKE_REACTION, MemeUrl, ReactionCount, UNLIKE_REACTION,
+            builtin_reaction,
+        },
+        search::index::{index_memes, remove_memes},
+        storage::StoredFile,
+        thumbnails::meme_thumbnails::get_thumbnails,
     },
     db::DbConnHelper,
 };
 use chrono::{DateTime, FixedOffset, Utc};
-use db_entity::{meme_urls, memes};
+use db_entity::{
+    meme_categories, meme_hot_scores,
+    meme_interactions::{self, Kind},
+    meme_reaction_counts, meme_reactions, meme_thumbnails, meme_urls, meme_votes, memes,
+};
 use migration::async_trait;
 use sea_orm::{
-    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait,
-    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait, prelude::Uuid,
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbBackend,
+    EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Select,
+    Set, TransactionTrait,
+    prelude::{Expr, Uuid},
+    sea_query::{Func, Query, SimpleExpr},
 };
 use serde_json::json;
 use tracing::debug;
 
 use super::{
-    GetFilter, Meme, MemeError, MemeRepository, MemeResult, PostMeme, meme_entity::MemeEntity,
+    GetFilter, ListFilter, MAX_PAGE_SIZE, Meme, MemeError, MemeRepository, MemeResult, PostMeme,
+    Purged, SortMode, Voter, cursor::MemeCursor, meme_entity::MemeEntity,
 };
 
 const PAGINATED_MEMES_CACHE_KEY: &str = "PAGINATED_MEMES_CACHE_KEY";
+const CURSOR_MEMES_CACHE_KEY: &str = "CURSOR_MEMES_CACHE_KEY";
 const DEFAULT_PAGE_SIZE: u64 = 10;
+/// a prime, the row ids times a multiplier below it land all over its range
+const SQLITE_SHUFFLE_MODULUS: u64 = 2_147_483_647;
 
 pub struct GenMemeRepo<TCache, TDb>
 where
@@ -31,6 +54,8 @@ where
     cache: Option<TCache>,
     db: TDb,
     page_size: u64,
+    /// the emoji reactions besides `like` and `unlike`
+    reactions: Vec<String>,
 }
 
 impl<TCache, TDb> GenMemeRepo<TCache, TDb>
@@ -39,11 +64,7 @@ where
     TDb: DbConnHelper + Clone + 'static,
 {
     pub fn new(db: TDb) -> Self {
-        Self {
-            cache: None,
-            db,
-            page_size: DEFAULT_PAGE_SIZE,
-        }
+        Self::with_cache(db, None)
     }
 
     pub fn with_cache(db: TDb, cache: Option<TCache>) -> Self {
@@ -51,8 +72,20 @@ where
             cache,
             db,
             page_size: DEFAULT_PAGE_SIZE,
+            reactions: DEFAULT_REACTIONS.iter().map(|r| r.to_string()).collect(),
         }
     }
+
+    /// replace the emoji reactions, the built-in ones and duplicates are dropped
+    pub fn with_reactions(mut self, reactions: Vec<String>) -> Self {
+        self.reactions.clear();
+        for reaction in reactions {
+            if builtin_reaction(&reaction).is_none() && !self.reactions.contains(&reaction) {
+                self.reactions.push(reaction);
+            }
+        }
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -61,52 +94,168 @@ where
     TCache: Cache<String, String> + Sync + Send,
     TDb: DbConnHelper + Sync + Send + Clone + 'static,
 {
-    async fn get_paginated_memes(&self, page: u64, category: Option<String>) -> Pagination<Meme> {
-        let key = get_paginated_meme_cache_key(page, &category);
+    async fn get_paginated_memes(
+        &self,
+        page: u64,
+        size: u64,
+        filter: ListFilter,
+    ) -> MemeResult<Pagination<Meme>> {
+        let size = match size {
+            0 => self.page_size,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let key = get_paginated_meme_cache_key(page, size, &filter);
         if let Some(cache) = &self.cache {
             if let Some(value) = cache.get(&key) {
                 if let Ok(value) = serde_json::from_str::<Pagination<Meme>>(value.as_str()) {
                     debug!("get meme from cache: {:?}", value);
-                    return value;
+                    return Ok(value);
                 } else {
                     cache.remove(&key);
                 }
             }
         }
 
-        let fetch_page = if page > 0 { page - 1 } else { 0 };
-
-        let db = self.db.get_connection().await.unwrap();
+        let db = self.db.get_connection().await?;
 
         let now: DateTime<FixedOffset> = Utc::now().into();
 
-        let mut paged_memes = memes::Entity::find();
+        let paged_memes = memes::Entity::find()
+            .filter(memes::Column::Status.eq(memes::Status::Published))
+            .filter(memes::Column::ShowDateTime.lt(now));
+
+        let (list, total) = match apply_list_filter(&db, paged_memes, &filter).await? {
+            Some(paged_memes) => fetch_sorted_page(&db, paged_memes, &filter, page, size).await?,
+            None => (vec![], 0),
+        };
+
+        let paginated_meme_list = models_2_meme_list(list, &db).await?;
+
+        let result = Pagination {
+            page,
+            total,
+            size,
+            list: paginated_meme_list,
+        };
 
-        match category {
-            Some(value) if !value.is_empty() => {
-                paged_memes =
-                    paged_memes.filter(memes::Column::Categories.contains(format!(";{};", value)))
+        if let Some(cache) = &self.cache {
+            let cache_value = json!(result).to_string();
+            debug!("set cache data: {:?}", cache_value);
+
+            cache.insert(key, cache_value);
+        }
+
+        Ok(result)
+    }
+
+    async fn get_cursor_memes(
+        &self,
+        cursor: Option<String>,
+        size: u64,
+        filter: ListFilter,
+    ) -> MemeResult<CursorPage<Meme>> {
+        // a cursor is a position in `show_date_time`, it means nothing in the other orders
+        if filter.sort != SortMode::Newest {
+            return Err(MemeError::CursorSort);
+        }
+
+        let size = match size {
+            0 => self.page_size,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let cursor = cursor
+            .filter(|value| !value.is_empty())
+            .map(|value| MemeCursor::decode(&value))
+            .transpose()?;
+
+        let key = get_cursor_meme_cache_key(&cursor, size, &filter);
+        if let Some(cache) = &self.cache
+            && let Some(value) = cache.get(&key)
+        {
+            if let Ok(value) = serde_json::from_str::<CursorPage<Meme>>(value.as_str()) {
+                debug!("get meme from cache: {:?}", value);
+                return Ok(value);
+            } else {
+                cache.remove(&key);
             }
-            _ => {}
         }
 
-        let paged_memes = paged_memes
+        let db = self.db.get_connection().await?;
+
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let select = memes::Entity::find()
             .filter(memes::Column::Status.eq(memes::Status::Published))
-            .filter(memes::Column::ShowDateTime.lt(now))
-            .order_by_desc(memes::Column::ShowDateTime)
-            .paginate(&db, self.page_size);
+            .filter(memes::Column::ShowDateTime.lt(now));
+
+        let backward = cursor.as_ref().is_some_and(|cursor| cursor.backward);
+
+        let (list, has_more) = match apply_list_filter(&db, select, &filter).await? {
+            Some(mut select) => {
+                if let Some(cursor) = &cursor {
+                    let (before, same) = if backward {
+                        (
+                            memes::Column::ShowDateTime.gt(cursor.show_date_time),
+                            memes::Column::Id.gt(cursor.id),
+                        )
+                    } else {
+                        (
+                            memes::Column::ShowDateTime.lt(cursor.show_date_time),
+                            memes::Column::Id.lt(cursor.id),
+                        )
+                    };
+
+                    select = select.filter(
+                        Condition::any().add(before).add(
+                            Condition::all()
+                                .add(memes::Column::ShowDateTime.eq(cursor.show_date_time))
+                                .add(same),
+                        ),
+                    );
+                }
 
-        let list: Vec<_> = paged_memes.fetch_page(fetch_page).await.unwrap();
+                let order = if backward { Order::Asc } else { Order::Desc };
 
-        let paginated_meme_list = models_2_meme_list(list, &db).await;
+                // one more row than asked tells whether the list goes on
+                let mut list = select
+                    .order_by(memes::Column::ShowDateTime, order.clone())
+                    .order_by(memes::Column::Id, order)
+                    .limit(size + 1)
+                    .all(&db)
+                    .await?;
 
-        let total = paged_memes.num_pages().await.unwrap();
+                let has_more = list.len() as u64 > size;
+                list.truncate(size as usize);
+                if backward {
+                    list.reverse();
+                }
 
-        let result = Pagination {
-            page,
-            total,
-            size: self.page_size,
-            list: paginated_meme_list,
+                (list, has_more)
+            }
+            None => (vec![], false),
+        };
+
+        let next_cursor = match list.last() {
+            Some(last) if has_more || backward => {
+                Some(MemeCursor::next(last.show_date_time, last.id).encode())
+            }
+            _ => None,
+        };
+
+        let prev_cursor = match (list.first(), &cursor) {
+            (Some(first), _) => Some(MemeCursor::prev(first.show_date_time, first.id).encode()),
+            // nothing newer yet, the same cursor can be tried again later
+            (None, Some(cursor)) if cursor.backward => Some(cursor.encode()),
+            _ => None,
+        };
+
+        let result = CursorPage {
+            size,
+            list: models_2_meme_list(list, &db).await?,
+            next_cursor,
+            prev_cursor,
         };
 
         if let Some(cache) = &self.cache {
@@ -116,54 +265,273 @@ where
             cache.insert(key, cache_value);
         }
 
-        result
+        Ok(result)
     }
 
-    async fn get_paginated_all_memes(&self, filter: GetFilter) -> Pagination<Meme> {
-        let db = self.db.get_connection().await.unwrap();
+    async fn get_paginated_all_memes(&self, filter: GetFilter) -> MemeResult<Pagination<Meme>> {
+        let size = match filter.size {
+            0 => self.page_size,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let db = self.db.get_connection().await?;
 
         let mut paged_memes = db_entity::memes::Entity::find();
         if let Some(status) = filter.status {
             paged_memes = paged_memes.filter(memes::Column::Status.eq(status));
+        } else {
+            paged_memes = paged_memes.filter(memes::Column::Status.ne(memes::Status::Deleted));
         }
 
-        let paged_memes = paged_memes
-            .order_by_desc(memes::Column::ShowDateTime)
-            .paginate(&db, filter.size);
-
-        let list = paged_memes.fetch_page(filter.page - 1).await.unwrap();
-
-        let meme_list = models_2_meme_list(list, &db).await;
+        let (list, total) = match apply_list_filter(&db, paged_memes, &filter.filter).await? {
+            Some(paged_memes) => {
+                fetch_sorted_page(&db, paged_memes, &filter.filter, filter.page, size).await?
+            }
+            None => (vec![], 0),
+        };
 
-        let total = paged_memes.num_pages().await.unwrap();
+        let meme_list = models_2_meme_list(list, &db).await?;
 
-        let result = Pagination {
+        Ok(Pagination {
             page: filter.page,
             total,
-            size: filter.size,
+            size,
             list: meme_list,
+        })
+    }
+
+    async fn get_paginated_deleted_memes(
+        &self,
+        page: u64,
+        size: u64,
+    ) -> MemeResult<Pagination<Meme>> {
+        let size = match size {
+            0 => self.page_size,
+            size => size.min(MAX_PAGE_SIZE),
         };
 
-        result
+        let db = self.db.get_connection().await?;
+
+        let fetch_page = if page > 0 { page - 1 } else { 0 };
+
+        let paged_memes = memes::Entity::find()
+            .filter(memes::Column::Status.eq(memes::Status::Deleted))
+            .order_by_desc(memes::Column::DeletedDateTime)
+            .paginate(&db, size);
+
+        let list = paged_memes.fetch_page(fetch_page).await?;
+
+        let meme_list = models_2_meme_list(list, &db).await?;
+
+        let total = paged_memes.num_pages().await?;
+
+        Ok(Pagination {
+            page,
+            total,
+            size,
+            list: meme_list,
+        })
     }
 
-    async fn get_interactions(&self, ids: Vec<Uuid>) -> Vec<Interaction> {
-        let db = self.db.get_connection().await.expect("get db failed");
+    async fn purge_deleted_memes(&self, days: u32) -> MemeResult<Purged> {
+        let db = self.db.get_connection().await?;
+
+        let deadline: DateTime<FixedOffset> =
+            (Utc::now() - chrono::Duration::days(days as i64)).into();
+
+        let txn = db.begin().await?;
+
+        let ids: Vec<Uuid> = memes::Entity::find()
+            .select_only()
+            .column(memes::Column::Id)
+            .filter(memes::Column::Status.eq(memes::Status::Deleted))
+            .filter(memes::Column::DeletedDateTime.lt(deadline))
+            .into_tuple()
+            .all(&txn)
+            .await?;
+
+        if ids.is_empty() {
+            txn.rollback().await?;
+            return Ok(Purged::default());
+        }
+
+        let files = purged_files(&txn, ids.clone()).await?;
+
+        remove_memes(&txn, ids.clone()).await?;
+
+        meme_categories::Entity::delete_many()
+            .filter(meme_categories::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_thumbnails::Entity::delete_many()
+            .filter(
+                meme_thumbnails::Column::UrlId.in_subquery(
+                    Query::select()
+                        .column(meme_urls::Column::Id)
+                        .from(meme_urls::Entity)
+                        .and_where(meme_urls::Column::MemeId.is_in(ids.clone()))
+                        .to_owned(),
+                ),
+            )
+            .exec(&txn)
+            .await?;
+
+        meme_urls::Entity::delete_many()
+            .filter(meme_urls::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        db_entity::suggests::Entity::delete_many()
+            .filter(db_entity::suggests::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_interactions::Entity::delete_many()
+            .filter(meme_interactions::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_hot_scores::Entity::delete_many()
+            .filter(meme_hot_scores::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_votes::Entity::delete_many()
+            .filter(meme_votes::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_reactions::Entity::delete_many()
+            .filter(meme_reactions::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        meme_reaction_counts::Entity::delete_many()
+            .filter(meme_reaction_counts::Column::MemeId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        let res = memes::Entity::delete_many()
+            .filter(memes::Column::Id.is_in(ids))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(Purged {
+            count: res.rows_affected,
+            files,
+        })
+    }
+
+    async fn get_interactions(
+        &self,
+        ids: Vec<Uuid>,
+        visitor: Option<String>,
+    ) -> MemeResult<Vec<Interaction>> {
+        let db = self.db.get_connection().await?;
+
+        let mut votes: HashMap<Uuid, Kind> = HashMap::new();
+        let mut mine: HashMap<Uuid, Vec<String>> = HashMap::new();
+        if let Some(visitor) = visitor {
+            votes = meme_votes::Entity::find()
+                .filter(meme_votes::Column::MemeId.is_in(ids.clone()))
+                .filter(meme_votes::Column::Visitor.eq(visitor.as_str()))
+                .all(&db)
+                .await?
+                .into_iter()
+                .map(|vote| (vote.meme_id, vote.kind))
+                .collect();
+
+            let reactions = meme_reactions::Entity::find()
+                .filter(meme_reactions::Column::MemeId.is_in(ids.clone()))
+                .filter(meme_reactions::Column::Visitor.eq(visitor))
+                .order_by_asc(meme_reactions::Column::CreatedDateTime)
+                .order_by_asc(meme_reactions::Column::Reaction)
+                .all(&db)
+                .await?;
+            for reaction in reactions {
+                mine.entry(reaction.meme_id)
+                    .or_default()
+                    .push(reaction.reaction);
+            }
+        }
+
+        let mut counts: HashMap<Uuid, Vec<meme_reaction_counts::Model>> = HashMap::new();
+        let rows = meme_reaction_counts::Entity::find()
+            .filter(meme_reaction_counts::Column::MemeId.is_in(ids.clone()))
+            .order_by_asc(meme_reaction_counts::Column::Reaction)
+            .all(&db)
+            .await?;
+        for row in rows {
+            counts.entry(row.meme_id).or_default().push(row);
+        }
 
         let models: Vec<_> = db_entity::memes::Entity::find()
             .filter(memes::Column::Id.is_in(ids))
             .all(&db)
-            .await
-            .expect("query interactions failed")
+            .await?
             .into_iter()
-            .map(|model| Interaction {
-                id: model.id,
-                likes: model.likes,
-                unlikes: model.unlikes,
+            .map(|model| {
+                let vote = votes.get(&model.id).copied();
+                let mut my_reactions: Vec<_> = vote
+                    .map(|kind| match kind {
+                        Kind::Like => LIKE_REACTION.to_owned(),
+                        Kind::Unlike => UNLIKE_REACTION.to_owned(),
+                    })
+                    .into_iter()
+                    .collect();
+                my_reactions.extend(mine.remove(&model.id).unwrap_or_default());
+
+                Interaction {
+                    id: model.id,
+                    likes: model.likes,
+                    unlikes: model.unlikes,
+                    mine: vote,
+                    reactions: self
+                        .reaction_histogram(&model, counts.remove(&model.id).unwrap_or_default()),
+                    my_reactions,
+                }
             })
             .collect();
 
-        models
+        Ok(models)
+    }
+
+    fn reactions(&self) -> Vec<String> {
+        [LIKE_REACTION, UNLIKE_REACTION]
+            .into_iter()
+            .map(str::to_owned)
+            .chain(self.reactions.iter().cloned())
+            .collect()
+    }
+
+    async fn add_reaction(&self, id: Uuid, voter: &Voter, reaction: &str) -> MemeResult<bool> {
+        if builtin_reaction(reaction).is_none() && !self.reactions.iter().any(|r| r == reaction) {
+            return Err(MemeError::UnknownReaction(reaction.to_owned()));
+        }
+
+        let meme = self
+            .get_published_meme(id)
+            .await?
+            .ok_or(MemeError::HasNotAnyMeme)?;
+        match builtin_reaction(reaction) {
+            Some(kind) => meme.vote(voter, kind).await,
+            None => meme.react(voter, reaction).await,
+        }
+    }
+
+    /// a reaction that is no longer configured can still be taken back
+    async fn remove_reaction(&self, id: Uuid, voter: &Voter, reaction: &str) -> MemeResult<bool> {
+        let meme = self
+            .get_published_meme(id)
+            .await?
+            .ok_or(MemeError::HasNotAnyMeme)?;
+        match builtin_reaction(reaction) {
+            Some(kind) => meme.withdraw_vote(voter, kind).await,
+            None => meme.withdraw_reaction(voter, reaction).await,
+        }
     }
 
     async fn post_memes(&self, memes: Vec<PostMeme>) -> MemeResult<()> {
@@ -171,8 +539,8 @@ where
             cache.clear();
         }
 
-        if memes.len() == 0 {
-            return Err(MemeError::HasNotAnyMeme);
+        if memes.is_empty() {
+            return Err(MemeError::EmptyPost);
         }
 
         let db = self.db.get_connection().await?;
@@ -180,9 +548,7 @@ where
         let txn = db.begin().await?;
 
         for item in memes {
-            if let Err(e) = self.post_meme(item, &db).await {
-                return Err(e);
-            }
+            self.post_meme(item, &txn).await?;
         }
 
         txn.commit().await?;
@@ -206,11 +572,22 @@ where
         }
     }
 
+    async fn get_published_meme(&self, id: Uuid) -> MemeResult<Option<MemeEntity>> {
+        let db = self.db.get_connection().await?;
+
+        let model = db_entity::memes::Entity::find_by_id(id)
+            .filter(memes::Column::Status.eq(memes::Status::Published))
+            .one(&db)
+            .await?;
+        Ok(model.map(|model| MemeEntity::new(model, self.db.clone())))
+    }
+
     async fn get_meme_by_short_id(&self, short_id: String) -> MemeResult<Option<MemeEntity>> {
         let db = self.db.get_connection().await?;
 
         let model = db_entity::memes::Entity::find()
             .filter(memes::Column::ShortId.eq(short_id))
+            .filter(memes::Column::Status.ne(memes::Status::Deleted))
             .one(&db)
             .await?;
         if let Some(model) = model {
@@ -220,6 +597,12 @@ where
             Ok(None)
         }
     }
+
+    async fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
 }
 
 impl<TCache, TDb> GenMemeRepo<TCache, TDb>
@@ -237,11 +620,6 @@ where
             status: Set(memes::Status::Published),
             nickname: Set(meme.username),
             message: Set(meme.message.clone()),
-            categories: Set(if meme.categories.len() == 0 {
-                format!(";{};", db_entity::DEFAULT_CATEGORY)
-            } else {
-                format!(";{};", meme.categories.join(";"))
-            }),
             ..memes::ActiveModel::new()
         }
         .insert(db)
@@ -256,6 +634,16 @@ where
                 cover: Set(item.cover.clone()),
                 format: Set(item.format.to_string()),
                 hash: Set(item.hash.clone()),
+                phash: Set(item.phash.map(|phash| phash as i64)),
+                blurhash: Set(item
+                    .placeholder
+                    .as_ref()
+                    .map(|placeholder| placeholder.blurhash.clone())),
+                dominant_color: Set(item
+                    .placeholder
+                    .as_ref()
+                    .map(|placeholder| placeholder.dominant_color.clone())),
+                bed: Set(item.bed),
                 bed_id: Set(item.bed_id.clone()),
                 ..meme_urls::ActiveModel::new()
             })
@@ -263,61 +651,322 @@ where
 
         meme_urls::Entity::insert_many(memes).exec(db).await?;
 
+        set_meme_categories(db, model.id, meme.categories).await?;
+
+        index_memes(db, vec![model.id]).await?;
+
         Ok(())
     }
+
+    /// the counters of `meme` as reactions, then the configured reactions in order and the
+    /// ones no longer configured that were still given
+    fn reaction_histogram(
+        &self,
+        meme: &memes::Model,
+        mut counts: Vec<meme_reaction_counts::Model>,
+    ) -> Vec<ReactionCount> {
+        let mut histogram = vec![
+            ReactionCount {
+                reaction: LIKE_REACTION.to_owned(),
+                count: meme.likes.into(),
+            },
+            ReactionCount {
+                reaction: UNLIKE_REACTION.to_owned(),
+                count: meme.unlikes.into(),
+            },
+        ];
+
+        for reaction in &self.reactions {
+            let count = counts
+                .iter()
+                .position(|row| &row.reaction == reaction)
+                .map(|index| counts.remove(index).count)
+                .unwrap_or_default();
+            histogram.push(ReactionCount {
+                reaction: reaction.clone(),
+                count,
+            });
+        }
+
+        histogram.extend(
+            counts
+                .into_iter()
+                .filter(|row| row.count > 0)
+                .map(|row| ReactionCount {
+                    reaction: row.reaction,
+                    count: row.count,
+                }),
+        );
+
+        histogram
+    }
+}
+
+/// the files of the memes, their thumbnails and the covers generated for them
+async fn purged_files(db: &impl ConnectionTrait, ids: Vec<Uuid>) -> MemeResult<Vec<StoredFile>> {
+    let urls = meme_urls::Entity::find()
+        .filter(meme_urls::Column::MemeId.is_in(ids))
+        .find_with_related(meme_thumbnails::Entity)
+        .all(db)
+        .await?;
+
+    let mut files = vec![];
+    for (url, thumbnails) in urls {
+        // the cover is stored next to the thumbnails, see `business::thumbnails`
+        let cover_key = format!("{}.cover.webp", url.id.simple());
+        if url.cover != url.url && url.cover.ends_with(&format!("/{}", cover_key)) {
+            files.push(StoredFile {
+                bed: thumbnails
+                    .first()
+                    .map_or(url.bed, |thumbnail| thumbnail.bed),
+                key: cover_key,
+            });
+        }
+        files.extend(thumbnails.into_iter().map(|thumbnail| StoredFile {
+            bed: thumbnail.bed,
+            key: thumbnail.bed_id,
+        }));
+        files.push(StoredFile {
+            bed: url.bed,
+            key: url.bed_id,
+        });
+    }
+
+    Ok(files)
+}
+
+fn get_paginated_meme_cache_key(page: u64, size: u64, filter: &ListFilter) -> String {
+    let filter = ListFilter {
+        seed: filter.order_seed(),
+        ..filter.clone()
+    };
+
+    format!(
+        "{}-{}-{}-{}",
+        PAGINATED_MEMES_CACHE_KEY,
+        page,
+        size,
+        json!(filter)
+    )
 }
 
-fn get_paginated_meme_cache_key(page: u64, category: &Option<String>) -> String {
-    let category = if let Some(value) = category {
-        value
+fn get_cursor_meme_cache_key(
+    cursor: &Option<MemeCursor>,
+    size: u64,
+    filter: &ListFilter,
+) -> String {
+    let cursor = if let Some(value) = cursor {
+        value.encode()
     } else {
-        ""
+        String::new()
+    };
+
+    let filter = ListFilter {
+        seed: filter.order_seed(),
+        ..filter.clone()
+    };
+
+    format!(
+        "{}-{}-{}-{}",
+        CURSOR_MEMES_CACHE_KEY,
+        cursor,
+        size,
+        json!(filter)
+    )
+}
+
+/// narrow `select` down by `filter`, `None` when no meme can match it
+async fn apply_list_filter(
+    db: &impl ConnectionTrait,
+    mut select: Select<memes::Entity>,
+    filter: &ListFilter,
+) -> MemeResult<Option<Select<memes::Entity>>> {
+    let mut groups = vec![];
+    for name in filter.categories.iter().filter(|name| !name.is_empty()) {
+        groups.push(category_ids_by_name(db, name, filter.include_descendants).await?);
+    }
+
+    if filter.match_all {
+        if groups.iter().any(|ids| ids.is_empty()) {
+            return Ok(None);
+        }
+
+        for ids in groups {
+            select = select.filter(memes::Column::Id.in_subquery(memes_in_categories(ids)));
+        }
+    } else if !groups.is_empty() {
+        let ids: Vec<_> = groups.into_iter().flatten().collect();
+        if ids.is_empty() {
+            return Ok(None);
+        }
+
+        select = select.filter(memes::Column::Id.in_subquery(memes_in_categories(ids)));
+    }
+
+    let mut excluded = vec![];
+    for name in filter
+        .exclude_categories
+        .iter()
+        .filter(|name| !name.is_empty())
+    {
+        excluded.extend(category_ids_by_name(db, name, filter.include_descendants).await?);
+    }
+
+    if !excluded.is_empty() {
+        select = select.filter(memes::Column::Id.not_in_subquery(memes_in_categories(excluded)));
+    }
+
+    if let Some(from) = filter.from {
+        select = select.filter(memes::Column::ShowDateTime.gte(from));
+    }
+
+    if let Some(to) = filter.to {
+        select = select.filter(memes::Column::ShowDateTime.lt(to));
+    }
+
+    if let Some(format) = &filter.format {
+        select = select.filter(
+            memes::Column::Id.in_subquery(
+                meme_urls::Entity::find()
+                    .select_only()
+                    .column(meme_urls::Column::MemeId)
+                    .filter(meme_urls::Column::Format.eq(format.to_string()))
+                    .into_query(),
+            ),
+        );
+    }
+
+    if let Some(nickname) = &filter.nickname {
+        select = select.filter(memes::Column::Nickname.eq(nickname.as_str()));
+    }
+
+    Ok(Some(select))
+}
+
+/// one page of `select` in the order of `filter.sort`, with the count of pages.
+/// the id breaks the ties, so that a meme is on one page only
+async fn fetch_sorted_page(
+    db: &impl ConnectionTrait,
+    select: Select<memes::Entity>,
+    filter: &ListFilter,
+    page: u64,
+    size: u64,
+) -> MemeResult<(Vec<memes::Model>, u64)> {
+    let fetch_page = if page > 0 { page - 1 } else { 0 };
+
+    let select = match filter.sort {
+        SortMode::Newest => select
+            .order_by_desc(memes::Column::ShowDateTime)
+            .order_by_desc(memes::Column::Id),
+        SortMode::Oldest => select
+            .order_by_asc(memes::Column::ShowDateTime)
+            .order_by_asc(memes::Column::Id),
+        SortMode::MostLiked => select
+            .order_by_desc(memes::Column::Likes)
+            .order_by_desc(memes::Column::ShowDateTime)
+            .order_by_desc(memes::Column::Id),
+        SortMode::BestRatio => {
+            // (likes + 1) / (likes + unlikes + 2)
+            let ratio = Expr::col(memes::Column::Likes).add(1.0).div(
+                Expr::col(memes::Column::Likes)
+                    .add(Expr::col(memes::Column::Unlikes))
+                    .add(2.0),
+            );
+
+            select
+                .order_by(ratio, Order::Desc)
+                .order_by_desc(memes::Column::ShowDateTime)
+                .order_by_desc(memes::Column::Id)
+        }
+        SortMode::Hot => {
+            let score = Query::select()
+                .column(meme_hot_scores::Column::Score)
+                .from(meme_hot_scores::Entity)
+                .and_where(
+                    Expr::col((meme_hot_scores::Entity, meme_hot_scores::Column::MemeId))
+                        .equals((memes::Entity, memes::Column::Id)),
+                )
+                .to_owned();
+            let hot = Func::coalesce([
+                SimpleExpr::SubQuery(None, Box::new(score.into_sub_query_statement())),
+                Expr::val(0.0).into(),
+            ]);
+
+            select
+                .order_by(SimpleExpr::FunctionCall(hot), Order::Desc)
+                .order_by_desc(memes::Column::ShowDateTime)
+                .order_by_desc(memes::Column::Id)
+        }
+        SortMode::Random => {
+            let seed = filter.order_seed();
+            // a hash of the seed and the id shuffles the memes, SQLite of the tests has no
+            // md5 and scatters the row ids by a multiplier of the seed instead
+            let shuffled = match db.get_database_backend() {
+                DbBackend::Sqlite => Expr::cust(format!(
+                    r#"("memes"."rowid" * {}) % {}"#,
+                    1 + seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) % (SQLITE_SHUFFLE_MODULUS - 1),
+                    SQLITE_SHUFFLE_MODULUS
+                )),
+                _ => Expr::cust(format!(r#"md5('{}' || "memes"."id"::text)"#, seed)),
+            };
+
+            select
+                .order_by(shuffled, Order::Asc)
+                .order_by_asc(memes::Column::Id)
+        }
     };
 
-    format!("{}-{}-{}", PAGINATED_MEMES_CACHE_KEY, page, category)
+    let paged_memes = select.paginate(db, size);
+    let total = paged_memes.num_pages().await?;
+
+    // far beyond the last page
+    if fetch_page.checked_mul(size).is_none() {
+        return Ok((vec![], total));
+    }
+
+    let list = paged_memes.fetch_page(fetch_page).await?;
+
+    Ok((list, total))
 }
 
-async fn models_2_meme_list(
+pub(crate) async fn models_2_meme_list(
     models: Vec<db_entity::memes::Model>,
     db: &impl ConnectionTrait,
-) -> Vec<Meme> {
+) -> MemeResult<Vec<Meme>> {
     let mut meme_list = vec![];
 
+    let meme_ids: Vec<_> = models.iter().map(|item| item.id).collect();
+
+    let mut categories = get_meme_categories(db, meme_ids.clone()).await?;
+
+    let url_models = meme_urls::Entity::find()
+        .filter(meme_urls::Column::MemeId.is_in(meme_ids))
+        .all(db)
+        .await?;
+    let thumbnails = get_thumbnails(db, url_models.iter().map(|item| item.id).collect()).await?;
+    let mut urls: HashMap<Uuid, Vec<MemeUrl>> = HashMap::new();
+    for url_model in url_models {
+        let meme_id = url_model.meme_id;
+        let url = MemeUrl::try_from(url_model)?.with_thumbnails(&thumbnails);
+        urls.entry(meme_id).or_default().push(url);
+    }
+
     for item in models {
+        let list = urls.remove(&item.id).unwrap_or_default();
+
         meme_list.push(Meme {
             id: item.id,
             short_id: item.short_id.to_string(),
-            categories: item
-                .categories
-                .split(';')
-                .filter_map(|c| {
-                    if c.is_empty() {
-                        None
-                    } else {
-                        Some(c.to_string())
-                    }
-                })
-                .collect(),
+            categories: categories.remove(&item.id).unwrap_or_default(),
             nickname: item.nickname.clone(),
             show_date_time: item.show_date_time,
             create_date_time: item.created_date_time,
             status: item.status,
-            list: item
-                .find_related(db_entity::meme_urls::Entity)
-                .all(db)
-                .await
-                .unwrap()
-                .into_iter()
-                .map(|e| MemeUrl {
-                    id: e.id,
-                    url: e.url,
-                    cover: e.cover,
-                    format: e.format.as_str().try_into().unwrap(),
-                    sort: e.sort,
-                })
-                .collect(),
+            deleted_by: item.deleted_by,
+            deleted_date_time: item.deleted_date_time,
+            list,
         });
     }
 
-    meme_list
+    Ok(meme_list)
 }
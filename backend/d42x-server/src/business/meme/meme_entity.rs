@@ -1,10 +1,22 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::{meme_interactions::Kind, meme_reaction_counts, meme_reactions, meme_votes};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, Set, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+    prelude::{Expr, Uuid},
+    sea_query::OnConflict,
 };
 
-use crate::db::DbConnHelper;
+use crate::{
+    business::{
+        category::meme_categories::get_meme_categories,
+        thumbnails::meme_thumbnails::get_thumbnails,
+        trending::events::{record_interaction, withdraw_interaction},
+    },
+    db::DbConnHelper,
+};
 
-use super::{Meme, MemeError, MemeResult, MemeUrl};
+use super::{Meme, MemeError, MemeResult, MemeUrl, Voter};
 
 pub struct MemeEntity {
     model: db_entity::memes::Model,
@@ -25,101 +37,303 @@ impl MemeEntity {
     pub async fn get_detail(&self) -> MemeResult<Meme> {
         let db = self.db.get_connection().await?;
 
-        let urls: Vec<_> = db_entity::meme_urls::Entity::find()
+        let url_models = db_entity::meme_urls::Entity::find()
             .filter(db_entity::meme_urls::Column::MemeId.eq(self.model.id))
             .all(&db)
-            .await
-            .unwrap()
+            .await?;
+        let thumbnails =
+            get_thumbnails(&db, url_models.iter().map(|item| item.id).collect()).await?;
+        let urls: Vec<_> = url_models
             .into_iter()
-            .map(|url| MemeUrl {
-                id: url.id,
-                url: url.url,
-                cover: url.cover,
-                format: crate::config::AllowMemeFormats::try_from(url.format.as_str()).unwrap(),
-                sort: url.sort,
-            })
-            .collect();
+            .map(|item| MemeUrl::try_from(item).map(|url| url.with_thumbnails(&thumbnails)))
+            .collect::<MemeResult<_>>()?;
+
+        let categories = get_meme_categories(&db, vec![self.model.id])
+            .await?
+            .remove(&self.model.id)
+            .unwrap_or_default();
 
         let detail = Meme {
             id: self.model.id,
             short_id: self.model.short_id.to_owned(),
-            categories: self
-                .model
-                .categories
-                .split(';')
-                .filter_map(|c| {
-                    if c.is_empty() {
-                        None
-                    } else {
-                        Some(c.to_string())
-                    }
-                })
-                .collect(),
+            categories,
             nickname: self.model.nickname.clone(),
             show_date_time: self.model.show_date_time,
             create_date_time: self.model.created_date_time,
             status: self.model.status,
+            deleted_by: self.model.deleted_by,
+            deleted_date_time: self.model.deleted_date_time,
             list: urls,
         };
 
         Ok(detail)
     }
 
-    pub async fn increase_like(&self) -> MemeResult<()> {
+    /// set the vote of `voter` to `kind`, voting the same again changes nothing and the
+    /// opposite vote is switched, return whether the vote changed
+    pub async fn vote(&self, voter: &Voter, kind: Kind) -> MemeResult<bool> {
+        let db = self.db.get_connection().await?;
+
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let txn = db.begin().await?;
+
+        let inserted = meme_votes::Entity::insert(meme_votes::ActiveModel {
+            meme_id: Set(self.model.id),
+            visitor: Set(voter.key.clone()),
+            kind: Set(kind),
+            created_date_time: Set(now),
+            updated_date_time: Set(now),
+        })
+        .on_conflict(
+            OnConflict::columns([meme_votes::Column::MemeId, meme_votes::Column::Visitor])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        let switched_from = if inserted > 0 {
+            None
+        } else {
+            let previous = opposite(kind);
+            let res = meme_votes::Entity::update_many()
+                .col_expr(meme_votes::Column::Kind, Expr::value(kind))
+                .col_expr(meme_votes::Column::UpdatedDateTime, Expr::value(now))
+                .filter(meme_votes::Column::MemeId.eq(self.model.id))
+                .filter(meme_votes::Column::Visitor.eq(voter.key.as_str()))
+                .filter(meme_votes::Column::Kind.eq(previous))
+                .exec(&txn)
+                .await?;
+
+            if res.rows_affected == 0 {
+                txn.rollback().await?;
+                return Ok(false);
+            }
+
+            Some(previous)
+        };
+
+        shift_counters(&txn, self.model.id, Some(kind), switched_from).await?;
+
+        if let Some(previous) = switched_from {
+            withdraw_interaction(&txn, self.model.id, previous, &voter.client_id).await?;
+        }
+        record_interaction(&txn, self.model.id, kind, &voter.client_id).await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    /// take back the `kind` vote of `voter`, return whether there was one
+    pub async fn withdraw_vote(&self, voter: &Voter, kind: Kind) -> MemeResult<bool> {
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+
+        let res = meme_votes::Entity::delete_many()
+            .filter(meme_votes::Column::MemeId.eq(self.model.id))
+            .filter(meme_votes::Column::Visitor.eq(voter.key.as_str()))
+            .filter(meme_votes::Column::Kind.eq(kind))
+            .exec(&txn)
+            .await?;
+
+        if res.rows_affected == 0 {
+            txn.rollback().await?;
+            return Ok(false);
+        }
+
+        shift_counters(&txn, self.model.id, None, Some(kind)).await?;
+        withdraw_interaction(&txn, self.model.id, kind, &voter.client_id).await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    /// add the emoji `reaction` of `voter`, a visitor can give several different reactions
+    /// but each of them once, return whether it was added
+    pub async fn react(&self, voter: &Voter, reaction: &str) -> MemeResult<bool> {
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+
+        let inserted = meme_reactions::Entity::insert(meme_reactions::ActiveModel {
+            meme_id: Set(self.model.id),
+            visitor: Set(voter.key.clone()),
+            reaction: Set(reaction.to_owned()),
+            created_date_time: Set(Utc::now().into()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                meme_reactions::Column::MemeId,
+                meme_reactions::Column::Visitor,
+                meme_reactions::Column::Reaction,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        if inserted == 0 {
+            txn.rollback().await?;
+            return Ok(false);
+        }
+
+        meme_reaction_counts::Entity::insert(meme_reaction_counts::ActiveModel {
+            meme_id: Set(self.model.id),
+            reaction: Set(reaction.to_owned()),
+            count: Set(1),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                meme_reaction_counts::Column::MemeId,
+                meme_reaction_counts::Column::Reaction,
+            ])
+            .value(
+                meme_reaction_counts::Column::Count,
+                Expr::col((
+                    meme_reaction_counts::Entity,
+                    meme_reaction_counts::Column::Count,
+                ))
+                .add(1),
+            )
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    /// take back the emoji `reaction` of `voter`, return whether there was one
+    pub async fn withdraw_reaction(&self, voter: &Voter, reaction: &str) -> MemeResult<bool> {
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+
+        let res = meme_reactions::Entity::delete_many()
+            .filter(meme_reactions::Column::MemeId.eq(self.model.id))
+            .filter(meme_reactions::Column::Visitor.eq(voter.key.as_str()))
+            .filter(meme_reactions::Column::Reaction.eq(reaction))
+            .exec(&txn)
+            .await?;
+
+        if res.rows_affected == 0 {
+            txn.rollback().await?;
+            return Ok(false);
+        }
+
+        meme_reaction_counts::Entity::update_many()
+            .col_expr(
+                meme_reaction_counts::Column::Count,
+                Expr::col(meme_reaction_counts::Column::Count).sub(1),
+            )
+            .filter(meme_reaction_counts::Column::MemeId.eq(self.model.id))
+            .filter(meme_reaction_counts::Column::Reaction.eq(reaction))
+            .exec(&txn)
+            .await?;
+
+        meme_reaction_counts::Entity::delete_many()
+            .filter(meme_reaction_counts::Column::MemeId.eq(self.model.id))
+            .filter(meme_reaction_counts::Column::Reaction.eq(reaction))
+            .filter(meme_reaction_counts::Column::Count.lte(0))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    /// move the meme to trash, the rows are kept until purged
+    pub async fn delete(self, operator_id: Uuid) -> MemeResult<db_entity::memes::Model> {
         let db = self.db.get_connection().await?;
 
         let meme = db_entity::memes::Entity::find_by_id(self.model.id)
             .one(&db)
             .await?
             .ok_or(MemeError::HasNotAnyMeme)?;
-        let likes = meme.likes + 1;
-        let mut meme: db_entity::memes::ActiveModel = meme.into();
-        meme.likes = Set(likes);
+        if meme.status == db_entity::memes::Status::Deleted {
+            return Err(MemeError::AlreadyDeleted);
+        }
 
-        meme.update(&db).await?;
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let status_before_delete = meme.status;
+        let mut meme: db_entity::memes::ActiveModel = meme.into();
+        meme.status = Set(db_entity::memes::Status::Deleted);
+        meme.status_before_delete = Set(Some(status_before_delete));
+        meme.deleted_by = Set(Some(operator_id));
+        meme.deleted_date_time = Set(Some(now));
+        meme.last_actiity_date_time = Set(now);
 
-        return Ok(());
+        Ok(meme.update(&db).await?)
     }
 
-    pub async fn increase_unlike(&self) -> MemeResult<()> {
+    /// take the meme out of trash, back to the status it had before,
+    /// the memes trashed before that status was kept are published
+    pub async fn restore(self) -> MemeResult<db_entity::memes::Model> {
         let db = self.db.get_connection().await?;
 
         let meme = db_entity::memes::Entity::find_by_id(self.model.id)
             .one(&db)
             .await?
             .ok_or(MemeError::HasNotAnyMeme)?;
-        let unlikes = meme.unlikes + 1;
-        let mut meme: db_entity::memes::ActiveModel = meme.into();
-        meme.unlikes = Set(unlikes);
+        if meme.status != db_entity::memes::Status::Deleted {
+            return Err(MemeError::NotDeleted);
+        }
 
-        meme.update(&db).await?;
+        let status = meme
+            .status_before_delete
+            .unwrap_or(db_entity::memes::Status::Published);
+        let mut meme: db_entity::memes::ActiveModel = meme.into();
+        meme.status = Set(status);
+        meme.status_before_delete = Set(None);
+        meme.deleted_by = Set(None);
+        meme.deleted_date_time = Set(None);
+        meme.last_actiity_date_time = Set(Utc::now().into());
 
-        return Ok(());
+        Ok(meme.update(&db).await?)
     }
+}
 
-    pub async fn delete(self) -> MemeResult<db_entity::memes::Model> {
-        let db = self.db.get_connection().await?;
-        let txn = db.begin().await?;
+fn opposite(kind: Kind) -> Kind {
+    match kind {
+        Kind::Like => Kind::Unlike,
+        Kind::Unlike => Kind::Like,
+    }
+}
 
-        let model = db_entity::memes::Entity::find_by_id(self.model.id)
-            .one(&db)
-            .await?;
+fn counter_of(kind: Kind) -> db_entity::memes::Column {
+    match kind {
+        Kind::Like => db_entity::memes::Column::Likes,
+        Kind::Unlike => db_entity::memes::Column::Unlikes,
+    }
+}
 
-        let backup_model;
-        if let Some(meme) = model {
-            backup_model = meme.clone();
-            db_entity::meme_urls::Entity::delete_many()
-                .filter(db_entity::meme_urls::Column::MemeId.eq(meme.id))
-                .exec(&txn)
-                .await?;
-            meme.delete(&txn).await?;
-        } else {
-            txn.rollback().await.unwrap();
-            return Err(MemeError::HasNotAnyMeme);
-        }
+/// move the counters in SQL so that concurrent votes do not overwrite each other
+async fn shift_counters(
+    conn: &impl ConnectionTrait,
+    meme_id: Uuid,
+    increase: Option<Kind>,
+    decrease: Option<Kind>,
+) -> MemeResult<()> {
+    let mut update =
+        db_entity::memes::Entity::update_many().filter(db_entity::memes::Column::Id.eq(meme_id));
 
-        txn.commit().await?;
+    if let Some(kind) = increase {
+        update = update.col_expr(counter_of(kind), Expr::col(counter_of(kind)).add(1));
+    }
 
-        Ok(backup_model)
+    if let Some(kind) = decrease {
+        update = update.col_expr(counter_of(kind), Expr::col(counter_of(kind)).sub(1));
     }
+
+    update.exec(conn).await?;
+
+    Ok(())
 }
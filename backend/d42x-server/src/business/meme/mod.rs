@@ -1,8 +1,13 @@
+pub mod cursor;
 pub mod gen_meme_repo;
 pub mod meme_entity;
 
 #[cfg(test)]
 mod test;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+use std::collections::HashMap;
 
 use chrono::{DateTime, FixedOffset};
 use meme_entity::MemeEntity;
@@ -14,21 +19,87 @@ use validator::Validate;
 
 use crate::config::AllowMemeFormats;
 
-use super::Pagination;
+use super::{
+    CursorPage, Pagination, media::placeholder::Placeholder, storage::StoredFile,
+    thumbnails::Thumbnail,
+};
 
 pub type MemeResult<T> = Result<T, MemeError>;
 
 #[async_trait::async_trait]
 pub trait MemeRepository {
-    async fn get_paginated_memes(&self, _page: u64, _category: Option<String>) -> Pagination<Meme> {
+    /// published memes matching `filter`, `size` is capped by `MAX_PAGE_SIZE`
+    /// and 0 means the default page size
+    async fn get_paginated_memes(
+        &self,
+        _page: u64,
+        _size: u64,
+        _filter: ListFilter,
+    ) -> MemeResult<Pagination<Meme>> {
+        unimplemented!()
+    }
+
+    /// published memes matching `filter` newest first, walked by the cursors of
+    /// `CursorPage` so that newly published memes do not shift the pages,
+    /// `filter.sort` is ignored
+    async fn get_cursor_memes(
+        &self,
+        _cursor: Option<String>,
+        _size: u64,
+        _filter: ListFilter,
+    ) -> MemeResult<CursorPage<Meme>> {
+        unimplemented!()
+    }
+
+    /// memes of any status but deleted unless `filter.status` asks for one,
+    /// `filter.size` is capped by `MAX_PAGE_SIZE` and 0 means the default page size
+    async fn get_paginated_all_memes(&self, _filter: GetFilter) -> MemeResult<Pagination<Meme>> {
+        unimplemented!()
+    }
+
+    /// memes in trash, the latest deleted first, `size` is capped by `MAX_PAGE_SIZE`
+    /// and 0 means the default page size
+    async fn get_paginated_deleted_memes(
+        &self,
+        _page: u64,
+        _size: u64,
+    ) -> MemeResult<Pagination<Meme>> {
+        unimplemented!()
+    }
+
+    /// delete the memes that have been in trash for more than `days` days for good,
+    /// their files are left to be deleted from the storage
+    async fn purge_deleted_memes(&self, _days: u32) -> MemeResult<Purged> {
         unimplemented!()
     }
 
-    async fn get_paginated_all_memes(&self, _filter: GetFilter) -> Pagination<Meme> {
+    /// counters of the memes, with the vote of `visitor` on each of them
+    async fn get_interactions(
+        &self,
+        _ids: Vec<Uuid>,
+        _visitor: Option<String>,
+    ) -> MemeResult<Vec<Interaction>> {
         unimplemented!()
     }
 
-    async fn get_interactions(&self, _ids: Vec<Uuid>) -> Vec<Interaction> {
+    /// the reactions a visitor can give, the built-in `like` and `unlike` first
+    fn reactions(&self) -> Vec<String> {
+        unimplemented!()
+    }
+
+    /// add `reaction` of `voter` to the meme, `like` and `unlike` cast its vote,
+    /// return whether anything changed
+    async fn add_reaction(&self, _id: Uuid, _voter: &Voter, _reaction: &str) -> MemeResult<bool> {
+        unimplemented!()
+    }
+
+    /// take back `reaction` of `voter`, return whether there was one
+    async fn remove_reaction(
+        &self,
+        _id: Uuid,
+        _voter: &Voter,
+        _reaction: &str,
+    ) -> MemeResult<bool> {
         unimplemented!()
     }
 
@@ -43,18 +114,86 @@ pub trait MemeRepository {
     async fn get_meme_by_short_id(&self, _short_id: String) -> MemeResult<Option<MemeEntity>> {
         unimplemented!()
     }
+
+    /// only a published meme, visitors can not vote on the others
+    async fn get_published_meme(&self, _id: Uuid) -> MemeResult<Option<MemeEntity>> {
+        unimplemented!()
+    }
+
+    /// remove all cached paginated memes
+    async fn clear_cache(&self) {
+        unimplemented!()
+    }
 }
 
 pub struct PanicMemeRepository;
 
 impl MemeRepository for PanicMemeRepository {}
 
+#[derive(Debug, Default)]
+pub struct Purged {
+    pub count: u64,
+    /// the files, thumbnails and generated covers of the purged memes
+    pub files: Vec<StoredFile>,
+}
+
 #[derive(Serialize)]
 pub struct GetFilter {
     /// page number, base 1
     pub page: u64,
     pub size: u64,
     pub status: Option<db_entity::memes::Status>,
+    pub filter: ListFilter,
+}
+
+/// the largest page a client can ask for
+pub const MAX_PAGE_SIZE: u64 = 50;
+
+/// conditions shared by the client feed and the admin list
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ListFilter {
+    /// names or aliases of categories
+    pub categories: Vec<String>,
+    /// a meme has to be in all of `categories` rather than in any of them
+    pub match_all: bool,
+    /// the categories below `categories` and `exclude_categories` count as well
+    pub include_descendants: bool,
+    pub exclude_categories: Vec<String>,
+    /// `show_date_time` from, inclusive
+    pub from: Option<DateTime<FixedOffset>>,
+    /// `show_date_time` to, exclusive
+    pub to: Option<DateTime<FixedOffset>>,
+    /// memes with at least one picture of this format
+    pub format: Option<AllowMemeFormats>,
+    pub nickname: Option<String>,
+    pub sort: SortMode,
+    /// the same seed gives the same order with `SortMode::Random`
+    pub seed: u64,
+}
+
+impl ListFilter {
+    /// the seed when it orders the list, the other sorts share one cached list whatever
+    /// the seed
+    pub fn order_seed(&self) -> u64 {
+        match self.sort {
+            SortMode::Random => self.seed,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    #[default]
+    Newest,
+    Oldest,
+    MostLiked,
+    /// likes against unlikes, smoothed so that a few votes do not win outright
+    BestRatio,
+    /// the time-decayed score of recent likes and unlikes, see `business::trending`
+    Hot,
+    Random,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -66,6 +205,10 @@ pub struct Meme {
     pub show_date_time: DateTime<FixedOffset>,
     pub create_date_time: DateTime<FixedOffset>,
     pub status: db_entity::memes::Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_date_time: Option<DateTime<FixedOffset>>,
     pub list: Vec<MemeUrl>,
 }
 
@@ -76,6 +219,41 @@ pub struct MemeUrl {
     pub cover: String,
     pub format: AllowMemeFormats,
     pub sort: i32,
+    /// in pixels, `None` until the thumbnails are generated
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub thumbnails: Vec<Thumbnail>,
+    /// see `media::placeholder`
+    pub blurhash: Option<String>,
+    pub dominant_color: Option<String>,
+}
+
+impl MemeUrl {
+    /// take the thumbnails of this file from the ones of `get_thumbnails`
+    pub(crate) fn with_thumbnails(mut self, thumbnails: &HashMap<Uuid, Vec<Thumbnail>>) -> Self {
+        self.thumbnails = thumbnails.get(&self.id).cloned().unwrap_or_default();
+        self
+    }
+}
+
+impl TryFrom<db_entity::meme_urls::Model> for MemeUrl {
+    type Error = MemeError;
+
+    fn try_from(value: db_entity::meme_urls::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            url: value.url,
+            cover: value.cover,
+            format: AllowMemeFormats::try_from(value.format.as_str())
+                .map_err(MemeError::UnsupportedFormat)?,
+            sort: value.sort,
+            width: value.width,
+            height: value.height,
+            thumbnails: vec![],
+            blurhash: value.blurhash,
+            dominant_color: value.dominant_color,
+        })
+    }
 }
 
 #[derive(Serialize, Debug, Validate)]
@@ -87,13 +265,19 @@ pub struct PostMeme {
     pub memes: Vec<PostMemeUrl>,
 }
 
-#[derive(Serialize, Debug, Validate)]
+#[derive(Serialize, Debug, Clone, Validate)]
 pub struct PostMemeUrl {
     #[validate(length(min = 1))]
     pub url: String,
     pub cover: String,
     pub format: AllowMemeFormats,
     pub hash: String,
+    /// see `media::perceptual`, computed by the server
+    pub phash: Option<u64>,
+    /// see `media::placeholder`, computed by the server
+    pub placeholder: Option<Placeholder>,
+    /// where `bed_id` is stored
+    pub bed: db_entity::meme_urls::Bed,
     pub bed_id: String,
 }
 
@@ -102,12 +286,63 @@ pub struct Interaction {
     id: Uuid,
     likes: i32,
     unlikes: i32,
+    /// the vote of the caller
+    mine: Option<db_entity::meme_interactions::Kind>,
+    /// the histogram of every reaction, `like` and `unlike` included
+    reactions: Vec<ReactionCount>,
+    /// the reactions of the caller, its vote included
+    my_reactions: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ReactionCount {
+    pub reaction: String,
+    pub count: i64,
+}
+
+/// the reaction backed by `memes.likes`
+pub const LIKE_REACTION: &str = "like";
+/// the reaction backed by `memes.unlikes`
+pub const UNLIKE_REACTION: &str = "unlike";
+/// the emoji reactions when none are configured
+pub const DEFAULT_REACTIONS: [&str; 5] = ["😂", "🔥", "💀", "😮", "😢"];
+
+/// the vote a built-in reaction stands for
+pub fn builtin_reaction(reaction: &str) -> Option<db_entity::meme_interactions::Kind> {
+    match reaction {
+        LIKE_REACTION => Some(db_entity::meme_interactions::Kind::Like),
+        UNLIKE_REACTION => Some(db_entity::meme_interactions::Kind::Unlike),
+        _ => None,
+    }
+}
+
+/// who votes on a meme
+#[derive(Debug, Clone)]
+pub struct Voter {
+    /// one vote per meme is kept for each key
+    pub key: String,
+    /// the anonymised key written to the interaction log
+    pub client_id: String,
 }
 
 #[derive(Error, Debug)]
 pub enum MemeError {
     #[error("has not any meme")]
     HasNotAnyMeme,
+    #[error("meme is already in trash")]
+    AlreadyDeleted,
+    #[error("meme is not in trash")]
+    NotDeleted,
+    #[error("post memes should not be empty")]
+    EmptyPost,
+    #[error("invalid cursor")]
+    InvalidCursor,
+    #[error("only the newest memes are walked by cursor, use `page` for the other sorts")]
+    CursorSort,
+    #[error("unknown reaction: {0}")]
+    UnknownReaction(String),
+    #[error("{0}")]
+    UnsupportedFormat(String),
     #[error("Database error ocurrs: {0}")]
     DatabaseErr(#[from] DbErr),
 }
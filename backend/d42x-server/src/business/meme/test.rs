@@ -1,14 +1,26 @@
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+
+    use db_entity::meme_interactions::Kind;
     use pretty_assertions::assert_eq;
-    use sea_orm::EntityTrait;
+    use sea_orm::{
+        ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set, prelude::Uuid,
+    };
 
     use crate::db::DbConnHelper;
     use crate::{
         business::{
             cache::MockCache,
-            meme::{GetFilter, MemeRepository, gen_meme_repo::GenMemeRepo},
+            meme::{
+                GetFilter, Interaction, ListFilter, MAX_PAGE_SIZE, MemeError, MemeRepository,
+                PostMeme, PostMemeUrl, SortMode, Voter,
+                cursor::MemeCursor,
+                gen_meme_repo::GenMemeRepo,
+                test_support::{meme_url, post_meme},
+            },
         },
+        config::AllowMemeFormats,
         db::test::TestDB,
     };
 
@@ -23,7 +35,10 @@ mod test {
 
         let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
 
-        let list = repo.get_paginated_memes(1, None).await;
+        let list = repo
+            .get_paginated_memes(1, 0, ListFilter::default())
+            .await
+            .unwrap();
         assert_eq!(list.page, EXPECTED_PAGE);
         assert_eq!(list.total, EXPECTED_TOTAL);
         assert_eq!(list.list.len(), EXPECTED_LIST_LENGTH);
@@ -49,8 +64,10 @@ mod test {
                 page: EXPECTED_PAGE,
                 size: EXPECTED_SIZE,
                 status: EXPECTED_STATUS,
+                filter: ListFilter::default(),
             })
-            .await;
+            .await
+            .unwrap();
 
         assert_eq!(list.page, EXPECTED_PAGE);
         assert_eq!(list.total, EXPECTED_TOTAL);
@@ -58,6 +75,36 @@ mod test {
         assert_eq!(list.list.len(), EXPECTED_LIST_LENGTH);
     }
 
+    #[tokio::test]
+    async fn get_all_memes_page_size_capped() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+
+        let list = repo
+            .get_paginated_all_memes(GetFilter {
+                page: 1,
+                size: 0,
+                status: None,
+                filter: ListFilter::default(),
+            })
+            .await
+            .unwrap();
+        assert!(list.size > 0);
+        assert_eq!(list.list.len(), 2);
+
+        let list = repo
+            .get_paginated_all_memes(GetFilter {
+                page: 1,
+                size: 100_000,
+                status: None,
+                filter: ListFilter::default(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(list.size, MAX_PAGE_SIZE);
+        assert_eq!(list.list.len(), 2);
+    }
+
     #[tokio::test]
     async fn get_memes_published_success() {
         const EXPECTED_PAGE: u64 = 1;
@@ -75,8 +122,10 @@ mod test {
                 page: EXPECTED_PAGE,
                 size: EXPECTED_SIZE,
                 status: EXPECTED_STATUS,
+                filter: ListFilter::default(),
             })
-            .await;
+            .await
+            .unwrap();
 
         assert_eq!(list.page, EXPECTED_PAGE);
         assert_eq!(list.total, EXPECTED_TOTAL);
@@ -101,8 +150,10 @@ mod test {
                 page: EXPECTED_PAGE,
                 size: EXPECTED_SIZE,
                 status: EXPECTED_STATUS,
+                filter: ListFilter::default(),
             })
-            .await;
+            .await
+            .unwrap();
 
         assert_eq!(list.page, EXPECTED_PAGE);
         assert_eq!(list.total, EXPECTED_TOTAL);
@@ -129,7 +180,7 @@ mod test {
 
         let meme_entity = repo.get_meme(id).await.unwrap().unwrap();
 
-        let res = meme_entity.increase_like().await;
+        let res = meme_entity.vote(&voter("a"), Kind::Like).await;
         assert!(res.is_ok());
 
         let model = db_entity::memes::Entity::find_by_id(id)
@@ -161,8 +212,8 @@ mod test {
         let meme_entity = repo.get_meme(id).await.unwrap().unwrap();
 
         // action
-        meme_entity.increase_like().await.unwrap();
-        meme_entity.increase_like().await.unwrap();
+        meme_entity.vote(&voter("a"), Kind::Like).await.unwrap();
+        meme_entity.vote(&voter("b"), Kind::Like).await.unwrap();
 
         let model = db_entity::memes::Entity::find_by_id(id)
             .one(&db_conn)
@@ -173,6 +224,231 @@ mod test {
         assert_eq!(model.likes, EXPECTED_LIKES);
     }
 
+    fn voter(name: &str) -> Voter {
+        Voter {
+            key: format!("device:{}", name),
+            client_id: format!("client-{}", name),
+        }
+    }
+
+    async fn counters(db: &TestDB, id: Uuid) -> (i32, i32) {
+        let db_conn = db.get_connection().await.unwrap();
+        let model = db_entity::memes::Entity::find_by_id(id)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        (model.likes, model.unlikes)
+    }
+
+    #[tokio::test]
+    async fn vote_idempotent_success() {
+        let db = TestDB::new().await;
+        let ids = post_list_memes(
+            &GenMemeRepo::<MockCache<_, _>, TestDB>::new(db.clone()),
+            &db,
+        )
+        .await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(ids[0]).await.unwrap().unwrap();
+
+        assert!(meme.vote(&voter("a"), Kind::Like).await.unwrap());
+        assert!(!meme.vote(&voter("a"), Kind::Like).await.unwrap());
+        assert!(!meme.vote(&voter("a"), Kind::Like).await.unwrap());
+        assert_eq!(counters(&db, ids[0]).await, (1, 0));
+
+        let db_conn = db.get_connection().await.unwrap();
+        let events = db_entity::meme_interactions::Entity::find()
+            .filter(db_entity::meme_interactions::Column::MemeId.eq(ids[0]))
+            .count(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(events, 1);
+    }
+
+    #[tokio::test]
+    async fn vote_switch_and_withdraw_success() {
+        let db = TestDB::new().await;
+        let ids = post_list_memes(
+            &GenMemeRepo::<MockCache<_, _>, TestDB>::new(db.clone()),
+            &db,
+        )
+        .await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(ids[0]).await.unwrap().unwrap();
+
+        meme.vote(&voter("a"), Kind::Like).await.unwrap();
+        meme.vote(&voter("b"), Kind::Like).await.unwrap();
+        assert_eq!(counters(&db, ids[0]).await, (2, 0));
+
+        assert!(meme.vote(&voter("a"), Kind::Unlike).await.unwrap());
+        assert_eq!(counters(&db, ids[0]).await, (1, 1));
+
+        // only the vote that was cast can be withdrawn
+        assert!(!meme.withdraw_vote(&voter("a"), Kind::Like).await.unwrap());
+        assert!(meme.withdraw_vote(&voter("a"), Kind::Unlike).await.unwrap());
+        assert!(!meme.withdraw_vote(&voter("a"), Kind::Unlike).await.unwrap());
+        assert_eq!(counters(&db, ids[0]).await, (1, 0));
+
+        let db_conn = db.get_connection().await.unwrap();
+        let events = db_entity::meme_interactions::Entity::find()
+            .filter(db_entity::meme_interactions::Column::MemeId.eq(ids[0]))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].client_id, "client-b");
+
+        let res = repo
+            .get_interactions(vec![ids[0]], Some(voter("b").key))
+            .await
+            .unwrap();
+        assert_eq!(res[0].mine, Some(Kind::Like));
+    }
+
+    fn histogram(interaction: &Interaction) -> Vec<(&str, i64)> {
+        interaction
+            .reactions
+            .iter()
+            .map(|item| (item.reaction.as_str(), item.count))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reaction_add_and_remove_success() {
+        let db = TestDB::new().await;
+        let ids = post_list_memes(
+            &GenMemeRepo::<MockCache<_, _>, TestDB>::new(db.clone()),
+            &db,
+        )
+        .await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone())
+            .with_reactions(vec!["🔥".into(), "like".into(), "😂".into(), "🔥".into()]);
+        assert_eq!(repo.reactions(), vec!["like", "unlike", "🔥", "😂"]);
+
+        assert!(repo.add_reaction(ids[0], &voter("a"), "🔥").await.unwrap());
+        assert!(!repo.add_reaction(ids[0], &voter("a"), "🔥").await.unwrap());
+        assert!(repo.add_reaction(ids[0], &voter("b"), "🔥").await.unwrap());
+        assert!(repo.add_reaction(ids[0], &voter("a"), "😂").await.unwrap());
+        // the built-in reactions are the votes
+        assert!(
+            repo.add_reaction(ids[0], &voter("a"), "like")
+                .await
+                .unwrap()
+        );
+        assert_eq!(counters(&db, ids[0]).await, (1, 0));
+
+        let res = repo.add_reaction(ids[0], &voter("a"), "💀").await;
+        assert!(matches!(res, Err(MemeError::UnknownReaction(_))));
+        let res = repo.add_reaction(Uuid::new_v4(), &voter("a"), "🔥").await;
+        assert!(matches!(res, Err(MemeError::HasNotAnyMeme)));
+
+        let res = repo
+            .get_interactions(vec![ids[0]], Some(voter("a").key))
+            .await
+            .unwrap();
+        assert_eq!(
+            histogram(&res[0]),
+            vec![("like", 1), ("unlike", 0), ("🔥", 2), ("😂", 1)]
+        );
+        let mut mine = res[0].my_reactions.clone();
+        mine.sort();
+        assert_eq!(mine, vec!["like", "🔥", "😂"]);
+
+        assert!(
+            repo.remove_reaction(ids[0], &voter("a"), "🔥")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .remove_reaction(ids[0], &voter("a"), "🔥")
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.remove_reaction(ids[0], &voter("b"), "🔥")
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.remove_reaction(ids[0], &voter("a"), "like")
+                .await
+                .unwrap()
+        );
+
+        let res = repo
+            .get_interactions(vec![ids[0]], Some(voter("a").key))
+            .await
+            .unwrap();
+        assert_eq!(
+            histogram(&res[0]),
+            vec![("like", 0), ("unlike", 0), ("🔥", 0), ("😂", 1)]
+        );
+        assert_eq!(res[0].my_reactions, vec!["😂"]);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let rows = db_entity::meme_reaction_counts::Entity::find()
+            .filter(db_entity::meme_reaction_counts::Column::MemeId.eq(ids[0]))
+            .count(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[tokio::test]
+    async fn reaction_unpublished_fail() {
+        let db = TestDB::new().await;
+        let ids = post_list_memes(
+            &GenMemeRepo::<MockCache<_, _>, TestDB>::new(db.clone()),
+            &db,
+        )
+        .await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> =
+            GenMemeRepo::new(db.clone()).with_reactions(vec!["🔥".into()]);
+        repo.add_reaction(ids[0], &voter("a"), "🔥").await.unwrap();
+
+        let meme = repo.get_meme(ids[0]).await.unwrap().unwrap();
+        meme.delete(Uuid::nil()).await.unwrap();
+
+        assert!(repo.get_published_meme(ids[0]).await.unwrap().is_none());
+        let res = repo.add_reaction(ids[0], &voter("b"), "like").await;
+        assert!(matches!(res, Err(MemeError::HasNotAnyMeme)));
+        let res = repo.remove_reaction(ids[0], &voter("a"), "🔥").await;
+        assert!(matches!(res, Err(MemeError::HasNotAnyMeme)));
+        assert_eq!(counters(&db, ids[0]).await, (0, 0));
+    }
+
+    #[tokio::test]
+    async fn reaction_no_longer_configured_success() {
+        let db = TestDB::new().await;
+        let ids = post_list_memes(
+            &GenMemeRepo::<MockCache<_, _>, TestDB>::new(db.clone()),
+            &db,
+        )
+        .await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> =
+            GenMemeRepo::new(db.clone()).with_reactions(vec!["🔥".into()]);
+        repo.add_reaction(ids[0], &voter("a"), "🔥").await.unwrap();
+
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> =
+            GenMemeRepo::new(db.clone()).with_reactions(vec!["😂".into()]);
+        let res = repo.get_interactions(vec![ids[0]], None).await.unwrap();
+        assert_eq!(
+            histogram(&res[0]),
+            vec![("like", 0), ("unlike", 0), ("😂", 0), ("🔥", 1)]
+        );
+        assert!(res[0].my_reactions.is_empty());
+
+        let res = repo.add_reaction(ids[0], &voter("b"), "🔥").await;
+        assert!(matches!(res, Err(MemeError::UnknownReaction(_))));
+        assert!(
+            repo.remove_reaction(ids[0], &voter("a"), "🔥")
+                .await
+                .unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn get_interactions_has_likes_success() {
         let db = TestDB::new().await;
@@ -188,18 +464,28 @@ mod test {
 
         let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
 
-        let res = repo.get_interactions(vec![id]).await;
+        let res = repo.get_interactions(vec![id], None).await.unwrap();
         assert_eq!(res.len(), 1);
         assert_eq!(res.get(0).unwrap().likes, 0);
 
         let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
         let meme_entity = repo.get_meme(id).await.unwrap().unwrap();
-        meme_entity.increase_like().await.unwrap();
+        meme_entity.vote(&voter("a"), Kind::Like).await.unwrap();
 
         let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
-        let res = repo.get_interactions(vec![id]).await;
+        let res = repo
+            .get_interactions(vec![id], Some(voter("a").key))
+            .await
+            .unwrap();
         assert_eq!(res.len(), 1);
         assert_eq!(res.get(0).unwrap().likes, 1);
+        assert_eq!(res[0].mine, Some(Kind::Like));
+
+        let res = repo
+            .get_interactions(vec![id], Some(voter("b").key))
+            .await
+            .unwrap();
+        assert_eq!(res[0].mine, None);
     }
 
     #[tokio::test]
@@ -224,5 +510,607 @@ mod test {
 
         assert_eq!(detail.id, id);
         assert!(detail.list.len() != 0);
+        assert_eq!(detail.categories, vec![db_entity::DEFAULT_CATEGORY]);
+    }
+
+    #[tokio::test]
+    async fn post_memes_with_categories_success() {
+        const EXPECTED_CATEGORIES: [&str; 2] = ["cat", "dog"];
+
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+
+        repo.post_memes(vec![PostMeme {
+            memes: vec![meme_url("https://example.com/cat.png")],
+            ..post_meme("", &["cat", "dog", "cat"])
+        }])
+        .await
+        .unwrap();
+
+        let list = repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec!["dog".to_owned()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.list.len(), 1);
+        assert_eq!(list.list[0].categories, EXPECTED_CATEGORIES);
+
+        let list = repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec![db_entity::DEFAULT_CATEGORY.to_owned()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(
+            list.list
+                .iter()
+                .all(|item| item.categories == vec![db_entity::DEFAULT_CATEGORY])
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_soft_success() {
+        const EXPECTED_LIST_LENGTH: usize = 1;
+
+        let db = TestDB::new().await;
+        let db_conn = db.get_connection().await.unwrap();
+        let (id, operator_id) = {
+            let model = db_entity::memes::Entity::find()
+                .one(&db_conn)
+                .await
+                .unwrap()
+                .unwrap();
+            (model.id, model.user_id)
+        };
+
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        meme.delete(operator_id).await.unwrap();
+
+        let model = db_entity::memes::Entity::find_by_id(id)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.status, db_entity::memes::Status::Deleted);
+        assert_eq!(model.deleted_by, Some(operator_id));
+        assert!(model.deleted_date_time.is_some());
+
+        let urls = db_entity::meme_urls::Entity::find()
+            .filter(db_entity::meme_urls::Column::MemeId.eq(id))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert!(!urls.is_empty());
+
+        let list = repo
+            .get_paginated_memes(1, 0, ListFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(list.list.len(), EXPECTED_LIST_LENGTH);
+        assert!(list.list.iter().all(|item| item.id != id));
+
+        let trash = repo.get_paginated_deleted_memes(1, 10).await.unwrap();
+        assert_eq!(trash.list.len(), 1);
+        assert_eq!(trash.list[0].id, id);
+
+        let trash = repo.get_paginated_deleted_memes(1, u64::MAX).await.unwrap();
+        assert_eq!(trash.size, MAX_PAGE_SIZE);
+
+        let trash = repo.get_paginated_deleted_memes(1, 0).await.unwrap();
+        assert!(trash.size > 1);
+        assert_eq!(trash.list.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn restore_success() {
+        let db = TestDB::new().await;
+        let db_conn = db.get_connection().await.unwrap();
+        let id = db_entity::memes::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        meme.delete(Uuid::nil()).await.unwrap();
+
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        meme.restore().await.unwrap();
+
+        let model = db_entity::memes::Entity::find_by_id(id)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.status, db_entity::memes::Status::Published);
+        assert_eq!(model.deleted_by, None);
+
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        assert!(matches!(
+            meme.restore().await,
+            Err(crate::business::meme::MemeError::NotDeleted)
+        ));
+    }
+
+    #[tokio::test]
+    async fn restore_previous_status_success() {
+        let db = TestDB::new().await;
+        let db_conn = db.get_connection().await.unwrap();
+        let model = db_entity::memes::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        let id = model.id;
+        let mut active: db_entity::memes::ActiveModel = model.into();
+        active.status = Set(db_entity::memes::Status::Uncensored);
+        active.update(&db_conn).await.unwrap();
+
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let deleted = repo
+            .get_meme(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .delete(Uuid::nil())
+            .await
+            .unwrap();
+        assert_eq!(
+            deleted.status_before_delete,
+            Some(db_entity::memes::Status::Uncensored)
+        );
+
+        let restored = repo
+            .get_meme(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .restore()
+            .await
+            .unwrap();
+        assert_eq!(restored.status, db_entity::memes::Status::Uncensored);
+        assert_eq!(restored.status_before_delete, None);
+    }
+
+    #[tokio::test]
+    async fn purge_deleted_memes_success() {
+        let db = TestDB::new().await;
+        let db_conn = db.get_connection().await.unwrap();
+        let id = db_entity::memes::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        meme.delete(Uuid::nil()).await.unwrap();
+
+        // deleted just now, not old enough
+        assert_eq!(repo.purge_deleted_memes(1).await.unwrap().count, 0);
+        let purged = repo.purge_deleted_memes(0).await.unwrap();
+        assert_eq!(purged.count, 1);
+        assert!(!purged.files.is_empty());
+
+        let model = db_entity::memes::Entity::find_by_id(id)
+            .one(&db_conn)
+            .await
+            .unwrap();
+        assert!(model.is_none());
+
+        let urls = db_entity::meme_urls::Entity::find()
+            .filter(db_entity::meme_urls::Column::MemeId.eq(id))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert!(urls.is_empty());
+
+        let categories = db_entity::meme_categories::Entity::find()
+            .filter(db_entity::meme_categories::Column::MemeId.eq(id))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert!(categories.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_meme_by_unknown_short_id_none() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+
+        let meme = repo
+            .get_meme_by_short_id("not-existed".to_owned())
+            .await
+            .unwrap();
+        assert!(meme.is_none());
+    }
+
+    /// post three memes and return their ids in the order of posting
+    async fn post_list_memes(
+        repo: &GenMemeRepo<MockCache<String, String>, TestDB>,
+        db: &TestDB,
+    ) -> Vec<Uuid> {
+        let memes = [
+            (
+                "alice",
+                "list-a",
+                vec!["list-cat", "list-dog"],
+                AllowMemeFormats::PNG,
+            ),
+            ("bob", "list-b", vec!["list-cat"], AllowMemeFormats::GIF),
+            ("bob", "list-c", vec!["list-dog"], AllowMemeFormats::PNG),
+        ];
+
+        repo.post_memes(
+            memes
+                .iter()
+                .map(|(username, message, categories, format)| PostMeme {
+                    username: username.to_string(),
+                    memes: vec![PostMemeUrl {
+                        format: *format,
+                        ..meme_url(&format!("https://example.com/{}", message))
+                    }],
+                    ..post_meme(message, categories)
+                })
+                .collect(),
+        )
+        .await
+        .unwrap();
+
+        let db_conn = db.get_connection().await.unwrap();
+        let mut ids = vec![];
+        for (_, message, _, _) in memes {
+            let model = db_entity::memes::Entity::find()
+                .filter(db_entity::memes::Column::Message.eq(message))
+                .one(&db_conn)
+                .await
+                .unwrap()
+                .unwrap();
+            ids.push(model.id);
+        }
+
+        ids
+    }
+
+    fn list_filter(categories: &[&str]) -> ListFilter {
+        ListFilter {
+            categories: categories.iter().map(|item| item.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_ids(
+        repo: &GenMemeRepo<MockCache<String, String>, TestDB>,
+        page: u64,
+        size: u64,
+        filter: ListFilter,
+    ) -> Vec<Uuid> {
+        repo.get_paginated_memes(page, size, filter)
+            .await
+            .unwrap()
+            .list
+            .into_iter()
+            .map(|item| item.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn get_paginated_memes_filtered_success() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let ids = post_list_memes(&repo, &db).await;
+
+        let mut list = list_ids(&repo, 1, 0, list_filter(&["list-cat", "list-dog"])).await;
+        list.sort();
+        let mut expected = ids.clone();
+        expected.sort();
+        assert_eq!(list, expected);
+
+        let list = list_ids(
+            &repo,
+            1,
+            0,
+            ListFilter {
+                match_all: true,
+                ..list_filter(&["list-cat", "list-dog"])
+            },
+        )
+        .await;
+        assert_eq!(list, vec![ids[0]]);
+
+        let list = list_ids(
+            &repo,
+            1,
+            0,
+            ListFilter {
+                exclude_categories: vec!["list-dog".to_owned()],
+                ..list_filter(&["list-cat"])
+            },
+        )
+        .await;
+        assert_eq!(list, vec![ids[1]]);
+
+        let list = list_ids(
+            &repo,
+            1,
+            0,
+            ListFilter {
+                format: Some(AllowMemeFormats::GIF),
+                ..list_filter(&["list-cat", "list-dog"])
+            },
+        )
+        .await;
+        assert_eq!(list, vec![ids[1]]);
+
+        let list = list_ids(
+            &repo,
+            1,
+            0,
+            ListFilter {
+                nickname: Some("alice".to_owned()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(list, vec![ids[0]]);
+
+        let list = list_ids(
+            &repo,
+            1,
+            0,
+            ListFilter {
+                from: Some((chrono::Utc::now() + chrono::Duration::days(1)).into()),
+                ..list_filter(&["list-cat", "list-dog"])
+            },
+        )
+        .await;
+        assert!(list.is_empty());
+
+        let list = list_ids(&repo, 1, 0, list_filter(&["not-a-category"])).await;
+        assert!(list.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_paginated_memes_sorted_success() {
+        let db = TestDB::new().await;
+        let db_conn = db.get_connection().await.unwrap();
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let ids = post_list_memes(&repo, &db).await;
+
+        // likes, unlikes and days ago
+        for (id, (likes, unlikes, days)) in ids.iter().zip([(10, 10, 3), (5, 0, 2), (20, 30, 1)]) {
+            let show_date_time: chrono::DateTime<chrono::FixedOffset> =
+                (chrono::Utc::now() - chrono::Duration::days(days)).into();
+            let mut model: db_entity::memes::ActiveModel =
+                db_entity::memes::Entity::find_by_id(*id)
+                    .one(&db_conn)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .into();
+            model.likes = Set(likes);
+            model.unlikes = Set(unlikes);
+            model.show_date_time = Set(show_date_time);
+            model.update(&db_conn).await.unwrap();
+        }
+
+        let filter = list_filter(&["list-cat", "list-dog"]);
+        let sorted = |sort| ListFilter {
+            sort,
+            ..filter.clone()
+        };
+
+        let list = list_ids(&repo, 1, 0, sorted(SortMode::Newest)).await;
+        assert_eq!(list, vec![ids[2], ids[1], ids[0]]);
+
+        let list = list_ids(&repo, 1, 0, sorted(SortMode::Oldest)).await;
+        assert_eq!(list, vec![ids[0], ids[1], ids[2]]);
+
+        let list = list_ids(&repo, 1, 0, sorted(SortMode::MostLiked)).await;
+        assert_eq!(list, vec![ids[2], ids[0], ids[1]]);
+
+        let list = list_ids(&repo, 1, 0, sorted(SortMode::BestRatio)).await;
+        assert_eq!(list, vec![ids[1], ids[0], ids[2]]);
+
+        let random = ListFilter {
+            seed: 42,
+            ..sorted(SortMode::Random)
+        };
+        let list = list_ids(&repo, 1, 0, random.clone()).await;
+        assert_eq!(list_ids(&repo, 1, 0, random.clone()).await, list);
+
+        let mut paged = vec![];
+        for page in 1..=3 {
+            paged.extend(list_ids(&repo, page, 1, random.clone()).await);
+        }
+        assert_eq!(paged, list);
+
+        // another seed shuffles them again
+        let mut orders = HashSet::new();
+        for seed in 0..10 {
+            let other = ListFilter {
+                seed,
+                ..random.clone()
+            };
+            orders.insert(list_ids(&repo, 1, 0, other).await);
+        }
+        assert!(orders.len() > 1);
+
+        // the id breaks the ties
+        for id in &ids {
+            let mut model: db_entity::memes::ActiveModel =
+                db_entity::memes::Entity::find_by_id(*id)
+                    .one(&db_conn)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .into();
+            model.likes = Set(1);
+            model.unlikes = Set(1);
+            model.show_date_time = Set(chrono::DateTime::UNIX_EPOCH.into());
+            model.update(&db_conn).await.unwrap();
+        }
+        let mut by_id = ids.clone();
+        by_id.sort();
+        by_id.reverse();
+        for sort in [SortMode::MostLiked, SortMode::BestRatio, SortMode::Hot] {
+            assert_eq!(list_ids(&repo, 1, 0, sorted(sort)).await, by_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn get_paginated_memes_far_page_success() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        post_list_memes(&repo, &db).await;
+
+        for sort in [SortMode::Newest, SortMode::Random] {
+            let filter = ListFilter {
+                sort,
+                ..list_filter(&["list-cat", "list-dog"])
+            };
+            let list = repo
+                .get_paginated_memes(u64::MAX, MAX_PAGE_SIZE, filter)
+                .await
+                .unwrap();
+            assert!(list.list.is_empty());
+            assert_eq!(list.total, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn get_paginated_memes_page_size_success() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        post_list_memes(&repo, &db).await;
+
+        let filter = list_filter(&["list-cat", "list-dog"]);
+
+        let list = repo
+            .get_paginated_memes(1, 2, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(list.size, 2);
+        assert_eq!(list.total, 2);
+        assert_eq!(list.list.len(), 2);
+
+        let list = repo
+            .get_paginated_memes(1, 0, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(list.size, 10);
+
+        let list = repo.get_paginated_memes(1, 1000, filter).await.unwrap();
+        assert_eq!(list.size, MAX_PAGE_SIZE);
+    }
+
+    #[tokio::test]
+    async fn get_cursor_memes_success() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let ids = post_list_memes(&repo, &db).await;
+
+        let filter = list_filter(&["list-cat", "list-dog"]);
+
+        let first = repo
+            .get_cursor_memes(None, 2, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            first.list.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![ids[2], ids[1]]
+        );
+        assert!(first.next_cursor.is_some());
+        assert!(first.prev_cursor.is_some());
+
+        // a meme published while scrolling does not shift the next page
+        repo.post_memes(vec![PostMeme {
+            username: "carol".to_owned(),
+            memes: vec![meme_url("https://example.com/list-d")],
+            ..post_meme("list-d", &["list-cat"])
+        }])
+        .await
+        .unwrap();
+
+        let next = repo
+            .get_cursor_memes(first.next_cursor, 2, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            next.list.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![ids[0]]
+        );
+        assert!(next.next_cursor.is_none());
+
+        let prev = repo
+            .get_cursor_memes(first.prev_cursor, 2, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(prev.list.len(), 1);
+        assert!(!ids.contains(&prev.list[0].id));
+        assert!(prev.next_cursor.is_some());
+
+        let newer = repo
+            .get_cursor_memes(prev.prev_cursor.clone(), 2, filter)
+            .await
+            .unwrap();
+        assert!(newer.list.is_empty());
+        assert_eq!(newer.prev_cursor, prev.prev_cursor);
+    }
+
+    #[tokio::test]
+    async fn get_cursor_memes_invalid_cursor() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+
+        let result = repo
+            .get_cursor_memes(Some("not a cursor".to_owned()), 0, ListFilter::default())
+            .await;
+        assert!(matches!(result, Err(MemeError::InvalidCursor)));
+
+        let cursor = MemeCursor::prev(chrono::Utc::now().into(), Uuid::now_v7());
+        assert_eq!(MemeCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[tokio::test]
+    async fn get_cursor_memes_sort_fail() {
+        let db = TestDB::new().await;
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+
+        for sort in [
+            SortMode::Oldest,
+            SortMode::MostLiked,
+            SortMode::BestRatio,
+            SortMode::Hot,
+            SortMode::Random,
+        ] {
+            let filter = ListFilter {
+                sort,
+                ..Default::default()
+            };
+            let result = repo.get_cursor_memes(None, 0, filter).await;
+            assert!(matches!(result, Err(MemeError::CursorSort)));
+        }
+
+        let filter = ListFilter {
+            seed: 42,
+            ..Default::default()
+        };
+        assert!(repo.get_cursor_memes(None, 0, filter).await.is_ok());
     }
 }
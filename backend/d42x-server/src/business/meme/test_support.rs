@@ -0,0 +1,87 @@
+//! factories of posted memes shared by the business tests
+
+use db_entity::{meme_urls, memes};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::Uuid};
+
+use crate::{
+    business::cache::MockCache,
+    config::AllowMemeFormats,
+    db::{DbConnHelper, test::TestDB},
+};
+
+use super::{MemeRepository, PostMeme, PostMemeUrl, gen_meme_repo::GenMemeRepo};
+
+/// a png at `url` with no hashes, override the fields a test is about
+pub(crate) fn meme_url(url: &str) -> PostMemeUrl {
+    PostMemeUrl {
+        url: url.to_owned(),
+        cover: String::new(),
+        format: AllowMemeFormats::PNG,
+        hash: String::new(),
+        phash: None,
+        placeholder: None,
+        bed: Default::default(),
+        bed_id: String::new(),
+    }
+}
+
+/// a file of the local storage, hashed by its name
+pub(crate) fn stored(bed_id: &str, format: AllowMemeFormats) -> PostMemeUrl {
+    PostMemeUrl {
+        format,
+        hash: bed_id.to_owned(),
+        bed: meme_urls::Bed::Local,
+        bed_id: bed_id.to_owned(),
+        ..meme_url(&format!("/media/{}", bed_id))
+    }
+}
+
+/// a meme of one `meme_url` posted by `tester`
+pub(crate) fn post_meme(message: &str, categories: &[&str]) -> PostMeme {
+    PostMeme {
+        username: "tester".to_owned(),
+        categories: categories.iter().map(|item| item.to_string()).collect(),
+        message: message.to_owned(),
+        memes: vec![meme_url("https://example.com/meme.png")],
+    }
+}
+
+/// post `meme`, return its id looked up by its message
+pub(crate) async fn post(db: &TestDB, meme: PostMeme) -> Uuid {
+    let message = meme.message.clone();
+    GenMemeRepo::<MockCache<String, String>, _>::new(db.clone())
+        .post_memes(vec![meme])
+        .await
+        .unwrap();
+
+    let db_conn = db.get_connection().await.unwrap();
+    memes::Entity::find()
+        .filter(memes::Column::Message.eq(message))
+        .one(&db_conn)
+        .await
+        .unwrap()
+        .unwrap()
+        .id
+}
+
+/// post a meme of the one file `media`, return the id of the file looked up by its url
+pub(crate) async fn post_file(db: &TestDB, media: PostMemeUrl) -> Uuid {
+    let url = media.url.clone();
+    post(
+        db,
+        PostMeme {
+            memes: vec![media],
+            ..post_meme(&url, &[])
+        },
+    )
+    .await;
+
+    let db_conn = db.get_connection().await.unwrap();
+    meme_urls::Entity::find()
+        .filter(meme_urls::Column::Url.eq(url))
+        .one(&db_conn)
+        .await
+        .unwrap()
+        .unwrap()
+        .id
+}
@@ -1,10 +1,22 @@
 use serde::{Deserialize, Serialize};
 
 pub mod accounts;
+pub mod audit;
 pub mod cache;
 pub mod category;
+pub mod duplicates;
+pub mod media;
 pub mod meme;
+pub mod placeholders;
+pub mod roles;
+pub mod search;
+pub mod sessions;
+pub mod storage;
 pub mod suggests;
+pub mod throttle;
+pub mod thumbnails;
+pub mod trending;
+pub mod uploads;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Pagination<T: std::fmt::Debug> {
@@ -13,3 +25,14 @@ pub struct Pagination<T: std::fmt::Debug> {
     pub size: u64,
     pub list: Vec<T>,
 }
+
+/// a slice of a list walked by opaque cursors instead of page numbers
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CursorPage<T: std::fmt::Debug> {
+    pub size: u64,
+    pub list: Vec<T>,
+    /// older items, `None` at the end of the list
+    pub next_cursor: Option<String>,
+    /// newer items, including the ones published after this page was fetched
+    pub prev_cursor: Option<String>,
+}
@@ -0,0 +1,117 @@
+use db_entity::meme_urls;
+use migration::async_trait;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    prelude::{Expr, Uuid},
+};
+use tracing::warn;
+
+use crate::{
+    business::{
+        media::{MediaError, MediaPolicy, placeholder::placeholder_of},
+        storage::Storage,
+        uploads::fetch::MediaFetcher,
+    },
+    config::AllowMemeFormats,
+    db::DbConnHelper,
+};
+
+use super::{Backfill, PlaceholderError, PlaceholderRepository, PlaceholderResult};
+
+pub struct GenPlaceholderRepo<TDb: DbConnHelper> {
+    db: TDb,
+    storage: Box<dyn Storage + 'static + Sync + Send>,
+    fetcher: MediaFetcher,
+}
+
+impl<TDb: DbConnHelper> GenPlaceholderRepo<TDb> {
+    pub fn new(db: TDb, storage: impl Storage + 'static + Sync + Send) -> Self {
+        Self::with_policy(db, storage, MediaPolicy::default())
+    }
+
+    /// only the size limit of `policy` is used, to cut off the downloads
+    pub fn with_policy(
+        db: TDb,
+        storage: impl Storage + 'static + Sync + Send,
+        policy: MediaPolicy,
+    ) -> Self {
+        Self {
+            db,
+            storage: Box::new(storage),
+            fetcher: MediaFetcher::new(policy.max_size()),
+        }
+    }
+
+    async fn fill(
+        &self,
+        db: &impl ConnectionTrait,
+        model: &meme_urls::Model,
+    ) -> PlaceholderResult<()> {
+        let format = AllowMemeFormats::try_from(model.format.as_str())
+            .map_err(|_| PlaceholderError::UnsupportedFormat(model.format.clone()))?;
+
+        let bytes = self
+            .fetcher
+            .load(&self.storage, model.bed, &model.bed_id, &model.url)
+            .await?;
+
+        // decoding a large picture takes a while
+        let placeholder = tokio::task::spawn_blocking(move || placeholder_of(&bytes, format))
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| MediaError::Unreadable(String::from("no placeholder")))?;
+
+        meme_urls::Entity::update_many()
+            .col_expr(
+                meme_urls::Column::Blurhash,
+                Expr::value(placeholder.blurhash),
+            )
+            .col_expr(
+                meme_urls::Column::DominantColor,
+                Expr::value(placeholder.dominant_color),
+            )
+            .filter(meme_urls::Column::Id.eq(model.id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb> PlaceholderRepository for GenPlaceholderRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn backfill(&self, after: Option<Uuid>, limit: u64) -> PlaceholderResult<Backfill> {
+        let db = self.db.get_connection().await?;
+
+        let mut query = meme_urls::Entity::find().filter(meme_urls::Column::Blurhash.is_null());
+        if let Some(after) = after {
+            query = query.filter(meme_urls::Column::Id.gt(after));
+        }
+        let models = query
+            .order_by_asc(meme_urls::Column::Id)
+            .limit(limit)
+            .all(&db)
+            .await?;
+
+        let mut backfill = Backfill {
+            last: models.last().map(|model| model.id),
+            ..Default::default()
+        };
+        for model in models {
+            match self.fill(&db, &model).await {
+                Ok(()) => backfill.filled += 1,
+                Err(PlaceholderError::DatabaseErr(e)) => return Err(e.into()),
+                Err(e) => {
+                    warn!("placeholder of {} failed: {}", model.id, e);
+                    backfill.skipped += 1;
+                }
+            }
+        }
+
+        Ok(backfill)
+    }
+}
@@ -0,0 +1,51 @@
+//! Placeholders
+//!
+//! the BlurHash and the dominant colour of a file are computed when it is posted, see
+//! `media::placeholder`, the files posted before are filled by a backfill
+
+use migration::async_trait;
+use sea_orm::{DbErr, prelude::Uuid};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{media::MediaError, uploads::UploadError};
+
+pub mod gen_placeholder_repo;
+
+#[cfg(test)]
+mod test;
+
+pub type PlaceholderResult<T> = Result<T, PlaceholderError>;
+
+#[async_trait::async_trait]
+pub trait PlaceholderRepository {
+    /// fill the placeholders of up to `limit` files without one, the ones after `after` by id,
+    /// a file that cannot be loaded or decoded is skipped
+    async fn backfill(&self, _after: Option<Uuid>, _limit: u64) -> PlaceholderResult<Backfill> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicPlaceholderRepository;
+
+impl PlaceholderRepository for PanicPlaceholderRepository {}
+
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct Backfill {
+    /// where the next batch starts, `None` when no file is left
+    pub last: Option<Uuid>,
+    pub filled: u64,
+    pub skipped: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum PlaceholderError {
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+    #[error("{0}")]
+    Media(#[from] MediaError),
+    #[error("{0}")]
+    Load(#[from] UploadError),
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+}
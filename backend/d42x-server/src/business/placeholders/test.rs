@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, path::PathBuf};
+
+    use db_entity::meme_urls;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{
+        ColumnTrait, EntityTrait, QueryFilter,
+        prelude::{Expr, Uuid},
+    };
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            meme::{
+                MemeRepository,
+                gen_meme_repo::GenMemeRepo,
+                test_support::{post_file, stored},
+            },
+            placeholders::{
+                Backfill, PlaceholderRepository, gen_placeholder_repo::GenPlaceholderRepo,
+            },
+            storage::local::LocalStorage,
+        },
+        config::AllowMemeFormats,
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    fn new_repo(db: TestDB) -> (GenPlaceholderRepo<TestDB>, PathBuf) {
+        let root = std::env::temp_dir().join(format!("d42x-placeholders-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let repo = GenPlaceholderRepo::new(db, LocalStorage::new(&root, "/media"));
+        (repo, root)
+    }
+
+    /// the seeded files are on the internet, leave them out
+    async fn skip_seeded(db: &TestDB) {
+        let db_conn = db.get_connection().await.unwrap();
+        meme_urls::Entity::update_many()
+            .col_expr(meme_urls::Column::Blurhash, Expr::value("seeded"))
+            .exec(&db_conn)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn backfill_success() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+        skip_seeded(&db).await;
+
+        let mut png = Cursor::new(vec![]);
+        RgbImage::from_pixel(20, 10, Rgb([255, 128, 0]))
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        std::fs::write(root.join("orange.png"), png.into_inner()).unwrap();
+        std::fs::write(root.join("broken.png"), b"not a picture").unwrap();
+        let mut ids = [
+            post_file(&db, stored("orange.png", AllowMemeFormats::PNG)).await,
+            post_file(&db, stored("broken.png", AllowMemeFormats::PNG)).await,
+        ];
+        ids.sort();
+
+        let first = repo.backfill(None, 1).await.unwrap();
+        let second = repo.backfill(first.last, 1).await.unwrap();
+        assert_eq!(
+            (first.filled + second.filled, first.skipped + second.skipped),
+            (1, 1)
+        );
+        assert_eq!(second.last, Some(ids[1]));
+        assert_eq!(
+            repo.backfill(second.last, 1).await.unwrap(),
+            Backfill::default()
+        );
+
+        let db_conn = db.get_connection().await.unwrap();
+        let orange = meme_urls::Entity::find()
+            .filter(meme_urls::Column::BedId.eq("orange.png"))
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(orange.dominant_color.as_deref(), Some("#ff8000"));
+        assert!(orange.blurhash.is_some());
+
+        // returned with the file
+        let detail = GenMemeRepo::<MockCache<String, String>, _>::new(db.clone())
+            .get_meme(orange.meme_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .get_detail()
+            .await
+            .unwrap();
+        assert_eq!(detail.list[0].blurhash, orange.blurhash);
+        assert_eq!(detail.list[0].dominant_color.as_deref(), Some("#ff8000"));
+
+        // the skipped file is picked up by the next backfill
+        assert_eq!(repo.backfill(None, 10).await.unwrap().skipped, 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use db_entity::{role_permissions, roles};
+use migration::async_trait;
+use sea_orm::{
+    ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::{
+    business::{
+        audit::{self, Actor},
+        cache::Cache,
+    },
+    db::DbConnHelper,
+};
+
+use super::{Permission, Role, RoleError, RoleRepository, RoleResult};
+
+pub struct GenRoleRepo<TCache, TDb>
+where
+    TCache: Cache<String, String>,
+    TDb: DbConnHelper,
+{
+    cache: Option<TCache>,
+    db: TDb,
+}
+
+impl<TCache, TDb> GenRoleRepo<TCache, TDb>
+where
+    TCache: Cache<String, String>,
+    TDb: DbConnHelper,
+{
+    pub fn new(db: TDb) -> Self {
+        Self { cache: None, db }
+    }
+
+    pub fn with_cache(db: TDb, cache: Option<TCache>) -> Self {
+        Self { cache, db }
+    }
+}
+
+/// the permissions the server does not know are ignored
+fn to_permissions(list: Vec<role_permissions::Model>) -> Vec<Permission> {
+    list.into_iter()
+        .filter_map(|rp| match Permission::try_from(rp.permission.as_str()) {
+            Ok(permission) => Some(permission),
+            Err(e) => {
+                warn!("role {}: {}", rp.role, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// in the order of `Permission::ALL`, for the audit log
+fn sorted(permissions: &HashSet<Permission>) -> Vec<&'static str> {
+    Permission::ALL
+        .into_iter()
+        .filter(|permission| permissions.contains(permission))
+        .map(|permission| permission.as_str())
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl<TCache, TDb> RoleRepository for GenRoleRepo<TCache, TDb>
+where
+    TCache: Cache<String, String> + Sync + Send,
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn get_roles(&self) -> RoleResult<Vec<Role>> {
+        let db = self.db.get_connection().await?;
+
+        let list = roles::Entity::find()
+            .order_by_asc(roles::Column::CreatedDateTime)
+            .order_by_asc(roles::Column::Name)
+            .find_with_related(role_permissions::Entity)
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(|(role, permissions)| Role {
+                name: role.name,
+                description: role.description,
+                permissions: to_permissions(permissions),
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    async fn get_permissions(&self, role: &str) -> RoleResult<HashSet<Permission>> {
+        let cache_key = format!("ROLE_PERMISSIONS_{}", role);
+        if let Some(cache) = &self.cache
+            && let Some(value) = cache.get(&cache_key)
+        {
+            if let Ok(value) = serde_json::from_str::<HashSet<Permission>>(value.as_str()) {
+                return Ok(value);
+            } else {
+                debug!("incorrect data in cache, remove");
+                cache.remove(&cache_key);
+            }
+        }
+
+        let db = self.db.get_connection().await?;
+
+        let list = role_permissions::Entity::find()
+            .filter(role_permissions::Column::Role.eq(role))
+            .all(&db)
+            .await?;
+        let permissions: HashSet<_> = to_permissions(list).into_iter().collect();
+
+        if let Some(cache) = &self.cache {
+            cache.insert(cache_key, json!(permissions).to_string());
+        }
+
+        Ok(permissions)
+    }
+
+    async fn set_permissions(
+        &self,
+        actor: &Actor,
+        role: &str,
+        permissions: Vec<Permission>,
+    ) -> RoleResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        if roles::Entity::find_by_id(role).one(&txn).await?.is_none() {
+            return Err(RoleError::NotFound(role.to_owned()));
+        }
+
+        let before: HashSet<_> = to_permissions(
+            role_permissions::Entity::find()
+                .filter(role_permissions::Column::Role.eq(role))
+                .all(&txn)
+                .await?,
+        )
+        .into_iter()
+        .collect();
+        let permissions: HashSet<_> = permissions.into_iter().collect();
+
+        // somebody has to be able to manage the accounts and the roles
+        let manage = Permission::ManageAccounts;
+        if before.contains(&manage) && !permissions.contains(&manage) {
+            let others = role_permissions::Entity::find()
+                .filter(role_permissions::Column::Permission.eq(manage.as_str()))
+                .filter(role_permissions::Column::Role.ne(role))
+                .count(&txn)
+                .await?;
+            if others == 0 {
+                return Err(RoleError::LastManager);
+            }
+        }
+
+        role_permissions::Entity::delete_many()
+            .filter(role_permissions::Column::Role.eq(role))
+            .exec(&txn)
+            .await?;
+
+        if !permissions.is_empty() {
+            role_permissions::Entity::insert_many(permissions.iter().map(|permission| {
+                role_permissions::ActiveModel {
+                    role: Set(role.to_owned()),
+                    permission: Set(permission.as_str().to_owned()),
+                }
+            }))
+            .exec(&txn)
+            .await?;
+        }
+
+        audit::record(
+            &txn,
+            actor,
+            "role.set_permissions",
+            Some(role.to_owned()),
+            json!({ "from": sorted(&before), "to": sorted(&permissions) }),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        self.clear_cache().await;
+
+        Ok(())
+    }
+
+    async fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+}
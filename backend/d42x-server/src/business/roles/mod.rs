@@ -0,0 +1,126 @@
+//! Roles
+//!
+//! an account has one role, a role grants a set of permissions.
+//! roles and their permissions are stored in the database, the permissions themselves are
+//! fixed by the server, every admin handler requires one of them through `app::authorization`
+
+use std::collections::HashSet;
+
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::audit::Actor;
+
+pub mod gen_role_repo;
+
+#[cfg(test)]
+mod test;
+
+/// the role every account with `is_admin` has been migrated to
+pub const ADMINISTRATOR: &str = "administrator";
+
+pub type RoleResult<T> = Result<T, RoleError>;
+
+#[async_trait::async_trait]
+pub trait RoleRepository {
+    async fn get_roles(&self) -> RoleResult<Vec<Role>> {
+        unimplemented!()
+    }
+
+    /// the permissions granted to a role, an unknown role has none
+    async fn get_permissions(&self, _role: &str) -> RoleResult<HashSet<Permission>> {
+        unimplemented!()
+    }
+
+    async fn has_permission(&self, role: &str, permission: Permission) -> RoleResult<bool> {
+        Ok(self.get_permissions(role).await?.contains(&permission))
+    }
+
+    /// replace the permissions of a role, `accounts.manage` stays with at least one role
+    async fn set_permissions(
+        &self,
+        _actor: &Actor,
+        _role: &str,
+        _permissions: Vec<Permission>,
+    ) -> RoleResult<()> {
+        unimplemented!()
+    }
+
+    async fn clear_cache(&self) {
+        unimplemented!()
+    }
+}
+
+pub struct PanicRoleRepository;
+
+impl RoleRepository for PanicRoleRepository {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    #[serde(rename = "memes.view")]
+    ViewMemes,
+    #[serde(rename = "memes.post")]
+    PostMemes,
+    /// delete, restore and purge
+    #[serde(rename = "memes.delete")]
+    DeleteMemes,
+    #[serde(rename = "categories.edit")]
+    EditCategories,
+    #[serde(rename = "suggests.review")]
+    ReviewSuggests,
+    /// accounts, roles and log in throttles
+    #[serde(rename = "accounts.manage")]
+    ManageAccounts,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 6] = [
+        Permission::ViewMemes,
+        Permission::PostMemes,
+        Permission::DeleteMemes,
+        Permission::EditCategories,
+        Permission::ReviewSuggests,
+        Permission::ManageAccounts,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ViewMemes => "memes.view",
+            Permission::PostMemes => "memes.post",
+            Permission::DeleteMemes => "memes.delete",
+            Permission::EditCategories => "categories.edit",
+            Permission::ReviewSuggests => "suggests.review",
+            Permission::ManageAccounts => "accounts.manage",
+        }
+    }
+}
+
+impl TryFrom<&str> for Permission {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Permission::ALL
+            .into_iter()
+            .find(|p| p.as_str() == value)
+            .ok_or_else(|| format!("incorrect permission: {}", value))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Error, Debug)]
+pub enum RoleError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error("role not found: {0}")]
+    NotFound(String),
+    #[error("accounts.manage can not be taken from the last role that has it")]
+    LastManager,
+}
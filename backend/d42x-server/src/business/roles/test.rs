@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{
+        business::{
+            audit::{Actor, AuditRepository, GetFilter, gen_audit_repo::GenAuditRepo},
+            cache::MokaCache,
+            roles::{
+                ADMINISTRATOR, Permission, RoleError, RoleRepository, gen_role_repo::GenRoleRepo,
+            },
+        },
+        db::test::TestDB,
+    };
+
+    fn repo(db: TestDB) -> GenRoleRepo<MokaCache, TestDB> {
+        GenRoleRepo::with_cache(db, Some(MokaCache::new()))
+    }
+
+    #[test]
+    fn permission_names_round_trip() {
+        for permission in Permission::ALL {
+            assert_eq!(Permission::try_from(permission.as_str()), Ok(permission));
+            assert_eq!(
+                serde_json::to_string(&permission).unwrap(),
+                format!("\"{}\"", permission.as_str())
+            );
+        }
+        assert!(Permission::try_from("memes.burn").is_err());
+    }
+
+    #[tokio::test]
+    async fn seeded_roles() {
+        let repo = repo(TestDB::new().await);
+
+        let roles = repo.get_roles().await.unwrap();
+        let names: Vec<_> = roles.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec![ADMINISTRATOR, "editor", "moderator"]);
+
+        let administrator = repo.get_permissions(ADMINISTRATOR).await.unwrap();
+        assert_eq!(administrator.len(), Permission::ALL.len());
+
+        assert!(
+            repo.has_permission("moderator", Permission::ReviewSuggests)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.has_permission("moderator", Permission::EditCategories)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .has_permission("moderator", Permission::DeleteMemes)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .has_permission("moderator", Permission::ManageAccounts)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.has_permission("editor", Permission::PostMemes)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .has_permission("editor", Permission::ReviewSuggests)
+                .await
+                .unwrap()
+        );
+        assert!(repo.get_permissions("nobody").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_permissions_clears_cache() {
+        let repo = repo(TestDB::new().await);
+
+        assert!(
+            !repo
+                .has_permission("editor", Permission::DeleteMemes)
+                .await
+                .unwrap()
+        );
+
+        repo.set_permissions(
+            &Actor::cli(),
+            "editor",
+            vec![Permission::PostMemes, Permission::DeleteMemes],
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            repo.has_permission("editor", Permission::DeleteMemes)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .has_permission("editor", Permission::ViewMemes)
+                .await
+                .unwrap()
+        );
+
+        let err = repo
+            .set_permissions(&Actor::cli(), "nobody", vec![Permission::ViewMemes])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RoleError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn set_permissions_audited() {
+        let db = TestDB::new().await;
+        let repo = repo(db.clone());
+        let before = repo.get_permissions("editor").await.unwrap();
+
+        repo.set_permissions(
+            &Actor::cli(),
+            "editor",
+            vec![Permission::DeleteMemes, Permission::ViewMemes],
+        )
+        .await
+        .unwrap();
+
+        let logs = GenAuditRepo::new(db)
+            .get_paginated_logs(GetFilter {
+                page: 1,
+                size: 10,
+                action: Some("role.set_permissions".to_owned()),
+                actor_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(logs.list.len(), 1);
+        assert_eq!(logs.list[0].target_id.as_deref(), Some("editor"));
+        let from: Vec<_> = Permission::ALL
+            .into_iter()
+            .filter(|permission| before.contains(permission))
+            .map(|permission| permission.as_str())
+            .collect();
+        assert_eq!(
+            logs.list[0].details,
+            json!({ "from": from, "to": ["memes.view", "memes.delete"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn set_permissions_last_manager_fail() {
+        let repo = repo(TestDB::new().await);
+
+        let err = repo
+            .set_permissions(&Actor::cli(), ADMINISTRATOR, vec![Permission::ViewMemes])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RoleError::LastManager));
+        assert!(
+            repo.has_permission(ADMINISTRATOR, Permission::ManageAccounts)
+                .await
+                .unwrap()
+        );
+
+        // another role has it now
+        repo.set_permissions(&Actor::cli(), "moderator", vec![Permission::ManageAccounts])
+            .await
+            .unwrap();
+        repo.set_permissions(&Actor::cli(), ADMINISTRATOR, vec![Permission::ViewMemes])
+            .await
+            .unwrap();
+        let err = repo
+            .set_permissions(&Actor::cli(), "moderator", vec![])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RoleError::LastManager));
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::{meme_search_terms as terms, memes};
+use migration::async_trait;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter, TransactionTrait,
+    prelude::Uuid,
+    sea_query::{Alias, CaseStatement, Expr, Func, Order, Query, SelectStatement, SimpleExpr},
+};
+
+use crate::{
+    business::{Pagination, meme::gen_meme_repo::models_2_meme_list},
+    db::DbConnHelper,
+};
+
+use super::{
+    Highlight, SearchHit, SearchRepository, SearchResult,
+    index::{FIELD_CATEGORY, FIELD_MESSAGE, FIELD_NICKNAME, field_boost, rebuild_index},
+    tokenizer::{highlight, tokenize},
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 10;
+
+pub struct GenSearchRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    db: TDb,
+    page_size: u64,
+}
+
+impl<TDb> GenSearchRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    pub fn new(db: TDb) -> Self {
+        Self {
+            db,
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TermFrequency {
+    term: String,
+    memes: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ScoredMeme {
+    meme_id: Uuid,
+    score: f64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct MatchedCount {
+    total: i64,
+}
+
+#[async_trait::async_trait]
+impl<TDb> SearchRepository for GenSearchRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn search(&self, query: String, page: u64) -> SearchResult<Pagination<SearchHit>> {
+        let mut query_terms: Vec<String> = vec![];
+        for term in tokenize(&query) {
+            if !query_terms.contains(&term) {
+                query_terms.push(term);
+            }
+        }
+
+        let mut result = Pagination {
+            page,
+            total: 0,
+            size: self.page_size,
+            list: vec![],
+        };
+        if query_terms.is_empty() {
+            return Ok(result);
+        }
+
+        let db = self.db.get_connection().await?;
+        let backend = db.get_database_backend();
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let published_memes = memes::Entity::find()
+            .filter(memes::Column::Status.eq(memes::Status::Published))
+            .filter(memes::Column::ShowDateTime.lt(now));
+        let meme_count = sea_orm::PaginatorTrait::count(published_memes, &db).await?;
+
+        // inverse document frequency, a term found in fewer memes weighs more
+        let mut stmt = matched_terms(&query_terms, now);
+        stmt.column((terms::Entity, terms::Column::Term))
+            .expr_as(
+                Expr::col((terms::Entity, terms::Column::MemeId)).count_distinct(),
+                Alias::new("memes"),
+            )
+            .group_by_col((terms::Entity, terms::Column::Term));
+        let idf: HashMap<String, f64> = TermFrequency::find_by_statement(backend.build(&stmt))
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(|item| {
+                let idf = (1.0 + meme_count as f64 / item.memes.max(1) as f64).ln();
+                (item.term, idf)
+            })
+            .collect();
+        if idf.is_empty() {
+            return Ok(result);
+        }
+
+        let mut stmt = matched_terms(&query_terms, now);
+        stmt.expr_as(
+            Expr::col((terms::Entity, terms::Column::MemeId)).count_distinct(),
+            Alias::new("total"),
+        );
+        let matched = MatchedCount::find_by_statement(backend.build(&stmt))
+            .one(&db)
+            .await?
+            .map(|item| item.total as u64)
+            .unwrap_or_default();
+        result.total = matched.div_ceil(self.page_size);
+
+        let mut term_weight = CaseStatement::new();
+        for (term, idf) in idf.iter() {
+            term_weight = term_weight.case(
+                Expr::col((terms::Entity, terms::Column::Term)).eq(term.as_str()),
+                *idf,
+            );
+        }
+        let mut field_weight = CaseStatement::new();
+        for field in [FIELD_MESSAGE, FIELD_NICKNAME, FIELD_CATEGORY] {
+            field_weight = field_weight.case(
+                Expr::col((terms::Entity, terms::Column::Field)).eq(field),
+                field_boost(field),
+            );
+        }
+        let score = Expr::col((terms::Entity, terms::Column::Frequency))
+            .mul(SimpleExpr::Case(Box::new(term_weight.finally(0.0))))
+            .mul(SimpleExpr::Case(Box::new(field_weight.finally(0.0))));
+
+        let fetch_page = if page > 0 { page - 1 } else { 0 };
+        let mut stmt = matched_terms(&query_terms, now);
+        stmt.column((terms::Entity, terms::Column::MemeId))
+            .expr_as(Func::sum(score), Alias::new("score"))
+            .group_by_col((terms::Entity, terms::Column::MemeId))
+            .order_by(Alias::new("score"), Order::Desc)
+            .order_by((terms::Entity, terms::Column::MemeId), Order::Desc)
+            .limit(self.page_size)
+            .offset(fetch_page * self.page_size);
+        let scored = ScoredMeme::find_by_statement(backend.build(&stmt))
+            .all(&db)
+            .await?;
+
+        let txn = db.begin().await?;
+        let models = memes::Entity::find()
+            .filter(memes::Column::Id.is_in(scored.iter().map(|item| item.meme_id)))
+            .all(&txn)
+            .await?;
+        let messages: HashMap<Uuid, String> = models
+            .iter()
+            .map(|model| (model.id, model.message.clone()))
+            .collect();
+        let mut meme_list: HashMap<Uuid, _> = models_2_meme_list(models, &txn)
+            .await?
+            .into_iter()
+            .map(|meme| (meme.id, meme))
+            .collect();
+        txn.commit().await?;
+
+        let query_terms: HashSet<String> = query_terms.into_iter().collect();
+        for item in scored {
+            let Some(meme) = meme_list.remove(&item.meme_id) else {
+                continue;
+            };
+
+            let highlight = Highlight {
+                message: messages
+                    .get(&meme.id)
+                    .map(|message| highlight(message, &query_terms))
+                    .unwrap_or_default(),
+                nickname: highlight(&meme.nickname, &query_terms),
+                categories: meme
+                    .categories
+                    .iter()
+                    .map(|category| highlight(category, &query_terms))
+                    .collect(),
+            };
+
+            result.list.push(SearchHit {
+                meme,
+                score: item.score,
+                highlight,
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn rebuild(&self) -> SearchResult<u64> {
+        let db = self.db.get_connection().await?;
+
+        let txn = db.begin().await?;
+        let count = rebuild_index(&txn).await?;
+        txn.commit().await?;
+
+        Ok(count)
+    }
+}
+
+/// index rows of the published memes matching any of `query_terms`
+fn matched_terms(query_terms: &[String], now: DateTime<FixedOffset>) -> SelectStatement {
+    Query::select()
+        .from(terms::Entity)
+        .inner_join(
+            memes::Entity,
+            Expr::col((memes::Entity, memes::Column::Id))
+                .equals((terms::Entity, terms::Column::MemeId)),
+        )
+        .and_where(terms::Column::Term.is_in(query_terms.iter().cloned()))
+        .and_where(memes::Column::Status.eq(memes::Status::Published))
+        .and_where(memes::Column::ShowDateTime.lt(now))
+        .to_owned()
+}
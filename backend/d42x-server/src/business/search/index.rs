@@ -0,0 +1,114 @@
+//! the `meme_search_terms` inverted index. the meme and category repositories call
+//! `index_memes` inside their transactions whenever a message or a category changes
+
+use std::collections::HashMap;
+
+use db_entity::{meme_search_terms, memes};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, prelude::Uuid,
+};
+
+use crate::business::category::meme_categories::get_meme_categories;
+
+use super::tokenizer::tokenize;
+
+pub const FIELD_MESSAGE: &str = "message";
+pub const FIELD_NICKNAME: &str = "nickname";
+pub const FIELD_CATEGORY: &str = "category";
+
+/// the rows of one insert, kept small for the bind parameter limit of SQLite
+const INSERT_CHUNK: usize = 200;
+const REBUILD_BATCH: u64 = 500;
+
+/// a match in a category or the nickname weighs more than one in the message
+pub fn field_boost(field: &str) -> f64 {
+    match field {
+        FIELD_CATEGORY => 3.0,
+        FIELD_NICKNAME => 2.0,
+        _ => 1.0,
+    }
+}
+
+/// replace the terms of the memes with their current message, nickname and categories
+pub async fn index_memes(conn: &impl ConnectionTrait, meme_ids: Vec<Uuid>) -> Result<(), DbErr> {
+    if meme_ids.is_empty() {
+        return Ok(());
+    }
+
+    remove_memes(conn, meme_ids.clone()).await?;
+
+    let models = memes::Entity::find()
+        .filter(memes::Column::Id.is_in(meme_ids.clone()))
+        .all(conn)
+        .await?;
+    let mut categories = get_meme_categories(conn, meme_ids).await?;
+
+    let mut rows = vec![];
+    for model in models {
+        let category_text = categories.remove(&model.id).unwrap_or_default().join(" ");
+        for (field, text) in [
+            (FIELD_MESSAGE, model.message.as_str()),
+            (FIELD_NICKNAME, model.nickname.as_str()),
+            (FIELD_CATEGORY, category_text.as_str()),
+        ] {
+            let mut frequencies: HashMap<String, i32> = HashMap::new();
+            for term in tokenize(text) {
+                *frequencies.entry(term).or_default() += 1;
+            }
+
+            rows.extend(frequencies.into_iter().map(|(term, frequency)| {
+                meme_search_terms::ActiveModel {
+                    term: Set(term),
+                    meme_id: Set(model.id),
+                    field: Set(field.to_owned()),
+                    frequency: Set(frequency),
+                }
+            }));
+        }
+    }
+
+    while !rows.is_empty() {
+        let chunk: Vec<_> = rows.drain(..rows.len().min(INSERT_CHUNK)).collect();
+        meme_search_terms::Entity::insert_many(chunk)
+            .exec(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn remove_memes(conn: &impl ConnectionTrait, meme_ids: Vec<Uuid>) -> Result<(), DbErr> {
+    meme_search_terms::Entity::delete_many()
+        .filter(meme_search_terms::Column::MemeId.is_in(meme_ids))
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// drop the whole index and index every meme again, returns how many memes are indexed
+pub async fn rebuild_index(conn: &impl ConnectionTrait) -> Result<u64, DbErr> {
+    meme_search_terms::Entity::delete_many().exec(conn).await?;
+
+    let pages = memes::Entity::find()
+        .select_only()
+        .column(memes::Column::Id)
+        .order_by_asc(memes::Column::Id)
+        .into_tuple::<Uuid>()
+        .paginate(conn, REBUILD_BATCH);
+
+    let mut count = 0;
+    let mut page = 0;
+    loop {
+        let ids = pages.fetch_page(page).await?;
+        if ids.is_empty() {
+            break;
+        }
+        count += ids.len() as u64;
+        index_memes(conn, ids).await?;
+        page += 1;
+    }
+
+    Ok(count)
+}
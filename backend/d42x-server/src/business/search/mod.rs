@@ -0,0 +1,66 @@
+//! Full text search
+//!
+//! `meme_search_terms` is an inverted index of the message, nickname and categories
+//! of every meme, it lives in the database so it works the same on Postgres and SQLite
+//! and is written in the same transaction as the meme, see `index`
+
+pub mod gen_search_repo;
+pub mod index;
+pub mod tokenizer;
+
+#[cfg(test)]
+mod test;
+
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    Pagination,
+    meme::{Meme, MemeError},
+};
+
+pub type SearchResult<T> = Result<T, SearchError>;
+
+#[async_trait::async_trait]
+pub trait SearchRepository {
+    /// published memes matching any term of `query`, the most relevant first
+    async fn search(&self, _query: String, _page: u64) -> SearchResult<Pagination<SearchHit>> {
+        unimplemented!()
+    }
+
+    /// index every meme from scratch, returns how many memes are indexed
+    async fn rebuild(&self) -> SearchResult<u64> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicSearchRepository;
+
+#[async_trait::async_trait]
+impl SearchRepository for PanicSearchRepository {}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub meme: Meme,
+    pub score: f64,
+    pub highlight: Highlight,
+}
+
+/// the matched terms are wrapped in `<mark>`, the rest is HTML escaped
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Highlight {
+    pub message: String,
+    pub nickname: String,
+    pub categories: Vec<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum SearchError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error(transparent)]
+    Meme(#[from] MemeError),
+}
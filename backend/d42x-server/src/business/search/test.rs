@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            category::{CategoryRepository, gen_cate_repo::GenCategoryRepo},
+            meme::{MemeRepository, gen_meme_repo::GenMemeRepo, test_support::post_meme},
+            search::{
+                SearchRepository,
+                gen_search_repo::GenSearchRepo,
+                tokenizer::{highlight, tokenize},
+            },
+        },
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    #[test]
+    fn tokenize_success() {
+        assert_eq!(
+            tokenize("Hello, ＷＯＲＬＤ 猫咪!"),
+            vec!["hello", "world", "猫", "猫咪", "咪"]
+        );
+    }
+
+    #[test]
+    fn highlight_success() {
+        let terms: HashSet<String> = ["cats".to_owned(), "猫".to_owned()].into();
+
+        assert_eq!(
+            highlight("I <3 Cats & 猫咪", &terms),
+            "I &lt;3 <mark>Cats</mark> &amp; <mark>猫</mark>咪"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_ranked_success() {
+        let db = TestDB::new().await;
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let repo = GenSearchRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![
+                post_meme("there is a cat", &["funny"]),
+                post_meme("hello", &["cat"]),
+                post_meme("nothing to see", &["dog"]),
+            ])
+            .await
+            .unwrap();
+
+        let result = repo.search("CAT".to_owned(), 1).await.unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.list.len(), 2);
+        assert_eq!(result.list[0].meme.categories, vec!["cat"]);
+        assert_eq!(
+            result.list[0].highlight.categories,
+            vec!["<mark>cat</mark>"]
+        );
+        assert_eq!(
+            result.list[1].highlight.message,
+            "there is a <mark>cat</mark>"
+        );
+        assert!(result.list[0].score > result.list[1].score);
+
+        let result = repo.search("!!".to_owned(), 1).await.unwrap();
+        assert!(result.list.is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_follows_changes_success() {
+        let db = TestDB::new().await;
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let cate_repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+        let repo = GenSearchRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![post_meme("hello", &["cat"])])
+            .await
+            .unwrap();
+        let meme_id = repo.search("cat".to_owned(), 1).await.unwrap().list[0]
+            .meme
+            .id;
+
+        let db_conn = db.get_connection().await.unwrap();
+        let cat = db_entity::categories::Entity::find()
+            .filter(db_entity::categories::Column::Name.eq("cat"))
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        cate_repo
+            .rename_category(cat.id, "kitty".to_owned())
+            .await
+            .unwrap();
+        assert!(
+            repo.search("cat".to_owned(), 1)
+                .await
+                .unwrap()
+                .list
+                .is_empty()
+        );
+        assert_eq!(
+            repo.search("kitty".to_owned(), 1).await.unwrap().list[0]
+                .meme
+                .id,
+            meme_id
+        );
+
+        let meme = meme_repo.get_meme(meme_id).await.unwrap().unwrap();
+        meme.delete(sea_orm::prelude::Uuid::nil()).await.unwrap();
+        assert!(
+            repo.search("kitty".to_owned(), 1)
+                .await
+                .unwrap()
+                .list
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn rebuild_success() {
+        let db = TestDB::new().await;
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let repo = GenSearchRepo::new(db.clone());
+
+        meme_repo
+            .post_memes(vec![post_meme("hello", &["cat"])])
+            .await
+            .unwrap();
+
+        let db_conn = db.get_connection().await.unwrap();
+        db_entity::meme_search_terms::Entity::delete_many()
+            .exec(&db_conn)
+            .await
+            .unwrap();
+        assert!(
+            repo.search("hello".to_owned(), 1)
+                .await
+                .unwrap()
+                .list
+                .is_empty()
+        );
+
+        let count = repo.rebuild().await.unwrap();
+        let meme_count = db_entity::memes::Entity::find()
+            .count(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(count, meme_count);
+        assert_eq!(
+            repo.search("hello".to_owned(), 1).await.unwrap().list.len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! words are split on anything not alphanumeric, CJK text has no spaces,
+//! so every character and every pair of adjacent characters is a term
+
+use std::{collections::HashSet, ops::Range};
+
+use unicode_normalization::UnicodeNormalization;
+
+/// the longest term kept in the index, in chars
+pub const MAX_TERM_LEN: usize = 64;
+
+#[derive(PartialEq)]
+enum CharKind {
+    Word,
+    Cjk,
+    Separator,
+}
+
+fn char_kind(c: char) -> CharKind {
+    match c {
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{20000}'..='\u{2ffff}' => CharKind::Cjk,
+        c if c.is_alphanumeric() => CharKind::Word,
+        _ => CharKind::Separator,
+    }
+}
+
+/// terms of `text` with their byte ranges in `text`,
+/// each char is NFKC normalized and lower cased like `category::aliases::normalize`
+pub fn tokens_with_spans(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    let mut word_span = 0..0;
+    let mut cjk_run: Vec<(String, Range<usize>)> = vec![];
+
+    let flush_word = |word: &mut String, span: &Range<usize>, tokens: &mut Vec<_>| {
+        if !word.is_empty() {
+            let term: String = word.chars().take(MAX_TERM_LEN).collect();
+            tokens.push((term, span.clone()));
+            word.clear();
+        }
+    };
+    let flush_cjk = |run: &mut Vec<(String, Range<usize>)>, tokens: &mut Vec<_>| {
+        for (i, (c, span)) in run.iter().enumerate() {
+            tokens.push((c.clone(), span.clone()));
+            if let Some((next, next_span)) = run.get(i + 1) {
+                tokens.push((format!("{}{}", c, next), span.start..next_span.end));
+            }
+        }
+        run.clear();
+    };
+
+    for (pos, c) in text.char_indices() {
+        let span = pos..pos + c.len_utf8();
+        let normalized = c.to_string().nfkc().collect::<String>().to_lowercase();
+        let kind = normalized
+            .chars()
+            .next()
+            .map(char_kind)
+            .unwrap_or(CharKind::Separator);
+
+        match kind {
+            CharKind::Word => {
+                flush_cjk(&mut cjk_run, &mut tokens);
+                if word.is_empty() {
+                    word_span = span;
+                } else {
+                    word_span.end = span.end;
+                }
+                word.push_str(&normalized);
+            }
+            CharKind::Cjk => {
+                flush_word(&mut word, &word_span, &mut tokens);
+                cjk_run.push((normalized, span));
+            }
+            CharKind::Separator => {
+                flush_word(&mut word, &word_span, &mut tokens);
+                flush_cjk(&mut cjk_run, &mut tokens);
+            }
+        }
+    }
+    flush_word(&mut word, &word_span, &mut tokens);
+    flush_cjk(&mut cjk_run, &mut tokens);
+
+    tokens
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    tokens_with_spans(text)
+        .into_iter()
+        .map(|(term, _)| term)
+        .collect()
+}
+
+/// `text` with the matched terms wrapped in `<mark>`, the rest is HTML escaped
+pub fn highlight(text: &str, terms: &HashSet<String>) -> String {
+    let mut spans: Vec<Range<usize>> = tokens_with_spans(text)
+        .into_iter()
+        .filter(|(term, _)| terms.contains(term))
+        .map(|(_, span)| span)
+        .collect();
+    spans.sort_by_key(|span| span.start);
+
+    let mut merged: Vec<Range<usize>> = vec![];
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for span in merged {
+        result.push_str(&escape_html(&text[pos..span.start]));
+        result.push_str("<mark>");
+        result.push_str(&escape_html(&text[span.start..span.end]));
+        result.push_str("</mark>");
+        pos = span.end;
+    }
+    result.push_str(&escape_html(&text[pos..]));
+
+    result
+}
+
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,283 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use db_entity::{accounts, revoked_tokens, sessions};
+use migration::async_trait;
+use rand::{RngCore, rngs::OsRng};
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction,
+    EntityTrait, IntoActiveModel, QueryFilter, Set, TransactionTrait, prelude::Uuid,
+    sea_query::OnConflict,
+};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{authentication::AccessTokenMeta, db::DbConnHelper};
+
+use super::{IssuedSession, SessionError, SessionRepository, SessionResult};
+
+const DEFAULT_REFRESH_LIFETIME: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const REFRESH_TOKEN_BYTES: usize = 32;
+
+pub struct GenSessionRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    db: TDb,
+    refresh_lifetime: Duration,
+}
+
+impl<TDb> GenSessionRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    pub fn new(db: TDb) -> Self {
+        Self::with_lifetime(db, DEFAULT_REFRESH_LIFETIME)
+    }
+
+    /// `refresh_lifetime`: a session expires if it is not refreshed within it
+    pub fn with_lifetime(db: TDb, refresh_lifetime: Duration) -> Self {
+        Self {
+            db,
+            refresh_lifetime,
+        }
+    }
+}
+
+/// a random refresh token and its hash
+fn new_refresh_token() -> (String, String) {
+    let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+
+    let token = hex::encode(bytes);
+    let hash = hash_refresh_token(&token);
+    (token, hash)
+}
+
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+async fn find_admin(
+    txn: &DatabaseTransaction,
+    account_id: Uuid,
+) -> SessionResult<Option<accounts::Model>> {
+    Ok(accounts::Entity::find_by_id(account_id)
+        .filter(accounts::Column::Role.is_not_null())
+        .filter(accounts::Column::DisabledDateTime.is_null())
+        .one(txn)
+        .await?)
+}
+
+/// put the access token on the revocation list, revoking twice is fine
+async fn revoke_token(txn: &DatabaseTransaction, session: &sessions::Model) -> SessionResult<()> {
+    revoked_tokens::Entity::insert(revoked_tokens::ActiveModel {
+        token_id: Set(session.access_token_id.clone()),
+        account_id: Set(session.account_id),
+        expires_date_time: Set(session.access_expires_date_time),
+        ..revoked_tokens::ActiveModel::new()
+    })
+    .on_conflict(
+        OnConflict::column(revoked_tokens::Column::TokenId)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(txn)
+    .await?;
+
+    Ok(())
+}
+
+async fn revoke_session(txn: &DatabaseTransaction, session: sessions::Model) -> SessionResult<()> {
+    revoke_token(txn, &session).await?;
+
+    if session.revoked_date_time.is_none() {
+        let mut model = session.into_active_model();
+        model.revoked_date_time = Set(Some(Utc::now().into()));
+        model.update(txn).await?;
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl<TDb> SessionRepository for GenSessionRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn create(
+        &self,
+        account_id: Uuid,
+        access: &AccessTokenMeta,
+    ) -> SessionResult<IssuedSession> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let account = find_admin(&txn, account_id)
+            .await?
+            .ok_or(SessionError::AccountNotFound)?;
+
+        let (refresh_token, refresh_token_hash) = new_refresh_token();
+        let now = Utc::now();
+
+        let session = sessions::ActiveModel {
+            account_id: Set(account.id),
+            refresh_token_hash: Set(refresh_token_hash),
+            access_token_id: Set(access.token_id.clone()),
+            access_expires_date_time: Set(access.expiration.into()),
+            expires_date_time: Set((now + self.refresh_lifetime).into()),
+            ..sessions::ActiveModel::new()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(IssuedSession {
+            session_id: session.id,
+            account_id: account.id,
+            username: account.username,
+            role: account.role.unwrap_or_default(),
+            refresh_token,
+        })
+    }
+
+    async fn refresh(
+        &self,
+        refresh_token: &str,
+        access: &AccessTokenMeta,
+    ) -> SessionResult<IssuedSession> {
+        let hash = hash_refresh_token(refresh_token);
+
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let session = sessions::Entity::find()
+            .filter(sessions::Column::RefreshTokenHash.eq(&hash))
+            .one(&txn)
+            .await?;
+
+        let Some(session) = session else {
+            let stolen = sessions::Entity::find()
+                .filter(sessions::Column::PreviousRefreshTokenHash.eq(&hash))
+                .filter(sessions::Column::RevokedDateTime.is_null())
+                .one(&txn)
+                .await?;
+
+            if let Some(stolen) = stolen {
+                warn!(
+                    "refresh token of session {} is reused, revoke it",
+                    stolen.id
+                );
+                revoke_session(&txn, stolen).await?;
+                txn.commit().await?;
+                return Err(SessionError::RefreshTokenReused);
+            }
+
+            return Err(SessionError::InvalidRefreshToken);
+        };
+
+        let now = Utc::now();
+        if session.revoked_date_time.is_some() || session.expires_date_time < now {
+            return Err(SessionError::InvalidRefreshToken);
+        }
+
+        let Some(account) = find_admin(&txn, session.account_id).await? else {
+            revoke_session(&txn, session).await?;
+            txn.commit().await?;
+            return Err(SessionError::AccountNotFound);
+        };
+
+        // the access token issued before must not outlive the refresh
+        revoke_token(&txn, &session).await?;
+
+        let (refresh_token, refresh_token_hash) = new_refresh_token();
+        let session_id = session.id;
+        let previous_hash = session.refresh_token_hash.clone();
+
+        let mut model = session.into_active_model();
+        model.previous_refresh_token_hash = Set(previous_hash);
+        model.refresh_token_hash = Set(refresh_token_hash);
+        model.access_token_id = Set(access.token_id.clone());
+        model.access_expires_date_time = Set(access.expiration.into());
+        model.refreshed_date_time = Set(now.into());
+        model.expires_date_time = Set((now + self.refresh_lifetime).into());
+        model.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(IssuedSession {
+            session_id,
+            account_id: account.id,
+            username: account.username,
+            role: account.role.unwrap_or_default(),
+            refresh_token,
+        })
+    }
+
+    async fn revoke(&self, session_id: Uuid) -> SessionResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let session = sessions::Entity::find_by_id(session_id)
+            .one(&txn)
+            .await?
+            .ok_or(SessionError::NotFound)?;
+        revoke_session(&txn, session).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn revoke_all(&self, account_id: Uuid) -> SessionResult<u64> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let list = sessions::Entity::find()
+            .filter(sessions::Column::AccountId.eq(account_id))
+            .filter(sessions::Column::RevokedDateTime.is_null())
+            .all(&txn)
+            .await?;
+
+        let count = list.len() as u64;
+        for session in list {
+            revoke_session(&txn, session).await?;
+        }
+
+        txn.commit().await?;
+        Ok(count)
+    }
+
+    async fn is_revoked(&self, token_id: &str) -> SessionResult<bool> {
+        let db = self.db.get_connection().await?;
+
+        let revoked = revoked_tokens::Entity::find_by_id(token_id.to_owned())
+            .one(&db)
+            .await?;
+
+        Ok(revoked.is_some())
+    }
+
+    async fn purge_expired(&self) -> SessionResult<u64> {
+        let db = self.db.get_connection().await?;
+        let now = Utc::now();
+
+        let tokens = revoked_tokens::Entity::delete_many()
+            .filter(revoked_tokens::Column::ExpiresDateTime.lt(now))
+            .exec(&db)
+            .await?;
+
+        // the access token of a removed session has expired, nothing has to be revoked
+        let sessions = sessions::Entity::delete_many()
+            .filter(sessions::Column::AccessExpiresDateTime.lt(now))
+            .filter(
+                Condition::any()
+                    .add(sessions::Column::ExpiresDateTime.lt(now))
+                    .add(sessions::Column::RevokedDateTime.is_not_null()),
+            )
+            .exec(&db)
+            .await?;
+
+        Ok(tokens.rows_affected + sessions.rows_affected)
+    }
+}
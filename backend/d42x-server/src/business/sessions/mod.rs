@@ -0,0 +1,89 @@
+//! Sessions
+//!
+//! logging in starts a session, it holds a rotating refresh token and the `jti` of the access
+//! token issued last. revoking a session puts that `jti` on the revocation list, which
+//! `jwt_auth_middleware` checks on every request
+
+use migration::async_trait;
+use sea_orm::{DbErr, prelude::Uuid};
+use thiserror::Error;
+
+use crate::authentication::AccessTokenMeta;
+
+pub mod gen_session_repo;
+
+#[cfg(test)]
+mod test;
+
+pub type SessionResult<T> = Result<T, SessionError>;
+
+#[async_trait::async_trait]
+pub trait SessionRepository {
+    /// start a session for an administrator, `access` is the first access token of it
+    async fn create(
+        &self,
+        _account_id: Uuid,
+        _access: &AccessTokenMeta,
+    ) -> SessionResult<IssuedSession> {
+        unimplemented!()
+    }
+
+    /// exchange a refresh token for a new one, the access token issued before is revoked.
+    ///
+    /// presenting a refresh token that has been exchanged already revokes the whole session
+    async fn refresh(
+        &self,
+        _refresh_token: &str,
+        _access: &AccessTokenMeta,
+    ) -> SessionResult<IssuedSession> {
+        unimplemented!()
+    }
+
+    async fn revoke(&self, _session_id: Uuid) -> SessionResult<()> {
+        unimplemented!()
+    }
+
+    /// revoke every session of the account, returns how many were revoked
+    async fn revoke_all(&self, _account_id: Uuid) -> SessionResult<u64> {
+        unimplemented!()
+    }
+
+    /// whether an access token is on the revocation list
+    async fn is_revoked(&self, _token_id: &str) -> SessionResult<bool> {
+        unimplemented!()
+    }
+
+    /// remove the sessions and revoked tokens nobody can use anymore
+    async fn purge_expired(&self) -> SessionResult<u64> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicSessionRepository;
+
+impl SessionRepository for PanicSessionRepository {}
+
+#[derive(Debug)]
+pub struct IssuedSession {
+    pub session_id: Uuid,
+    pub account_id: Uuid,
+    pub username: String,
+    /// role of the account when the session was issued or refreshed
+    pub role: String,
+    /// only returned once, the database keeps its hash
+    pub refresh_token: String,
+}
+
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error("session not found")]
+    NotFound,
+    #[error("account not found")]
+    AccountNotFound,
+    #[error("invalid or expired refresh token")]
+    InvalidRefreshToken,
+    #[error("refresh token has been used already, the session is revoked")]
+    RefreshTokenReused,
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::EntityTrait;
+
+    use crate::{
+        authentication::AccessTokenMeta,
+        business::sessions::{SessionError, SessionRepository, gen_session_repo::GenSessionRepo},
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    const ACCESS_LIFETIME: Duration = Duration::from_secs(60);
+
+    async fn admin_id(db: &TestDB) -> sea_orm::prelude::Uuid {
+        let db_conn = db.get_connection().await.unwrap();
+        db_entity::accounts::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn refresh_rotates_token_success() {
+        let db = TestDB::new().await;
+        let account_id = admin_id(&db).await;
+        let repo = GenSessionRepo::new(db);
+
+        let first_access = AccessTokenMeta::with_lifetime(ACCESS_LIFETIME);
+        let session = repo.create(account_id, &first_access).await.unwrap();
+
+        let second_access = AccessTokenMeta::with_lifetime(ACCESS_LIFETIME);
+        let refreshed = repo
+            .refresh(&session.refresh_token, &second_access)
+            .await
+            .unwrap();
+
+        assert_eq!(refreshed.session_id, session.session_id);
+        assert_eq!(refreshed.account_id, account_id);
+        assert_ne!(refreshed.refresh_token, session.refresh_token);
+        assert!(repo.is_revoked(&first_access.token_id).await.unwrap());
+        assert!(!repo.is_revoked(&second_access.token_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn refresh_reused_token_revokes_session() {
+        let db = TestDB::new().await;
+        let account_id = admin_id(&db).await;
+        let repo = GenSessionRepo::new(db);
+
+        let session = repo
+            .create(account_id, &AccessTokenMeta::with_lifetime(ACCESS_LIFETIME))
+            .await
+            .unwrap();
+        let second_access = AccessTokenMeta::with_lifetime(ACCESS_LIFETIME);
+        let refreshed = repo
+            .refresh(&session.refresh_token, &second_access)
+            .await
+            .unwrap();
+
+        let res = repo
+            .refresh(
+                &session.refresh_token,
+                &AccessTokenMeta::with_lifetime(ACCESS_LIFETIME),
+            )
+            .await;
+        assert!(matches!(res, Err(SessionError::RefreshTokenReused)));
+        assert!(repo.is_revoked(&second_access.token_id).await.unwrap());
+
+        let res = repo
+            .refresh(
+                &refreshed.refresh_token,
+                &AccessTokenMeta::with_lifetime(ACCESS_LIFETIME),
+            )
+            .await;
+        assert!(matches!(res, Err(SessionError::InvalidRefreshToken)));
+    }
+
+    #[tokio::test]
+    async fn revoke_all_success() {
+        const EXPECTED_REVOKED: u64 = 2;
+
+        let db = TestDB::new().await;
+        let account_id = admin_id(&db).await;
+        let repo = GenSessionRepo::new(db);
+
+        let first_access = AccessTokenMeta::with_lifetime(ACCESS_LIFETIME);
+        let second_access = AccessTokenMeta::with_lifetime(ACCESS_LIFETIME);
+        let first = repo.create(account_id, &first_access).await.unwrap();
+        repo.create(account_id, &second_access).await.unwrap();
+
+        let count = repo.revoke_all(account_id).await.unwrap();
+
+        assert_eq!(count, EXPECTED_REVOKED);
+        assert!(repo.is_revoked(&first_access.token_id).await.unwrap());
+        assert!(repo.is_revoked(&second_access.token_id).await.unwrap());
+
+        let res = repo
+            .refresh(
+                &first.refresh_token,
+                &AccessTokenMeta::with_lifetime(ACCESS_LIFETIME),
+            )
+            .await;
+        assert!(matches!(res, Err(SessionError::InvalidRefreshToken)));
+    }
+
+    #[tokio::test]
+    async fn purge_expired_success() {
+        let db = TestDB::new().await;
+        let account_id = admin_id(&db).await;
+        let repo = GenSessionRepo::with_lifetime(db, Duration::ZERO);
+
+        let access = AccessTokenMeta::with_lifetime(Duration::ZERO);
+        let session = repo.create(account_id, &access).await.unwrap();
+        repo.revoke(session.session_id).await.unwrap();
+
+        let count = repo.purge_expired().await.unwrap();
+
+        assert_eq!(count, 2);
+        assert!(!repo.is_revoked(&access.token_id).await.unwrap());
+    }
+}
@@ -0,0 +1,66 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use db_entity::meme_urls::Bed;
+use migration::async_trait;
+use tokio::fs;
+
+use super::{Storage, StorageError, StorageResult, check_key};
+
+/// files in a directory of the server, served by `ServeDir` under `base_url`
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    fn bed(&self) -> Bed {
+        Bed::Local
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+
+    async fn get(&self, key: &str) -> StorageResult<Vec<u8>> {
+        check_key(key)?;
+
+        match fs::read(self.root.join(key)).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(StorageError::NotFound(key.to_owned()))
+            }
+            res => Ok(res?),
+        }
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> StorageResult<()> {
+        check_key(key)?;
+
+        fs::create_dir_all(&self.root).await?;
+
+        // written aside and renamed, a file being served is never half written
+        let part = self.root.join(format!(".{}.part", key));
+        fs::write(&part, bytes).await?;
+        fs::rename(&part, self.root.join(key)).await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        check_key(key)?;
+
+        match fs::remove_file(self.root.join(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Media storage
+//!
+//! the bytes of the uploaded memes are kept by a `Storage`, either the media directory of the
+//! server or an S3-compatible bucket, and recorded in `meme_urls` by their bed and key
+
+use db_entity::meme_urls::Bed;
+use migration::async_trait;
+use thiserror::Error;
+
+pub mod local;
+pub mod s3;
+
+#[cfg(test)]
+mod test;
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+#[async_trait::async_trait]
+pub trait Storage {
+    /// the bed recorded in `meme_urls.bed` for the files stored here
+    fn bed(&self) -> Bed;
+
+    /// where the file of `key` is downloaded from
+    fn url(&self, key: &str) -> String;
+
+    /// the bytes stored under `key`
+    async fn get(&self, key: &str) -> StorageResult<Vec<u8>>;
+
+    /// store `bytes` under `key`, the file already stored under it is replaced
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()>;
+
+    /// delete the file of `key`, a missing file is not an error
+    async fn delete(&self, key: &str) -> StorageResult<()>;
+}
+
+#[async_trait::async_trait]
+impl<S: Storage + ?Sized + Sync + Send> Storage for Box<S> {
+    fn bed(&self) -> Bed {
+        (**self).bed()
+    }
+
+    fn url(&self, key: &str) -> String {
+        (**self).url(key)
+    }
+
+    async fn get(&self, key: &str) -> StorageResult<Vec<u8>> {
+        (**self).get(key).await
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        (**self).put(key, bytes, content_type).await
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        (**self).delete(key).await
+    }
+}
+
+/// a file recorded by its bed and key, the one of `Storage::bed` is in the storage
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredFile {
+    pub bed: Bed,
+    pub key: String,
+}
+
+/// a key is a single file name, so that it can not escape the directory or the bucket
+pub fn check_key(key: &str) -> StorageResult<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_owned()))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+    #[error("no file stored under {0}")]
+    NotFound(String),
+    #[error("wrong storage config: {0}")]
+    Config(String),
+    #[error("storage rejected the request with {0}: {1}")]
+    Rejected(u16, String),
+    #[error("storage request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("storage io error: {0}")]
+    Io(#[from] std::io::Error),
+}
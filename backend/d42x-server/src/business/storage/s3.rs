@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use db_entity::meme_urls::Bed;
+use hmac::{Hmac, Mac};
+use migration::async_trait;
+use reqwest::{Client, Method, Response, StatusCode, Url, header::AUTHORIZATION};
+use sha2::{Digest, Sha256};
+
+use super::{Storage, StorageError, StorageResult, check_key};
+
+/// an unreachable endpoint fails fast
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// for a whole request, a large file included
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// e.g. `http://localhost:9000` for a local MinIO, the bucket is addressed by path
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// where the objects are downloaded from, `endpoint/bucket` when empty
+    pub public_url: String,
+}
+
+/// objects in an S3-compatible bucket, the requests are signed with AWS Signature Version 4
+pub struct S3Storage {
+    config: S3Config,
+    client: Client,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config) -> Self {
+        let client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            // as `Client::new`, only when the TLS backend cannot be initialized
+            .expect("the S3 client cannot be built");
+
+        Self { config, client }
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.config.endpoint.trim_end_matches('/'),
+            self.config.bucket,
+            key
+        )
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> StorageResult<Response> {
+        check_key(key)?;
+
+        let url = Url::parse(&self.object_url(key))
+            .map_err(|e| StorageError::Config(format!("{}: {}", self.config.endpoint, e)))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => return Err(StorageError::Config(self.config.endpoint.clone())),
+        };
+
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(content_type) = content_type {
+            headers.push(("content-type", content_type.to_owned()));
+        }
+        headers.sort();
+
+        let authorization = authorization(
+            &self.config,
+            method.as_str(),
+            url.path(),
+            &headers,
+            &payload_hash,
+            &amz_date,
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(AUTHORIZATION, authorization)
+            .body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound(key.to_owned()));
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(StorageError::Rejected(status.as_u16(), text));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for S3Storage {
+    fn bed(&self) -> Bed {
+        Bed::S3
+    }
+
+    fn url(&self, key: &str) -> String {
+        if self.config.public_url.is_empty() {
+            self.object_url(key)
+        } else {
+            format!("{}/{}", self.config.public_url.trim_end_matches('/'), key)
+        }
+    }
+
+    async fn get(&self, key: &str) -> StorageResult<Vec<u8>> {
+        let response = self.send(Method::GET, key, vec![], None).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        self.send(Method::PUT, key, bytes, Some(content_type))
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        match self.send(Method::DELETE, key, vec![], None).await {
+            Err(StorageError::NotFound(_)) => Ok(()),
+            res => res.map(|_| ()),
+        }
+    }
+}
+
+/// the `Authorization` header of a request without query string, `headers` are lowercase
+/// and sorted by name
+pub(super) fn authorization(
+    config: &S3Config,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    let date = &amz_date[..8];
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method, path, canonical_headers, signed_headers, payload_hash
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date, config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = hmac_sha256(
+        format!("AWS4{}", config.secret_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [config.region.as_str(), "s3", "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        config.access_key, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac takes a key of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
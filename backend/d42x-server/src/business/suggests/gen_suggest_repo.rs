@@ -1,9 +1,15 @@
 use db_entity::suggests;
 use migration::async_trait;
-use sea_orm::{Condition, QueryOrder, Set, prelude::*};
+use sea_orm::{Condition, DatabaseTransaction, QueryOrder, Set, TransactionTrait, prelude::*};
 
 use crate::{
-    business::{Pagination, meme::MemeUrl, suggests::SuggestError},
+    business::{
+        Pagination,
+        category::{CategoryRepository, meme_categories::get_meme_categories},
+        meme::MemeUrl,
+        suggests::SuggestError,
+        thumbnails::meme_thumbnails::get_thumbnails,
+    },
     db::DbConnHelper,
 };
 
@@ -45,11 +51,16 @@ where
     ) -> SuggestResult<()> {
         let db = self.db.get_connection().await?;
 
-        let meme = db_entity::memes::Entity::find_by_id(meme_id)
+        db_entity::memes::Entity::find_by_id(meme_id)
             .one(&db)
             .await?
             .ok_or(SuggestError::CreateFail("cannot find related meme"))?;
 
+        let before = get_meme_categories(&db, vec![meme_id])
+            .await?
+            .remove(&meme_id)
+            .unwrap_or_default();
+
         let account = db_entity::accounts::Entity::find_by_id(apply_user_id)
             .one(&db)
             .await?
@@ -57,7 +68,7 @@ where
 
         db_entity::suggests::ActiveModel {
             meme_id: Set(meme_id),
-            before: Set(meme.categories),
+            before: Set(format!(";{};", before.join(";"))),
             after: Set(format!(";{};", list.join(";"))),
             status: Set(suggests::Status::Wait),
             account_id: Set(account.id),
@@ -65,14 +76,16 @@ where
             ..suggests::ActiveModel::new()
         }
         .insert(&db)
-        .await
-        .expect("create suggests failed");
+        .await?;
 
         Ok(())
     }
 
-    async fn get_paginated_suggests(&self, filter: GetFilter) -> Pagination<Suggestion> {
-        let db = self.db.get_connection().await.unwrap();
+    async fn get_paginated_suggests(
+        &self,
+        filter: GetFilter,
+    ) -> SuggestResult<Pagination<Suggestion>> {
+        let db = self.db.get_connection().await?;
 
         let mut condition = Condition::all();
 
@@ -87,37 +100,43 @@ where
             .order_by_asc(suggests::Column::CreatedDateTime)
             .paginate(&db, self.page_size);
 
-        let suggest_list = page_query
-            .fetch_page(fetch_page)
-            .await
-            .expect("get_paginated_suggest failed");
+        let suggest_list = page_query.fetch_page(fetch_page).await?;
 
-        let total = page_query.num_pages().await.unwrap();
+        let total = page_query.num_pages().await?;
 
         let memes = db_entity::memes::Entity::find()
             .find_with_related(db_entity::meme_urls::Entity)
             .filter(db_entity::memes::Column::Id.is_in(suggest_list.iter().map(|t| t.meme_id)))
             .all(&db)
-            .await
-            .expect("query memes failed");
+            .await?;
+
+        let meme_categories =
+            get_meme_categories(&db, memes.iter().map(|item| item.0.id).collect()).await?;
+
+        let thumbnails = get_thumbnails(
+            &db,
+            memes
+                .iter()
+                .flat_map(|item| item.1.iter().map(|url| url.id))
+                .collect(),
+        )
+        .await?;
 
         let apply_users = db_entity::accounts::Entity::find()
             .filter(
                 db_entity::accounts::Column::Id.is_in(suggest_list.iter().map(|t| t.account_id)),
             )
             .all(&db)
-            .await
-            .expect("query apply_users failed");
+            .await?;
 
         let operate_users = db_entity::accounts::Entity::find()
             .filter(
                 db_entity::accounts::Column::Id.is_in(suggest_list.iter().map(|t| t.operator_id)),
             )
             .all(&db)
-            .await
-            .expect("query operate_users failed");
+            .await?;
 
-        let suggest_model_2_suggestion = |suggest: suggests::Model| -> Suggestion {
+        let suggest_model_2_suggestion = |suggest: suggests::Model| -> SuggestResult<Suggestion> {
             let apply_username = apply_users
                 .iter()
                 .find_map(|item| {
@@ -127,8 +146,7 @@ where
                         None
                     }
                 })
-                .or(Some(String::new()))
-                .unwrap();
+                .unwrap_or_default();
 
             let operator_username = operate_users
                 .iter()
@@ -139,50 +157,29 @@ where
                         None
                     }
                 })
-                .or(Some(String::new()))
-                .unwrap();
+                .unwrap_or_default();
 
-            let cur_category = memes
-                .iter()
-                .find_map(|item| {
-                    if item.0.id == suggest.meme_id {
-                        Some(
-                            item.0
-                                .categories
-                                .split(';')
-                                .map(|t| t.to_string())
-                                .collect(),
-                        )
-                    } else {
-                        None
-                    }
-                })
-                .or(Some(vec![]))
-                .unwrap();
+            let cur_category = meme_categories
+                .get(&suggest.meme_id)
+                .cloned()
+                .unwrap_or_default();
 
             let meme_urls = memes
                 .iter()
-                .find_map(|item| {
-                    if item.0.id == suggest.meme_id {
-                        let meme_urls: Vec<_> = item
-                            .1
-                            .iter()
-                            .map(|url| MemeUrl {
-                                id: url.id,
-                                url: url.url.clone(),
-                                cover: url.cover.clone(),
-                                format: url.format.as_str().try_into().unwrap(),
-                                sort: url.sort,
-                            })
-                            .collect();
-                        Some(meme_urls)
-                    } else {
-                        None
-                    }
+                .find(|item| item.0.id == suggest.meme_id)
+                .map(|item| {
+                    item.1
+                        .iter()
+                        .cloned()
+                        .map(|url| {
+                            MemeUrl::try_from(url).map(|url| url.with_thumbnails(&thumbnails))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
                 })
-                .unwrap();
+                .transpose()?
+                .unwrap_or_default();
 
-            Suggestion {
+            Ok(Suggestion {
                 id: suggest.id,
                 meme_id: suggest.meme_id,
                 before_category: suggest.before.split(';').map(|t| t.to_string()).collect(),
@@ -194,20 +191,20 @@ where
                 operator_username,
                 meme_urls,
                 cur_category,
-            }
+            })
         };
 
-        let list: Vec<_> = suggest_list
+        let list = suggest_list
             .into_iter()
             .map(suggest_model_2_suggestion)
-            .collect();
+            .collect::<SuggestResult<Vec<_>>>()?;
 
-        Pagination {
+        Ok(Pagination {
             page: filter.page,
             total,
             size: self.page_size,
             list,
-        }
+        })
     }
 
     async fn set_suggest_status(
@@ -215,19 +212,85 @@ where
         id: Uuid,
         status: db_entity::suggests::Status,
         operator_id: Uuid,
-    ) {
-        let db = self.db.get_connection().await.unwrap();
-
-        let model = suggests::Entity::find_by_id(id).one(&db).await.unwrap();
-        if model.is_none() {
-            return;
-        }
+    ) -> SuggestResult<()> {
+        let db = self.db.get_connection().await?;
 
-        let mut model: suggests::ActiveModel = model.unwrap().into();
+        let mut model: suggests::ActiveModel = suggests::Entity::find_by_id(id)
+            .one(&db)
+            .await?
+            .ok_or(SuggestError::NotFound)?
+            .into();
 
         model.status = Set(status);
         model.operator_id = Set(operator_id);
 
-        model.update(&db).await.unwrap();
+        model.update(&db).await?;
+
+        Ok(())
+    }
+
+    async fn approve(
+        &self,
+        id: Uuid,
+        operator_id: Uuid,
+        category_repo: &(dyn CategoryRepository + Sync + Send),
+    ) -> SuggestResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let suggest = find_waiting_suggest(&txn, id).await?;
+
+        let after: Vec<_> = suggest
+            .after
+            .split(';')
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_string())
+            .collect();
+
+        category_repo
+            .update_catgories_in_txn(&txn, suggest.meme_id, after)
+            .await?;
+
+        let mut model: suggests::ActiveModel = suggest.into();
+        model.status = Set(suggests::Status::Approved);
+        model.operator_id = Set(operator_id);
+        model.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    async fn refuse(&self, id: Uuid, operator_id: Uuid) -> SuggestResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        let suggest = find_waiting_suggest(&txn, id).await?;
+
+        let mut model: suggests::ActiveModel = suggest.into();
+        model.status = Set(suggests::Status::Refused);
+        model.operator_id = Set(operator_id);
+        model.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
     }
 }
+
+/// find a suggest that is still waiting for review
+async fn find_waiting_suggest(
+    txn: &DatabaseTransaction,
+    id: Uuid,
+) -> SuggestResult<suggests::Model> {
+    let suggest = suggests::Entity::find_by_id(id)
+        .one(txn)
+        .await?
+        .ok_or(SuggestError::NotFound)?;
+
+    if suggest.status != suggests::Status::Wait {
+        return Err(SuggestError::AlreadyResolved);
+    }
+
+    Ok(suggest)
+}
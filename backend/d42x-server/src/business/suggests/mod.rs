@@ -4,10 +4,17 @@ use sea_orm::{DbErr, prelude::Uuid};
 use serde::Serialize;
 use thiserror::Error;
 
-use super::{Pagination, meme::MemeUrl};
+use super::{
+    Pagination,
+    category::{CategoryError, CategoryRepository},
+    meme::{MemeError, MemeUrl},
+};
 
 pub mod gen_suggest_repo;
 
+#[cfg(test)]
+mod test;
+
 pub type SuggestResult<T> = Result<T, SuggestError>;
 
 #[async_trait::async_trait]
@@ -21,7 +28,10 @@ pub trait SuggestRepository {
         unimplemented!()
     }
 
-    async fn get_paginated_suggests(&self, _filter: GetFilter) -> Pagination<Suggestion> {
+    async fn get_paginated_suggests(
+        &self,
+        _filter: GetFilter,
+    ) -> SuggestResult<Pagination<Suggestion>> {
         unimplemented!()
     }
 
@@ -30,7 +40,23 @@ pub trait SuggestRepository {
         _id: Uuid,
         _status: db_entity::suggests::Status,
         _operator_id: Uuid,
-    ) {
+    ) -> SuggestResult<()> {
+        unimplemented!()
+    }
+
+    /// approve a waiting suggestion, the `after` categories are applied to the meme
+    /// in the same transaction
+    async fn approve(
+        &self,
+        _id: Uuid,
+        _operator_id: Uuid,
+        _category_repo: &(dyn CategoryRepository + Sync + Send),
+    ) -> SuggestResult<()> {
+        unimplemented!()
+    }
+
+    /// refuse a waiting suggestion, the meme is left untouched
+    async fn refuse(&self, _id: Uuid, _operator_id: Uuid) -> SuggestResult<()> {
         unimplemented!()
     }
 }
@@ -41,8 +67,9 @@ impl SuggestRepository for PanicSuggestRepository {}
 
 #[derive(Serialize, Debug)]
 pub struct GetFilter {
-    page: u64,
-    status: Option<db_entity::suggests::Status>,
+    /// page number, base 1
+    pub page: u64,
+    pub status: Option<db_entity::suggests::Status>,
 }
 
 #[derive(Serialize, Debug)]
@@ -66,4 +93,12 @@ pub enum SuggestError {
     DatabaseErr(#[from] DbErr),
     #[error("create suggest failed: {0}")]
     CreateFail(&'static str),
+    #[error("suggest not found")]
+    NotFound,
+    #[error("suggest has already been resolved")]
+    AlreadyResolved,
+    #[error(transparent)]
+    CategoryErr(#[from] CategoryError),
+    #[error(transparent)]
+    MemeErr(#[from] MemeError),
 }
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            category::{gen_cate_repo::GenCategoryRepo, meme_categories::get_meme_categories},
+            suggests::{
+                GetFilter, SuggestError, SuggestRepository, gen_suggest_repo::GenSuggestRepo,
+            },
+        },
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    async fn create_suggest(db: &TestDB, list: Vec<String>) -> db_entity::suggests::Model {
+        let db_conn = db.get_connection().await.unwrap();
+        let meme = db_entity::memes::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        let account = db_entity::accounts::Entity::find()
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let repo = GenSuggestRepo::new(db.clone());
+        repo.create(meme.id, list, account.id).await.unwrap();
+
+        db_entity::suggests::Entity::find()
+            .filter(db_entity::suggests::Column::MemeId.eq(meme.id))
+            .filter(db_entity::suggests::Column::Status.eq(db_entity::suggests::Status::Wait))
+            .filter(db_entity::suggests::Column::OperatorId.eq(sea_orm::prelude::Uuid::nil()))
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_paginated_suggests_filter_status_success() {
+        const EXPECTED_LIST_LENGTH: usize = 1;
+
+        let db = TestDB::new().await;
+        let repo = GenSuggestRepo::new(db);
+
+        let list = repo
+            .get_paginated_suggests(GetFilter {
+                page: 1,
+                status: Some(db_entity::suggests::Status::Wait),
+            })
+            .await
+            .unwrap();
+        assert_eq!(list.list.len(), EXPECTED_LIST_LENGTH);
+
+        let list = repo
+            .get_paginated_suggests(GetFilter {
+                page: 1,
+                status: Some(db_entity::suggests::Status::Approved),
+            })
+            .await
+            .unwrap();
+        assert!(list.list.is_empty());
+    }
+
+    #[tokio::test]
+    async fn approve_success() {
+        const EXPECTED_CATEGORIES: [&str; 1] = ["cat"];
+
+        let db = TestDB::new().await;
+        let suggest = create_suggest(&db, vec!["cat".to_owned()]).await;
+        let operator_id = suggest.account_id;
+
+        let repo = GenSuggestRepo::new(db.clone());
+        let cate_repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+
+        repo.approve(suggest.id, operator_id, &cate_repo)
+            .await
+            .unwrap();
+
+        let db_conn = db.get_connection().await.unwrap();
+        let suggest = db_entity::suggests::Entity::find_by_id(suggest.id)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(suggest.status, db_entity::suggests::Status::Approved);
+        assert_eq!(suggest.operator_id, operator_id);
+
+        let categories = get_meme_categories(&db_conn, vec![suggest.meme_id])
+            .await
+            .unwrap()
+            .remove(&suggest.meme_id)
+            .unwrap();
+        assert_eq!(categories, EXPECTED_CATEGORIES);
+
+        let category = db_entity::categories::Entity::find()
+            .filter(db_entity::categories::Column::Name.eq("cat"))
+            .one(&db_conn)
+            .await
+            .unwrap();
+        assert!(category.is_some());
+    }
+
+    #[tokio::test]
+    async fn refuse_success() {
+        let db = TestDB::new().await;
+        let suggest = create_suggest(&db, vec!["cat".to_owned()]).await;
+
+        let repo = GenSuggestRepo::new(db.clone());
+        repo.refuse(suggest.id, suggest.account_id).await.unwrap();
+
+        let db_conn = db.get_connection().await.unwrap();
+        let refused = db_entity::suggests::Entity::find_by_id(suggest.id)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refused.status, db_entity::suggests::Status::Refused);
+
+        let categories = get_meme_categories(&db_conn, vec![suggest.meme_id])
+            .await
+            .unwrap()
+            .remove(&suggest.meme_id)
+            .unwrap();
+        assert_eq!(format!(";{};", categories.join(";")), suggest.before);
+    }
+
+    #[tokio::test]
+    async fn approve_resolved_fail() {
+        let db = TestDB::new().await;
+        let suggest = create_suggest(&db, vec!["cat".to_owned()]).await;
+
+        let repo = GenSuggestRepo::new(db.clone());
+        let cate_repo: GenCategoryRepo<MockCache<_, _>, TestDB> = GenCategoryRepo::new(db.clone());
+
+        repo.refuse(suggest.id, suggest.account_id).await.unwrap();
+        let res = repo
+            .approve(suggest.id, suggest.account_id, &cate_repo)
+            .await;
+
+        assert!(matches!(res, Err(SuggestError::AlreadyResolved)));
+    }
+}
@@ -0,0 +1,366 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::login_attempts;
+use db_entity::login_throttles::{self, Kind};
+use migration::async_trait;
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait,
+    DatabaseTransaction, DbErr, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QueryTrait,
+    Set, TransactionTrait,
+    sea_query::{Expr, OnConflict},
+};
+
+use crate::db::DbConnHelper;
+
+use super::{LoginThrottle, ThrottleError, ThrottlePolicy, ThrottleRepository, ThrottleResult};
+
+const ATTEMPT_RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+const IDLE_THROTTLE_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_IP_LOCKOUT_THRESHOLD: u32 = 50;
+const DEFAULT_VISITOR_FREE_TOKENS: u32 = 20;
+const DEFAULT_VISITOR_LOCKOUT_THRESHOLD: u32 = 200;
+
+pub struct GenThrottleRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    db: TDb,
+    username_policy: ThrottlePolicy,
+    ip_policy: ThrottlePolicy,
+    visitor_policy: ThrottlePolicy,
+}
+
+impl<TDb> GenThrottleRepo<TDb>
+where
+    TDb: DbConnHelper,
+{
+    pub fn new(db: TDb) -> Self {
+        let username_policy = ThrottlePolicy::default();
+        let ip_policy = ThrottlePolicy {
+            lockout_threshold: DEFAULT_IP_LOCKOUT_THRESHOLD,
+            ..username_policy.clone()
+        };
+
+        Self::with_policy(db, username_policy, ip_policy)
+    }
+
+    /// an ip is shared by several users behind a NAT, it usually deserves a higher threshold
+    pub fn with_policy(
+        db: TDb,
+        username_policy: ThrottlePolicy,
+        ip_policy: ThrottlePolicy,
+    ) -> Self {
+        Self {
+            db,
+            username_policy,
+            ip_policy,
+            visitor_policy: ThrottlePolicy {
+                free_attempts: DEFAULT_VISITOR_FREE_TOKENS,
+                lockout_threshold: DEFAULT_VISITOR_LOCKOUT_THRESHOLD,
+                lockout_duration: Duration::from_secs(60 * 60),
+                ..ThrottlePolicy::default()
+            },
+        }
+    }
+
+    /// every visitor token issued to an ip counts like a failed log in
+    pub fn with_visitor_policy(mut self, visitor_policy: ThrottlePolicy) -> Self {
+        self.visitor_policy = visitor_policy;
+        self
+    }
+
+    fn policy(&self, kind: Kind) -> &ThrottlePolicy {
+        match kind {
+            Kind::Username => &self.username_policy,
+            Kind::Ip => &self.ip_policy,
+            Kind::Visitor => &self.visitor_policy,
+        }
+    }
+}
+
+fn is_locked(model: &login_throttles::Model, now: DateTime<Utc>) -> bool {
+    model
+        .locked_until_date_time
+        .is_some_and(|until| until > now)
+}
+
+fn remaining(until: DateTime<FixedOffset>, now: DateTime<Utc>) -> Duration {
+    (until.to_utc() - now).to_std().unwrap_or_default()
+}
+
+async fn find_throttle(
+    txn: &DatabaseTransaction,
+    kind: Kind,
+    key: &str,
+) -> ThrottleResult<Option<login_throttles::Model>> {
+    Ok(login_throttles::Entity::find_by_id((kind, key.to_owned()))
+        .one(txn)
+        .await?)
+}
+
+async fn record_attempt(
+    txn: &DatabaseTransaction,
+    username: &str,
+    ip: &str,
+    success: bool,
+) -> ThrottleResult<()> {
+    login_attempts::ActiveModel {
+        username: Set(username.to_owned()),
+        ip_address: Set(ip.to_owned()),
+        success: Set(success),
+        ..login_attempts::ActiveModel::new()
+    }
+    .insert(txn)
+    .await?;
+
+    Ok(())
+}
+
+impl<TDb> GenThrottleRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    /// count one failure of the key in a single statement, so that concurrent failures
+    /// are all counted, and lock the key once the returned count reaches the threshold
+    async fn increase_failure(
+        &self,
+        txn: &DatabaseTransaction,
+        kind: Kind,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> ThrottleResult<()> {
+        let policy = self.policy(kind);
+        let now: DateTime<FixedOffset> = now.into();
+
+        let failure_count = Expr::col((
+            login_throttles::Entity,
+            login_throttles::Column::FailureCount,
+        ));
+        let locked_until = Expr::col((
+            login_throttles::Entity,
+            login_throttles::Column::LockedUntilDateTime,
+        ));
+        // a lock that has expired starts over
+        let expired = Condition::all()
+            .add(locked_until.clone().is_not_null())
+            .add(locked_until.clone().lte(now));
+
+        let mut insert = login_throttles::Entity::insert(login_throttles::ActiveModel {
+            kind: Set(kind),
+            key: Set(key.to_owned()),
+            failure_count: Set(1),
+            last_failure_date_time: Set(now),
+            locked_until_date_time: Set(None),
+        })
+        .on_conflict(
+            OnConflict::columns([login_throttles::Column::Kind, login_throttles::Column::Key])
+                .value(
+                    login_throttles::Column::FailureCount,
+                    Expr::case(expired.clone(), 1).finally(failure_count.add(1)),
+                )
+                .value(login_throttles::Column::LastFailureDateTime, now)
+                .value(
+                    login_throttles::Column::LockedUntilDateTime,
+                    Expr::case(expired, Option::<DateTime<FixedOffset>>::None)
+                        .finally(locked_until),
+                )
+                .to_owned(),
+        )
+        .into_query();
+        insert.returning_all();
+
+        let backend = txn.get_database_backend();
+        let model = txn
+            .query_one(backend.build(&insert))
+            .await?
+            .ok_or(DbErr::RecordNotInserted)?;
+        let model = login_throttles::Model::from_query_result(&model, "")?;
+
+        if model.locked_until_date_time.is_none()
+            && model.failure_count as u32 >= policy.lockout_threshold
+        {
+            login_throttles::Entity::update_many()
+                .col_expr(
+                    login_throttles::Column::LockedUntilDateTime,
+                    Expr::value(now + policy.lockout_duration),
+                )
+                .filter(login_throttles::Column::Kind.eq(kind))
+                .filter(login_throttles::Column::Key.eq(key))
+                .filter(login_throttles::Column::LockedUntilDateTime.is_null())
+                .exec(txn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb> ThrottleRepository for GenThrottleRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn check(&self, username: &str, ip: &str) -> ThrottleResult<()> {
+        let db = self.db.get_connection().await?;
+        let now = Utc::now();
+
+        let list = login_throttles::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(login_throttles::Column::Kind.eq(Kind::Username))
+                            .add(login_throttles::Column::Key.eq(username)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(login_throttles::Column::Kind.eq(Kind::Ip))
+                            .add(login_throttles::Column::Key.eq(ip)),
+                    ),
+            )
+            .all(&db)
+            .await?;
+
+        let mut locked = Duration::ZERO;
+        let mut backoff = Duration::ZERO;
+        for model in list {
+            if let Some(until) = model.locked_until_date_time {
+                if until > now {
+                    locked = locked.max(remaining(until, now));
+                }
+                // the lock has expired, the counter starts over on the next failure
+                continue;
+            }
+
+            let wait = self.policy(model.kind).backoff(model.failure_count as u32);
+            let retry_at = model.last_failure_date_time + wait;
+            backoff = backoff.max(remaining(retry_at, now));
+        }
+
+        if !locked.is_zero() {
+            return Err(ThrottleError::Locked(locked));
+        }
+        if !backoff.is_zero() {
+            return Err(ThrottleError::TooManyAttempts(backoff));
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(&self, username: &str, ip: &str) -> ThrottleResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+        let now = Utc::now();
+
+        self.increase_failure(&txn, Kind::Username, username, now)
+            .await?;
+        self.increase_failure(&txn, Kind::Ip, ip, now).await?;
+        record_attempt(&txn, username, ip, false).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn record_success(&self, username: &str, ip: &str) -> ThrottleResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+
+        login_throttles::Entity::delete_by_id((Kind::Username, username.to_owned()))
+            .exec(&txn)
+            .await?;
+        record_attempt(&txn, username, ip, true).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn issue_visitor(&self, ip: &str) -> ThrottleResult<()> {
+        let db = self.db.get_connection().await?;
+        let txn = db.begin().await?;
+        let now = Utc::now();
+
+        if let Some(model) = find_throttle(&txn, Kind::Visitor, ip).await? {
+            if let Some(until) = model.locked_until_date_time
+                && until > now
+            {
+                return Err(ThrottleError::Locked(remaining(until, now)));
+            }
+
+            if model.locked_until_date_time.is_none() {
+                let wait = self.visitor_policy.backoff(model.failure_count as u32);
+                let retry_at = model.last_failure_date_time + wait;
+                if retry_at > now {
+                    return Err(ThrottleError::TooManyAttempts(remaining(retry_at, now)));
+                }
+            }
+        }
+
+        self.increase_failure(&txn, Kind::Visitor, ip, now).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn get_throttles(&self, only_locked: bool) -> ThrottleResult<Vec<LoginThrottle>> {
+        let db = self.db.get_connection().await?;
+        let now = Utc::now();
+
+        let mut query = login_throttles::Entity::find();
+        if only_locked {
+            query = query.filter(login_throttles::Column::LockedUntilDateTime.gt(now));
+        }
+
+        let list = query
+            .order_by_desc(login_throttles::Column::LastFailureDateTime)
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(|model| LoginThrottle {
+                locked: is_locked(&model, now),
+                kind: model.kind,
+                key: model.key,
+                failure_count: model.failure_count,
+                last_failure_date_time: model.last_failure_date_time,
+                locked_until_date_time: model.locked_until_date_time,
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    async fn clear(&self, kind: Kind, key: &str) -> ThrottleResult<()> {
+        let db = self.db.get_connection().await?;
+
+        let res = login_throttles::Entity::delete_by_id((kind, key.to_owned()))
+            .exec(&db)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(ThrottleError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> ThrottleResult<u64> {
+        let db = self.db.get_connection().await?;
+        let now = Utc::now();
+
+        let throttles = login_throttles::Entity::delete_many()
+            .filter(login_throttles::Column::LastFailureDateTime.lt(now - IDLE_THROTTLE_RETENTION))
+            .filter(
+                Condition::any()
+                    .add(login_throttles::Column::LockedUntilDateTime.is_null())
+                    .add(login_throttles::Column::LockedUntilDateTime.lt(now)),
+            )
+            .exec(&db)
+            .await?;
+
+        let attempts = login_attempts::Entity::delete_many()
+            .filter(login_attempts::Column::CreatedDateTime.lt(now - ATTEMPT_RETENTION))
+            .exec(&db)
+            .await?;
+
+        Ok(throttles.rows_affected + attempts.rows_affected)
+    }
+}
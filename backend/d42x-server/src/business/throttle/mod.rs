@@ -0,0 +1,121 @@
+//! Log in throttling
+//!
+//! failed log in attempts are counted per username and per client ip.
+//! after `free_attempts` failures every attempt has to wait for an exponential back-off,
+//! after `lockout_threshold` failures the key is locked for `lockout_duration`,
+//! until it expires or an administrator clears it.
+//! the visitor tokens issued to a client ip are counted the same way
+
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use db_entity::login_throttles::Kind;
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::Serialize;
+use thiserror::Error;
+
+pub mod gen_throttle_repo;
+
+#[cfg(test)]
+mod test;
+
+pub type ThrottleResult<T> = Result<T, ThrottleError>;
+
+#[async_trait::async_trait]
+pub trait ThrottleRepository {
+    /// whether the username and the ip may try to log in now
+    async fn check(&self, _username: &str, _ip: &str) -> ThrottleResult<()> {
+        unimplemented!()
+    }
+
+    async fn record_failure(&self, _username: &str, _ip: &str) -> ThrottleResult<()> {
+        unimplemented!()
+    }
+
+    /// the failures of the username are forgotten, the ones of the ip are not
+    async fn record_success(&self, _username: &str, _ip: &str) -> ThrottleResult<()> {
+        unimplemented!()
+    }
+
+    /// whether the ip may get another visitor token, counts the token when it may
+    async fn issue_visitor(&self, _ip: &str) -> ThrottleResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_throttles(&self, _only_locked: bool) -> ThrottleResult<Vec<LoginThrottle>> {
+        unimplemented!()
+    }
+
+    async fn clear(&self, _kind: Kind, _key: &str) -> ThrottleResult<()> {
+        unimplemented!()
+    }
+
+    /// remove the stale counters and old attempts
+    async fn purge_expired(&self) -> ThrottleResult<u64> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicThrottleRepository;
+
+impl ThrottleRepository for PanicThrottleRepository {}
+
+#[derive(Clone, Debug)]
+pub struct ThrottlePolicy {
+    /// failures allowed before backing off
+    pub free_attempts: u32,
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    /// failures until locked
+    pub lockout_threshold: u32,
+    pub lockout_duration: Duration,
+}
+
+impl ThrottlePolicy {
+    /// how long to wait after the last failure
+    pub fn backoff(&self, failure_count: u32) -> Duration {
+        if failure_count < self.free_attempts {
+            return Duration::ZERO;
+        }
+
+        let exponent = (failure_count - self.free_attempts).min(31);
+        self.backoff_base
+            .saturating_mul(1 << exponent)
+            .min(self.backoff_max)
+    }
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            free_attempts: 3,
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(15 * 60),
+            lockout_threshold: 10,
+            lockout_duration: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct LoginThrottle {
+    pub kind: Kind,
+    pub key: String,
+    pub failure_count: i32,
+    pub last_failure_date_time: DateTime<FixedOffset>,
+    pub locked_until_date_time: Option<DateTime<FixedOffset>>,
+    pub locked: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum ThrottleError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error("too many log in attempts")]
+    TooManyAttempts(Duration),
+    #[error("log in is locked")]
+    Locked(Duration),
+    #[error("throttle not found")]
+    NotFound,
+}
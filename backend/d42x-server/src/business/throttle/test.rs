@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use db_entity::login_throttles::Kind;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        business::throttle::{
+            ThrottleError, ThrottlePolicy, ThrottleRepository, gen_throttle_repo::GenThrottleRepo,
+        },
+        db::test::TestDB,
+    };
+
+    const USERNAME: &str = "dvorak";
+    const IP: &str = "10.0.0.1";
+
+    fn policy() -> ThrottlePolicy {
+        ThrottlePolicy {
+            free_attempts: 2,
+            backoff_base: Duration::from_secs(60),
+            backoff_max: Duration::from_secs(600),
+            lockout_threshold: 4,
+            lockout_duration: Duration::from_secs(1800),
+        }
+    }
+
+    fn repo(db: TestDB) -> GenThrottleRepo<TestDB> {
+        let ip_policy = ThrottlePolicy {
+            lockout_threshold: 100,
+            ..policy()
+        };
+        GenThrottleRepo::with_policy(db, policy(), ip_policy)
+    }
+
+    #[test]
+    fn backoff_grows_exponentially() {
+        let policy = policy();
+
+        assert_eq!(policy.backoff(1), Duration::ZERO);
+        assert_eq!(policy.backoff(2), Duration::from_secs(60));
+        assert_eq!(policy.backoff(3), Duration::from_secs(120));
+        assert_eq!(policy.backoff(10), Duration::from_secs(600));
+    }
+
+    #[tokio::test]
+    async fn check_backoff_after_free_attempts() {
+        let db = TestDB::new().await;
+        let repo = repo(db);
+
+        repo.record_failure(USERNAME, IP).await.unwrap();
+        assert!(repo.check(USERNAME, IP).await.is_ok());
+
+        repo.record_failure(USERNAME, IP).await.unwrap();
+        let res = repo.check(USERNAME, IP).await;
+        assert!(matches!(res, Err(ThrottleError::TooManyAttempts(_))));
+
+        // another ip is throttled by the username as well
+        let res = repo.check(USERNAME, "10.0.0.2").await;
+        assert!(matches!(res, Err(ThrottleError::TooManyAttempts(_))));
+    }
+
+    #[tokio::test]
+    async fn lockout_and_clear_success() {
+        let db = TestDB::new().await;
+        let repo = repo(db);
+
+        for _ in 0..policy().lockout_threshold {
+            repo.record_failure(USERNAME, IP).await.unwrap();
+        }
+
+        let res = repo.check(USERNAME, "10.0.0.2").await;
+        assert!(matches!(res, Err(ThrottleError::Locked(_))));
+
+        let locked = repo.get_throttles(true).await.unwrap();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].kind, Kind::Username);
+        assert!(locked[0].locked);
+
+        repo.clear(Kind::Username, USERNAME).await.unwrap();
+        assert!(repo.check(USERNAME, "10.0.0.2").await.is_ok());
+        assert!(repo.get_throttles(true).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_failures_all_counted() {
+        const FAILURES: usize = 12;
+
+        let db = TestDB::new().await;
+        let repo = repo(db);
+
+        let results =
+            futures::future::join_all((0..FAILURES).map(|_| repo.record_failure(USERNAME, IP)))
+                .await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let list = repo.get_throttles(false).await.unwrap();
+        let username = list
+            .iter()
+            .find(|throttle| throttle.kind == Kind::Username)
+            .unwrap();
+        assert_eq!(username.failure_count, FAILURES as i32);
+        assert!(username.locked);
+
+        let ip = list
+            .iter()
+            .find(|throttle| throttle.kind == Kind::Ip)
+            .unwrap();
+        assert_eq!(ip.failure_count, FAILURES as i32);
+        assert!(!ip.locked);
+    }
+
+    #[tokio::test]
+    async fn success_resets_username_only() {
+        let db = TestDB::new().await;
+        let repo = repo(db);
+
+        repo.record_failure(USERNAME, IP).await.unwrap();
+        repo.record_failure(USERNAME, IP).await.unwrap();
+        repo.record_success(USERNAME, IP).await.unwrap();
+
+        let list = repo.get_throttles(false).await.unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].kind, Kind::Ip);
+    }
+
+    #[tokio::test]
+    async fn clear_unknown_fail() {
+        let db = TestDB::new().await;
+        let repo = repo(db);
+
+        let res = repo.clear(Kind::Ip, IP).await;
+
+        assert!(matches!(res, Err(ThrottleError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn issue_visitor_backoff() {
+        let db = TestDB::new().await;
+        let repo = repo(db).with_visitor_policy(policy());
+
+        repo.issue_visitor(IP).await.unwrap();
+        repo.issue_visitor(IP).await.unwrap();
+        let res = repo.issue_visitor(IP).await;
+        assert!(matches!(res, Err(ThrottleError::TooManyAttempts(_))));
+
+        // another ip and the log in are not affected
+        repo.issue_visitor("10.0.0.2").await.unwrap();
+        assert!(repo.check(USERNAME, IP).await.is_ok());
+
+        let list = repo.get_throttles(false).await.unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.iter().all(|throttle| throttle.kind == Kind::Visitor));
+
+        repo.clear(Kind::Visitor, IP).await.unwrap();
+        repo.issue_visitor(IP).await.unwrap();
+    }
+}
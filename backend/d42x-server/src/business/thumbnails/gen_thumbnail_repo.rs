@@ -0,0 +1,240 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::{
+    meme_thumbnails,
+    meme_urls::{self, ThumbnailStatus},
+};
+use migration::async_trait;
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait, prelude::Expr,
+};
+use tracing::warn;
+
+use crate::{
+    business::{storage::Storage, uploads::fetch::MediaFetcher},
+    config::AllowMemeFormats,
+    db::DbConnHelper,
+};
+
+use super::{
+    CLAIM_TIMEOUT, ThumbnailError, ThumbnailPolicy, ThumbnailRepository, ThumbnailResult,
+    render::{Rendered, render},
+};
+
+const WEBP_CONTENT_TYPE: &str = "image/webp";
+
+pub struct GenThumbnailRepo<TDb: DbConnHelper> {
+    db: TDb,
+    storage: Box<dyn Storage + 'static + Sync + Send>,
+    policy: ThumbnailPolicy,
+    fetcher: MediaFetcher,
+}
+
+impl<TDb: DbConnHelper> GenThumbnailRepo<TDb> {
+    pub fn new(db: TDb, storage: impl Storage + 'static + Sync + Send) -> Self {
+        Self::with_policy(db, storage, ThumbnailPolicy::default())
+    }
+
+    pub fn with_policy(
+        db: TDb,
+        storage: impl Storage + 'static + Sync + Send,
+        policy: ThumbnailPolicy,
+    ) -> Self {
+        Self {
+            db,
+            storage: Box::new(storage),
+            fetcher: MediaFetcher::new(policy.max_size),
+            policy,
+        }
+    }
+
+    async fn generate(
+        &self,
+        db: &(impl ConnectionTrait + TransactionTrait),
+        model: meme_urls::Model,
+    ) -> ThumbnailResult<()> {
+        let format = AllowMemeFormats::try_from(model.format.as_str())
+            .map_err(|_| ThumbnailError::UnsupportedFormat(model.format.clone()))?;
+
+        let bytes = self
+            .fetcher
+            .load(&self.storage, model.bed, &model.bed_id, &model.url)
+            .await?;
+
+        let policy = self.policy.clone();
+        // decoding and encoding take a while
+        let rendered = tokio::task::spawn_blocking(move || render(&bytes, format, &policy))
+            .await
+            .map_err(|e| ThumbnailError::Encode(e.to_string()))??;
+        let Rendered {
+            width,
+            height,
+            animated,
+            decoded,
+            thumbnails,
+            cover,
+        } = rendered;
+
+        if !decoded {
+            let mut active = model.into_active_model();
+            active.width = Set(Some(width as i32));
+            active.height = Set(Some(height as i32));
+            active.thumbnail_status = Set(ThumbnailStatus::Unsupported);
+            active.thumbnail_claimed_date_time = Set(None);
+            active.update(db).await?;
+            return Ok(());
+        }
+
+        let name = model.id.simple();
+        let mut thumbnail_models = vec![];
+        for thumbnail in thumbnails {
+            let key = format!("{}.{}w.webp", name, thumbnail.width);
+            self.storage
+                .put(&key, thumbnail.bytes, WEBP_CONTENT_TYPE)
+                .await?;
+            thumbnail_models.push(meme_thumbnails::ActiveModel {
+                url_id: Set(model.id),
+                width: Set(thumbnail.width as i32),
+                height: Set(thumbnail.height as i32),
+                url: Set(self.storage.url(&key)),
+                bed: Set(self.storage.bed()),
+                bed_id: Set(key),
+                ..meme_thumbnails::ActiveModel::new()
+            });
+        }
+
+        // an animation gets the still cover, a picture is its own cover
+        let cover = match cover {
+            Some(cover) => {
+                let key = format!("{}.cover.webp", name);
+                self.storage
+                    .put(&key, cover.bytes, WEBP_CONTENT_TYPE)
+                    .await?;
+                Some(self.storage.url(&key))
+            }
+            None if !animated => Some(model.url.clone()),
+            None => None,
+        };
+        // the cover chosen by the poster is kept
+        let cover = cover.filter(|_| model.cover.is_empty() || model.cover == model.url);
+
+        let txn = db.begin().await?;
+
+        meme_thumbnails::Entity::delete_many()
+            .filter(meme_thumbnails::Column::UrlId.eq(model.id))
+            .exec(&txn)
+            .await?;
+        if !thumbnail_models.is_empty() {
+            meme_thumbnails::Entity::insert_many(thumbnail_models)
+                .exec(&txn)
+                .await?;
+        }
+
+        let mut active = model.into_active_model();
+        active.width = Set(Some(width as i32));
+        active.height = Set(Some(height as i32));
+        active.thumbnail_status = Set(ThumbnailStatus::Ready);
+        active.thumbnail_claimed_date_time = Set(None);
+        if let Some(cover) = cover {
+            active.cover = Set(cover);
+        }
+        active.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// the pending files, and the ones whose claim has timed out
+fn claimable(now: DateTime<Utc>) -> Condition {
+    let stale: DateTime<FixedOffset> = (now - CLAIM_TIMEOUT).into();
+
+    Condition::any()
+        .add(meme_urls::Column::ThumbnailStatus.eq(ThumbnailStatus::Pending))
+        .add(
+            Condition::all()
+                .add(meme_urls::Column::ThumbnailStatus.eq(ThumbnailStatus::Processing))
+                .add(meme_urls::Column::ThumbnailClaimedDateTime.lt(stale)),
+        )
+}
+
+#[async_trait::async_trait]
+impl<TDb> ThumbnailRepository for GenThumbnailRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn generate_pending(&self, limit: u64) -> ThumbnailResult<u64> {
+        let db = self.db.get_connection().await?;
+
+        let pending = meme_urls::Entity::find()
+            .filter(claimable(Utc::now()))
+            .order_by_asc(meme_urls::Column::CreatedDateTime)
+            .order_by_asc(meme_urls::Column::Id)
+            .limit(limit)
+            .all(&db)
+            .await?;
+
+        let mut count = 0;
+        for model in pending {
+            let id = model.id;
+
+            // the row is only claimed when nobody took it since it was read
+            let now: DateTime<FixedOffset> = Utc::now().into();
+            let claimed = meme_urls::Entity::update_many()
+                .col_expr(
+                    meme_urls::Column::ThumbnailStatus,
+                    Expr::value(ThumbnailStatus::Processing),
+                )
+                .col_expr(
+                    meme_urls::Column::ThumbnailClaimedDateTime,
+                    Expr::value(Some(now)),
+                )
+                .filter(meme_urls::Column::Id.eq(id))
+                .filter(claimable(now.to_utc()))
+                .exec(&db)
+                .await?;
+            if claimed.rows_affected == 0 {
+                continue;
+            }
+
+            match self.generate(&db, model).await {
+                Ok(()) => {}
+                Err(ThumbnailError::DatabaseErr(e)) => return Err(e.into()),
+                Err(e) => {
+                    warn!("thumbnails of {} failed: {}", id, e);
+                    meme_urls::Entity::update_many()
+                        .col_expr(
+                            meme_urls::Column::ThumbnailStatus,
+                            Expr::value(ThumbnailStatus::Failed),
+                        )
+                        .col_expr(
+                            meme_urls::Column::ThumbnailClaimedDateTime,
+                            Expr::value(Option::<DateTime<FixedOffset>>::None),
+                        )
+                        .filter(meme_urls::Column::Id.eq(id))
+                        .exec(&db)
+                        .await?;
+                }
+            }
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    async fn retry_failed(&self) -> ThumbnailResult<u64> {
+        let db = self.db.get_connection().await?;
+
+        let res = meme_urls::Entity::update_many()
+            .col_expr(
+                meme_urls::Column::ThumbnailStatus,
+                Expr::value(ThumbnailStatus::Pending),
+            )
+            .filter(meme_urls::Column::ThumbnailStatus.eq(ThumbnailStatus::Failed))
+            .exec(&db)
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+}
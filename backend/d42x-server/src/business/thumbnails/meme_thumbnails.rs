@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use db_entity::meme_thumbnails;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, prelude::Uuid,
+};
+
+use super::Thumbnail;
+
+/// the thumbnails of each file of `meme_urls`, from the narrowest
+pub async fn get_thumbnails(
+    conn: &impl ConnectionTrait,
+    url_ids: Vec<Uuid>,
+) -> Result<HashMap<Uuid, Vec<Thumbnail>>, DbErr> {
+    let mut result: HashMap<Uuid, Vec<Thumbnail>> = HashMap::new();
+    if url_ids.is_empty() {
+        return Ok(result);
+    }
+
+    let rows = meme_thumbnails::Entity::find()
+        .filter(meme_thumbnails::Column::UrlId.is_in(url_ids))
+        .order_by_asc(meme_thumbnails::Column::Width)
+        .all(conn)
+        .await?;
+
+    for row in rows {
+        result.entry(row.url_id).or_default().push(Thumbnail {
+            width: row.width,
+            height: row.height,
+            url: row.url,
+        });
+    }
+
+    Ok(result)
+}
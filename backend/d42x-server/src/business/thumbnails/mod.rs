@@ -0,0 +1,96 @@
+//! Thumbnails
+//!
+//! a background job decodes every file of `meme_urls` once, records its size and stores resized
+//! WebP copies of it through the `Storage`, so the feed can choose one by `srcset` and reserve
+//! the space of the file before loading it.
+//!
+//! the thumbnails of an animation are still pictures of its first frame, the same frame at full
+//! size becomes the cover of an animated GIF, WebP or WebM. only VP8 videos are decoded, the
+//! other videos get their size from the headers, keep their cover and are marked `unsupported`.
+//!
+//! a file is claimed as `processing` before its thumbnails are generated, so several instances
+//! do not generate the same file, a claim older than `CLAIM_TIMEOUT` is given up
+
+use std::time::Duration;
+
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{media::MediaError, storage::StorageError, uploads::UploadError};
+
+pub mod gen_thumbnail_repo;
+pub mod meme_thumbnails;
+pub mod render;
+
+#[cfg(test)]
+mod test;
+
+pub type ThumbnailResult<T> = Result<T, ThumbnailError>;
+
+#[async_trait::async_trait]
+pub trait ThumbnailRepository {
+    /// generate the thumbnails of up to `limit` pending files, the oldest first, return how
+    /// many files were handled, the failed ones included and the ones claimed by another
+    /// instance left out
+    async fn generate_pending(&self, _limit: u64) -> ThumbnailResult<u64> {
+        unimplemented!()
+    }
+
+    /// queue the failed files again, return how many
+    async fn retry_failed(&self) -> ThumbnailResult<u64> {
+        unimplemented!()
+    }
+}
+
+/// a file `processing` for longer was claimed by an instance that stopped
+pub const CLAIM_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+pub struct PanicThumbnailRepository;
+
+impl ThumbnailRepository for PanicThumbnailRepository {}
+
+/// a resized WebP copy of a file, for `srcset`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: i32,
+    pub height: i32,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailPolicy {
+    /// only the widths narrower than the file are generated
+    pub widths: Vec<u32>,
+    /// of the lossy WebP encoder, from 0 to 100
+    pub quality: f32,
+    /// a download is cut off after it
+    pub max_size: usize,
+}
+
+impl Default for ThumbnailPolicy {
+    fn default() -> Self {
+        Self {
+            widths: vec![160, 320, 640],
+            quality: 75.0,
+            max_size: super::media::MediaPolicy::default().max_size(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ThumbnailError {
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+    #[error("{0}")]
+    Media(#[from] MediaError),
+    #[error("WebP encoding failed: {0}")]
+    Encode(String),
+    #[error("{0}")]
+    Load(#[from] UploadError),
+    #[error("{0}")]
+    Storage(#[from] StorageError),
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+}
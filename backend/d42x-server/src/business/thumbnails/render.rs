@@ -0,0 +1,109 @@
+use image::{DynamicImage, GenericImageView, imageops::FilterType};
+
+use crate::{
+    business::media::{first_frame, is_animated, webm},
+    config::AllowMemeFormats,
+};
+
+use super::{ThumbnailError, ThumbnailPolicy, ThumbnailResult};
+
+/// what is generated for a file
+#[derive(Debug)]
+pub struct Rendered {
+    pub width: u32,
+    pub height: u32,
+    pub animated: bool,
+    /// false for a video only the headers of which could be read
+    pub decoded: bool,
+    /// from the narrowest
+    pub thumbnails: Vec<WebpImage>,
+    /// the first frame of an animation
+    pub cover: Option<WebpImage>,
+}
+
+#[derive(Debug)]
+pub struct WebpImage {
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// decode `bytes` and encode the thumbnails and the cover, a video that cannot be decoded
+/// only gets its size
+pub fn render(
+    bytes: &[u8],
+    format: AllowMemeFormats,
+    policy: &ThumbnailPolicy,
+) -> ThumbnailResult<Rendered> {
+    let animated = is_animated(bytes, format);
+
+    let image = match first_frame(bytes, format) {
+        Ok(image) => image,
+        Err(e) => {
+            let (width, height) = (format == AllowMemeFormats::WEBM)
+                .then(|| webm::dimensions(bytes))
+                .flatten()
+                .ok_or(e)?;
+            return Ok(Rendered {
+                width,
+                height,
+                animated,
+                decoded: false,
+                thumbnails: vec![],
+                cover: None,
+            });
+        }
+    };
+    let (width, height) = image.dimensions();
+
+    let mut widths: Vec<_> = policy
+        .widths
+        .iter()
+        .copied()
+        .filter(|thumbnail_width| *thumbnail_width > 0 && *thumbnail_width < width)
+        .collect();
+    widths.sort_unstable();
+    widths.dedup();
+
+    let thumbnails = widths
+        .into_iter()
+        .map(|thumbnail_width| {
+            let thumbnail_height =
+                (u64::from(height) * u64::from(thumbnail_width) / u64::from(width)).max(1) as u32;
+            let thumbnail =
+                image.resize_exact(thumbnail_width, thumbnail_height, FilterType::CatmullRom);
+            encode(&thumbnail, policy.quality)
+        })
+        .collect::<ThumbnailResult<_>>()?;
+
+    let cover = if animated {
+        Some(encode(&image, policy.quality)?)
+    } else {
+        None
+    };
+
+    Ok(Rendered {
+        width,
+        height,
+        animated,
+        decoded: true,
+        thumbnails,
+        cover,
+    })
+}
+
+/// lossy WebP, which `image` cannot encode
+fn encode(image: &DynamicImage, quality: f32) -> ThumbnailResult<WebpImage> {
+    let (width, height) = image.dimensions();
+    let rgba = image.to_rgba8();
+
+    let bytes = webp::Encoder::from_rgba(&rgba, width, height)
+        .encode_simple(false, quality)
+        .map_err(|e| ThumbnailError::Encode(format!("{:?}", e)))?;
+
+    Ok(WebpImage {
+        width,
+        height,
+        bytes: bytes.to_vec(),
+    })
+}
@@ -0,0 +1,332 @@
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, path::PathBuf};
+
+    use chrono::{DateTime, FixedOffset, Utc};
+    use db_entity::meme_urls::{self, ThumbnailStatus};
+    use image::{ImageFormat, RgbImage, RgbaImage};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{
+        ColumnTrait, EntityTrait, QueryFilter,
+        prelude::{Expr, Uuid},
+    };
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            media::{MediaError, sniff},
+            meme::{
+                MemeRepository,
+                gen_meme_repo::GenMemeRepo,
+                test_support::{post_file, stored},
+            },
+            storage::local::LocalStorage,
+            thumbnails::{
+                CLAIM_TIMEOUT, ThumbnailError, ThumbnailPolicy, ThumbnailRepository,
+                gen_thumbnail_repo::GenThumbnailRepo, render::render,
+            },
+        },
+        config::AllowMemeFormats,
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    fn encode(format: ImageFormat, width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        match format {
+            ImageFormat::Gif => RgbaImage::new(width, height).write_to(&mut bytes, format),
+            _ => RgbImage::new(width, height).write_to(&mut bytes, format),
+        }
+        .unwrap();
+        bytes.into_inner()
+    }
+
+    fn element(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.push(0x80 | data.len() as u8);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// the headers of a 640x360 VP9 video without any frame
+    fn vp9_webm() -> Vec<u8> {
+        let mut bytes = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"webm"));
+
+        let mut size = element(&[0xB0], &[0x02, 0x80]);
+        size.extend(element(&[0xBA], &[0x01, 0x68]));
+        let mut entry = element(&[0xD7], &[0x01]);
+        entry.extend(element(&[0x86], b"V_VP9"));
+        entry.extend(element(&[0xE0], &size));
+        let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &entry));
+
+        bytes.extend(element(&[0x18, 0x53, 0x80, 0x67], &tracks));
+        bytes
+    }
+
+    fn new_repo(db: TestDB) -> (GenThumbnailRepo<TestDB>, PathBuf) {
+        let root = std::env::temp_dir().join(format!("d42x-thumbnails-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let repo = GenThumbnailRepo::new(db, LocalStorage::new(&root, "/media"));
+        (repo, root)
+    }
+
+    /// the seeded files are on the internet, leave them out
+    async fn skip_seeded(db: &TestDB) {
+        let db_conn = db.get_connection().await.unwrap();
+        meme_urls::Entity::update_many()
+            .col_expr(
+                meme_urls::Column::ThumbnailStatus,
+                Expr::value(ThumbnailStatus::Ready),
+            )
+            .exec(&db_conn)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn render_picture() {
+        let png = encode(ImageFormat::Png, 800, 400);
+
+        let rendered = render(&png, AllowMemeFormats::PNG, &ThumbnailPolicy::default()).unwrap();
+        assert_eq!((rendered.width, rendered.height), (800, 400));
+        assert!(!rendered.animated);
+        assert!(rendered.cover.is_none());
+        assert_eq!(
+            rendered
+                .thumbnails
+                .iter()
+                .map(|thumbnail| (thumbnail.width, thumbnail.height))
+                .collect::<Vec<_>>(),
+            vec![(160, 80), (320, 160), (640, 320)]
+        );
+        for thumbnail in rendered.thumbnails {
+            assert_eq!(sniff(&thumbnail.bytes), Some(AllowMemeFormats::WEBP));
+        }
+
+        // only the widths narrower than the picture
+        let policy = ThumbnailPolicy {
+            widths: vec![640, 0, 160, 160],
+            ..Default::default()
+        };
+        let rendered = render(
+            &encode(ImageFormat::Jpeg, 200, 1000),
+            AllowMemeFormats::JPG,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(
+            rendered
+                .thumbnails
+                .iter()
+                .map(|thumbnail| (thumbnail.width, thumbnail.height))
+                .collect::<Vec<_>>(),
+            vec![(160, 800)]
+        );
+    }
+
+    #[test]
+    fn render_animation() {
+        let gif = encode(ImageFormat::Gif, 300, 200);
+
+        let rendered = render(&gif, AllowMemeFormats::GIF, &ThumbnailPolicy::default()).unwrap();
+        assert!(rendered.animated);
+        assert_eq!(rendered.thumbnails.len(), 1);
+        let cover = rendered.cover.unwrap();
+        assert_eq!((cover.width, cover.height), (300, 200));
+        assert_eq!(sniff(&cover.bytes), Some(AllowMemeFormats::WEBP));
+    }
+
+    #[test]
+    fn render_unreadable() {
+        let res = render(
+            b"not a picture",
+            AllowMemeFormats::PNG,
+            &ThumbnailPolicy::default(),
+        );
+        assert!(matches!(
+            res,
+            Err(ThumbnailError::Media(MediaError::Unreadable(_)))
+        ));
+    }
+
+    #[test]
+    fn render_undecodable_video() {
+        let rendered = render(
+            &vp9_webm(),
+            AllowMemeFormats::WEBM,
+            &ThumbnailPolicy::default(),
+        )
+        .unwrap();
+        assert!(!rendered.decoded);
+        assert_eq!((rendered.width, rendered.height), (640, 360));
+        assert!(rendered.thumbnails.is_empty());
+        assert!(rendered.cover.is_none());
+    }
+
+    #[tokio::test]
+    async fn generate_pending_success() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+        skip_seeded(&db).await;
+
+        std::fs::write(root.join("cat.png"), encode(ImageFormat::Png, 400, 300)).unwrap();
+        std::fs::write(root.join("dance.gif"), encode(ImageFormat::Gif, 100, 80)).unwrap();
+        let cat = post_file(&db, stored("cat.png", AllowMemeFormats::PNG)).await;
+        let mut dance = stored("dance.gif", AllowMemeFormats::GIF);
+        dance.cover = dance.url.clone();
+        let dance = post_file(&db, dance).await;
+
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 2);
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 0);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let cat_model = meme_urls::Entity::find_by_id(cat)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cat_model.thumbnail_status, ThumbnailStatus::Ready);
+        assert_eq!((cat_model.width, cat_model.height), (Some(400), Some(300)));
+        assert_eq!(cat_model.cover, cat_model.url);
+
+        let dance_model = meme_urls::Entity::find_by_id(dance)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        let cover_key = format!("{}.cover.webp", dance.simple());
+        assert_eq!(dance_model.cover, format!("/media/{}", cover_key));
+        assert!(root.join(cover_key).exists());
+
+        // the thumbnails are listed with the file
+        let detail = GenMemeRepo::<MockCache<String, String>, _>::new(db.clone())
+            .get_meme(cat_model.meme_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .get_detail()
+            .await
+            .unwrap();
+        let url = &detail.list[0];
+        assert_eq!((url.width, url.height), (Some(400), Some(300)));
+        assert_eq!(
+            url.thumbnails
+                .iter()
+                .map(|thumbnail| (thumbnail.width, thumbnail.height, thumbnail.url.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (160, 120, format!("/media/{}.160w.webp", cat.simple())),
+                (320, 240, format!("/media/{}.320w.webp", cat.simple())),
+            ]
+        );
+        assert!(root.join(format!("{}.320w.webp", cat.simple())).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn generate_pending_failed() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+        skip_seeded(&db).await;
+
+        std::fs::write(root.join("broken.png"), b"not a picture").unwrap();
+        let broken = post_file(&db, stored("broken.png", AllowMemeFormats::PNG)).await;
+
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 1);
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 0);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let model = meme_urls::Entity::find_by_id(broken)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.thumbnail_status, ThumbnailStatus::Failed);
+        assert_eq!(model.width, None);
+
+        // queued again once fixed
+        std::fs::write(root.join("broken.png"), encode(ImageFormat::Png, 10, 10)).unwrap();
+        assert_eq!(repo.retry_failed().await.unwrap(), 1);
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 1);
+        let model = meme_urls::Entity::find_by_id(broken)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.thumbnail_status, ThumbnailStatus::Ready);
+        assert_eq!((model.width, model.height), (Some(10), Some(10)));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn generate_pending_unsupported() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+        skip_seeded(&db).await;
+
+        std::fs::write(root.join("clip.webm"), vp9_webm()).unwrap();
+        let clip = post_file(&db, stored("clip.webm", AllowMemeFormats::WEBM)).await;
+
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 1);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let model = meme_urls::Entity::find_by_id(clip)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.thumbnail_status, ThumbnailStatus::Unsupported);
+        assert_eq!((model.width, model.height), (Some(640), Some(360)));
+        assert_eq!(model.thumbnail_claimed_date_time, None);
+        assert!(model.cover.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn generate_pending_claimed() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+        skip_seeded(&db).await;
+
+        std::fs::write(root.join("cat.png"), encode(ImageFormat::Png, 40, 30)).unwrap();
+        let cat = post_file(&db, stored("cat.png", AllowMemeFormats::PNG)).await;
+
+        let db_conn = db.get_connection().await.unwrap();
+        let claim = async |claimed: DateTime<Utc>| {
+            let claimed: DateTime<FixedOffset> = claimed.into();
+            meme_urls::Entity::update_many()
+                .col_expr(
+                    meme_urls::Column::ThumbnailStatus,
+                    Expr::value(ThumbnailStatus::Processing),
+                )
+                .col_expr(
+                    meme_urls::Column::ThumbnailClaimedDateTime,
+                    Expr::value(Some(claimed)),
+                )
+                .filter(meme_urls::Column::Id.eq(cat))
+                .exec(&db_conn)
+                .await
+                .unwrap();
+        };
+
+        // another instance is on it
+        claim(Utc::now()).await;
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 0);
+
+        // that instance has stopped
+        claim(Utc::now() - CLAIM_TIMEOUT * 2).await;
+        assert_eq!(repo.generate_pending(10).await.unwrap(), 1);
+
+        let model = meme_urls::Entity::find_by_id(cat)
+            .one(&db_conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.thumbnail_status, ThumbnailStatus::Ready);
+        assert_eq!(model.thumbnail_claimed_date_time, None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+//! the interaction log, written in the same transaction as the counters on `memes`
+
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::meme_interactions::{self, Kind};
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait,
+    ModelTrait, QueryFilter, QueryOrder, Set, prelude::Uuid,
+};
+
+/// a like counts fully, an unlike takes half a like away
+pub fn kind_weight(kind: Kind) -> f64 {
+    match kind {
+        Kind::Like => 1.0,
+        Kind::Unlike => -0.5,
+    }
+}
+
+pub async fn record_interaction(
+    conn: &impl ConnectionTrait,
+    meme_id: Uuid,
+    kind: Kind,
+    client_id: &str,
+) -> Result<(), DbErr> {
+    let now: DateTime<FixedOffset> = Utc::now().into();
+
+    meme_interactions::ActiveModel {
+        meme_id: Set(meme_id),
+        kind: Set(kind),
+        client_id: Set(client_id.to_owned()),
+        created_date_time: Set(now),
+        ..meme_interactions::ActiveModel::new()
+    }
+    .insert(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// take back the latest interaction of `client_id`, when a vote is withdrawn or switched
+pub async fn withdraw_interaction(
+    conn: &impl ConnectionTrait,
+    meme_id: Uuid,
+    kind: Kind,
+    client_id: &str,
+) -> Result<(), DbErr> {
+    let latest = meme_interactions::Entity::find()
+        .filter(meme_interactions::Column::MemeId.eq(meme_id))
+        .filter(meme_interactions::Column::Kind.eq(kind))
+        .filter(meme_interactions::Column::ClientId.eq(client_id))
+        .order_by_desc(meme_interactions::Column::CreatedDateTime)
+        .one(conn)
+        .await?;
+
+    if let Some(latest) = latest {
+        latest.delete(conn).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use db_entity::{
+    meme_hot_scores,
+    meme_interactions::{self, Kind},
+    memes,
+};
+use migration::async_trait;
+use sea_orm::{
+    ColumnTrait, EntityTrait, QueryFilter, QuerySelect, QueryTrait, Set, TransactionTrait,
+    prelude::Uuid,
+};
+use serde_json::json;
+use tracing::debug;
+
+use crate::{
+    business::{cache::Cache, meme::gen_meme_repo::models_2_meme_list},
+    db::DbConnHelper,
+};
+
+use super::{
+    TrendingMeme, TrendingRepository, TrendingResult, TrendingWindow, events::kind_weight,
+};
+
+const TRENDING_MEMES_CACHE_KEY: &str = "TRENDING_MEMES_CACHE_KEY";
+const DEFAULT_TRENDING_SIZE: usize = 20;
+/// the weight of an interaction halves every this many hours
+const HOT_HALF_LIFE_HOURS: f64 = 24.0;
+/// older interactions weigh less than 1% and are left out of the hot score
+const HOT_HORIZON_DAYS: i64 = 7;
+/// the rows of one insert, kept small for the bind parameter limit of SQLite
+const INSERT_CHUNK: usize = 200;
+
+pub struct GenTrendingRepo<TCache, TDb>
+where
+    TCache: Cache<String, String>,
+    TDb: DbConnHelper,
+{
+    cache: Option<TCache>,
+    db: TDb,
+    size: usize,
+}
+
+impl<TCache, TDb> GenTrendingRepo<TCache, TDb>
+where
+    TCache: Cache<String, String>,
+    TDb: DbConnHelper,
+{
+    pub fn new(db: TDb) -> Self {
+        Self {
+            cache: None,
+            db,
+            size: DEFAULT_TRENDING_SIZE,
+        }
+    }
+
+    pub fn with_cache(db: TDb, cache: Option<TCache>) -> Self {
+        Self {
+            cache,
+            db,
+            size: DEFAULT_TRENDING_SIZE,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TCache, TDb> TrendingRepository for GenTrendingRepo<TCache, TDb>
+where
+    TCache: Cache<String, String> + Sync + Send,
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn trending(&self, window: TrendingWindow) -> TrendingResult<Vec<TrendingMeme>> {
+        let key = format!("{}-{}", TRENDING_MEMES_CACHE_KEY, json!(window));
+        if let Some(cache) = &self.cache
+            && let Some(value) = cache.get(&key)
+        {
+            if let Ok(value) = serde_json::from_str::<Vec<TrendingMeme>>(value.as_str()) {
+                debug!("get trending memes from cache: {:?}", value);
+                return Ok(value);
+            } else {
+                cache.remove(&key);
+            }
+        }
+
+        let db = self.db.get_connection().await?;
+
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let since = now - window.duration();
+
+        let published = memes::Entity::find()
+            .select_only()
+            .column(memes::Column::Id)
+            .filter(memes::Column::Status.eq(memes::Status::Published))
+            .filter(memes::Column::ShowDateTime.lt(now))
+            .into_query();
+
+        let counts: Vec<(Uuid, Kind, i64)> = meme_interactions::Entity::find()
+            .select_only()
+            .column(meme_interactions::Column::MemeId)
+            .column(meme_interactions::Column::Kind)
+            .column_as(meme_interactions::Column::Id.count(), "count")
+            .filter(meme_interactions::Column::CreatedDateTime.gte(since))
+            .filter(meme_interactions::Column::MemeId.in_subquery(published))
+            .group_by(meme_interactions::Column::MemeId)
+            .group_by(meme_interactions::Column::Kind)
+            .into_tuple()
+            .all(&db)
+            .await?;
+
+        let mut windowed: HashMap<Uuid, (u64, u64)> = HashMap::new();
+        for (meme_id, kind, count) in counts {
+            let entry = windowed.entry(meme_id).or_default();
+            match kind {
+                Kind::Like => entry.0 += count as u64,
+                Kind::Unlike => entry.1 += count as u64,
+            }
+        }
+
+        let mut scored: Vec<_> = windowed
+            .into_iter()
+            .map(|(meme_id, (likes, unlikes))| {
+                let score = likes as f64 * kind_weight(Kind::Like)
+                    + unlikes as f64 * kind_weight(Kind::Unlike);
+                (meme_id, score, likes, unlikes)
+            })
+            .filter(|(_, score, _, _)| *score > 0.0)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)).then(b.0.cmp(&a.0)));
+        scored.truncate(self.size);
+
+        let ids: Vec<_> = scored.iter().map(|(meme_id, ..)| *meme_id).collect();
+        let mut models = memes::Entity::find()
+            .filter(memes::Column::Id.is_in(ids.clone()))
+            .all(&db)
+            .await?;
+        models.sort_by_key(|item| ids.iter().position(|id| *id == item.id));
+
+        // a meme gone since it was counted leaves no gap
+        let scores: HashMap<_, _> = scored
+            .into_iter()
+            .map(|(meme_id, score, likes, unlikes)| (meme_id, (score, likes, unlikes)))
+            .collect();
+        let result: Vec<_> = models_2_meme_list(models, &db)
+            .await?
+            .into_iter()
+            .filter_map(|meme| {
+                let (score, window_likes, window_unlikes) = *scores.get(&meme.id)?;
+                Some(TrendingMeme {
+                    meme,
+                    score,
+                    window_likes,
+                    window_unlikes,
+                })
+            })
+            .collect();
+
+        if let Some(cache) = &self.cache {
+            let cache_value = json!(result).to_string();
+            debug!("set cache data: {:?}", cache_value);
+
+            cache.insert(key, cache_value);
+        }
+
+        Ok(result)
+    }
+
+    async fn refresh_hot_scores(&self) -> TrendingResult<u64> {
+        let db = self.db.get_connection().await?;
+
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let since = now - chrono::Duration::days(HOT_HORIZON_DAYS);
+
+        let events: Vec<(Uuid, Kind, DateTime<FixedOffset>)> = meme_interactions::Entity::find()
+            .select_only()
+            .column(meme_interactions::Column::MemeId)
+            .column(meme_interactions::Column::Kind)
+            .column(meme_interactions::Column::CreatedDateTime)
+            .filter(meme_interactions::Column::CreatedDateTime.gte(since))
+            .into_tuple()
+            .all(&db)
+            .await?;
+
+        let mut scores: HashMap<Uuid, f64> = HashMap::new();
+        for (meme_id, kind, created_date_time) in events {
+            let hours = (now - created_date_time).num_seconds().max(0) as f64 / 3600.0;
+            *scores.entry(meme_id).or_default() +=
+                kind_weight(kind) * 0.5_f64.powf(hours / HOT_HALF_LIFE_HOURS);
+        }
+
+        let models: Vec<_> = scores
+            .into_iter()
+            .map(|(meme_id, score)| meme_hot_scores::ActiveModel {
+                meme_id: Set(meme_id),
+                score: Set(score),
+                updated_date_time: Set(now),
+            })
+            .collect();
+        let count = models.len() as u64;
+
+        let txn = db.begin().await?;
+
+        meme_hot_scores::Entity::delete_many().exec(&txn).await?;
+
+        for chunk in models.chunks(INSERT_CHUNK) {
+            meme_hot_scores::Entity::insert_many(chunk.to_vec())
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(count)
+    }
+
+    async fn purge_interactions(&self) -> TrendingResult<u64> {
+        let db = self.db.get_connection().await?;
+
+        let deadline: DateTime<FixedOffset> =
+            (Utc::now() - TrendingWindow::Month.duration()).into();
+
+        let res = meme_interactions::Entity::delete_many()
+            .filter(meme_interactions::Column::CreatedDateTime.lt(deadline))
+            .exec(&db)
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+}
@@ -0,0 +1,83 @@
+//! Trending
+//!
+//! every like and unlike is logged in `meme_interactions` by `events::record_interaction`,
+//! `trending` ranks the memes by the interactions within a window, and the time-decayed
+//! score in `meme_hot_scores` is refreshed in the background for `SortMode::Hot`
+
+pub mod events;
+pub mod gen_trending_repo;
+
+#[cfg(test)]
+mod test;
+
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::meme::{Meme, MemeError};
+
+pub type TrendingResult<T> = Result<T, TrendingError>;
+
+#[async_trait::async_trait]
+pub trait TrendingRepository {
+    /// published memes with the most interactions within `window`, the hottest first
+    async fn trending(&self, _window: TrendingWindow) -> TrendingResult<Vec<TrendingMeme>> {
+        unimplemented!()
+    }
+
+    /// recompute the hot score of every meme, return how many memes are scored
+    async fn refresh_hot_scores(&self) -> TrendingResult<u64> {
+        unimplemented!()
+    }
+
+    /// delete the interactions older than the longest window, return how many are deleted
+    async fn purge_interactions(&self) -> TrendingResult<u64> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicTrendingRepository;
+
+#[async_trait::async_trait]
+impl TrendingRepository for PanicTrendingRepository {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrendingWindow {
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl TrendingWindow {
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            TrendingWindow::Day => chrono::Duration::hours(24),
+            TrendingWindow::Week => chrono::Duration::days(7),
+            TrendingWindow::Month => chrono::Duration::days(30),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TrendingMeme {
+    #[serde(flatten)]
+    pub meme: Meme,
+    pub score: f64,
+    /// likes within the window
+    pub window_likes: u64,
+    /// unlikes within the window
+    pub window_unlikes: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum TrendingError {
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+    #[error(transparent)]
+    Meme(#[from] MemeError),
+}
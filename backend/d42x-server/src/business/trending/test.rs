@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, Utc};
+    use db_entity::meme_interactions::{self, Kind};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{
+        ActiveModelBehavior, ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait,
+        QueryFilter, Set, prelude::Uuid,
+    };
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            meme::{
+                ListFilter, MemeRepository, SortMode, Voter,
+                gen_meme_repo::GenMemeRepo,
+                test_support::{post, post_meme},
+            },
+            trending::{TrendingRepository, TrendingWindow, gen_trending_repo::GenTrendingRepo},
+        },
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    /// post memes with the given messages in the `trending` category, return their ids
+    async fn post_memes(db: &TestDB, messages: &[&str]) -> Vec<Uuid> {
+        let mut ids = vec![];
+        for message in messages {
+            ids.push(post(db, post_meme(message, &["trending"])).await);
+        }
+
+        ids
+    }
+
+    async fn interact(db: &TestDB, id: Uuid, kind: Kind, times: usize) {
+        let repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let meme = repo.get_meme(id).await.unwrap().unwrap();
+        for _ in 0..times {
+            let voter = Voter {
+                key: format!("device:{}", Uuid::new_v4().simple()),
+                client_id: "client".to_owned(),
+            };
+            meme.vote(&voter, kind).await.unwrap();
+        }
+    }
+
+    /// log interactions as if they happened `days` ago
+    async fn interact_before(db: &TestDB, id: Uuid, kind: Kind, times: usize, days: i64) {
+        let db_conn = db.get_connection().await.unwrap();
+        let created_date_time: DateTime<FixedOffset> =
+            (Utc::now() - chrono::Duration::days(days)).into();
+        for _ in 0..times {
+            meme_interactions::ActiveModel {
+                meme_id: Set(id),
+                kind: Set(kind),
+                client_id: Set("client".to_owned()),
+                created_date_time: Set(created_date_time),
+                ..meme_interactions::ActiveModel::new()
+            }
+            .insert(&db_conn)
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn vote_logs_interaction_success() {
+        let db = TestDB::new().await;
+        let ids = post_memes(&db, &["logged"]).await;
+
+        interact(&db, ids[0], Kind::Like, 2).await;
+        interact(&db, ids[0], Kind::Unlike, 1).await;
+
+        let db_conn = db.get_connection().await.unwrap();
+        let events = meme_interactions::Entity::find()
+            .filter(meme_interactions::Column::MemeId.eq(ids[0]))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events.iter().filter(|item| item.kind == Kind::Like).count(),
+            2
+        );
+        assert!(events.iter().all(|item| item.client_id == "client"));
+    }
+
+    #[tokio::test]
+    async fn trending_success() {
+        let db = TestDB::new().await;
+        let ids = post_memes(&db, &["hot", "warm", "cold"]).await;
+
+        interact(&db, ids[0], Kind::Like, 3).await;
+        interact(&db, ids[1], Kind::Like, 1).await;
+        interact(&db, ids[1], Kind::Unlike, 1).await;
+        interact(&db, ids[2], Kind::Unlike, 2).await;
+        interact_before(&db, ids[1], Kind::Like, 5, 10).await;
+
+        let repo: GenTrendingRepo<MockCache<_, _>, TestDB> = GenTrendingRepo::new(db.clone());
+
+        let list = repo.trending(TrendingWindow::Day).await.unwrap();
+        assert_eq!(
+            list.iter().map(|item| item.meme.id).collect::<Vec<_>>(),
+            vec![ids[0], ids[1]]
+        );
+        assert_eq!(list[0].window_likes, 3);
+        assert_eq!(list[1].window_unlikes, 1);
+
+        let list = repo.trending(TrendingWindow::Month).await.unwrap();
+        assert_eq!(
+            list.iter().map(|item| item.meme.id).collect::<Vec<_>>(),
+            vec![ids[1], ids[0]]
+        );
+        assert_eq!(list[0].window_likes, 6);
+    }
+
+    #[tokio::test]
+    async fn refresh_hot_scores_success() {
+        let db = TestDB::new().await;
+        let ids = post_memes(&db, &["fresh", "stale"]).await;
+
+        interact(&db, ids[0], Kind::Like, 1).await;
+        interact_before(&db, ids[1], Kind::Like, 2, 3).await;
+        interact_before(&db, ids[1], Kind::Like, 10, 20).await;
+
+        let repo: GenTrendingRepo<MockCache<_, _>, TestDB> = GenTrendingRepo::new(db.clone());
+        assert_eq!(repo.refresh_hot_scores().await.unwrap(), 2);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let scores = db_entity::meme_hot_scores::Entity::find()
+            .all(&db_conn)
+            .await
+            .unwrap();
+        let score = |id| {
+            scores
+                .iter()
+                .find(|item| item.meme_id == id)
+                .map(|item| item.score)
+                .unwrap()
+        };
+        assert!(score(ids[0]) > 0.9);
+        assert!((score(ids[1]) - 0.25).abs() < 0.01);
+
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        let list = meme_repo
+            .get_paginated_memes(
+                1,
+                0,
+                ListFilter {
+                    categories: vec!["trending".to_owned()],
+                    sort: SortMode::Hot,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            list.list.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![ids[0], ids[1]]
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_interactions_success() {
+        let db = TestDB::new().await;
+        let ids = post_memes(&db, &["purged"]).await;
+
+        interact(&db, ids[0], Kind::Like, 1).await;
+        interact_before(&db, ids[0], Kind::Like, 2, 40).await;
+
+        let repo: GenTrendingRepo<MockCache<_, _>, TestDB> = GenTrendingRepo::new(db.clone());
+        assert_eq!(repo.purge_interactions().await.unwrap(), 2);
+
+        let db_conn = db.get_connection().await.unwrap();
+        let count = meme_interactions::Entity::find()
+            .count(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use db_entity::meme_urls::Bed;
+use reqwest::{Client, Url, header::CONTENT_LENGTH, redirect::Policy};
+
+use crate::business::{media::MediaError, storage::Storage};
+
+use super::{UploadError, UploadResult};
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// whether the files of `bed` are kept in `storage`, they are then read by their key
+pub fn is_stored(storage: &(impl Storage + ?Sized + Sync), bed: Bed) -> bool {
+    bed != Bed::SuperBed && bed == storage.bed()
+}
+
+/// reads the bytes of a file of `meme_urls`, from the storage or from the image bed it was
+/// posted from.
+///
+/// a download only goes to a public address: the host is resolved and checked first, the
+/// connection is pinned to the checked address and redirects are not followed, so a posted url
+/// cannot reach the server itself, its network or the cloud metadata
+pub struct MediaFetcher {
+    /// a download is cut off after it
+    max_size: usize,
+    allow_private_hosts: bool,
+}
+
+impl MediaFetcher {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            allow_private_hosts: false,
+        }
+    }
+
+    /// also download from private addresses, e.g. an image bed in the same network
+    pub fn allow_private_hosts(mut self, allow: bool) -> Self {
+        self.allow_private_hosts = allow;
+        self
+    }
+
+    /// the file of `storage` by its key, a file of another bed by its url
+    pub async fn load(
+        &self,
+        storage: &(impl Storage + ?Sized + Sync),
+        bed: Bed,
+        bed_id: &str,
+        url: &str,
+    ) -> UploadResult<Vec<u8>> {
+        if is_stored(storage, bed) {
+            Ok(storage.get(bed_id).await?)
+        } else {
+            self.download(url).await
+        }
+    }
+
+    pub async fn download(&self, url: &str) -> UploadResult<Vec<u8>> {
+        let url = Url::parse(url).map_err(|e| UploadError::Download(format!("{}: {}", url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(UploadError::Download(format!("unsupported url: {}", url)));
+        }
+
+        let mut client = Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .redirect(Policy::none());
+        let host = url
+            .host_str()
+            .ok_or_else(|| UploadError::Download(format!("no host: {}", url)))?;
+        match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            Ok(ip) => self.check_address(ip, &url)?,
+            Err(_) => {
+                let address = self.resolve(host, &url).await?;
+                client = client.resolve(host, address);
+            }
+        }
+        let client = client
+            .build()
+            .map_err(|e| UploadError::Download(e.to_string()))?;
+
+        let max_size = self.max_size;
+        let mut response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| UploadError::Download(e.to_string()))?;
+        if response.status().is_redirection() {
+            return Err(UploadError::Download(format!(
+                "{} redirects, post the url it redirects to",
+                url
+            )));
+        }
+        response = response
+            .error_for_status()
+            .map_err(|e| UploadError::Download(e.to_string()))?;
+
+        let length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok());
+        if let Some(length) = length
+            && length > max_size
+        {
+            return Err(MediaError::TooLarge(length, max_size).into());
+        }
+
+        let mut bytes = vec![];
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| UploadError::Download(e.to_string()))?
+        {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > max_size {
+                return Err(MediaError::TooLarge(bytes.len(), max_size).into());
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// the address to connect to, every address of the host has to be public
+    async fn resolve(&self, domain: &str, url: &Url) -> UploadResult<SocketAddr> {
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|e| UploadError::Download(format!("{}: {}", domain, e)))?
+            .collect();
+
+        for address in addresses.iter() {
+            self.check_address(address.ip(), url)?;
+        }
+
+        addresses
+            .into_iter()
+            .next()
+            .ok_or_else(|| UploadError::Download(format!("{} has no address", domain)))
+    }
+
+    fn check_address(&self, ip: IpAddr, url: &Url) -> UploadResult<()> {
+        if self.allow_private_hosts || is_public(ip) {
+            Ok(())
+        } else {
+            Err(UploadError::PrivateHost(url.to_string()))
+        }
+    }
+}
+
+/// an address on the internet, rather than one of the server, its network, the link or the
+/// cloud metadata (169.254.169.254)
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                // shared address space of carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xC0 == 64)
+                // reserved, 240.0.0.0/4
+                || first >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    // unique local, fc00::/7
+                    || first & 0xFE00 == 0xFC00
+                    // link local, fe80::/10
+                    || first & 0xFFC0 == 0xFE80)
+            }
+        },
+    }
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use db_entity::{meme_thumbnails, meme_urls};
+use futures::{StreamExt, stream};
+use migration::async_trait;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::{
+    business::{
+        media::{
+            MediaError, MediaPolicy, first_frame, inspect, perceptual::dhash,
+            placeholder::placeholder, same_format,
+        },
+        meme::PostMemeUrl,
+        storage::{Storage, StoredFile, check_key},
+    },
+    config::AllowMemeFormats,
+    db::DbConnHelper,
+};
+
+use super::{
+    Upload, UploadError, UploadFile, UploadRepository, UploadResult,
+    fetch::{MediaFetcher, is_stored},
+};
+
+/// the files loaded at the same time by `verify_all`
+const VERIFY_CONCURRENCY: usize = 4;
+/// for all the files of `verify_all`
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct GenUploadRepo<TDb: DbConnHelper> {
+    db: TDb,
+    storage: Box<dyn Storage + 'static + Sync + Send>,
+    policy: MediaPolicy,
+    fetcher: MediaFetcher,
+}
+
+impl<TDb: DbConnHelper> GenUploadRepo<TDb> {
+    pub fn new(db: TDb, storage: impl Storage + 'static + Sync + Send) -> Self {
+        Self::with_policy(db, storage, MediaPolicy::default())
+    }
+
+    pub fn with_policy(
+        db: TDb,
+        storage: impl Storage + 'static + Sync + Send,
+        policy: MediaPolicy,
+    ) -> Self {
+        Self {
+            db,
+            storage: Box::new(storage),
+            fetcher: MediaFetcher::new(policy.max_size()),
+            policy,
+        }
+    }
+
+    /// see `MediaFetcher::allow_private_hosts`
+    pub fn allow_private_hosts(mut self, allow: bool) -> Self {
+        self.fetcher = self.fetcher.allow_private_hosts(allow);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb> UploadRepository for GenUploadRepo<TDb>
+where
+    TDb: DbConnHelper + Sync + Send,
+{
+    async fn upload(&self, file: UploadFile) -> UploadResult<Upload> {
+        if file.bytes.is_empty() {
+            return Err(UploadError::EmptyFile);
+        }
+
+        let info = inspect(&file.bytes, &self.policy)?;
+        if let Some(declared) = declared_format(&file)
+            && !same_format(declared, info.format)
+        {
+            return Err(MediaError::FormatMismatch(declared, info.format).into());
+        }
+
+        let bed_id = format!("{}.{}", info.hash, info.format.to_string().to_lowercase());
+
+        self.storage
+            .put(&bed_id, file.bytes, content_type_of(info.format))
+            .await?;
+
+        let url = self.storage.url(&bed_id);
+        Ok(Upload {
+            cover: url.clone(),
+            url,
+            format: info.format,
+            hash: info.hash,
+            bed: self.storage.bed(),
+            bed_id,
+        })
+    }
+
+    async fn verify(&self, mut media: PostMemeUrl) -> UploadResult<PostMemeUrl> {
+        let bytes = self
+            .fetcher
+            .load(&self.storage, media.bed, &media.bed_id, &media.url)
+            .await?;
+        if bytes.is_empty() {
+            return Err(UploadError::EmptyFile);
+        }
+
+        let info = inspect(&bytes, &self.policy)?;
+        if !same_format(media.format, info.format) {
+            return Err(MediaError::FormatMismatch(media.format, info.format).into());
+        }
+
+        media.format = info.format;
+        media.hash = info.hash;
+        // the checked file is the one served, whatever urls the client sent
+        if is_stored(&self.storage, media.bed) {
+            media.url = self.storage.url(&media.bed_id);
+            media.cover = media.url.clone();
+        }
+        // decoding a large picture takes a while
+        (media.phash, media.placeholder) = tokio::task::spawn_blocking(move || {
+            let image = first_frame(&bytes, info.format).ok();
+            (
+                image.as_ref().map(dhash),
+                image.as_ref().and_then(placeholder),
+            )
+        })
+        .await
+        .unwrap_or_default();
+
+        Ok(media)
+    }
+
+    async fn verify_all(&self, media: Vec<PostMemeUrl>) -> Vec<UploadResult<PostMemeUrl>> {
+        let deadline = Instant::now() + VERIFY_TIMEOUT;
+
+        stream::iter(media)
+            .map(|media| async move {
+                tokio::time::timeout_at(deadline, self.verify(media))
+                    .await
+                    .unwrap_or(Err(UploadError::Timeout))
+            })
+            .buffered(VERIFY_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    async fn remove(&self, bed_id: String) -> UploadResult<()> {
+        check_key(&bed_id)?;
+
+        let db = self.db.get_connection().await?;
+
+        let used = meme_urls::Entity::find()
+            .filter(meme_urls::Column::Bed.eq(self.storage.bed()))
+            .filter(meme_urls::Column::BedId.eq(bed_id.as_str()))
+            .count(&db)
+            .await?;
+        if used > 0 {
+            return Err(UploadError::InUse);
+        }
+
+        self.storage.delete(&bed_id).await?;
+
+        Ok(())
+    }
+
+    async fn remove_purged(&self, files: Vec<StoredFile>) -> UploadResult<u64> {
+        let db = self.db.get_connection().await?;
+        let bed = self.storage.bed();
+
+        let mut removed = 0;
+        for file in files.into_iter().filter(|file| file.bed == bed) {
+            if check_key(&file.key).is_err() {
+                continue;
+            }
+
+            // the same file can be posted in several memes
+            let used = meme_urls::Entity::find()
+                .filter(meme_urls::Column::Bed.eq(bed))
+                .filter(meme_urls::Column::BedId.eq(file.key.as_str()))
+                .count(&db)
+                .await?
+                + meme_thumbnails::Entity::find()
+                    .filter(meme_thumbnails::Column::Bed.eq(bed))
+                    .filter(meme_thumbnails::Column::BedId.eq(file.key.as_str()))
+                    .count(&db)
+                    .await?;
+            if used > 0 {
+                continue;
+            }
+
+            // the rows are gone already, a file left behind is not worth failing the purge
+            match self.storage.delete(&file.key).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("delete purged file {} failed: {}", file.key, e),
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// by the extension of the file name, or by the content type without one,
+/// `None` when neither names a supported format
+fn declared_format(file: &UploadFile) -> Option<AllowMemeFormats> {
+    let extension = file
+        .file_name
+        .as_deref()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension);
+    let subtype = file
+        .content_type
+        .as_deref()
+        .and_then(|content_type| content_type.split_once('/'))
+        .map(|(_, subtype)| subtype);
+
+    extension
+        .and_then(|value| AllowMemeFormats::try_from(value).ok())
+        .or_else(|| subtype.and_then(|value| AllowMemeFormats::try_from(value).ok()))
+}
+
+fn content_type_of(format: AllowMemeFormats) -> &'static str {
+    match format {
+        AllowMemeFormats::JPG | AllowMemeFormats::JPEG => "image/jpeg",
+        AllowMemeFormats::PNG => "image/png",
+        AllowMemeFormats::GIF => "image/gif",
+        AllowMemeFormats::WEBP => "image/webp",
+        AllowMemeFormats::WEBM => "video/webm",
+    }
+}
@@ -0,0 +1,117 @@
+//! Uploads
+//!
+//! the admin UI uploads the files of a meme before posting it, they are stored by the configured
+//! `Storage` under their content hash and referenced from `PostMemeUrl` by bed and key.
+//!
+//! every file is checked by `business::media`, also the ones posted from another bed
+
+use db_entity::meme_urls::Bed;
+use migration::async_trait;
+use sea_orm::DbErr;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::config::AllowMemeFormats;
+
+use super::{
+    media::MediaError,
+    meme::PostMemeUrl,
+    storage::{StorageError, StoredFile},
+};
+
+pub mod fetch;
+pub mod gen_upload_repo;
+
+#[cfg(test)]
+mod test;
+
+pub type UploadResult<T> = Result<T, UploadError>;
+
+#[async_trait::async_trait]
+pub trait UploadRepository {
+    /// store the file, the same bytes of the same format are stored once
+    async fn upload(&self, _file: UploadFile) -> UploadResult<Upload> {
+        unimplemented!()
+    }
+
+    /// load the file of `media` and check it, the format, the hash, the perceptual hash and
+    /// the placeholder are replaced by the ones of the content,
+    /// the url and the cover of a stored file by its url in the storage
+    async fn verify(&self, _media: PostMemeUrl) -> UploadResult<PostMemeUrl> {
+        unimplemented!()
+    }
+
+    /// `verify` the files a few at a time, in their order, the ones not done before the
+    /// deadline of the whole batch fail with `UploadError::Timeout`
+    async fn verify_all(&self, _media: Vec<PostMemeUrl>) -> Vec<UploadResult<PostMemeUrl>> {
+        unimplemented!()
+    }
+
+    /// delete a stored file that no meme refers to
+    async fn remove(&self, _bed_id: String) -> UploadResult<()> {
+        unimplemented!()
+    }
+
+    /// delete the files of purged memes, the ones of other beds and the ones another meme
+    /// still refers to are kept, return the count of deleted files
+    async fn remove_purged(&self, _files: Vec<StoredFile>) -> UploadResult<u64> {
+        unimplemented!()
+    }
+}
+
+pub struct PanicUploadRepository;
+
+impl UploadRepository for PanicUploadRepository {}
+
+#[derive(Debug)]
+pub struct UploadFile {
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+/// what `PostMemeUrl` needs to refer to a stored file
+#[derive(Serialize, Debug)]
+pub struct Upload {
+    pub url: String,
+    pub cover: String,
+    pub format: AllowMemeFormats,
+    pub hash: String,
+    pub bed: Bed,
+    pub bed_id: String,
+}
+
+#[derive(Error, Debug)]
+pub enum UploadError {
+    #[error("the file is empty")]
+    EmptyFile,
+    #[error("{0}")]
+    Media(#[from] MediaError),
+    #[error("download failed: {0}")]
+    Download(String),
+    #[error("not a public host: {0}")]
+    PrivateHost(String),
+    #[error("the files took too long to load")]
+    Timeout,
+    #[error("the file is used by a meme")]
+    InUse,
+    #[error("{0}")]
+    Storage(#[from] StorageError),
+    #[error("Database error ocurrs: {0}")]
+    DatabaseErr(#[from] DbErr),
+}
+
+impl UploadError {
+    /// the file itself is wrong, rather than the server
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            UploadError::EmptyFile
+                | UploadError::Media(_)
+                | UploadError::Download(_)
+                | UploadError::PrivateHost(_)
+                | UploadError::Timeout
+                | UploadError::Storage(StorageError::InvalidKey(_) | StorageError::NotFound(_))
+        )
+    }
+}
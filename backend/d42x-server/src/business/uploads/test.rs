@@ -0,0 +1,417 @@
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, path::PathBuf};
+
+    use axum::{Router, http::StatusCode, response::Redirect, routing::get};
+    use db_entity::meme_urls::Bed;
+    use image::{ImageFormat, RgbImage};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::Uuid};
+
+    use crate::{
+        business::{
+            cache::MockCache,
+            media::{MediaError, hash},
+            meme::{MemeRepository, PostMeme, PostMemeUrl, gen_meme_repo::GenMemeRepo},
+            storage::{StorageError, StoredFile, local::LocalStorage},
+            uploads::{
+                Upload, UploadError, UploadFile, UploadRepository, fetch::is_public,
+                gen_upload_repo::GenUploadRepo,
+            },
+        },
+        config::AllowMemeFormats,
+        db::{DbConnHelper, test::TestDB},
+    };
+
+    fn new_repo(db: TestDB) -> (GenUploadRepo<TestDB>, PathBuf) {
+        let root = std::env::temp_dir().join(format!("d42x-uploads-{}", Uuid::new_v4()));
+        let repo = GenUploadRepo::new(db, LocalStorage::new(&root, "/media"));
+        (repo, root)
+    }
+
+    fn encode(format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        RgbImage::new(8, 6).write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    fn file(name: Option<&str>, content_type: Option<&str>, bytes: &[u8]) -> UploadFile {
+        UploadFile {
+            file_name: name.map(str::to_owned),
+            content_type: content_type.map(str::to_owned),
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    fn post_url(upload: &Upload) -> PostMemeUrl {
+        PostMemeUrl {
+            url: upload.url.clone(),
+            cover: upload.cover.clone(),
+            format: upload.format,
+            hash: upload.hash.clone(),
+            phash: None,
+            placeholder: None,
+            bed: upload.bed,
+            bed_id: upload.bed_id.clone(),
+        }
+    }
+
+    fn remote_url(url: String, format: AllowMemeFormats) -> PostMemeUrl {
+        PostMemeUrl {
+            url,
+            cover: String::new(),
+            format,
+            hash: String::from("declared by the client"),
+            phash: None,
+            placeholder: None,
+            bed: Bed::SuperBed,
+            bed_id: String::from("superbed-id"),
+        }
+    }
+
+    fn post_upload(upload: &Upload) -> PostMeme {
+        PostMeme {
+            username: "tester".to_owned(),
+            categories: vec![],
+            message: String::new(),
+            memes: vec![post_url(upload)],
+        }
+    }
+
+    /// an image bed on a random port serving `/meme.png`, `/text`, `/redirect` to the meme and
+    /// nothing else
+    async fn serve_bed() -> String {
+        let router = Router::new()
+            .route("/meme.png", get(|| async { encode(ImageFormat::Png) }))
+            .route("/text", get(|| async { "not an image" }))
+            .route(
+                "/redirect",
+                get(|| async { Redirect::temporary("/meme.png") }),
+            )
+            .fallback(|| async { StatusCode::NOT_FOUND });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn upload_success() {
+        let (repo, root) = new_repo(TestDB::new().await);
+
+        let png = encode(ImageFormat::Png);
+        let upload = repo
+            .upload(file(Some("cat.PNG"), Some("image/png"), &png))
+            .await
+            .unwrap();
+        let hash = hash(&png);
+        assert_eq!(upload.hash, hash);
+        assert_eq!(upload.format, AllowMemeFormats::PNG);
+        assert_eq!(upload.bed, Bed::Local);
+        assert_eq!(upload.bed_id, format!("{}.png", hash));
+        assert_eq!(upload.url, format!("/media/{}.png", hash));
+        assert_eq!(upload.cover, upload.url);
+        assert_eq!(std::fs::read(root.join(&upload.bed_id)).unwrap(), png);
+
+        // the same bytes are stored once
+        let again = repo
+            .upload(file(Some("another.png"), None, &png))
+            .await
+            .unwrap();
+        assert_eq!(again.bed_id, upload.bed_id);
+
+        // the content tells the format when nothing is declared
+        let upload = repo
+            .upload(file(Some("blob"), None, &encode(ImageFormat::Jpeg)))
+            .await
+            .unwrap();
+        assert_eq!(upload.format, AllowMemeFormats::JPEG);
+        assert!(upload.bed_id.ends_with(".jpeg"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn upload_rejected() {
+        let (repo, root) = new_repo(TestDB::new().await);
+
+        let res = repo.upload(file(Some("cat.png"), None, b"")).await;
+        assert!(matches!(res, Err(UploadError::EmptyFile)));
+
+        let res = repo
+            .upload(file(Some("cat.png"), Some("image/png"), b"cat"))
+            .await;
+        assert!(matches!(
+            res,
+            Err(UploadError::Media(MediaError::UnknownFormat))
+        ));
+
+        let res = repo
+            .upload(file(Some("cat.gif"), None, &encode(ImageFormat::Png)))
+            .await;
+        assert!(matches!(
+            res,
+            Err(UploadError::Media(MediaError::FormatMismatch(
+                AllowMemeFormats::GIF,
+                AllowMemeFormats::PNG
+            )))
+        ));
+
+        assert!(!root.exists());
+    }
+
+    #[tokio::test]
+    async fn remove_unused_only() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+
+        let posted = repo
+            .upload(file(Some("cat.png"), None, &encode(ImageFormat::Png)))
+            .await
+            .unwrap();
+        let unused = repo
+            .upload(file(Some("dog.gif"), None, &encode(ImageFormat::Gif)))
+            .await
+            .unwrap();
+
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db);
+        meme_repo
+            .post_memes(vec![post_upload(&posted)])
+            .await
+            .unwrap();
+
+        let res = repo.remove(posted.bed_id.clone()).await;
+        assert!(matches!(res, Err(UploadError::InUse)));
+        assert!(root.join(&posted.bed_id).exists());
+
+        repo.remove(unused.bed_id.clone()).await.unwrap();
+        assert!(!root.join(&unused.bed_id).exists());
+
+        let res = repo.remove("../cat.png".to_owned()).await;
+        assert!(matches!(res, Err(UploadError::Storage(_))));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove_purged_success() {
+        let db = TestDB::new().await;
+        let (repo, root) = new_repo(db.clone());
+
+        let shared = repo
+            .upload(file(Some("cat.png"), None, &encode(ImageFormat::Png)))
+            .await
+            .unwrap();
+        let single = repo
+            .upload(file(Some("dog.gif"), None, &encode(ImageFormat::Gif)))
+            .await
+            .unwrap();
+
+        let meme_repo: GenMemeRepo<MockCache<_, _>, TestDB> = GenMemeRepo::new(db.clone());
+        meme_repo
+            .post_memes(vec![
+                post_upload(&shared),
+                post_upload(&shared),
+                post_upload(&single),
+            ])
+            .await
+            .unwrap();
+
+        let db_conn = db.get_connection().await.unwrap();
+        let urls = db_entity::meme_urls::Entity::find()
+            .filter(db_entity::meme_urls::Column::Bed.eq(Bed::Local))
+            .all(&db_conn)
+            .await
+            .unwrap();
+        assert_eq!(urls.len(), 3);
+        // one of the memes of the shared file is kept
+        let kept = urls
+            .iter()
+            .find(|url| url.bed_id == shared.bed_id)
+            .unwrap()
+            .meme_id;
+        for url in urls.iter().filter(|url| url.meme_id != kept) {
+            meme_repo
+                .get_meme(url.meme_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .delete(Uuid::nil())
+                .await
+                .unwrap();
+        }
+
+        let mut purged = meme_repo.purge_deleted_memes(0).await.unwrap();
+        assert_eq!(purged.count, 2);
+        purged.files.push(StoredFile {
+            bed: Bed::SuperBed,
+            key: String::from("superbed-id"),
+        });
+        assert_eq!(repo.remove_purged(purged.files).await.unwrap(), 1);
+        assert!(root.join(&shared.bed_id).exists());
+        assert!(!root.join(&single.bed_id).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_stored_success() {
+        let (repo, root) = new_repo(TestDB::new().await);
+        let upload = repo
+            .upload(file(Some("cat.jpg"), None, &encode(ImageFormat::Jpeg)))
+            .await
+            .unwrap();
+
+        let mut declared = post_url(&upload);
+        declared.format = AllowMemeFormats::JPG;
+        declared.hash = String::from("declared by the client");
+        let verified = repo.verify(declared).await.unwrap();
+        assert_eq!(verified.format, AllowMemeFormats::JPEG);
+        assert_eq!(verified.hash, upload.hash);
+        assert!(verified.phash.is_some());
+        assert!(verified.placeholder.is_some());
+
+        // the urls of a stored file are its own, not the ones sent
+        let mut declared = post_url(&upload);
+        declared.url = String::from("https://elsewhere.test/other.png");
+        declared.cover = String::from("https://elsewhere.test/cover.png");
+        let verified = repo.verify(declared).await.unwrap();
+        assert_eq!(verified.url, upload.url);
+        assert_eq!(verified.cover, upload.url);
+
+        let mut declared = post_url(&upload);
+        declared.format = AllowMemeFormats::WEBM;
+        let res = repo.verify(declared).await;
+        assert!(matches!(
+            res,
+            Err(UploadError::Media(MediaError::FormatMismatch(..)))
+        ));
+
+        let mut declared = post_url(&upload);
+        declared.bed_id = String::from("missing.png");
+        let res = repo.verify(declared).await;
+        assert!(matches!(
+            res,
+            Err(UploadError::Storage(StorageError::NotFound(_)))
+        ));
+        assert!(res.unwrap_err().is_rejection());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_downloaded_success() {
+        let (repo, _) = new_repo(TestDB::new().await);
+        let repo = repo.allow_private_hosts(true);
+        let bed = serve_bed().await;
+
+        let verified = repo
+            .verify(remote_url(
+                format!("{}/meme.png", bed),
+                AllowMemeFormats::PNG,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(verified.hash, hash(&encode(ImageFormat::Png)));
+        assert_eq!(verified.bed, Bed::SuperBed);
+        assert_eq!(verified.bed_id, "superbed-id");
+
+        let res = repo
+            .verify(remote_url(format!("{}/text", bed), AllowMemeFormats::PNG))
+            .await;
+        assert!(matches!(
+            res,
+            Err(UploadError::Media(MediaError::UnknownFormat))
+        ));
+
+        for url in [
+            format!("{}/missing.png", bed),
+            String::from("file:///etc/passwd"),
+        ] {
+            let res = repo.verify(remote_url(url, AllowMemeFormats::PNG)).await;
+            assert!(matches!(res, Err(UploadError::Download(_))));
+        }
+
+        // redirects are not followed
+        let res = repo
+            .verify(remote_url(
+                format!("{}/redirect", bed),
+                AllowMemeFormats::PNG,
+            ))
+            .await;
+        assert!(matches!(res, Err(UploadError::Download(_))));
+    }
+
+    #[tokio::test]
+    async fn verify_private_host() {
+        let (repo, _) = new_repo(TestDB::new().await);
+        let bed = serve_bed().await;
+
+        for url in [
+            format!("{}/meme.png", bed),
+            bed.replace("127.0.0.1", "localhost") + "/meme.png",
+            String::from("http://[::1]/meme.png"),
+            String::from("http://169.254.169.254/latest/meta-data"),
+            String::from("http://[::ffff:10.0.0.1]/meme.png"),
+        ] {
+            let res = repo.verify(remote_url(url, AllowMemeFormats::PNG)).await;
+            assert!(matches!(res, Err(UploadError::PrivateHost(_))), "{:?}", res);
+        }
+    }
+
+    #[test]
+    fn is_public_success() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "172.31.255.255",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_all_success() {
+        let (repo, root) = new_repo(TestDB::new().await);
+        let repo = repo.allow_private_hosts(true);
+        let bed = serve_bed().await;
+
+        let upload = repo
+            .upload(file(None, None, &encode(ImageFormat::Png)))
+            .await
+            .unwrap();
+        let res = repo
+            .verify_all(vec![
+                remote_url(format!("{}/text", bed), AllowMemeFormats::PNG),
+                post_url(&upload),
+                remote_url(format!("{}/meme.png", bed), AllowMemeFormats::PNG),
+            ])
+            .await;
+
+        // in the order of the files
+        assert_eq!(res.len(), 3);
+        assert!(matches!(
+            res[0],
+            Err(UploadError::Media(MediaError::UnknownFormat))
+        ));
+        assert_eq!(res[1].as_ref().unwrap().bed, Bed::Local);
+        assert_eq!(res[2].as_ref().unwrap().bed, Bed::SuperBed);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,12 +1,20 @@
-use std::fmt::Display;
+use std::{fmt::Display, net::IpAddr, str::FromStr, time::Duration};
 
-use hmac::Mac;
 use lazy_static::lazy_static;
-use sea_orm::prelude::Uuid;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
 use soft_aes::aes::AES_BLOCK_SIZE;
 
+use crate::{
+    authentication::keyring::JwtKeyring,
+    business::{
+        media::{FormatLimit, MediaPolicy},
+        storage::s3::S3Config,
+        throttle::ThrottlePolicy,
+        thumbnails::ThumbnailPolicy,
+    },
+    db::shared_db_helper::PoolOptions,
+};
+
 lazy_static! {
     pub static ref KEY: String = dotenv::var("AES_KEY").expect("not found AES_KEY");
     pub static ref IV: [u8; 16] = {
@@ -21,17 +29,129 @@ lazy_static! {
     };
     pub static ref DATABASE_URL: String =
         dotenv::var("DATABASE_URL").expect("not found DATABASE_URL");
+    pub static ref DB_POOL: PoolOptions = PoolOptions {
+        max_connections: var_or("DB_MAX_CONNECTIONS", 10),
+        min_connections: var_or("DB_MIN_CONNECTIONS", 1),
+        connect_timeout: Duration::from_secs(var_or("DB_CONNECT_TIMEOUT", 8)),
+        idle_timeout: Duration::from_secs(var_or("DB_IDLE_TIMEOUT", 600)),
+        acquire_timeout: Duration::from_secs(var_or("DB_ACQUIRE_TIMEOUT", 8)),
+    };
     pub static ref ADDRESS: String = dotenv::var("ADDRESS").expect("not found ADDRESS");
     pub static ref CORS: String = dotenv::var("CORS").expect("not found CORS");
-    static ref _JWT_KEY: Uuid = Uuid::new_v4();
-    pub static ref JWT_KEY: hmac::Hmac<Sha256> =
-        hmac::Hmac::new_from_slice(_JWT_KEY.as_bytes()).unwrap();
+    /// issuer shown by authenticator apps
+    pub static ref TOTP_ISSUER: String = var_or("TOTP_ISSUER", String::from("d42x"));
+    /// proxies allowed to set `X-Forwarded-For`, separated by `,`
+    pub static ref TRUSTED_PROXIES: Vec<IpAddr> = dotenv::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| ip.parse().unwrap_or_else(|_| panic!("Wrong TRUSTED_PROXIES: {}", ip)))
+        .collect();
+    /// emoji reactions besides `like` and `unlike`, separated by `,`
+    pub static ref REACTIONS: Vec<String> = dotenv::var("REACTIONS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|reaction| !reaction.is_empty())
+        .map(|reaction| {
+            if reaction.len() > 32 {
+                panic!("Wrong REACTIONS: {}", reaction)
+            }
+            reaction.to_owned()
+        })
+        .collect();
+    /// where the uploaded memes are stored, `local` or `s3`
+    pub static ref MEDIA_STORAGE: String = var_or("MEDIA_STORAGE", String::from("local"));
+    /// the directory of the `local` storage, served under `/media`
+    pub static ref MEDIA_DIR: String = var_or("MEDIA_DIR", String::from("wwwroot/media"));
+    /// the prefix of the urls of the `local` storage, e.g. a CDN in front of `/media`
+    pub static ref MEDIA_URL: String = var_or("MEDIA_URL", String::from(crate::app::MEDIA_PATH));
+    /// the largest upload request, in bytes
+    pub static ref MAX_UPLOAD_SIZE: usize = var_or("MAX_UPLOAD_SIZE", 20 * 1024 * 1024);
+    /// `MAX_SIZE_<FORMAT>` in bytes and `MAX_DIMENSION_<FORMAT>` in pixels, e.g. `MAX_SIZE_GIF`
+    pub static ref MEDIA_POLICY: MediaPolicy = {
+        let default = MediaPolicy::default();
+        let limit = |format: &str, default: FormatLimit| FormatLimit {
+            max_size: var_or(&format!("MAX_SIZE_{}", format), default.max_size),
+            max_dimension: var_or(&format!("MAX_DIMENSION_{}", format), default.max_dimension),
+        };
+        MediaPolicy {
+            jpeg: limit("JPEG", default.jpeg),
+            png: limit("PNG", default.png),
+            gif: limit("GIF", default.gif),
+            webp: limit("WEBP", default.webp),
+            webm: limit("WEBM", default.webm),
+        }
+    };
+    /// `THUMBNAIL_WIDTHS` in pixels separated by `,` and `THUMBNAIL_QUALITY` from 0 to 100
+    pub static ref THUMBNAIL_POLICY: ThumbnailPolicy = {
+        let default = ThumbnailPolicy::default();
+        let widths: Vec<u32> = dotenv::var("THUMBNAIL_WIDTHS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|width| !width.is_empty())
+            .map(|width| {
+                width
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Wrong THUMBNAIL_WIDTHS: {}", width))
+            })
+            .collect();
+        ThumbnailPolicy {
+            widths: if widths.is_empty() { default.widths } else { widths },
+            quality: var_or("THUMBNAIL_QUALITY", default.quality),
+            max_size: MEDIA_POLICY.max_size(),
+        }
+    };
+    pub static ref S3: S3Config = S3Config {
+        endpoint: var_or("S3_ENDPOINT", String::new()),
+        region: var_or("S3_REGION", String::from("us-east-1")),
+        bucket: var_or("S3_BUCKET", String::new()),
+        access_key: var_or("S3_ACCESS_KEY", String::new()),
+        secret_key: var_or("S3_SECRET_KEY", String::new()),
+        public_url: var_or("S3_PUBLIC_URL", String::new()),
+    };
+    pub static ref LOGIN_THROTTLE: ThrottlePolicy = ThrottlePolicy {
+        free_attempts: var_or("LOGIN_FREE_ATTEMPTS", 3),
+        backoff_base: Duration::from_secs(var_or("LOGIN_BACKOFF_BASE", 1)),
+        backoff_max: Duration::from_secs(var_or("LOGIN_BACKOFF_MAX", 15 * 60)),
+        lockout_threshold: var_or("LOGIN_LOCKOUT_THRESHOLD", 10),
+        lockout_duration: Duration::from_secs(var_or("LOGIN_LOCKOUT_DURATION", 30 * 60)),
+    };
+    /// an ip is shared by the users behind a NAT, it is locked later than a username
+    pub static ref LOGIN_IP_LOCKOUT_THRESHOLD: u32 = var_or("LOGIN_IP_LOCKOUT_THRESHOLD", 50);
+    pub static ref JWT_KEY_FILE: Option<String> =
+        dotenv::var("JWT_KEY_FILE").ok().filter(|v| !v.is_empty());
+    pub static ref JWT_KEYRING: JwtKeyring = match JWT_KEY_FILE.as_deref() {
+        Some(path) =>
+            JwtKeyring::load(path).unwrap_or_else(|e| panic!("Wrong JWT_KEY_FILE: {}", e)),
+        None => {
+            let secret = dotenv::var("JWT_SECRET").expect("not found JWT_SECRET or JWT_KEY_FILE");
+            JwtKeyring::from_secret(&var_or("JWT_KID", String::from("default")), &secret)
+                .unwrap_or_else(|e| panic!("Wrong JWT_SECRET: {}", e))
+        }
+    };
     pub static ref ISS: String = dotenv::var("ISS").expect("not found ISS");
     pub static ref AUD: String = dotenv::var("AUD").expect("not found AUD");
+    /// lifetime of an access token, in seconds
     pub static ref EXP: usize = dotenv::var("EXP").expect("not found EXP").parse().unwrap();
+    /// a session expires if it is not refreshed within it
+    pub static ref REFRESH_EXP: Duration =
+        Duration::from_secs(var_or("REFRESH_EXP", 7 * 24 * 60 * 60));
+}
+
+/// read an optional variable, fall back to `default` if not set
+fn var_or<T: FromStr>(key: &str, default: T) -> T {
+    match dotenv::var(key) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .unwrap_or_else(|_| panic!("Wrong {}: {}", key, value)),
+        _ => default,
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AllowMemeFormats {
     JPG,
     JPEG,
@@ -0,0 +1,198 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use sea_orm::prelude::Uuid;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::{AccountRepoSSType, AuditRepoSSType, SessionRepoSSType},
+    },
+    authentication::AuthInformation,
+    business::{
+        Pagination,
+        accounts::{Account, NewAccount, UpdateAccount},
+        audit::{Actor, AuditLog, GetFilter},
+    },
+    controllers::{ApiError, ApiResult},
+};
+
+use super::models::{CreateAccountReq, ResetPasswordReq, UpdateAccountReq};
+
+fn actor(admin_user: &AuthInformation) -> Actor {
+    Actor::account(admin_user.id, &admin_user.username)
+}
+
+pub async fn list_accounts(
+    _: Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+) -> ApiResult<Json<Vec<Account>>> {
+    let list = account_repo.repo.get_accounts().await?;
+
+    Ok(Json(list))
+}
+
+pub async fn get_account(
+    Path(id): Path<Uuid>,
+    _: Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+) -> ApiResult<Json<Account>> {
+    let account = account_repo
+        .repo
+        .get_account(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Account not found: {}", id)))?;
+
+    Ok(Json(account))
+}
+
+pub async fn create_account(
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+    Json(create_req): Json<CreateAccountReq>,
+) -> ApiResult<Json<Account>> {
+    create_req.validate()?;
+
+    let account = account_repo
+        .repo
+        .create_account(
+            &actor(&admin_user),
+            NewAccount {
+                username: create_req.username,
+                email: create_req.email,
+                hashed_password: create_req.hashed_password,
+                role: create_req.role,
+            },
+        )
+        .await?;
+
+    Ok(Json(account))
+}
+
+/// the sessions of the account are revoked when its role changes
+pub async fn update_account(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+    Json(update_req): Json<UpdateAccountReq>,
+) -> ApiResult<Json<Account>> {
+    update_req.validate()?;
+
+    let role_before = account_repo
+        .repo
+        .get_account(id)
+        .await?
+        .and_then(|account| account.role);
+
+    let account = account_repo
+        .repo
+        .update_account(
+            &actor(&admin_user),
+            id,
+            UpdateAccount {
+                email: update_req.email,
+                role: update_req.role,
+            },
+        )
+        .await?;
+
+    if account.role != role_before {
+        session_repo.repo.revoke_all(id).await?;
+    }
+
+    Ok(Json(account))
+}
+
+/// every session of the account is revoked, it logs in with the new password
+pub async fn reset_password(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+    Json(reset_req): Json<ResetPasswordReq>,
+) -> ApiResult<StatusCode> {
+    reset_req.validate()?;
+
+    account_repo
+        .repo
+        .reset_password(&actor(&admin_user), id, reset_req.hashed_password)
+        .await?;
+    session_repo.repo.revoke_all(id).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn disable_account(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+) -> ApiResult<StatusCode> {
+    account_repo
+        .repo
+        .set_disabled(&actor(&admin_user), id, true)
+        .await?;
+    session_repo.repo.revoke_all(id).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn enable_account(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+) -> ApiResult<StatusCode> {
+    account_repo
+        .repo
+        .set_disabled(&actor(&admin_user), id, false)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn delete_account(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+) -> ApiResult<StatusCode> {
+    account_repo
+        .repo
+        .delete_account(&actor(&admin_user), id)
+        .await?;
+    session_repo.repo.revoke_all(id).await?;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct AuditQueryParams {
+    /// page number, base 1
+    pub page: u64,
+    pub size: u64,
+    pub action: Option<String>,
+    pub actor_id: Option<Uuid>,
+}
+
+pub async fn list_audit_logs(
+    Query(params): Query<AuditQueryParams>,
+    _: Authorized<perm::ManageAccounts>,
+    State(audit_repo): State<AuditRepoSSType>,
+) -> ApiResult<Json<Pagination<AuditLog>>> {
+    let list = audit_repo
+        .repo
+        .get_paginated_logs(GetFilter {
+            page: params.page,
+            size: params.size,
+            action: params.action,
+            actor_id: params.actor_id,
+        })
+        .await?;
+
+    Ok(Json(list))
+}
@@ -1,28 +1,132 @@
 use axum::{
-    Extension, Json,
-    extract::{Path, State},
+    Json,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::Response,
 };
 use sea_orm::prelude::Uuid;
 
+use validator::Validate;
+
 use crate::{
-    app::shared_data::{AccountRepoSSType, CategoryRepoSSType},
-    authentication::AuthInformation,
-    need_administrator,
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::{CategoryRepoSSType, MemeRepoSSType},
+    },
+    business::category::{CategoryItem, CategoryNode},
+    controllers::ApiResult,
+};
+
+use super::models::{
+    CreateCategoryReq, DeleteCategoryQuery, MergeCategoriesReq, MoveCategoryReq, RenameCategoryReq,
+    SetAliasesReq,
 };
 
-pub async fn update_categories(
-    Path(meme_id): Path<Uuid>,
-    Extension(admin_user): Extension<AuthInformation>,
+pub async fn get_category_tree(
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+) -> ApiResult<Json<Vec<CategoryNode>>> {
+    let cate = category_repo.read().await;
+    let tree = cate.repo.get_category_tree().await?;
+
+    Ok(Json(tree))
+}
+
+pub async fn create_category(
+    _: Authorized<perm::EditCategories>,
     State(category_repo): State<CategoryRepoSSType>,
-    State(account_repo): State<AccountRepoSSType>,
-    Json(list): Json<Vec<String>>,
-) -> Response {
-    need_administrator!(account_repo, admin_user.id);
+    Json(req): Json<CreateCategoryReq>,
+) -> ApiResult<(StatusCode, Json<CategoryItem>)> {
+    req.validate()?;
 
     let cate = category_repo.write().await;
-    cate.repo.update_catgories(meme_id, list).await;
+    let item = cate.repo.create_category(req.name, req.parent).await?;
+
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+/// the cached meme pages filtered with descendants depend on the tree, they are cleared
+pub async fn move_category(
+    Path(id): Path<Uuid>,
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    Json(req): Json<MoveCategoryReq>,
+) -> ApiResult<StatusCode> {
+    {
+        let cate = category_repo.write().await;
+        cate.repo.move_category(id, req.parent).await?;
+    }
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+/// replace the aliases of a category, the client filter resolves them to the category
+pub async fn set_category_aliases(
+    Path(id): Path<Uuid>,
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    Json(req): Json<SetAliasesReq>,
+) -> ApiResult<StatusCode> {
+    req.validate()?;
+
+    {
+        let cate = category_repo.write().await;
+        cate.repo.set_aliases(id, req.aliases).await?;
+    }
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn rename_category(
+    Path(id): Path<Uuid>,
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    Json(req): Json<RenameCategoryReq>,
+) -> ApiResult<StatusCode> {
+    req.validate()?;
+
+    {
+        let cate = category_repo.write().await;
+        cate.repo.rename_category(id, req.name).await?;
+    }
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn merge_categories(
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    Json(req): Json<MergeCategoriesReq>,
+) -> ApiResult<StatusCode> {
+    req.validate()?;
+
+    {
+        let cate = category_repo.write().await;
+        cate.repo.merge_categories(req.sources, req.target).await?;
+    }
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn delete_category(
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeleteCategoryQuery>,
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+) -> ApiResult<StatusCode> {
+    {
+        let cate = category_repo.write().await;
+        cate.repo.delete_category(id, query.reassign).await?;
+    }
+    meme_repo.repo.clear_cache().await;
 
-    StatusCode::OK.into_response()
+    Ok(StatusCode::OK)
 }
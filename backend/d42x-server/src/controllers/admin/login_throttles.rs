@@ -0,0 +1,45 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+
+use crate::{
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::ThrottleRepoSSType,
+    },
+    business::throttle::LoginThrottle,
+    controllers::{ApiError, ApiResult},
+};
+
+#[derive(Deserialize)]
+pub struct ThrottleQueryParams {
+    /// only the locked usernames and ips
+    #[serde(default)]
+    pub locked: bool,
+}
+
+pub async fn list_login_throttles(
+    Query(params): Query<ThrottleQueryParams>,
+    _: Authorized<perm::ManageAccounts>,
+    State(throttle_repo): State<ThrottleRepoSSType>,
+) -> ApiResult<Json<Vec<LoginThrottle>>> {
+    let list = throttle_repo.repo.get_throttles(params.locked).await?;
+
+    Ok(Json(list))
+}
+
+/// unlock a username or an ip, and forget its failures
+pub async fn clear_login_throttle(
+    Path((kind, key)): Path<(String, String)>,
+    _: Authorized<perm::ManageAccounts>,
+    State(throttle_repo): State<ThrottleRepoSSType>,
+) -> ApiResult<StatusCode> {
+    let kind =
+        db_entity::login_throttles::Kind::try_from(kind.as_str()).map_err(ApiError::BadRequest)?;
+    throttle_repo.repo.clear(kind, &key).await?;
+
+    Ok(StatusCode::OK)
+}
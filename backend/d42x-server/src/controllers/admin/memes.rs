@@ -1,53 +1,150 @@
 use axum::{
-    Extension, Json,
+    Json,
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Response,
 };
+// a list in the query string repeats its key
+use axum_extra::extract::Query as FilterQuery;
+use std::collections::HashMap;
+
 use sea_orm::prelude::Uuid;
-use serde::Deserialize;
-use tracing::error;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 use crate::{
-    app::shared_data::{AccountRepoSSType, CategoryRepoSSType, MemeRepoSSType},
-    authentication::AuthInformation,
-    business::meme::{GetFilter, Meme},
-    need_administrator,
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::{CategoryRepoSSType, DuplicateRepoSSType, MemeRepoSSType, UploadRepoSSType},
+    },
+    business::{
+        Pagination,
+        audit::Actor,
+        duplicates::{DEFAULT_MAX_DISTANCE, DuplicateCluster, DuplicateMeme},
+        meme::{GetFilter, Meme, MemeError},
+    },
+    controllers::{ApiError, ApiResult, list_filter::ListFilterQuery},
+};
+
+use super::models::{
+    DuplicateMedia, DuplicatesQuery, MediaIndex, MergeMemesReq, PostMemesQuery, PostMemesReq,
+    PostMemesRes, RejectedMedia,
 };
 
-use super::models::PostMemesReq;
+const DEFAULT_CLUSTERS: usize = 50;
 
+/// every file is checked by its content first, when any of them is rejected nothing is posted
+/// and the answer is `422` with the rejected files. a file already posted or repeated in the
+/// request is answered with `409` unless `allow_duplicates` is set
 pub async fn post_memes(
-    Extension(admin_user): Extension<AuthInformation>,
-    State(account_repo): State<AccountRepoSSType>,
+    _: Authorized<perm::PostMemes>,
+    Query(params): Query<PostMemesQuery>,
     State(category_repo): State<CategoryRepoSSType>,
     State(meme_repo): State<MemeRepoSSType>,
+    State(upload_repo): State<UploadRepoSSType>,
+    State(duplicate_repo): State<DuplicateRepoSSType>,
     Json(post_memes): Json<Vec<PostMemesReq>>,
-) -> Response {
-    need_administrator!(account_repo, admin_user.id);
+) -> ApiResult<(StatusCode, Json<PostMemesRes>)> {
+    for item in post_memes.iter() {
+        item.validate()?;
+    }
 
-    let new_catepories: Vec<_> = post_memes
+    let mut new_memes: Vec<_> = post_memes
+        .into_iter()
+        .map(crate::business::meme::PostMeme::from)
+        .collect();
+
+    let files = new_memes
         .iter()
-        .map(|item| item.categories.clone())
-        .flatten()
+        .flat_map(|item| item.memes.iter().cloned())
         .collect();
+    let mut verified = upload_repo.repo.verify_all(files).await.into_iter();
 
-    {
-        let cate = category_repo.read().await;
-        cate.repo.append_categories(new_catepories).await;
+    let mut rejected = vec![];
+    for (meme, item) in new_memes.iter_mut().enumerate() {
+        for (media, url) in item.memes.iter_mut().enumerate() {
+            let result = verified
+                .next()
+                .ok_or_else(|| ApiError::Internal("a file was not verified".to_owned()))?;
+            match result {
+                Ok(verified) => *url = verified,
+                Err(e) if e.is_rejection() => rejected.push(RejectedMedia {
+                    meme,
+                    media,
+                    url: url.url.clone(),
+                    error: e.to_string(),
+                }),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+    if !rejected.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(PostMemesRes {
+                rejected,
+                duplicates: vec![],
+            }),
+        ));
     }
 
-    let new_memes = post_memes
-        .into_iter()
-        .map(crate::business::meme::PostMeme::from)
+    let hashes = new_memes
+        .iter()
+        .flat_map(|item| item.memes.iter().map(|url| url.hash.clone()))
         .collect();
+    let posted = duplicate_repo.repo.find_by_hashes(hashes).await?;
 
-    if let Err(e) = meme_repo.repo.post_memes(new_memes).await {
-        error!("post memes error: {:?}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR).into_response()
-    } else {
-        StatusCode::OK.into_response()
+    let mut duplicates = vec![];
+    let mut seen: HashMap<&str, MediaIndex> = HashMap::new();
+    for (meme, item) in new_memes.iter().enumerate() {
+        for (media, url) in item.memes.iter().enumerate() {
+            let posted: Vec<_> = posted
+                .iter()
+                .filter(|file| file.hash == url.hash)
+                .cloned()
+                .collect();
+            let earlier = seen.get(url.hash.as_str()).copied();
+            if !posted.is_empty() || earlier.is_some() {
+                duplicates.push(DuplicateMedia {
+                    meme,
+                    media,
+                    url: url.url.clone(),
+                    posted,
+                    earlier,
+                });
+            }
+            seen.entry(url.hash.as_str())
+                .or_insert(MediaIndex { meme, media });
+        }
     }
+    if !duplicates.is_empty() && !params.allow_duplicates {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(PostMemesRes {
+                rejected,
+                duplicates,
+            }),
+        ));
+    }
+
+    let new_catepories: Vec<_> = new_memes
+        .iter()
+        .flat_map(|item| item.categories.clone())
+        .collect();
+
+    {
+        let cate = category_repo.read().await;
+        cate.repo.append_categories(new_catepories).await?;
+    }
+
+    meme_repo.repo.post_memes(new_memes).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PostMemesRes {
+            rejected,
+            duplicates,
+        }),
+    ))
 }
 
 #[derive(Deserialize)]
@@ -60,48 +157,170 @@ pub struct QueryParams {
 
 pub async fn list_memes(
     Query(params): Query<QueryParams>,
-    State(account_repo): State<AccountRepoSSType>,
+    FilterQuery(filter): FilterQuery<ListFilterQuery>,
     State(meme_repo): State<MemeRepoSSType>,
-    Extension(admin_user): Extension<AuthInformation>,
-) -> Response {
-    need_administrator!(account_repo, admin_user.id);
-
-    let page = if params.page > 0 { params.page } else { 0 };
-    let mut status = params.status;
-    if let Some(s) = &status {
-        if db_entity::memes::Status::try_from(s.as_str()).is_err() {
-            status = None;
-        }
-    }
+    _: Authorized<perm::ViewMemes>,
+) -> ApiResult<Json<Pagination<Meme>>> {
+    let status = params
+        .status
+        .map(|s| db_entity::memes::Status::try_from(s.as_str()))
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
 
-    let list: crate::business::Pagination<Meme> = meme_repo
+    let list = meme_repo
         .repo
         .get_paginated_all_memes(GetFilter {
-            page,
+            page: params.page,
             size: params.size,
-            status: status.map(|s| db_entity::memes::Status::try_from(s.as_str()).unwrap()),
+            status,
+            filter: filter.into(),
         })
-        .await;
+        .await?;
 
-    Json(list).into_response()
+    Ok(Json(list))
 }
 
 pub async fn delete_meme(
     Path(id): Path<Uuid>,
-    State(account_repo): State<AccountRepoSSType>,
+    State(category_repo): State<CategoryRepoSSType>,
     State(meme_repo): State<MemeRepoSSType>,
-    Extension(admin_user): Extension<AuthInformation>,
-) -> Response {
-    need_administrator!(account_repo, admin_user.id);
-
-    if let Ok(Some(meme)) = meme_repo.repo.get_meme(id).await {
-        if let Ok(_) = meme.delete().await {
-            StatusCode::OK
-        } else {
-            StatusCode::INTERNAL_SERVER_ERROR
-        }
-    } else {
-        StatusCode::INTERNAL_SERVER_ERROR
-    }
-    .into_response()
+    Authorized(admin_user, _): Authorized<perm::DeleteMemes>,
+) -> ApiResult<StatusCode> {
+    let meme = meme_repo
+        .repo
+        .get_meme(id)
+        .await?
+        .ok_or(MemeError::HasNotAnyMeme)?;
+    meme.delete(admin_user.id).await?;
+
+    meme_repo.repo.clear_cache().await;
+    category_repo.read().await.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct TrashQueryParams {
+    /// page number, base 1
+    pub page: u64,
+    pub size: u64,
+}
+
+pub async fn list_trash(
+    Query(params): Query<TrashQueryParams>,
+    State(meme_repo): State<MemeRepoSSType>,
+    _: Authorized<perm::DeleteMemes>,
+) -> ApiResult<Json<Pagination<Meme>>> {
+    let list = meme_repo
+        .repo
+        .get_paginated_deleted_memes(params.page, params.size)
+        .await?;
+
+    Ok(Json(list))
+}
+
+/// replace the categories of the meme of the path
+pub async fn update_meme_categories(
+    Path(id): Path<Uuid>,
+    _: Authorized<perm::EditCategories>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    Json(list): Json<Vec<String>>,
+) -> ApiResult<StatusCode> {
+    let cate = category_repo.write().await;
+    cate.repo.update_catgories(id, list).await?;
+
+    // new categories can be created, the pages list the categories of their memes
+    cate.repo.clear_cache().await;
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn restore_meme(
+    Path(id): Path<Uuid>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    _: Authorized<perm::DeleteMemes>,
+) -> ApiResult<StatusCode> {
+    let meme = meme_repo
+        .repo
+        .get_meme(id)
+        .await?
+        .ok_or(MemeError::HasNotAnyMeme)?;
+    meme.restore().await?;
+
+    meme_repo.repo.clear_cache().await;
+    category_repo.read().await.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct PurgeQueryParams {
+    /// purge the memes deleted more than `days` days ago
+    pub days: u32,
+}
+
+#[derive(Serialize)]
+pub struct PurgeRes {
+    pub purged: u64,
+    /// the stored files deleted with the memes
+    pub removed_files: u64,
+}
+
+pub async fn purge_trash(
+    Query(params): Query<PurgeQueryParams>,
+    State(meme_repo): State<MemeRepoSSType>,
+    State(upload_repo): State<UploadRepoSSType>,
+    _: Authorized<perm::DeleteMemes>,
+) -> ApiResult<Json<PurgeRes>> {
+    let purged = meme_repo.repo.purge_deleted_memes(params.days).await?;
+    let removed_files = upload_repo.repo.remove_purged(purged.files).await?;
+
+    Ok(Json(PurgeRes {
+        purged: purged.count,
+        removed_files,
+    }))
+}
+
+/// groups of memes with the same or similar files, see `business::duplicates`
+pub async fn list_duplicates(
+    Query(params): Query<DuplicatesQuery>,
+    State(duplicate_repo): State<DuplicateRepoSSType>,
+    _: Authorized<perm::ViewMemes>,
+) -> ApiResult<Json<Vec<DuplicateCluster>>> {
+    let clusters = duplicate_repo
+        .repo
+        .clusters(
+            params.max_distance.unwrap_or(DEFAULT_MAX_DISTANCE),
+            params.limit.unwrap_or(DEFAULT_CLUSTERS),
+        )
+        .await?;
+
+    Ok(Json(clusters))
+}
+
+/// fold `duplicate` into the meme of the path, keeping the likes of both
+pub async fn merge_memes(
+    Path(id): Path<Uuid>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    State(duplicate_repo): State<DuplicateRepoSSType>,
+    Authorized(admin_user, _): Authorized<perm::DeleteMemes>,
+    Json(merge_req): Json<MergeMemesReq>,
+) -> ApiResult<Json<DuplicateMeme>> {
+    let meme = duplicate_repo
+        .repo
+        .merge(
+            &Actor::account(admin_user.id, &admin_user.username),
+            id,
+            merge_req.duplicate,
+        )
+        .await?;
+
+    meme_repo.repo.clear_cache().await;
+    category_repo.read().await.repo.clear_cache().await;
+
+    Ok(Json(meme))
 }
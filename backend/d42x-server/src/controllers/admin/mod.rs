@@ -1,118 +1,189 @@
+mod accounts;
 mod category;
+mod login_throttles;
 mod memes;
 mod models;
+mod roles;
+mod suggests;
+mod two_factor;
+mod uploads;
 
-use axum::{
-    Extension,
-    extract::State,
-    http::{HeaderMap, StatusCode, header::ORIGIN},
-    response::{IntoResponse, Json, Response},
-};
-use models::{ChangePwdReq, LogInReq, LogInRes};
+use axum::{Extension, extract::State, http::StatusCode, response::Json};
+use models::{ChangePwdReq, LogInReq, LogInRes, RefreshTokenReq, RefreshTokenRes};
 use tracing::warn;
 use validator::Validate;
 
 use crate::{
-    app::shared_data::AccountRepoSSType,
-    authentication::{AuthInformation, gen_jwt_token},
-    business::accounts::admin::AdministratorError,
+    app::{
+        client_ip::ClientIp,
+        shared_data::{AccountRepoSSType, SessionRepoSSType, ThrottleRepoSSType},
+    },
+    authentication::{AccessTokenMeta, AuthInformation, gen_challenge_token, gen_jwt_token},
+    business::accounts::admin::Administrator,
+    controllers::{ApiError, ApiResult},
 };
 
+pub use accounts::*;
 pub use category::*;
+pub use login_throttles::*;
 pub use memes::*;
+pub use roles::*;
+pub use suggests::*;
+pub use two_factor::*;
+pub use uploads::*;
 
-#[macro_export]
-macro_rules! need_administrator {
-    ($acc_repo: expr, $admin_id: expr) => {
-        use axum::response::IntoResponse;
-        if $acc_repo
-            .repo
-            .get_administractor_by_id($admin_id)
-            .await
-            .is_none()
-        {
-            return axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        }
-    };
-}
-
-pub async fn check_logged_in() -> Response {
-    StatusCode::OK.into_response()
+pub async fn check_logged_in() -> StatusCode {
+    StatusCode::OK
 }
 
+/// failures are throttled per username and per client ip, see `business::throttle`.
+///
+/// an account with two-factor enabled gets a challenge token, see `log_in_two_factor`
 pub(crate) async fn log_in(
-    header: HeaderMap,
+    ClientIp(client_ip): ClientIp,
     State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+    State(throttle_repo): State<ThrottleRepoSSType>,
     Json(log_in_req): Json<LogInReq>,
-) -> Response {
-    if let Err(e) = log_in_req.validate() {
-        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
-    }
+) -> ApiResult<Json<LogInRes>> {
+    log_in_req.validate()?;
+
+    let client_ip = client_ip.to_string();
+    throttle_repo
+        .repo
+        .check(&log_in_req.username, &client_ip)
+        .await?;
 
     let admin = account_repo
         .repo
         .get_administractor_by_username(log_in_req.username.clone())
-        .await;
-    match admin {
-        Some(mut admin) => {
-            if !admin.verify_password(&log_in_req.hashed_password) {
-                return StatusCode::BAD_REQUEST.into_response();
-            }
+        .await?;
 
-            let origin = header.get(ORIGIN).unwrap();
-            admin
-                .log_in_activity(origin.to_str().unwrap())
-                .await
-                .unwrap();
-
-            let jwt_token = gen_jwt_token(&admin.model.id, &admin.model.username);
-
-            Json(LogInRes {
-                username: admin.model.username,
-                email: admin.model.email,
-                jwt_token,
-            })
-            .into_response()
-        }
+    let admin = match admin {
+        Some(admin) if admin.verify_password(&log_in_req.hashed_password) => Some(admin),
+        Some(_) => None,
         None => {
             warn!("not found: {}", log_in_req.username);
-
-            StatusCode::NO_CONTENT.into_response()
+            None
         }
+    };
+
+    let Some(mut admin) = admin else {
+        throttle_repo
+            .repo
+            .record_failure(&log_in_req.username, &client_ip)
+            .await?;
+        return Err(ApiError::BadRequest(String::from(
+            "incorrect username or password",
+        )));
+    };
+
+    // the throttle is only reset once the second factor passes
+    if admin.totp_enabled() {
+        return Ok(Json(LogInRes {
+            challenge_token: Some(gen_challenge_token(&admin.model.id, &admin.model.username)),
+            username: admin.model.username,
+            email: admin.model.email,
+            jwt_token: None,
+            refresh_token: None,
+        }));
     }
+
+    throttle_repo
+        .repo
+        .record_success(&log_in_req.username, &client_ip)
+        .await?;
+
+    start_session(&mut admin, &client_ip, &session_repo).await
+}
+
+/// every factor has passed, issue the tokens
+async fn start_session(
+    admin: &mut Administrator,
+    client_ip: &str,
+    session_repo: &SessionRepoSSType,
+) -> ApiResult<Json<LogInRes>> {
+    admin.log_in_activity(client_ip).await?;
+
+    let access = AccessTokenMeta::new();
+    let session = session_repo.repo.create(admin.model.id, &access).await?;
+    let jwt_token = gen_jwt_token(
+        &admin.model.id,
+        &admin.model.username,
+        admin.role(),
+        &session.session_id,
+        &access,
+    );
+
+    Ok(Json(LogInRes {
+        username: admin.model.username.clone(),
+        email: admin.model.email.clone(),
+        challenge_token: None,
+        jwt_token: Some(jwt_token),
+        refresh_token: Some(session.refresh_token),
+    }))
+}
+
+pub async fn log_out(
+    Extension(admin_user): Extension<AuthInformation>,
+    State(session_repo): State<SessionRepoSSType>,
+) -> ApiResult<StatusCode> {
+    session_repo.repo.revoke(admin_user.session_id).await?;
+
+    Ok(StatusCode::OK)
 }
 
+/// exchange the refresh token for a new access token and a new refresh token
+pub(crate) async fn refresh_token(
+    State(session_repo): State<SessionRepoSSType>,
+    Json(refresh_req): Json<RefreshTokenReq>,
+) -> ApiResult<Json<RefreshTokenRes>> {
+    refresh_req.validate()?;
+
+    let access = AccessTokenMeta::new();
+    let session = session_repo
+        .repo
+        .refresh(&refresh_req.refresh_token, &access)
+        .await?;
+    let jwt_token = gen_jwt_token(
+        &session.account_id,
+        &session.username,
+        &session.role,
+        &session.session_id,
+        &access,
+    );
+
+    Ok(Json(RefreshTokenRes {
+        jwt_token,
+        refresh_token: session.refresh_token,
+    }))
+}
+
+/// every session of the account is revoked after changing, log in again
 pub async fn change_password(
     Extension(admin_user): Extension<AuthInformation>,
     State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
     Json(change_pwd_req): Json<ChangePwdReq>,
-) -> impl IntoResponse {
-    need_administrator!(account_repo, admin_user.id);
-
-    if let Err(e) = change_pwd_req.validate() {
-        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
-    }
+) -> ApiResult<StatusCode> {
+    change_pwd_req.validate()?;
 
     let admin = account_repo
         .repo
         .get_administractor_by_id(admin_user.id)
-        .await;
+        .await?;
     match admin {
         Some(mut admin) if admin.model.username == admin_user.username => {
-            match admin
+            admin
                 .change_password(
                     &change_pwd_req.hashed_password_current,
                     &change_pwd_req.hashed_password_new,
                 )
-                .await
-            {
-                Ok(_) => StatusCode::OK.into_response(),
-                Err(AdministratorError::IncorrectPassword) => {
-                    StatusCode::BAD_REQUEST.into_response()
-                }
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-            }
+                .await?;
+            session_repo.repo.revoke_all(admin.model.id).await?;
+
+            Ok(StatusCode::OK)
         }
-        _ => (StatusCode::BAD_REQUEST, "Not the same admin user").into_response(),
+        _ => Err(ApiError::Forbidden),
     }
 }
@@ -1,8 +1,9 @@
+use db_entity::meme_urls::Bed;
 use sea_orm::prelude::Uuid;
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-use crate::config::AllowMemeFormats;
+use crate::{business::duplicates::PostedFile, config::AllowMemeFormats};
 
 #[derive(Serialize, Deserialize, Debug, Validate)]
 pub(crate) struct LogInReq {
@@ -12,11 +13,58 @@ pub(crate) struct LogInReq {
     pub hashed_password: String,
 }
 
+/// with two-factor enabled only `challenge_token` is set,
+/// it is exchanged for the tokens by `TwoFactorLogInReq`
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct LogInRes {
     pub username: String,
     pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub(crate) struct TwoFactorLogInReq {
+    #[validate(length(min = 1, code = "challenge_token_empty"))]
+    pub challenge_token: String,
+    /// a TOTP code or a recovery code
+    #[validate(length(min = 1, code = "code_empty"))]
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct ConfirmTotpReq {
+    #[validate(length(min = 1, code = "code_empty"))]
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct DisableTotpReq {
+    #[validate(length(min = 6, code = "hashed_password empty"))]
+    pub hashed_password: String,
+    #[validate(length(min = 1, code = "code_empty"))]
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecoveryCodesRes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub(crate) struct RefreshTokenReq {
+    #[validate(length(min = 1, code = "refresh_token_empty"))]
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct RefreshTokenRes {
     pub jwt_token: String,
+    pub refresh_token: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Validate)]
@@ -27,6 +75,119 @@ pub struct ChangePwdReq {
     pub hashed_password_new: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct CreateAccountReq {
+    #[validate(
+        length(min = 3, max = 32, code = "username_length"),
+        custom(function = "validate_username")
+    )]
+    pub username: String,
+    #[validate(email(code = "email_invalid"))]
+    pub email: String,
+    /// the stored form of the password, see `business::accounts::hash_password`
+    #[validate(length(min = 6, code = "hashed_password empty"))]
+    pub hashed_password: String,
+    #[validate(length(min = 1, code = "role_empty"))]
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct UpdateAccountReq {
+    #[validate(email(code = "email_invalid"))]
+    pub email: Option<String>,
+    #[validate(length(min = 1, code = "role_empty"))]
+    pub role: Option<String>,
+}
+
+/// `parent` as `None` creates a top category
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct CreateCategoryReq {
+    #[validate(
+        length(min = 1, max = 64, code = "name_length"),
+        custom(function = "validate_category_name")
+    )]
+    pub name: String,
+    pub parent: Option<Uuid>,
+}
+
+/// `parent` as `None` moves the category to the top
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MoveCategoryReq {
+    pub parent: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct RenameCategoryReq {
+    #[validate(
+        length(min = 1, max = 64, code = "name_length"),
+        custom(function = "validate_category_name")
+    )]
+    pub name: String,
+}
+
+/// the other names of a category
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct SetAliasesReq {
+    #[validate(
+        length(max = 32, code = "aliases_length"),
+        custom(function = "validate_aliases")
+    )]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct MergeCategoriesReq {
+    #[validate(length(min = 1, code = "sources_empty"))]
+    pub sources: Vec<Uuid>,
+    pub target: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeleteCategoryQuery {
+    /// tag the memes with the default category instead
+    #[serde(default)]
+    pub reassign: bool,
+}
+
+/// categories were stored as `;a;b;` once, the separator is still kept out of names
+fn validate_category_name(name: &str) -> Result<(), ValidationError> {
+    if name.contains(';') || name.trim() != name {
+        return Err(ValidationError::new("name_invalid"));
+    }
+
+    Ok(())
+}
+
+/// an alias is held to the rules of a name
+fn validate_aliases(aliases: &[String]) -> Result<(), ValidationError> {
+    for alias in aliases {
+        let length = alias.chars().count();
+        if length == 0 || length > 64 {
+            return Err(ValidationError::new("alias_length"));
+        }
+        validate_category_name(alias)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct ResetPasswordReq {
+    #[validate(length(min = 6, code = "hashed_password empty"))]
+    pub hashed_password: String,
+}
+
+fn validate_username(username: &str) -> Result<(), ValidationError> {
+    if username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("username_chars"))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Validate)]
 pub struct PostMemesReq {
     pub username: String,
@@ -43,9 +204,55 @@ pub struct Meme {
     pub cover: String,
     pub format: AllowMemeFormats,
     pub hash: String,
+    /// `super_bed` unless the file comes from `/api/admin/uploads`
+    #[serde(default)]
+    pub bed: Bed,
     pub bed_id: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PostMemesQuery {
+    /// post the files already posted anyway, they are still listed in `duplicates`
+    #[serde(default)]
+    pub allow_duplicates: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PostMemesRes {
+    pub rejected: Vec<RejectedMedia>,
+    pub duplicates: Vec<DuplicateMedia>,
+}
+
+/// a file that did not pass `business::media`
+#[derive(Serialize, Debug)]
+pub struct RejectedMedia {
+    /// index of the meme in the request
+    pub meme: usize,
+    /// index of the file in the meme
+    pub media: usize,
+    pub url: String,
+    pub error: String,
+}
+
+/// a file with the same content as a posted one or as an earlier one of the request
+#[derive(Serialize, Debug)]
+pub struct DuplicateMedia {
+    /// index of the meme in the request
+    pub meme: usize,
+    /// index of the file in the meme
+    pub media: usize,
+    pub url: String,
+    pub posted: Vec<PostedFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earlier: Option<MediaIndex>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct MediaIndex {
+    pub meme: usize,
+    pub media: usize,
+}
+
 impl From<PostMemesReq> for crate::business::meme::PostMeme {
     fn from(value: PostMemesReq) -> Self {
         Self {
@@ -60,6 +267,9 @@ impl From<PostMemesReq> for crate::business::meme::PostMeme {
                     cover: p.cover,
                     format: p.format,
                     hash: p.hash,
+                    phash: None,
+                    placeholder: None,
+                    bed: p.bed,
                     bed_id: p.bed_id,
                 })
                 .collect(),
@@ -67,6 +277,19 @@ impl From<PostMemesReq> for crate::business::meme::PostMeme {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicatesQuery {
+    /// see `business::duplicates::DEFAULT_MAX_DISTANCE`
+    pub max_distance: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MergeMemesReq {
+    /// moved to trash once merged
+    pub duplicate: Uuid,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MemeItemRes {
     pub id: Uuid,
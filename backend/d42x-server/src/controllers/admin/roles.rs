@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+use crate::{
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::RoleRepoSSType,
+    },
+    business::{
+        audit::Actor,
+        roles::{Permission, Role},
+    },
+    controllers::ApiResult,
+};
+
+pub async fn list_roles(
+    _: Authorized<perm::ManageAccounts>,
+    State(role_repo): State<RoleRepoSSType>,
+) -> ApiResult<Json<Vec<Role>>> {
+    let list = role_repo.repo.get_roles().await?;
+
+    Ok(Json(list))
+}
+
+/// replace the permissions of a role, the tokens issued already keep the role name,
+/// so the change applies to them as well
+pub async fn set_role_permissions(
+    Path(name): Path<String>,
+    Authorized(admin_user, _): Authorized<perm::ManageAccounts>,
+    State(role_repo): State<RoleRepoSSType>,
+    Json(permissions): Json<Vec<Permission>>,
+) -> ApiResult<StatusCode> {
+    let actor = Actor::account(admin_user.id, &admin_user.username);
+    role_repo
+        .repo
+        .set_permissions(&actor, &name, permissions)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
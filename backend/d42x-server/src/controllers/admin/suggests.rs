@@ -0,0 +1,77 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use sea_orm::prelude::Uuid;
+use serde::Deserialize;
+
+use crate::{
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::{CategoryRepoSSType, MemeRepoSSType, SuggestRepoSSType},
+    },
+    business::{
+        Pagination,
+        suggests::{GetFilter, Suggestion},
+    },
+    controllers::{ApiError, ApiResult},
+};
+
+#[derive(Deserialize)]
+pub struct SuggestQueryParams {
+    /// page number, base 1
+    pub page: u64,
+    pub status: Option<String>,
+}
+
+pub async fn list_suggests(
+    Query(params): Query<SuggestQueryParams>,
+    State(suggest_repo): State<SuggestRepoSSType>,
+    _: Authorized<perm::ReviewSuggests>,
+) -> ApiResult<Json<Pagination<Suggestion>>> {
+    let status = params
+        .status
+        .map(|s| db_entity::suggests::Status::try_from(s.as_str()))
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+
+    let list = suggest_repo
+        .repo
+        .get_paginated_suggests(GetFilter {
+            page: params.page,
+            status,
+        })
+        .await?;
+
+    Ok(Json(list))
+}
+
+pub async fn approve_suggest(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ReviewSuggests>,
+    State(category_repo): State<CategoryRepoSSType>,
+    State(meme_repo): State<MemeRepoSSType>,
+    State(suggest_repo): State<SuggestRepoSSType>,
+) -> ApiResult<StatusCode> {
+    let cate = category_repo.read().await;
+    suggest_repo
+        .repo
+        .approve(id, admin_user.id, cate.repo.as_ref())
+        .await?;
+
+    cate.repo.clear_cache().await;
+    meme_repo.repo.clear_cache().await;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn refuse_suggest(
+    Path(id): Path<Uuid>,
+    Authorized(admin_user, _): Authorized<perm::ReviewSuggests>,
+    State(suggest_repo): State<SuggestRepoSSType>,
+) -> ApiResult<StatusCode> {
+    suggest_repo.repo.refuse(id, admin_user.id).await?;
+
+    Ok(StatusCode::OK)
+}
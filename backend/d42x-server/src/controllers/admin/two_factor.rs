@@ -0,0 +1,112 @@
+use axum::{Extension, Json, extract::State, http::StatusCode};
+use validator::Validate;
+
+use crate::{
+    app::{
+        client_ip::ClientIp,
+        shared_data::{AccountRepoSSType, SessionRepoSSType, ThrottleRepoSSType},
+    },
+    authentication::{AuthInformation, verify_challenge_token},
+    business::accounts::admin::{Administrator, TotpEnrollment},
+    config,
+    controllers::{ApiError, ApiResult},
+};
+
+use super::{
+    models::{ConfirmTotpReq, DisableTotpReq, LogInRes, RecoveryCodesRes, TwoFactorLogInReq},
+    start_session,
+};
+
+async fn current_administrator(
+    account_repo: &AccountRepoSSType,
+    admin_user: &AuthInformation,
+) -> ApiResult<Administrator> {
+    account_repo
+        .repo
+        .get_administractor_by_id(admin_user.id)
+        .await?
+        .ok_or(ApiError::Forbidden)
+}
+
+/// the second step of `log_in`, a TOTP code or a recovery code for the challenge token
+pub(crate) async fn log_in_two_factor(
+    ClientIp(client_ip): ClientIp,
+    State(account_repo): State<AccountRepoSSType>,
+    State(session_repo): State<SessionRepoSSType>,
+    State(throttle_repo): State<ThrottleRepoSSType>,
+    Json(two_factor_req): Json<TwoFactorLogInReq>,
+) -> ApiResult<Json<LogInRes>> {
+    two_factor_req.validate()?;
+
+    let (id, username) =
+        verify_challenge_token(&two_factor_req.challenge_token).ok_or(ApiError::Unauthorized)?;
+
+    let client_ip = client_ip.to_string();
+    throttle_repo.repo.check(&username, &client_ip).await?;
+
+    let mut admin = account_repo
+        .repo
+        .get_administractor_by_id(id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+    if !admin.verify_second_factor(&two_factor_req.code).await? {
+        throttle_repo
+            .repo
+            .record_failure(&username, &client_ip)
+            .await?;
+        return Err(ApiError::BadRequest(String::from(
+            "invalid two-factor code",
+        )));
+    }
+
+    throttle_repo
+        .repo
+        .record_success(&username, &client_ip)
+        .await?;
+
+    start_session(&mut admin, &client_ip, &session_repo).await
+}
+
+/// a pending secret and its otpauth uri, confirm it with a code to enable it
+pub async fn enroll_totp(
+    Extension(admin_user): Extension<AuthInformation>,
+    State(account_repo): State<AccountRepoSSType>,
+) -> ApiResult<Json<TotpEnrollment>> {
+    let mut admin = current_administrator(&account_repo, &admin_user).await?;
+
+    let enrollment = admin.begin_totp_enrollment(&config::TOTP_ISSUER).await?;
+
+    Ok(Json(enrollment))
+}
+
+/// enable two-factor, the recovery codes are only returned here
+pub async fn confirm_totp(
+    Extension(admin_user): Extension<AuthInformation>,
+    State(account_repo): State<AccountRepoSSType>,
+    Json(confirm_req): Json<ConfirmTotpReq>,
+) -> ApiResult<Json<RecoveryCodesRes>> {
+    confirm_req.validate()?;
+
+    let mut admin = current_administrator(&account_repo, &admin_user).await?;
+
+    let recovery_codes = admin.confirm_totp(&confirm_req.code).await?;
+
+    Ok(Json(RecoveryCodesRes { recovery_codes }))
+}
+
+pub async fn disable_totp(
+    Extension(admin_user): Extension<AuthInformation>,
+    State(account_repo): State<AccountRepoSSType>,
+    Json(disable_req): Json<DisableTotpReq>,
+) -> ApiResult<StatusCode> {
+    disable_req.validate()?;
+
+    let mut admin = current_administrator(&account_repo, &admin_user).await?;
+
+    admin
+        .disable_totp(&disable_req.hashed_password, &disable_req.code)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
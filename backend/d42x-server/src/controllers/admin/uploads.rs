@@ -0,0 +1,93 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+};
+use tracing::warn;
+
+use crate::{
+    app::{
+        authorization::{Authorized, perm},
+        shared_data::UploadRepoSSType,
+    },
+    business::uploads::{Upload, UploadFile},
+    controllers::{ApiError, ApiResult},
+};
+
+/// store every file field of the form, answer with what `PostMemeUrl` refers to them by.
+/// when one of them fails the ones already stored are deleted
+pub async fn upload_media(
+    _: Authorized<perm::PostMemes>,
+    State(upload_repo): State<UploadRepoSSType>,
+    multipart: Multipart,
+) -> ApiResult<Json<Vec<Upload>>> {
+    let mut uploads = vec![];
+    if let Err(err) = store_fields(&upload_repo, multipart, &mut uploads).await {
+        remove_stored(&upload_repo, uploads).await;
+        return Err(err);
+    }
+
+    if uploads.is_empty() {
+        return Err(ApiError::BadRequest(String::from("no file uploaded")));
+    }
+
+    Ok(Json(uploads))
+}
+
+async fn store_fields(
+    upload_repo: &UploadRepoSSType,
+    mut multipart: Multipart,
+    uploads: &mut Vec<Upload>,
+) -> ApiResult<()> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(e.body_text()))?
+    {
+        if field.file_name().is_none() {
+            continue;
+        }
+
+        let file_name = field.file_name().map(str::to_owned);
+        let content_type = field.content_type().map(str::to_owned);
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| ApiError::BadRequest(e.body_text()))?;
+
+        let upload = upload_repo
+            .repo
+            .upload(UploadFile {
+                file_name,
+                content_type,
+                bytes: bytes.to_vec(),
+            })
+            .await?;
+        uploads.push(upload);
+    }
+
+    Ok(())
+}
+
+/// the files a meme already refers to are kept by `remove`
+async fn remove_stored(upload_repo: &UploadRepoSSType, uploads: Vec<Upload>) {
+    let mut bed_ids: Vec<String> = uploads.into_iter().map(|upload| upload.bed_id).collect();
+    bed_ids.sort();
+    bed_ids.dedup();
+
+    for bed_id in bed_ids {
+        if let Err(e) = upload_repo.repo.remove(bed_id.clone()).await {
+            warn!("cannot remove the upload {}: {}", bed_id, e);
+        }
+    }
+}
+
+/// delete an uploaded file that was not posted
+pub async fn remove_upload(
+    _: Authorized<perm::PostMemes>,
+    Path(bed_id): Path<String>,
+    State(upload_repo): State<UploadRepoSSType>,
+) -> ApiResult<StatusCode> {
+    upload_repo.repo.remove(bed_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
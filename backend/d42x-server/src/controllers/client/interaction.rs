@@ -1,48 +1,160 @@
-use axum::{
-    extract::{Json, Path, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
-};
+use axum::extract::{Json, Path, State};
+use db_entity::meme_interactions::Kind;
 use sea_orm::prelude::Uuid;
+use serde::Serialize;
 
-use crate::{app::shared_data::MemeRepoSSType, business::meme::Interaction};
+use crate::{
+    app::{
+        client_ip::ClientIp,
+        shared_data::{MemeRepoSSType, ThrottleRepoSSType},
+        visitor::Visitor,
+    },
+    authentication::gen_visitor_token,
+    business::meme::{Interaction, MemeError},
+    controllers::ApiResult,
+};
 
 pub async fn like_increase(
     Path(id): Path<Uuid>,
+    visitor: Visitor,
     State(meme_repo): State<MemeRepoSSType>,
-) -> Response {
-    if let Ok(Some(meme)) = meme_repo.repo.get_meme(id).await {
-        if let Ok(_) = meme.increase_like().await {
-            StatusCode::OK
-        } else {
-            StatusCode::INTERNAL_SERVER_ERROR
-        }
-    } else {
-        StatusCode::INTERNAL_SERVER_ERROR
-    }
-    .into_response()
+) -> ApiResult<Json<Interaction>> {
+    vote(id, visitor, Kind::Like, true, meme_repo).await
+}
+
+pub async fn like_withdraw(
+    Path(id): Path<Uuid>,
+    visitor: Visitor,
+    State(meme_repo): State<MemeRepoSSType>,
+) -> ApiResult<Json<Interaction>> {
+    vote(id, visitor, Kind::Like, false, meme_repo).await
 }
 
 pub async fn unlike_increase(
     Path(id): Path<Uuid>,
+    visitor: Visitor,
+    State(meme_repo): State<MemeRepoSSType>,
+) -> ApiResult<Json<Interaction>> {
+    vote(id, visitor, Kind::Unlike, true, meme_repo).await
+}
+
+pub async fn unlike_withdraw(
+    Path(id): Path<Uuid>,
+    visitor: Visitor,
+    State(meme_repo): State<MemeRepoSSType>,
+) -> ApiResult<Json<Interaction>> {
+    vote(id, visitor, Kind::Unlike, false, meme_repo).await
+}
+
+/// cast or withdraw the vote, answer with the counters after it
+async fn vote(
+    id: Uuid,
+    visitor: Visitor,
+    kind: Kind,
+    cast: bool,
+    meme_repo: MemeRepoSSType,
+) -> ApiResult<Json<Interaction>> {
+    let meme = meme_repo
+        .repo
+        .get_published_meme(id)
+        .await?
+        .ok_or(MemeError::HasNotAnyMeme)?;
+
+    let voter = visitor.voter();
+    if cast {
+        meme.vote(&voter, kind).await?;
+    } else {
+        meme.withdraw_vote(&voter, kind).await?;
+    }
+
+    let interaction = meme_repo
+        .repo
+        .get_interactions(vec![id], Some(voter.key))
+        .await?
+        .pop()
+        .ok_or(MemeError::HasNotAnyMeme)?;
+
+    Ok(Json(interaction))
+}
+
+pub async fn reaction_add(
+    Path((id, reaction)): Path<(Uuid, String)>,
+    visitor: Visitor,
     State(meme_repo): State<MemeRepoSSType>,
-) -> Response {
-    if let Ok(Some(meme)) = meme_repo.repo.get_meme(id).await {
-        if let Ok(_) = meme.increase_unlike().await {
-            StatusCode::OK
-        } else {
-            StatusCode::INTERNAL_SERVER_ERROR
-        }
+) -> ApiResult<Json<Interaction>> {
+    react(id, reaction, visitor, true, meme_repo).await
+}
+
+pub async fn reaction_remove(
+    Path((id, reaction)): Path<(Uuid, String)>,
+    visitor: Visitor,
+    State(meme_repo): State<MemeRepoSSType>,
+) -> ApiResult<Json<Interaction>> {
+    react(id, reaction, visitor, false, meme_repo).await
+}
+
+/// add or remove the reaction, answer with the counters after it
+async fn react(
+    id: Uuid,
+    reaction: String,
+    visitor: Visitor,
+    add: bool,
+    meme_repo: MemeRepoSSType,
+) -> ApiResult<Json<Interaction>> {
+    let voter = visitor.voter();
+    if add {
+        meme_repo.repo.add_reaction(id, &voter, &reaction).await?;
     } else {
-        StatusCode::INTERNAL_SERVER_ERROR
+        meme_repo
+            .repo
+            .remove_reaction(id, &voter, &reaction)
+            .await?;
     }
-    .into_response()
+
+    let interaction = meme_repo
+        .repo
+        .get_interactions(vec![id], Some(voter.key))
+        .await?
+        .pop()
+        .ok_or(MemeError::HasNotAnyMeme)?;
+
+    Ok(Json(interaction))
+}
+
+/// the reactions a visitor can give, `like` and `unlike` first
+pub async fn get_reactions(State(meme_repo): State<MemeRepoSSType>) -> Json<Vec<String>> {
+    Json(meme_repo.repo.reactions())
 }
 
 pub async fn get_interactions(
     State(meme_repo): State<MemeRepoSSType>,
+    visitor: Option<Visitor>,
     Json(ids): Json<Vec<Uuid>>,
-) -> Json<Vec<Interaction>> {
-    let list = meme_repo.repo.get_interactions(ids).await;
-    Json(list)
+) -> ApiResult<Json<Vec<Interaction>>> {
+    let list = meme_repo
+        .repo
+        .get_interactions(ids, visitor.map(|visitor| visitor.key()))
+        .await?;
+    Ok(Json(list))
+}
+
+#[derive(Serialize)]
+pub struct VisitorTokenRes {
+    pub token: String,
+}
+
+/// a new anonymous device, its token goes to `X-Visitor-Token` to vote on memes.
+/// the tokens issued to an ip are throttled like log in failures
+pub async fn issue_visitor_token(
+    ClientIp(client_ip): ClientIp,
+    State(throttle_repo): State<ThrottleRepoSSType>,
+) -> ApiResult<Json<VisitorTokenRes>> {
+    throttle_repo
+        .repo
+        .issue_visitor(&client_ip.to_string())
+        .await?;
+
+    Ok(Json(VisitorTokenRes {
+        token: gen_visitor_token(&Uuid::new_v4()),
+    }))
 }
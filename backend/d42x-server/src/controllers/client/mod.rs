@@ -1,3 +1,3 @@
-pub mod ui;
 pub mod interaction;
-pub mod models;
\ No newline at end of file
+pub mod models;
+pub mod ui;
@@ -2,81 +2,144 @@ use axum::{
     Json,
     extract::{Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Response},
 };
-use serde::Deserialize;
+// a list in the query string repeats its key
+use axum_extra::extract::Query as FilterQuery;
+use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::{
-    app::shared_data::{CategoryRepoSSType, MemeRepoSSType, SuggestRepoSSType},
-    business::{category::CategoryItem, meme::Meme},
+    app::shared_data::{
+        CategoryRepoSSType, MemeRepoSSType, SearchRepoSSType, SuggestRepoSSType, TrendingRepoSSType,
+    },
+    business::{
+        CursorPage,
+        category::CategoryItem,
+        meme::{Meme, MemeError},
+        search::SearchHit,
+        trending::{TrendingMeme, TrendingWindow},
+    },
+    controllers::{ApiResult, list_filter::ListFilterQuery},
 };
 
 use super::models::CreateSuggestReq;
 
 #[derive(Deserialize)]
 pub struct Pagination {
-    pub page: u64,
-    pub category: Option<String>,
+    /// page number, base 1, without it the list is walked by `cursor`, newest first only
+    pub page: Option<u64>,
+    /// `next_cursor` or `prev_cursor` of the last response, none for the newest memes
+    pub cursor: Option<String>,
+    /// 0 for the default page size, capped by `MAX_PAGE_SIZE`
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MemeList {
+    Paged(crate::business::Pagination<Meme>),
+    Cursor(CursorPage<Meme>),
 }
 
 pub async fn get_paginated_memes(
     Query(pagination): Query<Pagination>,
+    FilterQuery(filter): FilterQuery<ListFilterQuery>,
     State(meme_repo): State<MemeRepoSSType>,
-) -> Json<crate::business::Pagination<crate::business::meme::Meme>> {
-    let list: crate::business::Pagination<crate::business::meme::Meme> = meme_repo
-        .repo
-        .get_paginated_memes(pagination.page, pagination.category)
-        .await;
+) -> ApiResult<Json<MemeList>> {
+    let list = match pagination.page {
+        Some(page) => MemeList::Paged(
+            meme_repo
+                .repo
+                .get_paginated_memes(page, pagination.size, filter.into())
+                .await?,
+        ),
+        None => MemeList::Cursor(
+            meme_repo
+                .repo
+                .get_cursor_memes(pagination.cursor, pagination.size, filter.into())
+                .await?,
+        ),
+    };
+
+    Ok(Json(list))
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SearchQuery {
+    #[validate(length(min = 1, max = 100, code = "q_length"))]
+    pub q: String,
+    #[serde(default = "first_page")]
+    pub page: u64,
+}
 
-    Json(list)
+fn first_page() -> u64 {
+    1
 }
 
-/// get all top categories
+/// the most relevant first, see `business::search`
+pub async fn search_memes(
+    Query(query): Query<SearchQuery>,
+    State(search_repo): State<SearchRepoSSType>,
+) -> ApiResult<Json<crate::business::Pagination<SearchHit>>> {
+    query.validate()?;
+
+    let list = search_repo.repo.search(query.q, query.page).await?;
+
+    Ok(Json(list))
+}
+
+#[derive(Deserialize)]
+pub struct TrendingQuery {
+    /// 24h, 7d or 30d
+    #[serde(default)]
+    pub window: TrendingWindow,
+}
+
+pub async fn get_trending_memes(
+    Query(query): Query<TrendingQuery>,
+    State(trending_repo): State<TrendingRepoSSType>,
+) -> ApiResult<Json<Vec<TrendingMeme>>> {
+    let list = trending_repo.repo.trending(query.window).await?;
+
+    Ok(Json(list))
+}
+
+/// get all categories, `parent` links them into a tree
 pub async fn get_categories(
     State(category_repo): State<CategoryRepoSSType>,
-) -> Json<Vec<CategoryItem>> {
+) -> ApiResult<Json<Vec<CategoryItem>>> {
     let list = {
         let cate = category_repo.read().await;
-        cate.repo.get_categories().await
+        cate.repo.get_categories().await?
     };
 
-    Json(list)
+    Ok(Json(list))
 }
 
 pub async fn create_suggest(
     State(suggest_repo): State<SuggestRepoSSType>,
     Json(req): Json<CreateSuggestReq>,
-) -> Response {
-    if let Err(_) = req.validate() {
-        return (StatusCode::BAD_REQUEST).into_response();
-    }
+) -> ApiResult<StatusCode> {
+    req.validate()?;
 
-    if let Ok(()) = suggest_repo
+    suggest_repo
         .repo
         .create(req.meme_id, req.list, req.apply_user_id)
-        .await
-    {
-        StatusCode::OK
-    } else {
-        StatusCode::INTERNAL_SERVER_ERROR
-    }
-    .into_response()
+        .await?;
+
+    Ok(StatusCode::OK)
 }
 
 pub async fn meme_detail(
     Path(short_id): Path<String>,
     State(meme_repo): State<MemeRepoSSType>,
-) -> Json<Meme> {
-    Json(
-        meme_repo
-            .repo
-            .get_meme_by_short_id(short_id)
-            .await
-            .unwrap()
-            .unwrap()
-            .get_detail()
-            .await
-            .unwrap(),
-    )
+) -> ApiResult<Json<Meme>> {
+    let meme = meme_repo
+        .repo
+        .get_meme_by_short_id(short_id)
+        .await?
+        .ok_or(MemeError::HasNotAnyMeme)?;
+
+    Ok(Json(meme.get_detail().await?))
 }
@@ -0,0 +1,283 @@
+//! API error
+//!
+//! every handler returns `ApiResult<T>`, the error is rendered as a stable JSON body:
+//! ```json
+//! { "code": "not_found", "message": "meme not found", "details": null }
+//! ```
+//! `details` only exists for validation errors, it is the serialized `validator::ValidationErrors`.
+//! `TooManyRequests` also sets the `Retry-After` header, in seconds
+
+use std::time::Duration;
+
+use axum::{
+    Json,
+    http::{StatusCode, header::RETRY_AFTER},
+    response::{IntoResponse, Response},
+};
+use sea_orm::DbErr;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+use validator::ValidationErrors;
+
+use crate::business::{
+    accounts::admin::AdministratorError, audit::AuditError, category::CategoryError,
+    duplicates::DuplicateError, meme::MemeError, roles::RoleError, search::SearchError,
+    sessions::SessionError, suggests::SuggestError, throttle::ThrottleError,
+    trending::TrendingError, uploads::UploadError,
+};
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("validation failed")]
+    Validation(#[from] ValidationErrors),
+    #[error("unauthorized")]
+    Unauthorized,
+    #[error("permission denied")]
+    Forbidden,
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    TooManyRequests(String, Duration),
+    #[error("internal server error")]
+    Internal(String),
+}
+
+#[derive(Serialize, Debug)]
+pub struct ApiErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<ValidationErrors>,
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::TooManyRequests(..) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::Forbidden => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::TooManyRequests(..) => "too_many_requests",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+        let retry_after = match &self {
+            // round up, retrying a bit early only gets another 429
+            ApiError::TooManyRequests(_, after) => {
+                Some(after.as_secs() + u64::from(after.subsec_nanos() > 0))
+            }
+            _ => None,
+        };
+
+        let (message, details) = match self {
+            ApiError::Internal(e) => {
+                // never leak internal details to the client
+                error!("internal error: {}", e);
+                (String::from("internal server error"), None)
+            }
+            ApiError::Validation(e) => (String::from("validation failed"), Some(e)),
+            e => (e.to_string(), None),
+        };
+
+        let mut response = (
+            status,
+            Json(ApiErrorBody {
+                code,
+                message,
+                details,
+            }),
+        )
+            .into_response();
+
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after.max(1).into());
+        }
+
+        response
+    }
+}
+
+impl From<DbErr> for ApiError {
+    fn from(value: DbErr) -> Self {
+        ApiError::Internal(value.to_string())
+    }
+}
+
+impl From<MemeError> for ApiError {
+    fn from(value: MemeError) -> Self {
+        match value {
+            MemeError::HasNotAnyMeme => ApiError::NotFound(String::from("meme not found")),
+            MemeError::AlreadyDeleted | MemeError::NotDeleted => {
+                ApiError::Conflict(value.to_string())
+            }
+            MemeError::EmptyPost
+            | MemeError::InvalidCursor
+            | MemeError::CursorSort
+            | MemeError::UnknownReaction(_) => ApiError::BadRequest(value.to_string()),
+            MemeError::UnsupportedFormat(_) | MemeError::DatabaseErr(_) => {
+                ApiError::Internal(value.to_string())
+            }
+        }
+    }
+}
+
+impl From<SuggestError> for ApiError {
+    fn from(value: SuggestError) -> Self {
+        match value {
+            SuggestError::NotFound => ApiError::NotFound(value.to_string()),
+            SuggestError::CreateFail(_) => ApiError::BadRequest(value.to_string()),
+            SuggestError::AlreadyResolved => ApiError::Conflict(value.to_string()),
+            SuggestError::CategoryErr(e) => e.into(),
+            SuggestError::MemeErr(e) => e.into(),
+            SuggestError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<CategoryError> for ApiError {
+    fn from(value: CategoryError) -> Self {
+        match value {
+            CategoryError::MemeNotFound(_) | CategoryError::NotFound(_) => {
+                ApiError::NotFound(value.to_string())
+            }
+            CategoryError::ParentNotFound(_)
+            | CategoryError::InvalidParent
+            | CategoryError::DefaultCategory
+            | CategoryError::AliasTooLong(_) => ApiError::BadRequest(value.to_string()),
+            CategoryError::NameTaken(_) | CategoryError::AliasTaken(_) => {
+                ApiError::Conflict(value.to_string())
+            }
+            CategoryError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<SearchError> for ApiError {
+    fn from(value: SearchError) -> Self {
+        match value {
+            SearchError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+            SearchError::Meme(err) => err.into(),
+        }
+    }
+}
+
+impl From<DuplicateError> for ApiError {
+    fn from(value: DuplicateError) -> Self {
+        match value {
+            DuplicateError::SameMeme => ApiError::BadRequest(value.to_string()),
+            DuplicateError::Cluster(_) | DuplicateError::DatabaseErr(_) => {
+                ApiError::Internal(value.to_string())
+            }
+            DuplicateError::Meme(err) => err.into(),
+        }
+    }
+}
+
+impl From<TrendingError> for ApiError {
+    fn from(value: TrendingError) -> Self {
+        match value {
+            TrendingError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+            TrendingError::Meme(err) => err.into(),
+        }
+    }
+}
+
+impl From<UploadError> for ApiError {
+    fn from(value: UploadError) -> Self {
+        match value {
+            UploadError::InUse => ApiError::Conflict(value.to_string()),
+            _ if value.is_rejection() => ApiError::BadRequest(value.to_string()),
+            _ => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<AdministratorError> for ApiError {
+    fn from(value: AdministratorError) -> Self {
+        match value {
+            AdministratorError::NotFound(_) => ApiError::NotFound(value.to_string()),
+            AdministratorError::IncorrectPassword
+            | AdministratorError::TotpNotEnrolled
+            | AdministratorError::InvalidTotpCode
+            | AdministratorError::RoleNotFound(_) => ApiError::BadRequest(value.to_string()),
+            AdministratorError::TotpAlreadyEnabled
+            | AdministratorError::TotpNotEnabled
+            | AdministratorError::UsernameTaken(_)
+            | AdministratorError::OwnAccount
+            | AdministratorError::LastAdministrator => ApiError::Conflict(value.to_string()),
+            AdministratorError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<SessionError> for ApiError {
+    fn from(value: SessionError) -> Self {
+        match value {
+            SessionError::NotFound => ApiError::NotFound(value.to_string()),
+            SessionError::AccountNotFound
+            | SessionError::InvalidRefreshToken
+            | SessionError::RefreshTokenReused => ApiError::Unauthorized,
+            SessionError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<ThrottleError> for ApiError {
+    fn from(value: ThrottleError) -> Self {
+        match value {
+            ThrottleError::TooManyAttempts(after) | ThrottleError::Locked(after) => {
+                ApiError::TooManyRequests(value.to_string(), after)
+            }
+            ThrottleError::NotFound => ApiError::NotFound(value.to_string()),
+            ThrottleError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<RoleError> for ApiError {
+    fn from(value: RoleError) -> Self {
+        match value {
+            RoleError::NotFound(_) => ApiError::NotFound(value.to_string()),
+            RoleError::LastManager => ApiError::Conflict(value.to_string()),
+            RoleError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
+
+impl From<AuditError> for ApiError {
+    fn from(value: AuditError) -> Self {
+        match value {
+            AuditError::DatabaseErr(_) => ApiError::Internal(value.to_string()),
+        }
+    }
+}
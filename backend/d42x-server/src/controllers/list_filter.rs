@@ -0,0 +1,61 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::{
+    business::meme::{ListFilter, SortMode},
+    config::AllowMemeFormats,
+};
+
+/// query string of the meme lists, a list repeats its key, e.g. `categories=a&categories=b`,
+/// so it is read by `axum_extra::extract::Query`
+#[derive(Deserialize, Debug, Default)]
+pub struct ListFilterQuery {
+    /// a single category, kept for older clients
+    pub category: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// a meme has to be in all of the categories rather than in any of them
+    #[serde(default)]
+    pub match_all: bool,
+    /// the categories below the listed ones count as well
+    #[serde(default)]
+    pub include_descendants: bool,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+    pub format: Option<AllowMemeFormats>,
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub sort: SortMode,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn names(value: impl IntoIterator<Item = String>) -> Vec<String> {
+    value
+        .into_iter()
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+impl From<ListFilterQuery> for ListFilter {
+    fn from(value: ListFilterQuery) -> Self {
+        let mut categories = names(value.category);
+        categories.extend(names(value.categories));
+
+        Self {
+            categories,
+            match_all: value.match_all,
+            include_descendants: value.include_descendants,
+            exclude_categories: names(value.exclude),
+            from: value.from,
+            to: value.to,
+            format: value.format,
+            nickname: value.nickname.filter(|nickname| !nickname.is_empty()),
+            sort: value.sort,
+            seed: value.seed,
+        }
+    }
+}
@@ -1,2 +1,6 @@
 pub mod admin;
-pub mod client;
\ No newline at end of file
+pub mod client;
+pub mod error;
+pub mod list_filter;
+
+pub use error::{ApiError, ApiResult};
@@ -1,25 +1,55 @@
+use std::time::Duration;
+
 use migration::async_trait;
-use sea_orm::{Database, DatabaseConnection, DbErr};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+use tracing::info;
 
 use super::DbConnHelper;
 
+/// one long-lived connection pool per process,
+/// cloning the helper shares the same pool
 #[derive(Clone, Debug)]
 pub struct SharedDbHelper {
-    db_url: String,
+    db: DatabaseConnection,
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolOptions {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout: Duration,
+    pub idle_timeout: Duration,
+    pub acquire_timeout: Duration,
 }
 
 impl SharedDbHelper {
-    pub fn new(db_url: String) -> Self {
-        Self { db_url }
+    pub async fn connect(db_url: &str, pool: PoolOptions) -> Result<Self, DbErr> {
+        info!("connect database, pool: {:?}", pool);
+
+        let mut options = ConnectOptions::new(db_url);
+        options
+            .max_connections(pool.max_connections)
+            .min_connections(pool.min_connections)
+            .connect_timeout(pool.connect_timeout)
+            .idle_timeout(pool.idle_timeout)
+            .acquire_timeout(pool.acquire_timeout)
+            .sqlx_logging(false);
+
+        let db = Database::connect(options).await?;
+        db.ping().await?;
+
+        Ok(Self { db })
+    }
+
+    /// close the pool, all the clones of this helper cannot be used anymore
+    pub async fn close(self) -> Result<(), DbErr> {
+        self.db.close().await
     }
 }
 
 #[async_trait::async_trait]
 impl DbConnHelper for SharedDbHelper {
     async fn get_connection(&self) -> Result<DatabaseConnection, DbErr> {
-        let db: DatabaseConnection = Database::connect(self.db_url.as_str()).await?;
-        db.ping().await?;
-
-        Ok(db)
+        Ok(self.db.clone())
     }
 }
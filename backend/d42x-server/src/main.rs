@@ -1,17 +1,39 @@
-use clap::Parser;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
 use d42x_server::{
-    app::shared_data::{AccountRepoSS, CategoryRepoSS, MemeRepoSS, SuggestRepoSS},
+    app::shared_data::{
+        AccountRepoSS, AuditRepoSS, CategoryRepoSS, DuplicateRepoSS, MemeRepoSS, RoleRepoSS,
+        SearchRepoSS, SessionRepoSS, SuggestRepoSS, ThrottleRepoSS, TrendingRepoSS, UploadRepoSS,
+    },
+    authentication::keyring::{KeyFile, KeyringError, KeyringResult},
     business::{
-        accounts::gen_account_repo::GenAccountRepo, cache::MokaCache,
-        category::gen_cate_repo::GenCategoryRepo, meme::gen_meme_repo::GenMemeRepo,
+        accounts::{
+            Account, AccountRepository, NewAccount, admin::AdministratorError,
+            gen_account_repo::GenAccountRepo, hash_password,
+        },
+        audit::{Actor, gen_audit_repo::GenAuditRepo},
+        cache::MokaCache,
+        category::gen_cate_repo::GenCategoryRepo,
+        duplicates::gen_duplicate_repo::GenDuplicateRepo,
+        meme::gen_meme_repo::GenMemeRepo,
+        placeholders::{PlaceholderRepository, gen_placeholder_repo::GenPlaceholderRepo},
+        roles::{self, gen_role_repo::GenRoleRepo},
+        search::{SearchRepository, gen_search_repo::GenSearchRepo},
+        sessions::{SessionRepository, gen_session_repo::GenSessionRepo},
+        storage::{Storage, local::LocalStorage, s3::S3Storage},
         suggests::gen_suggest_repo::GenSuggestRepo,
+        throttle::{ThrottlePolicy, ThrottleRepository, gen_throttle_repo::GenThrottleRepo},
+        thumbnails::{ThumbnailRepository, gen_thumbnail_repo::GenThumbnailRepo},
+        trending::{TrendingRepository, gen_trending_repo::GenTrendingRepo},
+        uploads::gen_upload_repo::GenUploadRepo,
     },
     config,
     db::{DbConnHelper, shared_db_helper::SharedDbHelper},
 };
 use migration::{Migrator, MigratorTrait};
 use sea_orm::DbErr;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -20,6 +42,104 @@ struct Args {
     pub fresh_db: bool,
     #[arg(short, long, help = "migrate database while app launcing")]
     pub migrate_db: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// manage the JWT signing keys, the running servers pick the changes up after restarting
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// manage the accounts, e.g. create the first administrator of a fresh deployment
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// manage the full text search index of the memes
+    #[command(subcommand)]
+    Search(SearchCommand),
+    /// manage the thumbnails, covers and sizes of the meme files
+    #[command(subcommand)]
+    Thumbnails(ThumbnailsCommand),
+    /// manage the BlurHashes and dominant colours of the meme files
+    #[command(subcommand)]
+    Placeholders(PlaceholdersCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum PlaceholdersCommand {
+    /// compute them for every file without one, e.g. the files posted before placeholders existed
+    Backfill,
+}
+
+#[derive(Subcommand, Debug)]
+enum ThumbnailsCommand {
+    /// generate for every pending file, e.g. the files posted before thumbnails existed
+    Generate {
+        #[arg(long, help = "queue the failed files again first")]
+        retry_failed: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SearchCommand {
+    /// index every meme from scratch, run it once after migrating an existing database
+    Rebuild,
+}
+
+#[derive(Subcommand, Debug)]
+enum AccountCommand {
+    /// create an account, the password is read from stdin without --password
+    Create {
+        username: String,
+        #[arg(long, default_value = "")]
+        email: String,
+        #[arg(long, default_value = roles::ADMINISTRATOR)]
+        role: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// set a new password and log out every session of the account
+    ResetPassword {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// disable the account and log out every session of it
+    Disable {
+        username: String,
+        #[arg(long, help = "enable the account again")]
+        enable: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum KeyCommand {
+    /// create a key file with one active key, print JWT_KID and JWT_SECRET if there is no key file
+    Generate {
+        #[arg(long, help = "key file, default JWT_KEY_FILE")]
+        file: Option<String>,
+        #[arg(long, help = "overwrite the existing key file")]
+        force: bool,
+    },
+    /// add a new key and sign the new tokens with it, the old keys still verify the issued tokens
+    Rotate {
+        #[arg(long, help = "key file, default JWT_KEY_FILE")]
+        file: Option<String>,
+        #[arg(
+            long,
+            default_value_t = 2,
+            help = "how many of the newest keys are kept"
+        )]
+        keep: usize,
+        #[arg(long, help = "only add the key for verification, activate it later")]
+        stage: bool,
+    },
+    /// sign the new tokens with a staged key
+    Activate {
+        kid: String,
+        #[arg(long, help = "key file, default JWT_KEY_FILE")]
+        file: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -28,20 +148,75 @@ async fn main() {
     set_log();
 
     let args = Args::parse();
+    if let Some(Command::Key(command)) = args.command {
+        if let Err(e) = run_key_command(command) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let db = SharedDbHelper::connect(&config::DATABASE_URL, config::DB_POOL.clone())
+        .await
+        .unwrap();
+
     if args.fresh_db {
         debug!("fresh");
-        fresh_db().await.unwrap();
+        fresh_db(&db).await.unwrap();
+        db.close().await.unwrap();
         return;
     }
 
     if args.migrate_db {
         info!("migrate_db");
-        migrate_db().await.unwrap();
+        migrate_db(&db).await.unwrap();
+    }
+
+    if let Some(Command::Account(command)) = args.command {
+        let res = run_account_command(command, db.clone()).await;
+        db.close().await.unwrap();
+        if let Err(e) = res {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Search(command)) = args.command {
+        let res = run_search_command(command, db.clone()).await;
+        db.close().await.unwrap();
+        if let Err(e) = res {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Thumbnails(command)) = args.command {
+        let res = run_thumbnails_command(command, db.clone()).await;
+        db.close().await.unwrap();
+        if let Err(e) = res {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Placeholders(command)) = args.command {
+        let res = run_placeholders_command(command, db.clone()).await;
+        db.close().await.unwrap();
+        if let Err(e) = res {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
     }
 
     info!("run app");
-    build_run().await;
-    // run_app().await;
+    build_run(db.clone()).await;
+
+    info!("close database");
+    db.close().await.unwrap();
 }
 
 fn set_env() {
@@ -57,11 +232,27 @@ fn set_log() {
         .init();
 }
 
-async fn build_run() {
-    let acc_repo = account_repo_shared_state();
-    let cate_repo = category_repo_shared_state();
-    let meme_repo = meme_repo_shared_state();
-    let suggest_repo = suggest_repo_shared_state();
+async fn build_run(db: SharedDbHelper) {
+    let acc_repo = account_repo_shared_state(db.clone());
+    let cate_repo = category_repo_shared_state(db.clone());
+    let meme_repo = meme_repo_shared_state(db.clone());
+    let suggest_repo = suggest_repo_shared_state(db.clone());
+    let session_repo = session_repo_shared_state(db.clone());
+
+    let throttle_repo = throttle_repo_shared_state(db.clone());
+    let role_repo = role_repo_shared_state(db.clone());
+    let audit_repo = AuditRepoSS::new(GenAuditRepo::new(db.clone()));
+    let search_repo = SearchRepoSS::new(GenSearchRepo::new(db.clone()));
+    let trending_repo = TrendingRepoSS::new(GenTrendingRepo::with_cache(
+        db.clone(),
+        Some(MokaCache::new()),
+    ));
+    let upload_repo = upload_repo_shared_state(db.clone());
+    let duplicate_repo = DuplicateRepoSS::new(GenDuplicateRepo::new(db.clone()));
+
+    spawn_refresh_hot_scores(db.clone());
+    spawn_generate_thumbnails(db.clone());
+    spawn_purge_expired(db);
 
     d42x_server::app::AppBuilder::new()
         .address(config::ADDRESS.to_string())
@@ -70,6 +261,16 @@ async fn build_run() {
         .category_repo(cate_repo)
         .meme_repo(meme_repo)
         .suggest_repo(suggest_repo)
+        .session_repo(session_repo)
+        .throttle_repo(throttle_repo)
+        .role_repo(role_repo)
+        .audit_repo(audit_repo)
+        .search_repo(search_repo)
+        .trending_repo(trending_repo)
+        .upload_repo(upload_repo)
+        .duplicate_repo(duplicate_repo)
+        .media_dir(config::MEDIA_DIR.to_string())
+        .upload_limit(*config::MAX_UPLOAD_SIZE)
         .aes_key(config::KEY.to_string())
         .aes_iv(config::IV.clone())
         .build()
@@ -78,40 +279,356 @@ async fn build_run() {
         .await;
 }
 
-fn account_repo_shared_state() -> AccountRepoSS {
-    let db = SharedDbHelper::new(config::DATABASE_URL.to_string());
+fn account_repo_shared_state(db: SharedDbHelper) -> AccountRepoSS {
     let acc_repo = GenAccountRepo::new(db);
     AccountRepoSS::new(acc_repo)
 }
 
-fn category_repo_shared_state() -> CategoryRepoSS {
-    let db = SharedDbHelper::new(config::DATABASE_URL.to_string());
+fn category_repo_shared_state(db: SharedDbHelper) -> CategoryRepoSS {
     let cate_repo = GenCategoryRepo::with_cache(db, Some(MokaCache::new()));
     CategoryRepoSS::new(cate_repo)
 }
 
-fn meme_repo_shared_state() -> MemeRepoSS {
-    let db = SharedDbHelper::new(config::DATABASE_URL.to_string());
-    let meme_repo = GenMemeRepo::with_cache(db, Some(MokaCache::new()));
+fn meme_repo_shared_state(db: SharedDbHelper) -> MemeRepoSS {
+    let mut meme_repo = GenMemeRepo::with_cache(db, Some(MokaCache::new()));
+    if !config::REACTIONS.is_empty() {
+        meme_repo = meme_repo.with_reactions(config::REACTIONS.clone());
+    }
     MemeRepoSS::new(meme_repo)
 }
 
-fn suggest_repo_shared_state() -> SuggestRepoSS {
-    let db = SharedDbHelper::new(config::DATABASE_URL.to_string());
+fn suggest_repo_shared_state(db: SharedDbHelper) -> SuggestRepoSS {
     let suggest_repo = GenSuggestRepo::new(db);
     SuggestRepoSS::new(suggest_repo)
 }
 
-async fn fresh_db() -> Result<(), DbErr> {
-    let db_helper = SharedDbHelper::new(config::DATABASE_URL.to_string());
-    let db = db_helper.get_connection().await.unwrap();
-    Migrator::fresh(&db).await?;
-    db.close().await
+fn session_repo_shared_state(db: SharedDbHelper) -> SessionRepoSS {
+    let session_repo = GenSessionRepo::with_lifetime(db, *config::REFRESH_EXP);
+    SessionRepoSS::new(session_repo)
+}
+
+fn throttle_repo_shared_state(db: SharedDbHelper) -> ThrottleRepoSS {
+    ThrottleRepoSS::new(new_throttle_repo(db))
+}
+
+fn role_repo_shared_state(db: SharedDbHelper) -> RoleRepoSS {
+    let role_repo = GenRoleRepo::with_cache(db, Some(MokaCache::new()));
+    RoleRepoSS::new(role_repo)
+}
+
+fn upload_repo_shared_state(db: SharedDbHelper) -> UploadRepoSS {
+    UploadRepoSS::new(GenUploadRepo::with_policy(
+        db,
+        media_storage(),
+        config::MEDIA_POLICY.clone(),
+    ))
+}
+
+fn media_storage() -> Box<dyn Storage + Sync + Send> {
+    match config::MEDIA_STORAGE.as_str() {
+        "local" => Box::new(LocalStorage::new(
+            config::MEDIA_DIR.as_str(),
+            &config::MEDIA_URL,
+        )),
+        "s3" => Box::new(S3Storage::new(config::S3.clone())),
+        other => panic!("Wrong MEDIA_STORAGE: {}", other),
+    }
+}
+
+fn new_thumbnail_repo(db: SharedDbHelper) -> GenThumbnailRepo<SharedDbHelper> {
+    GenThumbnailRepo::with_policy(db, media_storage(), config::THUMBNAIL_POLICY.clone())
+}
+
+fn new_throttle_repo(db: SharedDbHelper) -> GenThrottleRepo<SharedDbHelper> {
+    let ip_policy = ThrottlePolicy {
+        lockout_threshold: *config::LOGIN_IP_LOCKOUT_THRESHOLD,
+        ..config::LOGIN_THROTTLE.clone()
+    };
+    GenThrottleRepo::with_policy(db, config::LOGIN_THROTTLE.clone(), ip_policy)
+}
+
+/// remove the expired sessions, revoked tokens and stale log in throttles every hour
+fn spawn_purge_expired(db: SharedDbHelper) {
+    const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+    let session_repo = GenSessionRepo::with_lifetime(db.clone(), *config::REFRESH_EXP);
+    let throttle_repo = new_throttle_repo(db);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match session_repo.purge_expired().await {
+                Ok(count) => debug!("purged {} expired sessions and tokens", count),
+                Err(e) => error!("purge expired sessions failed: {}", e),
+            }
+            match throttle_repo.purge_expired().await {
+                Ok(count) => debug!("purged {} log in throttles and attempts", count),
+                Err(e) => error!("purge log in throttles failed: {}", e),
+            }
+        }
+    });
+}
+
+/// refresh the hot scores every ten minutes and drop the interactions older than the longest
+/// trending window
+fn spawn_refresh_hot_scores(db: SharedDbHelper) {
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+    let trending_repo = GenTrendingRepo::<MokaCache, _>::new(db);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            match trending_repo.refresh_hot_scores().await {
+                Ok(count) => debug!("refreshed the hot scores of {} memes", count),
+                Err(e) => error!("refresh hot scores failed: {}", e),
+            }
+            match trending_repo.purge_interactions().await {
+                Ok(count) => debug!("purged {} interactions", count),
+                Err(e) => error!("purge interactions failed: {}", e),
+            }
+        }
+    });
+}
+
+/// generate the thumbnails of the newly posted files every minute
+fn spawn_generate_thumbnails(db: SharedDbHelper) {
+    const GENERATE_INTERVAL: Duration = Duration::from_secs(60);
+    const BATCH_SIZE: u64 = 50;
+
+    let thumbnail_repo = new_thumbnail_repo(db);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(GENERATE_INTERVAL);
+        loop {
+            interval.tick().await;
+            // a batch that took a while may be followed by more pending files
+            loop {
+                match thumbnail_repo.generate_pending(BATCH_SIZE).await {
+                    Ok(0) => break,
+                    Ok(count) => debug!("generated the thumbnails of {} files", count),
+                    Err(e) => {
+                        error!("generate thumbnails failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn fresh_db(db_helper: &SharedDbHelper) -> Result<(), DbErr> {
+    let db = db_helper.get_connection().await?;
+    Migrator::fresh(&db).await
+}
+
+async fn migrate_db(db_helper: &SharedDbHelper) -> Result<(), DbErr> {
+    let db = db_helper.get_connection().await?;
+    Migrator::up(&db, None).await
+}
+
+fn run_key_command(command: KeyCommand) -> KeyringResult<()> {
+    match command {
+        KeyCommand::Generate { file, force } => {
+            let key_file = KeyFile::generate();
+            match file.or_else(|| config::JWT_KEY_FILE.clone()) {
+                Some(path) => {
+                    if !force && std::path::Path::new(&path).exists() {
+                        return Err(KeyringError::AlreadyExists(path));
+                    }
+                    key_file.save(&path)?;
+                    println!("generated key `{}` in {}", key_file.active, path);
+                }
+                None => {
+                    let key = &key_file.keys[0];
+                    println!("JWT_KID={}", key.kid);
+                    println!("JWT_SECRET={}", key.secret);
+                }
+            }
+        }
+        KeyCommand::Rotate { file, keep, stage } => {
+            let path = key_file_path(file)?;
+            let mut key_file = KeyFile::load(&path)?;
+            let key = key_file.rotate(keep, !stage);
+            key_file.save(&path)?;
+            println!(
+                "{} key `{}`, active key is `{}`",
+                if stage { "staged" } else { "rotated to" },
+                key.kid,
+                key_file.active
+            );
+        }
+        KeyCommand::Activate { kid, file } => {
+            let path = key_file_path(file)?;
+            let mut key_file = KeyFile::load(&path)?;
+            key_file.activate(&kid)?;
+            key_file.save(&path)?;
+            println!("active key is `{}`", kid);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_search_command(
+    command: SearchCommand,
+    db: SharedDbHelper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        SearchCommand::Rebuild => {
+            let count = GenSearchRepo::new(db).rebuild().await?;
+            println!("indexed {} memes", count);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_thumbnails_command(
+    command: ThumbnailsCommand,
+    db: SharedDbHelper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const BATCH_SIZE: u64 = 100;
+
+    let thumbnail_repo = new_thumbnail_repo(db);
+    match command {
+        ThumbnailsCommand::Generate { retry_failed } => {
+            if retry_failed {
+                let count = thumbnail_repo.retry_failed().await?;
+                println!("queued {} failed files again", count);
+            }
+            let mut total = 0;
+            loop {
+                let count = thumbnail_repo.generate_pending(BATCH_SIZE).await?;
+                if count == 0 {
+                    break;
+                }
+                total += count;
+                println!("handled {} files", total);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_placeholders_command(
+    command: PlaceholdersCommand,
+    db: SharedDbHelper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    const BATCH_SIZE: u64 = 100;
+
+    let placeholder_repo =
+        GenPlaceholderRepo::with_policy(db, media_storage(), config::MEDIA_POLICY.clone());
+    match command {
+        PlaceholdersCommand::Backfill => {
+            let (mut filled, mut skipped) = (0, 0);
+            let mut after = None;
+            loop {
+                let backfill = placeholder_repo.backfill(after, BATCH_SIZE).await?;
+                let Some(last) = backfill.last else {
+                    break;
+                };
+                after = Some(last);
+                filled += backfill.filled;
+                skipped += backfill.skipped;
+                println!("filled {} files, skipped {}", filled, skipped);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_account_command(
+    command: AccountCommand,
+    db: SharedDbHelper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let account_repo = GenAccountRepo::new(db.clone());
+    let session_repo = GenSessionRepo::with_lifetime(db, *config::REFRESH_EXP);
+    let actor = Actor::cli();
+
+    match command {
+        AccountCommand::Create {
+            username,
+            email,
+            role,
+            password,
+        } => {
+            let password = read_password(password)?;
+            let account = account_repo
+                .create_account(
+                    &actor,
+                    NewAccount {
+                        username,
+                        email,
+                        hashed_password: hash_password(&password),
+                        role,
+                    },
+                )
+                .await?;
+            println!("created `{}` ({})", account.username, account.id);
+        }
+        AccountCommand::ResetPassword { username, password } => {
+            let account = find_account(&account_repo, &username).await?;
+            let password = read_password(password)?;
+            account_repo
+                .reset_password(&actor, account.id, hash_password(&password))
+                .await?;
+            session_repo.revoke_all(account.id).await?;
+            println!("reset the password of `{}`", username);
+        }
+        AccountCommand::Disable { username, enable } => {
+            let account = find_account(&account_repo, &username).await?;
+            account_repo
+                .set_disabled(&actor, account.id, !enable)
+                .await?;
+            if !enable {
+                session_repo.revoke_all(account.id).await?;
+            }
+            println!(
+                "{} `{}`",
+                if enable { "enabled" } else { "disabled" },
+                username
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_account(
+    account_repo: &GenAccountRepo<SharedDbHelper>,
+    username: &str,
+) -> Result<Account, AdministratorError> {
+    account_repo
+        .get_account_by_username(username)
+        .await?
+        .ok_or_else(|| AdministratorError::NotFound(username.to_owned()))
+}
+
+/// the password argument, or the first line of stdin
+fn read_password(password: Option<String>) -> std::io::Result<String> {
+    let password = match password {
+        Some(password) => password,
+        None => {
+            eprint!("password: ");
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_owned()
+        }
+    };
+
+    if password.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the password is empty",
+        ));
+    }
+
+    Ok(password)
 }
 
-async fn migrate_db() -> Result<(), DbErr> {
-    let db_helper = SharedDbHelper::new(config::DATABASE_URL.to_string());
-    let db = db_helper.get_connection().await.unwrap();
-    Migrator::up(&db, None).await?;
-    db.close().await
+fn key_file_path(file: Option<String>) -> KeyringResult<String> {
+    file.or_else(|| config::JWT_KEY_FILE.clone())
+        .ok_or(KeyringError::NoKeyFile)
 }
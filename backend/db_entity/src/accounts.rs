@@ -14,6 +14,15 @@ pub struct Model {
     pub is_admin: bool,
     pub created_date_time: chrono::DateTime<FixedOffset>,
     pub last_actiity_date_time: chrono::DateTime<FixedOffset>,
+    /// base32 TOTP secret, it is pending until `totp_enabled`
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    /// time step of the last accepted code, a code is never accepted twice
+    pub totp_last_step: Option<i64>,
+    /// name of the role, an account without a role cannot log in
+    pub role: Option<String>,
+    /// a disabled account cannot log in
+    pub disabled_date_time: Option<chrono::DateTime<FixedOffset>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -30,6 +39,11 @@ impl ActiveModelBehavior for ActiveModel {
             is_admin: Set(false),
             created_date_time: Set(chrono::Utc::now().into()),
             last_actiity_date_time: Set(chrono::Utc::now().into()),
+            totp_secret: Set(None),
+            totp_enabled: Set(false),
+            totp_last_step: Set(None),
+            role: Set(None),
+            disabled_date_time: Set(None),
         }
     }
 }
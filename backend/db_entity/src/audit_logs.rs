@@ -0,0 +1,36 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+/// who did what, written in the same transaction as the change
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// none when done from the command line
+    pub actor_id: Option<Uuid>,
+    pub actor_name: String,
+    pub action: String,
+    pub target_id: Option<String>,
+    /// JSON
+    #[sea_orm(column_type = "Text")]
+    pub details: String,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            actor_id: Set(None),
+            actor_name: Set(String::new()),
+            action: Set(String::new()),
+            target_id: Set(None),
+            details: Set(String::from("{}")),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -12,7 +12,34 @@ pub struct Model {
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::meme_categories::Entity")]
+    MemeCategories,
+    #[sea_orm(has_many = "super::category_aliases::Entity")]
+    CategoryAliases,
+}
+
+impl Related<super::category_aliases::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CategoryAliases.def()
+    }
+}
+
+impl Related<super::meme_categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemeCategories.def()
+    }
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::meme_categories::Relation::Meme.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::meme_categories::Relation::Category.def().rev())
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {
     fn new() -> Self {
@@ -0,0 +1,40 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "category_aliases")]
+pub struct Model {
+    /// lower cased and NFKC normalized `alias`
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub normalized: String,
+    pub alias: String,
+    pub category_id: Uuid,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::categories::Entity",
+        from = "Column::CategoryId",
+        to = "super::categories::Column::Id"
+    )]
+    Category,
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            normalized: Set(String::new()),
+            alias: Set(String::new()),
+            category_id: Set(Uuid::nil()),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
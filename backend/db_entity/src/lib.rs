@@ -5,6 +5,24 @@ pub mod categories;
 pub mod memes;
 pub mod meme_urls;
 pub mod suggests;
+pub mod sessions;
+pub mod revoked_tokens;
+pub mod login_throttles;
+pub mod login_attempts;
+pub mod recovery_codes;
+pub mod roles;
+pub mod permissions;
+pub mod role_permissions;
+pub mod audit_logs;
+pub mod meme_categories;
+pub mod category_aliases;
+pub mod meme_search_terms;
+pub mod meme_interactions;
+pub mod meme_hot_scores;
+pub mod meme_votes;
+pub mod meme_reactions;
+pub mod meme_reaction_counts;
+pub mod meme_thumbnails;
 pub mod prelude;
 
 pub const DEFAULT_CATEGORY: &str = "meme";
@@ -0,0 +1,29 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "login_attempts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub username: String,
+    /// peer address of the client, see `ClientIp`
+    pub ip_address: String,
+    pub success: bool,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            username: Set(String::new()),
+            ip_address: Set(String::new()),
+            success: Set(false),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// failed log in attempts counted per username and per client ip,
+/// and the visitor tokens issued per client ip
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "login_throttles")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub kind: Kind,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key: String,
+    pub failure_count: i32,
+    pub last_failure_date_time: chrono::DateTime<FixedOffset>,
+    pub locked_until_date_time: Option<chrono::DateTime<FixedOffset>>,
+}
+
+#[derive(EnumIter, DeriveActiveEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[sea_orm(string_value = "username")]
+    Username,
+    #[sea_orm(string_value = "ip")]
+    Ip,
+    #[sea_orm(string_value = "visitor")]
+    Visitor,
+}
+
+impl TryFrom<&str> for Kind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "username" => Ok(Kind::Username),
+            "ip" => Ok(Kind::Ip),
+            "visitor" => Ok(Kind::Visitor),
+            _ => Err(format!("incorrect value: {}", value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            kind: Set(Kind::Username),
+            key: Set(String::new()),
+            failure_count: Set(0),
+            last_failure_date_time: Set(Utc::now().into()),
+            locked_until_date_time: Set(None),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_categories")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub meme_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub category_id: Uuid,
+    /// position of the category in the meme's category list
+    pub sort: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+    #[sea_orm(
+        belongs_to = "super::categories::Entity",
+        from = "Column::CategoryId",
+        to = "super::categories::Column::Id"
+    )]
+    Category,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            meme_id: Set(Uuid::nil()),
+            category_id: Set(Uuid::nil()),
+            sort: Set(0),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_hot_scores")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub meme_id: Uuid,
+    /// interactions weighted by how long ago they happened
+    pub score: f64,
+    pub updated_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            meme_id: Set(Uuid::nil()),
+            score: Set(0.0),
+            updated_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_interactions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub meme_id: Uuid,
+    pub kind: Kind,
+    /// keyed hash of the visitor, the visitor itself is not kept
+    pub client_id: String,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(EnumIter, DeriveActiveEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    #[sea_orm(string_value = "like")]
+    Like,
+    #[sea_orm(string_value = "unlike")]
+    Unlike,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            meme_id: Set(Uuid::nil()),
+            kind: Set(Kind::Like),
+            client_id: Set(String::new()),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_reaction_counts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub meme_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub reaction: String,
+    pub count: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            meme_id: Set(Uuid::nil()),
+            reaction: Set(String::new()),
+            count: Set(0),
+        }
+    }
+}
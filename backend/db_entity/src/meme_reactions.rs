@@ -0,0 +1,43 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_reactions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub meme_id: Uuid,
+    /// the logged in account or the device of a visitor token
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub visitor: String,
+    /// the emoji
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub reaction: String,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            meme_id: Set(Uuid::nil()),
+            visitor: Set(String::new()),
+            reaction: Set(String::new()),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use sea_orm::{Set, entity::prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_search_terms")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub term: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub meme_id: Uuid,
+    /// message, nickname or category
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub field: String,
+    pub frequency: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::memes::Entity",
+        from = "Column::MemeId",
+        to = "super::memes::Column::Id"
+    )]
+    Meme,
+}
+
+impl Related<super::memes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Meme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            term: Set(String::new()),
+            meme_id: Set(Uuid::nil()),
+            field: Set(String::new()),
+            frequency: Set(0),
+        }
+    }
+}
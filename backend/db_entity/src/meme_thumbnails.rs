@@ -0,0 +1,49 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+use super::meme_urls::Bed;
+
+/// a resized WebP copy of a file of `meme_urls`
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "meme_thumbnails")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub url_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub width: i32,
+    pub height: i32,
+    pub url: String,
+    pub bed: Bed,
+    pub bed_id: String,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::meme_urls::Entity",
+        from = "Column::UrlId",
+        to = "super::meme_urls::Column::Id"
+    )]
+    MemeUrl,
+}
+
+impl Related<super::meme_urls::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemeUrl.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            url_id: Set(Uuid::nil()),
+            width: Set(0),
+            height: Set(0),
+            url: Set(String::new()),
+            bed: Set(Bed::SuperBed),
+            bed_id: Set(String::new()),
+            created_date_time: Set(Utc::now().into()),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::{FixedOffset, Utc};
 use sea_orm::{Set, entity::prelude::*};
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "meme_urls")]
@@ -12,18 +13,62 @@ pub struct Model {
     pub source: String,
     pub format: String,
     pub hash: String,
+    /// the dHash of the picture or its first frame, the 64 bits kept as a signed integer
+    pub phash: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub thumbnail_status: ThumbnailStatus,
+    /// when an instance started on the thumbnails of the file
+    pub thumbnail_claimed_date_time: Option<chrono::DateTime<FixedOffset>>,
+    /// a BlurHash of the picture or its first frame
+    pub blurhash: Option<String>,
+    /// `#rrggbb`
+    pub dominant_color: Option<String>,
     pub bed: Bed,
     pub bed_id: String,
     pub sort: i32,
     pub created_date_time: chrono::DateTime<FixedOffset>,
 }
 
-#[derive(EnumIter, DeriveActiveEnum, Clone, Debug, PartialEq, Eq)]
+#[derive(
+    EnumIter, DeriveActiveEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
 #[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "snake_case")]
 pub enum Bed {
     /// 聚合图床
+    #[default]
     #[sea_orm(string_value = "superbad")]
     SuperBed,
+    /// the media directory of the server
+    #[sea_orm(string_value = "local")]
+    Local,
+    /// an S3-compatible bucket
+    #[sea_orm(string_value = "s3")]
+    S3,
+}
+
+/// where the file is in the thumbnail pipeline
+#[derive(
+    EnumIter, DeriveActiveEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailStatus {
+    #[default]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    /// claimed by an instance that is generating the thumbnails
+    #[sea_orm(string_value = "processing")]
+    Processing,
+    #[sea_orm(string_value = "ready")]
+    Ready,
+    /// a video that cannot be decoded, only its size is known
+    #[sea_orm(string_value = "unsupported")]
+    Unsupported,
+    /// the file could not be loaded or decoded
+    #[sea_orm(string_value = "failed")]
+    Failed,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -34,6 +79,8 @@ pub enum Relation {
         to = "super::memes::Column::Id"
     )]
     Meme,
+    #[sea_orm(has_many = "super::meme_thumbnails::Entity")]
+    MemeThumbnails,
 }
 
 impl Related<super::memes::Entity> for Entity {
@@ -42,6 +89,12 @@ impl Related<super::memes::Entity> for Entity {
     }
 }
 
+impl Related<super::meme_thumbnails::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemeThumbnails.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {
     fn new() -> Self {
         let now = Utc::now().into();
@@ -53,6 +106,13 @@ impl ActiveModelBehavior for ActiveModel {
             source: Set(String::new()),
             format: Set(String::new()),
             hash: Set(String::new()),
+            phash: Set(None),
+            width: Set(None),
+            height: Set(None),
+            thumbnail_status: Set(ThumbnailStatus::Pending),
+            thumbnail_claimed_date_time: Set(None),
+            blurhash: Set(None),
+            dominant_color: Set(None),
             bed: Set(Bed::SuperBed),
             bed_id: Set(String::new()),
             sort: Set(0),
@@ -17,13 +17,16 @@ pub struct Model {
     pub id_addr: String,
     pub likes: i32,
     pub unlikes: i32,
-    /// ;categories_1;categories_2;
-    pub categories: String,
     pub status: Status,
     pub user_id: Uuid,
     pub show_date_time: chrono::DateTime<FixedOffset>,
     pub created_date_time: chrono::DateTime<FixedOffset>,
     pub last_actiity_date_time: chrono::DateTime<FixedOffset>,
+    /// the account that moved the meme to trash
+    pub deleted_by: Option<Uuid>,
+    pub deleted_date_time: Option<chrono::DateTime<FixedOffset>>,
+    /// the status to restore the meme to
+    pub status_before_delete: Option<Status>,
 }
 
 #[derive(EnumIter, DeriveActiveEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -69,6 +72,8 @@ pub enum Relation {
     MemeUrls,
     #[sea_orm(has_many = "super::suggests::Entity")]
     Suggests,
+    #[sea_orm(has_many = "super::meme_categories::Entity")]
+    MemeCategories,
 }
 
 impl Related<super::meme_urls::Entity> for Entity {
@@ -83,6 +88,22 @@ impl Related<super::suggests::Entity> for Entity {
     }
 }
 
+impl Related<super::meme_categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemeCategories.def()
+    }
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::meme_categories::Relation::Category.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::meme_categories::Relation::Meme.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {
     fn new() -> Self {
         let now = Utc::now().into();
@@ -95,12 +116,14 @@ impl ActiveModelBehavior for ActiveModel {
             id_addr: Set(String::new()),
             likes: Set(0),
             unlikes: Set(0),
-            categories: Set(format!(";{};", crate::DEFAULT_CATEGORY)),
             status: Set(Status::Uncensored),
             user_id: Set(Uuid::nil()),
             show_date_time: Set(now),
             created_date_time: Set(now),
             last_actiity_date_time: Set(now),
+            deleted_by: Set(None),
+            deleted_date_time: Set(None),
+            status_before_delete: Set(None),
         }
     }
 }
@@ -0,0 +1,22 @@
+use sea_orm::{Set, entity::prelude::*};
+
+/// the permissions checked by the server, seeded by the migration
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "permissions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            name: Set(String::new()),
+            description: Set(String::new()),
+        }
+    }
+}
@@ -5,3 +5,21 @@ pub use super::memes;
 pub use super::meme_urls;
 pub use super::categories;
 pub use super::suggests;
+pub use super::sessions;
+pub use super::revoked_tokens;
+pub use super::login_throttles;
+pub use super::login_attempts;
+pub use super::recovery_codes;
+pub use super::roles;
+pub use super::permissions;
+pub use super::role_permissions;
+pub use super::audit_logs;
+pub use super::meme_categories;
+pub use super::category_aliases;
+pub use super::meme_search_terms;
+pub use super::meme_interactions;
+pub use super::meme_hot_scores;
+pub use super::meme_votes;
+pub use super::meme_reactions;
+pub use super::meme_reaction_counts;
+pub use super::meme_thumbnails;
@@ -0,0 +1,29 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+/// revoked access tokens, a row can be removed once the token expired
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "revoked_tokens")]
+pub struct Model {
+    /// `jti` claim of the access token
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub token_id: String,
+    pub account_id: Uuid,
+    pub expires_date_time: chrono::DateTime<FixedOffset>,
+    pub revoked_date_time: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        let now = Utc::now().into();
+        Self {
+            token_id: Set(String::new()),
+            account_id: Set(Uuid::nil()),
+            expires_date_time: Set(now),
+            revoked_date_time: Set(now),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{FixedOffset, Utc};
+use sea_orm::{Set, entity::prelude::*};
+
+/// a logged in session, the refresh token is rotated on every refresh
+/// and only its sha256 is stored
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub refresh_token_hash: String,
+    /// the refresh token replaced by the last refresh, presenting it again means it was stolen
+    pub previous_refresh_token_hash: String,
+    /// `jti` of the last issued access token
+    pub access_token_id: String,
+    pub access_expires_date_time: chrono::DateTime<FixedOffset>,
+    pub created_date_time: chrono::DateTime<FixedOffset>,
+    pub refreshed_date_time: chrono::DateTime<FixedOffset>,
+    pub expires_date_time: chrono::DateTime<FixedOffset>,
+    pub revoked_date_time: Option<chrono::DateTime<FixedOffset>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::accounts::Entity",
+        from = "Column::AccountId",
+        to = "super::accounts::Column::Id"
+    )]
+    Account,
+}
+
+impl Related<super::accounts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        let now = Utc::now().into();
+        Self {
+            id: Set(Uuid::now_v7()),
+            account_id: Set(Uuid::nil()),
+            refresh_token_hash: Set(String::new()),
+            previous_refresh_token_hash: Set(String::new()),
+            access_token_id: Set(String::new()),
+            access_expires_date_time: Set(now),
+            created_date_time: Set(now),
+            refreshed_date_time: Set(now),
+            expires_date_time: Set(now),
+            revoked_date_time: Set(None),
+        }
+    }
+}
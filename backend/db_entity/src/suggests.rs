@@ -27,6 +27,20 @@ pub enum Status {
     Wait,
 }
 
+impl TryFrom<&str> for Status {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "approved" => Ok(Status::Approved),
+            "refused" => Ok(Status::Refused),
+            "wait" => Ok(Status::Wait),
+            _ => Err(format!("incorrect value: {}", value)),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
@@ -0,0 +1,13 @@
+//! the entities of `db_entity` as the first migrations left them
+//!
+//! those migrations seed rows through the entities, the columns added later to `db_entity`
+//! do not exist yet when they run. do not change these, add the columns to `db_entity`
+
+pub mod accounts;
+pub mod categories;
+pub mod memes;
+pub mod meme_urls;
+pub mod suggests;
+pub mod prelude;
+
+pub const DEFAULT_CATEGORY: &str = "meme";
@@ -5,6 +5,23 @@ mod m20250225_082047_create_memes;
 mod m20250303_085702_create_categories;
 mod m20250324_110708_create_suggests;
 mod m20250405_031951_create_meme_index;
+mod m20250420_000000_add_meme_deletion;
+mod m20250425_000000_create_sessions;
+mod m20250428_000000_create_login_throttles;
+mod m20250502_000000_add_account_totp;
+mod m20250506_000000_create_roles;
+mod m20250510_000000_add_account_management;
+mod m20250514_000000_create_meme_categories;
+mod m20250518_000000_create_category_aliases;
+mod m20250522_000000_create_meme_search_terms;
+mod m20250526_000000_create_meme_interactions;
+mod m20250530_000000_create_meme_votes;
+mod m20250603_000000_create_meme_reactions;
+mod m20250607_000000_add_meme_url_phash;
+mod m20250611_000000_create_meme_thumbnails;
+mod m20250615_000000_add_meme_url_placeholder;
+mod m20250619_000000_add_meme_status_before_delete;
+mod m20250623_000000_add_meme_url_thumbnail_claim;
 
 pub struct Migrator;
 
@@ -17,6 +34,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20250303_085702_create_categories::Migration),
             Box::new(m20250324_110708_create_suggests::Migration),
             Box::new(m20250405_031951_create_meme_index::Migration),
+            Box::new(m20250420_000000_add_meme_deletion::Migration),
+            Box::new(m20250425_000000_create_sessions::Migration),
+            Box::new(m20250428_000000_create_login_throttles::Migration),
+            Box::new(m20250502_000000_add_account_totp::Migration),
+            Box::new(m20250506_000000_create_roles::Migration),
+            Box::new(m20250510_000000_add_account_management::Migration),
+            Box::new(m20250514_000000_create_meme_categories::Migration),
+            Box::new(m20250518_000000_create_category_aliases::Migration),
+            Box::new(m20250522_000000_create_meme_search_terms::Migration),
+            Box::new(m20250526_000000_create_meme_interactions::Migration),
+            Box::new(m20250530_000000_create_meme_votes::Migration),
+            Box::new(m20250603_000000_create_meme_reactions::Migration),
+            Box::new(m20250607_000000_add_meme_url_phash::Migration),
+            Box::new(m20250611_000000_create_meme_thumbnails::Migration),
+            Box::new(m20250615_000000_add_meme_url_placeholder::Migration),
+            Box::new(m20250619_000000_add_meme_status_before_delete::Migration),
+            Box::new(m20250623_000000_add_meme_url_thumbnail_claim::Migration),
         ]
     }
 }
@@ -1,9 +1,4 @@
-use db_entity::{meme_urls, memes};
-use sea_orm_migration::{
-    prelude::*,
-    schema::*,
-    sea_orm::{ActiveModelBehavior, ActiveModelTrait, Set},
-};
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::Uuid};
 
 #[derive(DeriveMigrationName)]
 pub struct Migration;
@@ -53,78 +48,34 @@ impl MigrationTrait for Migration {
             )
             .await?;
 
-        let db = manager.get_connection();
-
-        let model = memes::ActiveModel {
-            status: Set(memes::Status::Published),
-            nickname: Set(String::from("dvorak")),
-            ..memes::ActiveModel::new()
-        }
-        .insert(db)
-        .await?;
-
-        meme_urls::ActiveModel {
-            meme_id: Set(model.id),
-            url: Set(String::from(
-                "https://pic1.imgdb.cn/item/67c5b905d0e0a243d40ae56d.png",
-            )),
-            cover: Set(String::new()),
-            source: Set(String::new()),
-            format: Set(String::from("JPG")),
-            sort: Set(0),
-            ..meme_urls::ActiveModel::new()
-        }
-        .insert(db)
-        .await?;
-
-        meme_urls::ActiveModel {
-            meme_id: Set(model.id),
-            url: Set(String::from(
-                "https://pic1.imgdb.cn/item/67c5b228d0e0a243d40ae1ae.jpg",
-            )),
-            cover: Set(String::new()),
-            source: Set(String::new()),
-            format: Set(String::from("JPG")),
-            sort: Set(1),
-            ..meme_urls::ActiveModel::new()
-        }
-        .insert(db)
-        .await?;
-
-        let model = memes::ActiveModel {
-            status: Set(memes::Status::Published),
-            nickname: Set(String::from("dvorak")),
-            ..memes::ActiveModel::new()
-        }
-        .insert(db)
-        .await?;
-
-        meme_urls::ActiveModel {
-            meme_id: Set(model.id),
-            url: Set(String::from(
-                "https://pic1.imgdb.cn/item/67c5b83cd0e0a243d40ae473.png",
-            )),
-            cover: Set(String::new()),
-            source: Set(String::new()),
-            format: Set(String::from("PNG")),
-            sort: Set(0),
-            ..meme_urls::ActiveModel::new()
-        }
-        .insert(db)
+        seed_meme(
+            manager,
+            &[
+                (
+                    "https://pic1.imgdb.cn/item/67c5b905d0e0a243d40ae56d.png",
+                    "JPG",
+                ),
+                (
+                    "https://pic1.imgdb.cn/item/67c5b228d0e0a243d40ae1ae.jpg",
+                    "JPG",
+                ),
+            ],
+        )
         .await?;
 
-        meme_urls::ActiveModel {
-            meme_id: Set(model.id),
-            url: Set(String::from(
-                "https://pic1.imgdb.cn/item/67c573ddd0e0a243d40abd09.webp",
-            )),
-            cover: Set(String::new()),
-            source: Set(String::new()),
-            format: Set(String::from("WEBP")),
-            sort: Set(1),
-            ..meme_urls::ActiveModel::new()
-        }
-        .insert(db)
+        seed_meme(
+            manager,
+            &[
+                (
+                    "https://pic1.imgdb.cn/item/67c5b83cd0e0a243d40ae473.png",
+                    "PNG",
+                ),
+                (
+                    "https://pic1.imgdb.cn/item/67c573ddd0e0a243d40abd09.webp",
+                    "WEBP",
+                ),
+            ],
+        )
         .await?;
 
         Ok(())
@@ -137,6 +88,86 @@ impl MigrationTrait for Migration {
     }
 }
 
+/// seed a published meme through the columns of this migration,
+/// the entities keep growing so they cannot be used here
+async fn seed_meme(manager: &SchemaManager<'_>, urls: &[(&str, &str)]) -> Result<(), DbErr> {
+    let now = chrono::Utc::now();
+    let meme_id = Uuid::now_v7();
+
+    manager
+        .exec_stmt(
+            Query::insert()
+                .into_table(Memes::Table)
+                .columns([
+                    Memes::Id,
+                    Memes::Nickname,
+                    Memes::Message,
+                    Memes::Email,
+                    Memes::IdAddr,
+                    Memes::Likes,
+                    Memes::Unlikes,
+                    Memes::Categories,
+                    Memes::Status,
+                    Memes::UserId,
+                    Memes::ShowDateTime,
+                    Memes::CreatedDateTime,
+                    Memes::LastActiityDateTime,
+                ])
+                .values_panic([
+                    meme_id.into(),
+                    "dvorak".into(),
+                    "".into(),
+                    "".into(),
+                    "".into(),
+                    0.into(),
+                    0.into(),
+                    format!(";{};", db_entity::DEFAULT_CATEGORY).into(),
+                    "published".into(),
+                    Uuid::nil().into(),
+                    now.into(),
+                    now.into(),
+                    now.into(),
+                ])
+                .to_owned(),
+        )
+        .await?;
+
+    let mut insert = Query::insert()
+        .into_table(MemeUrls::Table)
+        .columns([
+            MemeUrls::Id,
+            MemeUrls::MemeId,
+            MemeUrls::Url,
+            MemeUrls::Cover,
+            MemeUrls::Source,
+            MemeUrls::Format,
+            MemeUrls::Hash,
+            MemeUrls::Bed,
+            MemeUrls::BedId,
+            MemeUrls::Sort,
+            MemeUrls::CreatedDateTime,
+        ])
+        .to_owned();
+
+    for (sort, (url, format)) in urls.iter().enumerate() {
+        insert.values_panic([
+            Uuid::now_v7().into(),
+            meme_id.into(),
+            (*url).into(),
+            "".into(),
+            "".into(),
+            (*format).into(),
+            "".into(),
+            "superbad".into(),
+            "".into(),
+            (sort as i32).into(),
+            now.into(),
+        ]);
+    }
+
+    manager.exec_stmt(insert).await
+}
+
 #[derive(DeriveIden)]
 enum Memes {
     #[sea_orm(iden = "memes")]
@@ -1,8 +1,7 @@
-use db_entity::suggests;
 use sea_orm_migration::{
     prelude::*,
     schema::*,
-    sea_orm::{ActiveModelBehavior, ActiveModelTrait, EntityTrait, Set},
+    sea_orm::{ConnectionTrait, prelude::Uuid},
 };
 
 #[derive(DeriveMigrationName)]
@@ -28,31 +27,36 @@ impl MigrationTrait for Migration {
             )
             .await?;
 
-        let db = manager.get_connection();
+        let meme_id = first_id(manager, Alias::new("memes")).await?;
+        let account_id = first_id(manager, Alias::new("accounts")).await?;
 
-        let meme = db_entity::memes::Entity::find()
-            .one(db)
-            .await
-            .unwrap()
-            .unwrap();
-
-        let account = db_entity::accounts::Entity::find()
-            .one(db)
-            .await
-            .unwrap()
-            .unwrap();
-
-        suggests::ActiveModel {
-            meme_id: Set(meme.id),
-            before: Set(String::new()),
-            after: Set(String::from(";meme;")),
-            status: Set(suggests::Status::Wait),
-            account_id: Set(account.id),
-            operator_id: Set(account.id),
-            ..suggests::ActiveModel::new()
-        }
-        .insert(db)
-        .await?;
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Post::Table)
+                    .columns([
+                        Post::Id,
+                        Post::MemeId,
+                        Post::Before,
+                        Post::After,
+                        Post::Status,
+                        Post::AccountId,
+                        Post::OperatorId,
+                        Post::CreatedDateTime,
+                    ])
+                    .values_panic([
+                        Uuid::now_v7().into(),
+                        meme_id.into(),
+                        "".into(),
+                        ";meme;".into(),
+                        "wait".into(),
+                        account_id.into(),
+                        account_id.into(),
+                        chrono::Utc::now().into(),
+                    ])
+                    .to_owned(),
+            )
+            .await?;
 
         Ok(())
     }
@@ -64,6 +68,21 @@ impl MigrationTrait for Migration {
     }
 }
 
+/// id of the first row of a seeded table
+async fn first_id(manager: &SchemaManager<'_>, table: Alias) -> Result<Uuid, DbErr> {
+    let db = manager.get_connection();
+    let select = Query::select()
+        .column(Alias::new("id"))
+        .from(table)
+        .limit(1)
+        .to_owned();
+
+    db.query_one(db.get_database_backend().build(&select))
+        .await?
+        .ok_or(DbErr::RecordNotFound("seed row not found".to_owned()))?
+        .try_get("", "id")
+}
+
 #[derive(DeriveIden)]
 enum Post {
     #[sea_orm(iden = "suggests")]
@@ -0,0 +1,90 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_STATUS_NAME: &str = "idx_meme_status";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(Memes::Table.to_string(), Memes::DeletedBy.to_string())
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Memes::Table)
+                        .add_column_if_not_exists(uuid_null(Memes::DeletedBy))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Memes::Table)
+                        .add_column_if_not_exists(timestamp_with_time_zone_null(
+                            Memes::DeletedDateTime,
+                        ))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .name(IDX_STATUS_NAME)
+                        .table(Memes::Table)
+                        .col(Memes::Status)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name(IDX_STATUS_NAME)
+                    .table(Memes::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memes::Table)
+                    .drop_column(Memes::DeletedDateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memes::Table)
+                    .drop_column(Memes::DeletedBy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memes {
+    #[sea_orm(iden = "memes")]
+    Table,
+    #[sea_orm(iden = "status")]
+    Status,
+    #[sea_orm(iden = "deleted_by")]
+    DeletedBy,
+    #[sea_orm(iden = "deleted_date_time")]
+    DeletedDateTime,
+}
@@ -0,0 +1,140 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_SESSION_ACCOUNT: &str = "idx_session_account";
+const IDX_SESSION_REFRESH_TOKEN: &str = "idx_session_refresh_token";
+const IDX_SESSION_PREVIOUS_REFRESH_TOKEN: &str = "idx_session_previous_refresh_token";
+const IDX_REVOKED_TOKEN_EXPIRES: &str = "idx_revoked_token_expires";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Sessions::Table)
+                    .if_not_exists()
+                    .col(uuid(Sessions::Id).primary_key())
+                    .col(uuid(Sessions::AccountId))
+                    .col(string(Sessions::RefreshTokenHash))
+                    .col(string(Sessions::PreviousRefreshTokenHash))
+                    .col(string(Sessions::AccessTokenId))
+                    .col(timestamp_with_time_zone(Sessions::AccessExpiresDateTime))
+                    .col(timestamp_with_time_zone(Sessions::CreatedDateTime))
+                    .col(timestamp_with_time_zone(Sessions::RefreshedDateTime))
+                    .col(timestamp_with_time_zone(Sessions::ExpiresDateTime))
+                    .col(timestamp_with_time_zone_null(Sessions::RevokedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_SESSION_ACCOUNT)
+                    .table(Sessions::Table)
+                    .col(Sessions::AccountId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_SESSION_REFRESH_TOKEN)
+                    .table(Sessions::Table)
+                    .col(Sessions::RefreshTokenHash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_SESSION_PREVIOUS_REFRESH_TOKEN)
+                    .table(Sessions::Table)
+                    .col(Sessions::PreviousRefreshTokenHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RevokedTokens::Table)
+                    .if_not_exists()
+                    .col(string(RevokedTokens::TokenId).primary_key())
+                    .col(uuid(RevokedTokens::AccountId))
+                    .col(timestamp_with_time_zone(RevokedTokens::ExpiresDateTime))
+                    .col(timestamp_with_time_zone(RevokedTokens::RevokedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_REVOKED_TOKEN_EXPIRES)
+                    .table(RevokedTokens::Table)
+                    .col(RevokedTokens::ExpiresDateTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RevokedTokens::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Sessions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    #[sea_orm(iden = "sessions")]
+    Table,
+    Id,
+    #[sea_orm(iden = "account_id")]
+    AccountId,
+    #[sea_orm(iden = "refresh_token_hash")]
+    RefreshTokenHash,
+    #[sea_orm(iden = "previous_refresh_token_hash")]
+    PreviousRefreshTokenHash,
+    #[sea_orm(iden = "access_token_id")]
+    AccessTokenId,
+    #[sea_orm(iden = "access_expires_date_time")]
+    AccessExpiresDateTime,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+    #[sea_orm(iden = "refreshed_date_time")]
+    RefreshedDateTime,
+    #[sea_orm(iden = "expires_date_time")]
+    ExpiresDateTime,
+    #[sea_orm(iden = "revoked_date_time")]
+    RevokedDateTime,
+}
+
+#[derive(DeriveIden)]
+enum RevokedTokens {
+    #[sea_orm(iden = "revoked_tokens")]
+    Table,
+    #[sea_orm(iden = "token_id")]
+    TokenId,
+    #[sea_orm(iden = "account_id")]
+    AccountId,
+    #[sea_orm(iden = "expires_date_time")]
+    ExpiresDateTime,
+    #[sea_orm(iden = "revoked_date_time")]
+    RevokedDateTime,
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_LOGIN_ATTEMPT_USERNAME: &str = "idx_login_attempt_username";
+const IDX_LOGIN_ATTEMPT_CREATED: &str = "idx_login_attempt_created";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginThrottles::Table)
+                    .if_not_exists()
+                    .col(string_len(LoginThrottles::Kind, 32))
+                    .col(string(LoginThrottles::Key))
+                    .col(integer(LoginThrottles::FailureCount))
+                    .col(timestamp_with_time_zone(LoginThrottles::LastFailureDateTime))
+                    .col(timestamp_with_time_zone_null(
+                        LoginThrottles::LockedUntilDateTime,
+                    ))
+                    .primary_key(
+                        Index::create()
+                            .col(LoginThrottles::Kind)
+                            .col(LoginThrottles::Key),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempts::Table)
+                    .if_not_exists()
+                    .col(uuid(LoginAttempts::Id).primary_key())
+                    .col(string(LoginAttempts::Username))
+                    .col(string(LoginAttempts::IpAddress))
+                    .col(boolean(LoginAttempts::Success))
+                    .col(timestamp_with_time_zone(LoginAttempts::CreatedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_LOGIN_ATTEMPT_USERNAME)
+                    .table(LoginAttempts::Table)
+                    .col(LoginAttempts::Username)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_LOGIN_ATTEMPT_CREATED)
+                    .table(LoginAttempts::Table)
+                    .col(LoginAttempts::CreatedDateTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginAttempts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(LoginThrottles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginThrottles {
+    #[sea_orm(iden = "login_throttles")]
+    Table,
+    #[sea_orm(iden = "kind")]
+    Kind,
+    #[sea_orm(iden = "key")]
+    Key,
+    #[sea_orm(iden = "failure_count")]
+    FailureCount,
+    #[sea_orm(iden = "last_failure_date_time")]
+    LastFailureDateTime,
+    #[sea_orm(iden = "locked_until_date_time")]
+    LockedUntilDateTime,
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempts {
+    #[sea_orm(iden = "login_attempts")]
+    Table,
+    Id,
+    #[sea_orm(iden = "username")]
+    Username,
+    #[sea_orm(iden = "ip_address")]
+    IpAddress,
+    #[sea_orm(iden = "success")]
+    Success,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
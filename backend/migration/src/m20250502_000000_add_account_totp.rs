@@ -0,0 +1,121 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_RECOVERY_CODE_ACCOUNT: &str = "idx_recovery_code_account";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(Accounts::Table.to_string(), Accounts::TotpSecret.to_string())
+            .await?
+        {
+            // sqlite only alters one column at a time
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .add_column_if_not_exists(string_null(Accounts::TotpSecret))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .add_column_if_not_exists(
+                            boolean(Accounts::TotpEnabled).default(false),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .add_column_if_not_exists(big_integer_null(Accounts::TotpLastStep))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecoveryCodes::Table)
+                    .if_not_exists()
+                    .col(uuid(RecoveryCodes::Id).primary_key())
+                    .col(uuid(RecoveryCodes::AccountId))
+                    .col(string(RecoveryCodes::CodeHash))
+                    .col(timestamp_with_time_zone(RecoveryCodes::CreatedDateTime))
+                    .col(timestamp_with_time_zone_null(RecoveryCodes::UsedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_RECOVERY_CODE_ACCOUNT)
+                    .table(RecoveryCodes::Table)
+                    .col(RecoveryCodes::AccountId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RecoveryCodes::Table).to_owned())
+            .await?;
+
+        for column in [
+            Accounts::TotpLastStep,
+            Accounts::TotpEnabled,
+            Accounts::TotpSecret,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accounts {
+    #[sea_orm(iden = "accounts")]
+    Table,
+    #[sea_orm(iden = "totp_secret")]
+    TotpSecret,
+    #[sea_orm(iden = "totp_enabled")]
+    TotpEnabled,
+    #[sea_orm(iden = "totp_last_step")]
+    TotpLastStep,
+}
+
+#[derive(DeriveIden)]
+enum RecoveryCodes {
+    #[sea_orm(iden = "recovery_codes")]
+    Table,
+    Id,
+    #[sea_orm(iden = "account_id")]
+    AccountId,
+    #[sea_orm(iden = "code_hash")]
+    CodeHash,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+    #[sea_orm(iden = "used_date_time")]
+    UsedDateTime,
+}
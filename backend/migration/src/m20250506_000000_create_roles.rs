@@ -0,0 +1,218 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const ADMINISTRATOR: &str = "administrator";
+const MODERATOR: &str = "moderator";
+const EDITOR: &str = "editor";
+
+/// the permissions known by the server, `business::roles::Permission`
+const PERMISSIONS: &[(&str, &str)] = &[
+    ("memes.view", "list the memes"),
+    ("memes.post", "post memes"),
+    ("memes.delete", "delete, restore and purge memes"),
+    ("categories.edit", "change the categories of memes"),
+    ("suggests.review", "approve and refuse suggestions"),
+    (
+        "accounts.manage",
+        "manage accounts, roles and log in throttles",
+    ),
+];
+
+const ROLES: &[(&str, &str, &[&str])] = &[
+    (
+        ADMINISTRATOR,
+        "full access",
+        &[
+            "memes.view",
+            "memes.post",
+            "memes.delete",
+            "categories.edit",
+            "suggests.review",
+            "accounts.manage",
+        ],
+    ),
+    (
+        MODERATOR,
+        "reviews suggestions and changes categories",
+        &["memes.view", "categories.edit", "suggests.review"],
+    ),
+    (EDITOR, "posts memes", &["memes.view", "memes.post"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Roles::Table)
+                    .if_not_exists()
+                    .col(string_len(Roles::Name, 64).primary_key())
+                    .col(string(Roles::Description))
+                    .col(timestamp_with_time_zone(Roles::CreatedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Permissions::Table)
+                    .if_not_exists()
+                    .col(string_len(Permissions::Name, 64).primary_key())
+                    .col(string(Permissions::Description))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RolePermissions::Table)
+                    .if_not_exists()
+                    .col(string_len(RolePermissions::Role, 64))
+                    .col(string_len(RolePermissions::Permission, 64))
+                    .primary_key(
+                        Index::create()
+                            .col(RolePermissions::Role)
+                            .col(RolePermissions::Permission),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        if !manager
+            .has_column(Accounts::Table.to_string(), Accounts::Role.to_string())
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .add_column_if_not_exists(string_len_null(Accounts::Role, 64))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        let mut insert = Query::insert()
+            .into_table(Permissions::Table)
+            .columns([Permissions::Name, Permissions::Description])
+            .on_conflict(
+                OnConflict::column(Permissions::Name)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .to_owned();
+        for (name, description) in PERMISSIONS {
+            insert.values_panic([(*name).into(), (*description).into()]);
+        }
+        manager.exec_stmt(insert).await?;
+
+        let now = chrono::Utc::now();
+        let mut insert = Query::insert()
+            .into_table(Roles::Table)
+            .columns([Roles::Name, Roles::Description, Roles::CreatedDateTime])
+            .on_conflict(OnConflict::column(Roles::Name).do_nothing().to_owned())
+            .to_owned();
+        for (name, description, _) in ROLES {
+            insert.values_panic([(*name).into(), (*description).into(), now.into()]);
+        }
+        manager.exec_stmt(insert).await?;
+
+        let mut insert = Query::insert()
+            .into_table(RolePermissions::Table)
+            .columns([RolePermissions::Role, RolePermissions::Permission])
+            .on_conflict(
+                OnConflict::columns([RolePermissions::Role, RolePermissions::Permission])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .to_owned();
+        for (role, _, permissions) in ROLES {
+            for permission in *permissions {
+                insert.values_panic([(*role).into(), (*permission).into()]);
+            }
+        }
+        manager.exec_stmt(insert).await?;
+
+        // the administrators keep their full access
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Accounts::Table)
+                    .value(Accounts::Role, ADMINISTRATOR)
+                    .and_where(Expr::col(Accounts::IsAdmin).eq(true))
+                    .and_where(Expr::col(Accounts::Role).is_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accounts::Table)
+                    .drop_column(Accounts::Role)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RolePermissions::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Permissions::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Roles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accounts {
+    #[sea_orm(iden = "accounts")]
+    Table,
+    #[sea_orm(iden = "is_admin")]
+    IsAdmin,
+    #[sea_orm(iden = "role")]
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum Roles {
+    #[sea_orm(iden = "roles")]
+    Table,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "description")]
+    Description,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
+
+#[derive(DeriveIden)]
+enum Permissions {
+    #[sea_orm(iden = "permissions")]
+    Table,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "description")]
+    Description,
+}
+
+#[derive(DeriveIden)]
+enum RolePermissions {
+    #[sea_orm(iden = "role_permissions")]
+    Table,
+    #[sea_orm(iden = "role")]
+    Role,
+    #[sea_orm(iden = "permission")]
+    Permission,
+}
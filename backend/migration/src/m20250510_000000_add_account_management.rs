@@ -0,0 +1,122 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_ACCOUNT_USERNAME: &str = "idx_account_username";
+const IDX_AUDIT_LOG_CREATED: &str = "idx_audit_log_created";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(
+                Accounts::Table.to_string(),
+                Accounts::DisabledDateTime.to_string(),
+            )
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Accounts::Table)
+                        .add_column_if_not_exists(timestamp_with_time_zone_null(
+                            Accounts::DisabledDateTime,
+                        ))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_ACCOUNT_USERNAME)
+                    .table(Accounts::Table)
+                    .col(Accounts::Username)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLogs::Table)
+                    .if_not_exists()
+                    .col(uuid(AuditLogs::Id).primary_key())
+                    .col(uuid_null(AuditLogs::ActorId))
+                    .col(string(AuditLogs::ActorName))
+                    .col(string_len(AuditLogs::Action, 64))
+                    .col(string_null(AuditLogs::TargetId))
+                    .col(text(AuditLogs::Details))
+                    .col(timestamp_with_time_zone(AuditLogs::CreatedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_AUDIT_LOG_CREATED)
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::CreatedDateTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLogs::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(IDX_ACCOUNT_USERNAME)
+                    .table(Accounts::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accounts::Table)
+                    .drop_column(Accounts::DisabledDateTime)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accounts {
+    #[sea_orm(iden = "accounts")]
+    Table,
+    Username,
+    #[sea_orm(iden = "disabled_date_time")]
+    DisabledDateTime,
+}
+
+#[derive(DeriveIden)]
+enum AuditLogs {
+    #[sea_orm(iden = "audit_logs")]
+    Table,
+    Id,
+    #[sea_orm(iden = "actor_id")]
+    ActorId,
+    #[sea_orm(iden = "actor_name")]
+    ActorName,
+    #[sea_orm(iden = "action")]
+    Action,
+    #[sea_orm(iden = "target_id")]
+    TargetId,
+    #[sea_orm(iden = "details")]
+    Details,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use sea_orm_migration::{
+    prelude::*,
+    schema::*,
+    sea_orm::{ConnectionTrait, TransactionTrait, prelude::Uuid},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_MEME_CATEGORY_CATEGORY: &str = "idx_meme_category_category";
+
+/// `memes.categories` was a `;a;b;` string, it is moved to `meme_categories`
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeCategories::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeCategories::MemeId))
+                    .col(uuid(MemeCategories::CategoryId))
+                    .col(integer(MemeCategories::Sort))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeCategories::MemeId)
+                            .col(MemeCategories::CategoryId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_MEME_CATEGORY_CATEGORY)
+                    .table(MemeCategories::Table)
+                    .col(MemeCategories::CategoryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        if !manager
+            .has_column(Memes::Table.to_string(), Memes::Categories.to_string())
+            .await?
+        {
+            return Ok(());
+        }
+
+        let db = manager.get_connection();
+        let txn = db.begin().await?;
+        let backend = txn.get_database_backend();
+
+        let mut category_ids: HashMap<String, Uuid> = HashMap::new();
+        for row in txn
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Categories::Id, Categories::Name])
+                        .from(Categories::Table),
+                ),
+            )
+            .await?
+        {
+            let id: Uuid = row.try_get("", "id")?;
+            let name: String = row.try_get("", "name")?;
+            category_ids.entry(name).or_insert(id);
+        }
+
+        let memes = txn
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Memes::Id, Memes::Categories])
+                        .from(Memes::Table),
+                ),
+            )
+            .await?;
+
+        let now = chrono::Utc::now();
+        for row in memes {
+            let meme_id: Uuid = row.try_get("", "id")?;
+            let categories: String = row.try_get("", "categories")?;
+
+            let mut names: Vec<&str> = vec![];
+            for name in categories.split(';').filter(|name| !name.is_empty()) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            if names.is_empty() {
+                names.push(db_entity::DEFAULT_CATEGORY);
+            }
+
+            for (sort, name) in names.into_iter().enumerate() {
+                let category_id = match category_ids.get(name) {
+                    Some(id) => *id,
+                    None => {
+                        let id = Uuid::now_v7();
+                        txn.execute(
+                            backend.build(
+                                Query::insert()
+                                    .into_table(Categories::Table)
+                                    .columns([
+                                        Categories::Id,
+                                        Categories::Parent,
+                                        Categories::Name,
+                                        Categories::CreatedDateTime,
+                                    ])
+                                    .values_panic([
+                                        id.into(),
+                                        Uuid::nil().into(),
+                                        name.into(),
+                                        now.into(),
+                                    ]),
+                            ),
+                        )
+                        .await?;
+                        category_ids.insert(name.to_owned(), id);
+                        id
+                    }
+                };
+
+                txn.execute(
+                    backend.build(
+                        Query::insert()
+                            .into_table(MemeCategories::Table)
+                            .columns([
+                                MemeCategories::MemeId,
+                                MemeCategories::CategoryId,
+                                MemeCategories::Sort,
+                            ])
+                            .values_panic([
+                                meme_id.into(),
+                                category_id.into(),
+                                (sort as i32).into(),
+                            ])
+                            .on_conflict(
+                                OnConflict::columns([
+                                    MemeCategories::MemeId,
+                                    MemeCategories::CategoryId,
+                                ])
+                                .do_nothing()
+                                .to_owned(),
+                            ),
+                    ),
+                )
+                .await?;
+            }
+        }
+
+        txn.commit().await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memes::Table)
+                    .drop_column(Memes::Categories)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memes::Table)
+                    .add_column_if_not_exists(string(Memes::Categories).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        let txn = db.begin().await?;
+        let backend = txn.get_database_backend();
+
+        let rows = txn
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .column((MemeCategories::Table, MemeCategories::MemeId))
+                        .column((Categories::Table, Categories::Name))
+                        .from(MemeCategories::Table)
+                        .inner_join(
+                            Categories::Table,
+                            Expr::col((Categories::Table, Categories::Id))
+                                .equals((MemeCategories::Table, MemeCategories::CategoryId)),
+                        )
+                        .order_by((MemeCategories::Table, MemeCategories::Sort), Order::Asc),
+                ),
+            )
+            .await?;
+
+        let mut categories: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            let meme_id: Uuid = row.try_get("", "meme_id")?;
+            let name: String = row.try_get("", "name")?;
+            categories.entry(meme_id).or_default().push(name);
+        }
+
+        for (meme_id, names) in categories {
+            txn.execute(
+                backend.build(
+                    Query::update()
+                        .table(Memes::Table)
+                        .value(Memes::Categories, format!(";{};", names.join(";")))
+                        .and_where(Expr::col(Memes::Id).eq(meme_id)),
+                ),
+            )
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        manager
+            .drop_table(Table::drop().table(MemeCategories::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memes {
+    #[sea_orm(iden = "memes")]
+    Table,
+    Id,
+    #[sea_orm(iden = "categories")]
+    Categories,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    #[sea_orm(iden = "categories")]
+    Table,
+    Id,
+    #[sea_orm(iden = "parent")]
+    Parent,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
+
+#[derive(DeriveIden)]
+enum MemeCategories {
+    #[sea_orm(iden = "meme_categories")]
+    Table,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    #[sea_orm(iden = "category_id")]
+    CategoryId,
+    #[sea_orm(iden = "sort")]
+    Sort,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_CATEGORY_ALIAS_CATEGORY: &str = "idx_category_alias_category";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategoryAliases::Table)
+                    .if_not_exists()
+                    .col(string_len(CategoryAliases::Normalized, 64).primary_key())
+                    .col(string_len(CategoryAliases::Alias, 64))
+                    .col(uuid(CategoryAliases::CategoryId))
+                    .col(timestamp_with_time_zone(CategoryAliases::CreatedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_CATEGORY_ALIAS_CATEGORY)
+                    .table(CategoryAliases::Table)
+                    .col(CategoryAliases::CategoryId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CategoryAliases::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategoryAliases {
+    #[sea_orm(iden = "category_aliases")]
+    Table,
+    /// the alias after `normalize`, an alias maps to one category only
+    #[sea_orm(iden = "normalized")]
+    Normalized,
+    #[sea_orm(iden = "alias")]
+    Alias,
+    #[sea_orm(iden = "category_id")]
+    CategoryId,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
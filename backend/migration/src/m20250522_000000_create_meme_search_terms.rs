@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_MEME_SEARCH_TERM_MEME: &str = "idx_meme_search_term_meme";
+
+/// the terms are produced by the server's tokenizer, the existing memes are
+/// indexed with the `search rebuild` command after migrating
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeSearchTerms::Table)
+                    .if_not_exists()
+                    .col(string_len(MemeSearchTerms::Term, 64))
+                    .col(uuid(MemeSearchTerms::MemeId))
+                    .col(string_len(MemeSearchTerms::Field, 16))
+                    .col(integer(MemeSearchTerms::Frequency))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeSearchTerms::Term)
+                            .col(MemeSearchTerms::MemeId)
+                            .col(MemeSearchTerms::Field),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_MEME_SEARCH_TERM_MEME)
+                    .table(MemeSearchTerms::Table)
+                    .col(MemeSearchTerms::MemeId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemeSearchTerms::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeSearchTerms {
+    #[sea_orm(iden = "meme_search_terms")]
+    Table,
+    #[sea_orm(iden = "term")]
+    Term,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    /// message, nickname or category
+    #[sea_orm(iden = "field")]
+    Field,
+    /// how many times the term occurs in the field
+    #[sea_orm(iden = "frequency")]
+    Frequency,
+}
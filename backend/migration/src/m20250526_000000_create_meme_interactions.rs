@@ -0,0 +1,102 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_MEME_INTERACTION_CREATED: &str = "idx_meme_interaction_created";
+const IDX_MEME_INTERACTION_MEME: &str = "idx_meme_interaction_meme";
+
+/// an event per like or unlike, and the hot score that is refreshed from them in the background
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeInteractions::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeInteractions::Id).primary_key())
+                    .col(uuid(MemeInteractions::MemeId))
+                    .col(string_len(MemeInteractions::Kind, 16))
+                    .col(string_len(MemeInteractions::ClientId, 64))
+                    .col(timestamp_with_time_zone(MemeInteractions::CreatedDateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_MEME_INTERACTION_CREATED)
+                    .table(MemeInteractions::Table)
+                    .col(MemeInteractions::CreatedDateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(IDX_MEME_INTERACTION_MEME)
+                    .table(MemeInteractions::Table)
+                    .col(MemeInteractions::MemeId)
+                    .col(MemeInteractions::CreatedDateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeHotScores::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeHotScores::MemeId).primary_key())
+                    .col(double(MemeHotScores::Score))
+                    .col(timestamp_with_time_zone(MemeHotScores::UpdatedDateTime))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemeHotScores::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MemeInteractions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeInteractions {
+    #[sea_orm(iden = "meme_interactions")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    /// like or unlike
+    #[sea_orm(iden = "kind")]
+    Kind,
+    /// keyed hash of the visitor, the visitor itself is not kept
+    #[sea_orm(iden = "client_id")]
+    ClientId,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
+
+#[derive(DeriveIden)]
+enum MemeHotScores {
+    #[sea_orm(iden = "meme_hot_scores")]
+    Table,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    #[sea_orm(iden = "score")]
+    Score,
+    #[sea_orm(iden = "updated_date_time")]
+    UpdatedDateTime,
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// one like or unlike per visitor and meme, `memes.likes` and `memes.unlikes` count them
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeVotes::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeVotes::MemeId))
+                    .col(string_len(MemeVotes::Visitor, 64))
+                    .col(string_len(MemeVotes::Kind, 16))
+                    .col(timestamp_with_time_zone(MemeVotes::CreatedDateTime))
+                    .col(timestamp_with_time_zone(MemeVotes::UpdatedDateTime))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeVotes::MemeId)
+                            .col(MemeVotes::Visitor),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemeVotes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeVotes {
+    #[sea_orm(iden = "meme_votes")]
+    Table,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    /// the logged in account or the device of a visitor token
+    #[sea_orm(iden = "visitor")]
+    Visitor,
+    /// like or unlike
+    #[sea_orm(iden = "kind")]
+    Kind,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+    #[sea_orm(iden = "updated_date_time")]
+    UpdatedDateTime,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// the emoji reactions of each visitor, and their counts per meme so that reading does not
+/// have to group them
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeReactions::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeReactions::MemeId))
+                    .col(string_len(MemeReactions::Visitor, 64))
+                    .col(string_len(MemeReactions::Reaction, 32))
+                    .col(timestamp_with_time_zone(MemeReactions::CreatedDateTime))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeReactions::MemeId)
+                            .col(MemeReactions::Visitor)
+                            .col(MemeReactions::Reaction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeReactionCounts::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeReactionCounts::MemeId))
+                    .col(string_len(MemeReactionCounts::Reaction, 32))
+                    .col(big_integer(MemeReactionCounts::Count))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeReactionCounts::MemeId)
+                            .col(MemeReactionCounts::Reaction),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemeReactionCounts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MemeReactions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeReactions {
+    #[sea_orm(iden = "meme_reactions")]
+    Table,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    /// the logged in account or the device of a visitor token
+    #[sea_orm(iden = "visitor")]
+    Visitor,
+    /// the emoji
+    #[sea_orm(iden = "reaction")]
+    Reaction,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
+
+#[derive(DeriveIden)]
+enum MemeReactionCounts {
+    #[sea_orm(iden = "meme_reaction_counts")]
+    Table,
+    #[sea_orm(iden = "meme_id")]
+    MemeId,
+    #[sea_orm(iden = "reaction")]
+    Reaction,
+    #[sea_orm(iden = "count")]
+    Count,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_HASH_NAME: &str = "idx_meme_urls_hash";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(MemeUrls::Table.to_string(), MemeUrls::Phash.to_string())
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(big_integer_null(MemeUrls::Phash))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .name(IDX_HASH_NAME)
+                        .table(MemeUrls::Table)
+                        .col(MemeUrls::Hash)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name(IDX_HASH_NAME)
+                    .table(MemeUrls::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MemeUrls::Table)
+                    .drop_column(MemeUrls::Phash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeUrls {
+    #[sea_orm(iden = "meme_urls")]
+    Table,
+    #[sea_orm(iden = "hash")]
+    Hash,
+    #[sea_orm(iden = "phash")]
+    Phash,
+}
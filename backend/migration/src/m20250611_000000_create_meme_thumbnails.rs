@@ -0,0 +1,142 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const IDX_THUMBNAIL_STATUS_NAME: &str = "idx_meme_urls_thumbnail_status";
+
+/// the size of each file and the thumbnails made of it in the background, the rows posted
+/// before are `pending` and picked up as well
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(
+                MemeUrls::Table.to_string(),
+                MemeUrls::ThumbnailStatus.to_string(),
+            )
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(integer_null(MemeUrls::Width))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(integer_null(MemeUrls::Height))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(
+                            string_len(MemeUrls::ThumbnailStatus, 16).default("pending"),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .name(IDX_THUMBNAIL_STATUS_NAME)
+                        .table(MemeUrls::Table)
+                        .col(MemeUrls::ThumbnailStatus)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemeThumbnails::Table)
+                    .if_not_exists()
+                    .col(uuid(MemeThumbnails::UrlId))
+                    .col(integer(MemeThumbnails::Width))
+                    .col(integer(MemeThumbnails::Height))
+                    .col(string(MemeThumbnails::Url))
+                    .col(string_len(MemeThumbnails::Bed, 32))
+                    .col(string(MemeThumbnails::BedId))
+                    .col(timestamp_with_time_zone(MemeThumbnails::CreatedDateTime))
+                    .primary_key(
+                        Index::create()
+                            .col(MemeThumbnails::UrlId)
+                            .col(MemeThumbnails::Width),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemeThumbnails::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name(IDX_THUMBNAIL_STATUS_NAME)
+                    .table(MemeUrls::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for column in [MemeUrls::ThumbnailStatus, MemeUrls::Height, MemeUrls::Width] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeUrls {
+    #[sea_orm(iden = "meme_urls")]
+    Table,
+    #[sea_orm(iden = "width")]
+    Width,
+    #[sea_orm(iden = "height")]
+    Height,
+    #[sea_orm(iden = "thumbnail_status")]
+    ThumbnailStatus,
+}
+
+#[derive(DeriveIden)]
+enum MemeThumbnails {
+    #[sea_orm(iden = "meme_thumbnails")]
+    Table,
+    #[sea_orm(iden = "url_id")]
+    UrlId,
+    #[sea_orm(iden = "width")]
+    Width,
+    #[sea_orm(iden = "height")]
+    Height,
+    #[sea_orm(iden = "url")]
+    Url,
+    #[sea_orm(iden = "bed")]
+    Bed,
+    #[sea_orm(iden = "bed_id")]
+    BedId,
+    #[sea_orm(iden = "created_date_time")]
+    CreatedDateTime,
+}
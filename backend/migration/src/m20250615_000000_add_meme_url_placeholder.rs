@@ -0,0 +1,61 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// what the client shows while a file loads, the rows posted before are filled by
+/// `placeholders backfill`
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column(MemeUrls::Table.to_string(), MemeUrls::Blurhash.to_string())
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(string_len_null(MemeUrls::Blurhash, 64))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .add_column_if_not_exists(string_len_null(MemeUrls::DominantColor, 7))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [MemeUrls::DominantColor, MemeUrls::Blurhash] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MemeUrls::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeUrls {
+    #[sea_orm(iden = "meme_urls")]
+    Table,
+    #[sea_orm(iden = "blurhash")]
+    Blurhash,
+    #[sea_orm(iden = "dominant_color")]
+    DominantColor,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// when a file went `processing`, so that a file claimed by an instance that stopped is
+/// picked up again
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MemeUrls::Table)
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(
+                        MemeUrls::ThumbnailClaimedDateTime,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MemeUrls::Table)
+                    .drop_column(MemeUrls::ThumbnailClaimedDateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemeUrls {
+    #[sea_orm(iden = "meme_urls")]
+    Table,
+    #[sea_orm(iden = "thumbnail_claimed_date_time")]
+    ThumbnailClaimedDateTime,
+}